@@ -0,0 +1,138 @@
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::sync::LazyLock;
+
+use crate::error::AppError;
+use crate::prompts;
+use crate::summarize::{self, LlamaRun};
+
+const EXTRACT_PROMPT: &str = "You extract action items and decisions from meeting transcripts. Reply with only a JSON array, no other text. Each element is either {\"type\": \"action\", \"owner\": string or null, \"task\": string, \"due\": string or null} or {\"type\": \"decision\", \"decision\": string}. Only include what was actually said; reply [] if there is nothing.\n\nTranscript:\n{{transcript}}\n\nJSON:";
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ActionItem {
+    pub owner: Option<String>,
+    pub task: String,
+    /// Due date as said, e.g. "Friday" or "end of the month"
+    pub due: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct Decision {
+    pub decision: String,
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct ExtractedActions {
+    pub action_items: Vec<ActionItem>,
+    pub decisions: Vec<Decision>,
+    /// "llama", or "heuristic" when llama-cli or its model isn't installed
+    pub source: &'static str,
+}
+
+/// One element of the JSON array llama is asked for
+#[derive(Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+enum Extracted {
+    Action(ActionItem),
+    Decision(Decision),
+}
+
+/// Parse the first JSON array in llama's reply, ignoring any chatter around it
+fn parse_reply(reply: &str) -> Result<ExtractedActions, String> {
+    let (Some(start), Some(end)) = (reply.find('['), reply.rfind(']')) else {
+        return Err("no JSON array in the reply".to_string());
+    };
+    if end < start {
+        return Err("no JSON array in the reply".to_string());
+    }
+    let entries: Vec<Extracted> = serde_json::from_str(&reply[start..=end]).map_err(|e| e.to_string())?;
+
+    let mut extracted = ExtractedActions { action_items: Vec::new(), decisions: Vec::new(), source: "llama" };
+    for entry in entries {
+        match entry {
+            Extracted::Action(item) if !item.task.trim().is_empty() => extracted.action_items.push(item),
+            Extracted::Decision(decision) if !decision.decision.trim().is_empty() => extracted.decisions.push(decision),
+            _ => {}
+        }
+    }
+    Ok(extracted)
+}
+
+static TODO_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(?i)\b(?:todo|action(?: item)?)\s*(?:\((?P<owner>[^)]+)\))?\s*:\s*(?P<task>.+)$").unwrap()
+});
+
+static WILL_RE: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"^(?P<owner>[A-Z][a-z]+)\s+will\s+(?P<task>.+)$").unwrap());
+
+static DUE_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(?i)\b(?:by|before|due(?: on)?)\s+(?P<due>(?:next |this )?(?:monday|tuesday|wednesday|thursday|friday|saturday|sunday|tomorrow|today|tonight|end of (?:the )?(?:day|week|month)|eod|eow|\d{1,2}/\d{1,2}(?:/\d{2,4})?))\b").unwrap()
+});
+
+static DECISION_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(?i)^(?:decision\s*:\s*|we(?:'ve| have)? (?:decided|agreed)(?: to| that| on)?\s+|it was (?:decided|agreed)(?: to| that)?\s+)(?P<decision>.+)$").unwrap()
+});
+
+/// Capitalized sentence openers that aren't people
+const NOT_NAMES: &[&str] = &["I", "It", "That", "This", "There", "We", "You", "He", "She", "They", "Someone", "Everyone", "Nobody", "Which", "What", "Who"];
+
+/// Transcript split into sentences and lines, without list bullets
+fn sentences(text: &str) -> impl Iterator<Item = &str> {
+    text.split(['\n', '.', '!', '?'])
+        .map(|s| s.trim().trim_start_matches(['-', '*', '•']).trim())
+        .filter(|s| !s.is_empty())
+}
+
+/// Pattern-based extraction for when llama isn't available: TODO/Action lines, "<Name> will ...", and "we decided ..."
+fn extract_heuristic(text: &str) -> ExtractedActions {
+    let mut extracted = ExtractedActions { action_items: Vec::new(), decisions: Vec::new(), source: "heuristic" };
+    for sentence in sentences(text) {
+        let due = DUE_RE.captures(sentence).map(|c| c["due"].to_string());
+        if let Some(caps) = TODO_RE.captures(sentence) {
+            extracted.action_items.push(ActionItem {
+                owner: caps.name("owner").map(|m| m.as_str().trim().to_string()),
+                task: caps["task"].trim().to_string(),
+                due,
+            });
+        } else if let Some(caps) = WILL_RE.captures(sentence).filter(|c| !NOT_NAMES.contains(&&c["owner"])) {
+            extracted.action_items.push(ActionItem {
+                owner: Some(caps["owner"].to_string()),
+                task: caps["task"].trim().to_string(),
+                due,
+            });
+        } else if let Some(caps) = DECISION_RE.captures(sentence) {
+            extracted.decisions.push(Decision { decision: caps["decision"].trim().to_string() });
+        }
+    }
+    extracted
+}
+
+/// Pull action items and decisions out of a transcript with llama, retrying once if its JSON doesn't parse.
+/// Falls back to pattern matching when llama-cli or a model isn't installed.
+#[tauri::command]
+pub async fn extract_action_items(app: tauri::AppHandle, text: String) -> Result<ExtractedActions, AppError> {
+    let run = match LlamaRun::resolve(&app, None, None, Some(0.2)) {
+        Ok(run) => run,
+        Err(AppError::SetupRequired { step, .. }) => {
+            log::info!("llama not set up ({}), extracting action items heuristically", step);
+            return Ok(extract_heuristic(&text));
+        }
+        Err(e) => return Err(e),
+    };
+
+    let prompt = prompts::render(EXTRACT_PROMPT, &text);
+    let reply = summarize::run_llama(&app, &run, prompt.clone(), false).await?;
+    let error = match parse_reply(&reply) {
+        Ok(extracted) => return Ok(extracted),
+        Err(e) => e,
+    };
+
+    log::info!("Action item JSON didn't parse ({}), retrying", error);
+    let retry_prompt = format!(
+        "{}\n\nYour previous reply was:\n{}\n\nThat is not valid JSON ({}). Reply again with only the JSON array:",
+        prompt.trim_end_matches("JSON:").trim_end(),
+        reply,
+        error
+    );
+    let reply = summarize::run_llama(&app, &run, retry_prompt, false).await?;
+    parse_reply(&reply).map_err(|e| AppError::Other(format!("llama didn't return valid action item JSON: {}", e)))
+}
@@ -0,0 +1,259 @@
+use std::fs;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+enum ArchiveKind {
+    Zip,
+    TarGz,
+    TarXz,
+    Tar,
+}
+
+/// Identify an archive by its magic bytes, falling back to the file extension
+fn detect_kind(path: &Path) -> Result<ArchiveKind, String> {
+    let mut magic = [0u8; 262];
+    let n = fs::File::open(path)
+        .and_then(|mut f| f.read(&mut magic))
+        .map_err(|e| format!("Failed to open archive: {}", e))?;
+    let magic = &magic[..n];
+
+    if magic.starts_with(b"PK\x03\x04") {
+        return Ok(ArchiveKind::Zip);
+    }
+    if magic.starts_with(&[0x1f, 0x8b]) {
+        return Ok(ArchiveKind::TarGz);
+    }
+    if magic.starts_with(&[0xfd, b'7', b'z', b'X', b'Z', 0x00]) {
+        return Ok(ArchiveKind::TarXz);
+    }
+    if magic.len() >= 262 && &magic[257..262] == b"ustar" {
+        return Ok(ArchiveKind::Tar);
+    }
+
+    let name = path.file_name().map(|n| n.to_string_lossy().to_lowercase()).unwrap_or_default();
+    if name.ends_with(".zip") {
+        Ok(ArchiveKind::Zip)
+    } else if name.ends_with(".tar.gz") || name.ends_with(".tgz") {
+        Ok(ArchiveKind::TarGz)
+    } else if name.ends_with(".tar.xz") || name.ends_with(".txz") {
+        Ok(ArchiveKind::TarXz)
+    } else if name.ends_with(".tar") {
+        Ok(ArchiveKind::Tar)
+    } else {
+        Err(format!("Unrecognized archive format: {}", path.display()))
+    }
+}
+
+/// Find a file called `name` in `dir` or up to `depth` levels below it
+pub fn find_file(dir: &Path, name: &str, depth: usize) -> Option<PathBuf> {
+    let entries: Vec<PathBuf> = fs::read_dir(dir).ok()?.flatten().map(|e| e.path()).collect();
+    if let Some(found) = entries.iter().find(|p| p.is_file() && p.file_name().map(|n| n == name).unwrap_or(false)) {
+        return Some(found.clone());
+    }
+    if depth == 0 {
+        return None;
+    }
+    entries.iter().filter(|p| p.is_dir()).find_map(|p| find_file(p, name, depth - 1))
+}
+
+/// Extract a .zip, .tar, .tar.gz or .tar.xz into `dest_dir`
+pub fn extract_archive(archive_path: &Path, dest_dir: &Path) -> Result<(), String> {
+    fs::create_dir_all(dest_dir).map_err(|e| format!("Failed to create {}: {}", dest_dir.display(), e))?;
+    let open = || fs::File::open(archive_path).map_err(|e| format!("Failed to open archive: {}", e));
+    match detect_kind(archive_path)? {
+        ArchiveKind::Zip => extract_zip(archive_path, dest_dir),
+        ArchiveKind::TarGz => extract_tar(flate2::read::GzDecoder::new(open()?), dest_dir),
+        ArchiveKind::TarXz => extract_tar(xz2::read::XzDecoder::new(open()?), dest_dir),
+        ArchiveKind::Tar => extract_tar(open()?, dest_dir),
+    }
+}
+
+#[cfg(unix)]
+fn set_mode(path: &Path, mode: u32) {
+    use std::os::unix::fs::PermissionsExt;
+    let _ = fs::set_permissions(path, fs::Permissions::from_mode(mode & 0o777));
+}
+
+#[cfg(not(unix))]
+fn set_mode(_path: &Path, _mode: u32) {}
+
+fn extract_zip(archive_path: &Path, dest_dir: &Path) -> Result<(), String> {
+    let file = fs::File::open(archive_path)
+        .map_err(|e| format!("Failed to open archive: {}", e))?;
+
+    let mut archive = zip::ZipArchive::new(file)
+        .map_err(|e| format!("Failed to read zip archive: {}", e))?;
+
+    for i in 0..archive.len() {
+        let mut file = archive.by_index(i)
+            .map_err(|e| format!("Failed to read archive entry: {}", e))?;
+
+        let outpath = match file.enclosed_name() {
+            Some(path) => dest_dir.join(path),
+            None => continue,
+        };
+
+        if file.name().ends_with('/') {
+            fs::create_dir_all(&outpath).ok();
+        } else {
+            if let Some(p) = outpath.parent() {
+                fs::create_dir_all(p).ok();
+            }
+            let mut outfile = fs::File::create(&outpath)
+                .map_err(|e| format!("Failed to create extracted file: {}", e))?;
+            std::io::copy(&mut file, &mut outfile)
+                .map_err(|e| format!("Failed to extract file: {}", e))?;
+            if let Some(mode) = file.unix_mode() {
+                set_mode(&outpath, mode);
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Extract a tar stream entry by entry with tar's unpack_in, which refuses absolute and ".." paths
+/// and writing through a symlink that leads out of `dest_dir`
+fn extract_tar(reader: impl Read, dest_dir: &Path) -> Result<(), String> {
+    let mut archive = tar::Archive::new(reader);
+    archive.set_preserve_permissions(true);
+    let entries = archive.entries().map_err(|e| format!("Failed to read tar archive: {}", e))?;
+    for entry in entries {
+        let mut entry = entry.map_err(|e| format!("Failed to read tar archive: {}", e))?;
+        let name = entry.path().map(|p| p.display().to_string()).unwrap_or_default();
+        let unpacked = entry
+            .unpack_in(dest_dir)
+            .map_err(|e| format!("Failed to extract {}: {}", name, e))?;
+        if !unpacked {
+            log::warn!("Skipped archive entry {} outside the destination", name);
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    /// A fresh temp dir with the archive written to `<dir>/<name>` and `<dir>/out` to extract into,
+    /// so an entry climbing out of `out` would land in `<dir>`
+    fn scratch(name: &str) -> (PathBuf, PathBuf, PathBuf) {
+        let dir = std::env::temp_dir().join(crate::audio::unique_name("archive-test"));
+        fs::create_dir_all(&dir).unwrap();
+        (dir.join(name), dir.join("out"), dir)
+    }
+
+    fn file_header(size: usize, mode: u32) -> tar::Header {
+        let mut header = tar::Header::new_gnu();
+        header.set_size(size as u64);
+        header.set_mode(mode);
+        header.set_entry_type(tar::EntryType::Regular);
+        header
+    }
+
+    /// A tar with a binary in a subdirectory, a symlink to it, and an entry named "../escaped"
+    fn tar_bytes() -> Vec<u8> {
+        let mut builder = tar::Builder::new(Vec::new());
+        let mut header = file_header(4, 0o755);
+        builder.append_data(&mut header, "build/bin/whisper-cli", &b"elf!"[..]).unwrap();
+
+        let mut link = tar::Header::new_gnu();
+        link.set_entry_type(tar::EntryType::Symlink);
+        link.set_size(0);
+        builder.append_link(&mut link, "build/bin/main", "whisper-cli").unwrap();
+
+        // Builder refuses ".." in a path, so the name goes straight into the header
+        let mut evil = file_header(5, 0o644);
+        evil.as_old_mut().name[..10].copy_from_slice(b"../escaped");
+        evil.set_cksum();
+        builder.append(&evil, &b"owned"[..]).unwrap();
+        builder.into_inner().unwrap()
+    }
+
+    fn assert_tar_extracted(out: &Path, dir: &Path) {
+        let binary = out.join("build/bin/whisper-cli");
+        assert_eq!(fs::read(&binary).unwrap(), b"elf!");
+        assert_eq!(find_file(out, "whisper-cli", 3), Some(binary.clone()));
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            assert_eq!(fs::metadata(&binary).unwrap().permissions().mode() & 0o777, 0o755);
+            assert_eq!(fs::read_link(out.join("build/bin/main")).unwrap(), Path::new("whisper-cli"));
+        }
+        assert!(!dir.join("escaped").exists());
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn extracts_a_tar() {
+        let (archive, out, dir) = scratch("bin.tar");
+        fs::write(&archive, tar_bytes()).unwrap();
+        assert_eq!(detect_kind(&archive).unwrap(), ArchiveKind::Tar);
+        extract_archive(&archive, &out).unwrap();
+        assert_tar_extracted(&out, &dir);
+    }
+
+    #[test]
+    fn extracts_a_tar_gz() {
+        let (archive, out, dir) = scratch("bin.tar.gz");
+        let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::fast());
+        encoder.write_all(&tar_bytes()).unwrap();
+        fs::write(&archive, encoder.finish().unwrap()).unwrap();
+        assert_eq!(detect_kind(&archive).unwrap(), ArchiveKind::TarGz);
+        extract_archive(&archive, &out).unwrap();
+        assert_tar_extracted(&out, &dir);
+    }
+
+    #[test]
+    fn extracts_a_tar_xz() {
+        let (archive, out, dir) = scratch("bin.tar.xz");
+        let mut encoder = xz2::write::XzEncoder::new(Vec::new(), 1);
+        encoder.write_all(&tar_bytes()).unwrap();
+        fs::write(&archive, encoder.finish().unwrap()).unwrap();
+        assert_eq!(detect_kind(&archive).unwrap(), ArchiveKind::TarXz);
+        extract_archive(&archive, &out).unwrap();
+        assert_tar_extracted(&out, &dir);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn refuses_to_write_through_a_symlink_out_of_the_destination() {
+        let (archive, out, dir) = scratch("link.tar");
+        let outside = dir.join("outside");
+        fs::create_dir_all(&outside).unwrap();
+
+        let mut builder = tar::Builder::new(Vec::new());
+        let mut link = tar::Header::new_gnu();
+        link.set_entry_type(tar::EntryType::Symlink);
+        link.set_size(0);
+        builder.append_link(&mut link, "lib", &outside).unwrap();
+        let mut header = file_header(5, 0o644);
+        builder.append_data(&mut header, "lib/planted", &b"owned"[..]).unwrap();
+        fs::write(&archive, builder.into_inner().unwrap()).unwrap();
+
+        // Either error or skip is fine, as long as nothing lands outside
+        let _ = extract_archive(&archive, &out);
+        assert!(!outside.join("planted").exists());
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn extracts_a_zip_and_skips_escaping_entries() {
+        let (archive, out, dir) = scratch("bin.zip");
+        let mut writer = zip::ZipWriter::new(fs::File::create(&archive).unwrap());
+        let options = zip::write::SimpleFileOptions::default().unix_permissions(0o755);
+        writer.start_file("bin/whisper-cli", options).unwrap();
+        writer.write_all(b"elf!").unwrap();
+        writer.start_file("../escaped", options).unwrap();
+        writer.write_all(b"owned").unwrap();
+        writer.finish().unwrap();
+
+        assert_eq!(detect_kind(&archive).unwrap(), ArchiveKind::Zip);
+        extract_archive(&archive, &out).unwrap();
+        assert_eq!(fs::read(out.join("bin/whisper-cli")).unwrap(), b"elf!");
+        assert!(!dir.join("escaped").exists());
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
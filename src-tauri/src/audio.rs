@@ -0,0 +1,889 @@
+use serde::Serialize;
+use std::fs;
+use std::io::{BufRead, BufReader, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::sync::atomic::{AtomicU64, Ordering};
+use tauri::Emitter;
+
+use crate::error::AppError;
+use crate::{get_cache_dir, has_ffmpeg, paths};
+
+/// Format whisper-cli expects: 16 kHz mono 16-bit PCM
+pub const WHISPER_SAMPLE_RATE: u32 = 16000;
+
+/// Format details parsed from a RIFF/WAVE header
+#[derive(Clone, Debug)]
+pub struct WavInfo {
+    pub audio_format: u16,
+    pub channels: u16,
+    pub sample_rate: u32,
+    pub bits_per_sample: u16,
+    pub data_len: u64,
+}
+
+impl WavInfo {
+    pub fn duration_secs(&self) -> f64 {
+        let bytes_per_sec = self.sample_rate as u64 * self.channels as u64 * (self.bits_per_sample as u64 / 8);
+        if bytes_per_sec == 0 {
+            return 0.0;
+        }
+        self.data_len as f64 / bytes_per_sec as f64
+    }
+
+    /// Bytes per sample frame, across all channels
+    pub fn frame_bytes(&self) -> u64 {
+        (self.channels as u64 * (self.bits_per_sample as u64 / 8)).max(1)
+    }
+
+    pub fn is_whisper_ready(&self) -> bool {
+        self.audio_format == 1
+            && self.channels == 1
+            && self.sample_rate == WHISPER_SAMPLE_RATE
+            && self.bits_per_sample == 16
+    }
+}
+
+/// Parse the fmt and data chunks of a WAV file
+pub fn read_wav_info(path: &Path) -> Result<WavInfo, String> {
+    open_wav(path).map(|(_, info)| info)
+}
+
+/// Open a WAV file and walk its chunks, leaving the reader positioned at the start of the sample data
+fn open_wav(path: &Path) -> Result<(fs::File, WavInfo), String> {
+    let mut file = fs::File::open(path)
+        .map_err(|e| format!("Failed to open audio file: {}", e))?;
+
+    let mut riff = [0u8; 12];
+    file.read_exact(&mut riff)
+        .map_err(|_| "File too short for a WAV header".to_string())?;
+    if &riff[0..4] != b"RIFF" || &riff[8..12] != b"WAVE" {
+        return Err("Not a RIFF/WAVE file".to_string());
+    }
+
+    let mut fmt: Option<(u16, u16, u32, u16)> = None;
+    loop {
+        let mut header = [0u8; 8];
+        if file.read_exact(&mut header).is_err() {
+            return Err("WAV file has no data chunk".to_string());
+        }
+        let id = &header[0..4];
+        let size = u32::from_le_bytes([header[4], header[5], header[6], header[7]]) as u64;
+
+        let padded = size + (size % 2);
+
+        if id == b"fmt " {
+            let mut body = vec![0u8; padded as usize];
+            file.read_exact(&mut body)
+                .map_err(|_| "Truncated fmt chunk".to_string())?;
+            if size < 16 {
+                return Err("fmt chunk too small".to_string());
+            }
+            fmt = Some((
+                u16::from_le_bytes([body[0], body[1]]),
+                u16::from_le_bytes([body[2], body[3]]),
+                u32::from_le_bytes([body[4], body[5], body[6], body[7]]),
+                u16::from_le_bytes([body[14], body[15]]),
+            ));
+        } else if id == b"data" {
+            let (audio_format, channels, sample_rate, bits_per_sample) =
+                fmt.ok_or("WAV data chunk appears before fmt chunk")?;
+            let info = WavInfo {
+                audio_format,
+                channels,
+                sample_rate,
+                bits_per_sample,
+                data_len: size,
+            };
+            return Ok((file, info));
+        } else {
+            // Skip chunks we don't care about (LIST, fact, ...)
+            std::io::copy(&mut (&mut file).take(padded), &mut std::io::sink())
+                .map_err(|_| "Truncated WAV chunk".to_string())?;
+        }
+    }
+}
+
+/// Size placeholders recorders write up front and fix on a clean exit
+const PLACEHOLDER_SIZES: &[u64] = &[0, 0x7FFF_FFFF, 0xFFFF_FFFF];
+
+/// Rewrite the RIFF and data chunk sizes from the real file length when a killed recorder left
+/// placeholders (or sizes past EOF) behind. Returns whether the header was changed.
+pub fn repair_wav_header(path: &Path) -> Result<bool, String> {
+    let (mut file, info) = open_wav(path)?;
+    let data_start = file.stream_position().map_err(|e| format!("Failed to read WAV header: {}", e))?;
+    let file_len = file.metadata().map_err(|e| format!("Failed to read {}: {}", path.display(), e))?.len();
+    let actual = file_len.saturating_sub(data_start).min(u32::MAX as u64);
+
+    file.seek(SeekFrom::Start(4)).map_err(|e| format!("Failed to read WAV header: {}", e))?;
+    let mut riff_size = [0u8; 4];
+    file.read_exact(&mut riff_size).map_err(|e| format!("Failed to read WAV header: {}", e))?;
+    let riff_size = u32::from_le_bytes(riff_size) as u64;
+    let riff_expected = file_len.saturating_sub(8).min(u32::MAX as u64);
+
+    // A data size smaller than the rest of the file may just mean trailing chunks; leave that alone
+    let data_wrong = info.data_len != actual && (info.data_len > actual || PLACEHOLDER_SIZES.contains(&info.data_len));
+    let riff_wrong = riff_size != riff_expected && (riff_size > riff_expected || PLACEHOLDER_SIZES.contains(&riff_size));
+    if !data_wrong && !riff_wrong {
+        return Ok(false);
+    }
+
+    let mut file = fs::OpenOptions::new()
+        .write(true)
+        .open(path)
+        .map_err(|e| format!("Failed to open {} for repair: {}", path.display(), e))?;
+    let write_u32 = |file: &mut fs::File, offset: u64, value: u64| {
+        file.seek(SeekFrom::Start(offset))?;
+        file.write_all(&(value as u32).to_le_bytes())
+    };
+    write_u32(&mut file, 4, riff_expected)
+        .and_then(|_| write_u32(&mut file, data_start - 4, actual))
+        .and_then(|_| file.sync_all())
+        .map_err(|e| format!("Failed to repair WAV header: {}", e))?;
+    Ok(true)
+}
+
+/// How trustworthy a recording's header is; crashed sessions leave unfinalized or cut-off WAVs
+#[derive(Serialize, Clone, Copy, PartialEq, Eq, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum HeaderStatus {
+    Ok,
+    /// The header's data size is unset or larger than the file; duration comes from the bytes present
+    Truncated,
+    /// Not parseable at all (bad RIFF header, missing chunks, or ffprobe couldn't read it)
+    Invalid,
+    /// Not a WAV and ffprobe isn't installed, so only the size is known
+    Unprobed,
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct AudioMetadata {
+    pub path: String,
+    pub size_bytes: u64,
+    pub duration_secs: Option<f64>,
+    pub sample_rate: Option<u32>,
+    pub channels: Option<u16>,
+    pub bits_per_sample: Option<u16>,
+    pub header: HeaderStatus,
+    pub message: Option<String>,
+}
+
+impl AudioMetadata {
+    fn empty(path: &Path, size_bytes: u64, header: HeaderStatus, message: Option<String>) -> Self {
+        AudioMetadata {
+            path: path.to_string_lossy().to_string(),
+            size_bytes,
+            duration_secs: None,
+            sample_rate: None,
+            channels: None,
+            bits_per_sample: None,
+            header,
+            message,
+        }
+    }
+}
+
+fn wav_metadata(path: &Path, size_bytes: u64) -> AudioMetadata {
+    let (mut file, mut info) = match open_wav(path) {
+        Ok(opened) => opened,
+        Err(e) => return AudioMetadata::empty(path, size_bytes, HeaderStatus::Invalid, Some(e)),
+    };
+    let data_start = file.stream_position().unwrap_or(0);
+    let available = size_bytes.saturating_sub(data_start);
+
+    let (header, message) = if info.data_len == 0 || info.data_len == u32::MAX as u64 {
+        (HeaderStatus::Truncated, Some("WAV header was never finalized (recording interrupted?)".to_string()))
+    } else if info.data_len > available {
+        (
+            HeaderStatus::Truncated,
+            Some(format!("WAV header claims {} bytes of audio but only {} are present", info.data_len, available)),
+        )
+    } else {
+        (HeaderStatus::Ok, None)
+    };
+    if header == HeaderStatus::Truncated {
+        info.data_len = available;
+    }
+
+    AudioMetadata {
+        path: path.to_string_lossy().to_string(),
+        size_bytes,
+        duration_secs: Some(info.duration_secs()),
+        sample_rate: Some(info.sample_rate),
+        channels: Some(info.channels),
+        bits_per_sample: Some(info.bits_per_sample),
+        header,
+        message,
+    }
+}
+
+/// Non-WAV files: `ffprobe -print_format json`, first audio stream
+fn ffprobe_metadata(path: &Path, size_bytes: u64) -> AudioMetadata {
+    let output = match Command::new("ffprobe")
+        .arg("-v").arg("error")
+        .arg("-print_format").arg("json")
+        .arg("-show_format")
+        .arg("-show_streams")
+        .arg(path)
+        .output()
+    {
+        Ok(output) => output,
+        Err(_) => {
+            let message = "Not a WAV file and ffprobe isn't installed".to_string();
+            return AudioMetadata::empty(path, size_bytes, HeaderStatus::Unprobed, Some(message));
+        }
+    };
+    let parsed: Option<serde_json::Value> = serde_json::from_slice(&output.stdout).ok();
+    let Some(probe) = parsed.filter(|_| output.status.success()) else {
+        let message = format!("ffprobe couldn't read the file: {}", String::from_utf8_lossy(&output.stderr).trim());
+        return AudioMetadata::empty(path, size_bytes, HeaderStatus::Invalid, Some(message));
+    };
+
+    // ffprobe reports most numbers as strings
+    let number = |v: &serde_json::Value| v.as_str().and_then(|s| s.parse::<f64>().ok()).or_else(|| v.as_f64());
+    let stream = probe["streams"]
+        .as_array()
+        .and_then(|streams| streams.iter().find(|s| s["codec_type"] == "audio"))
+        .cloned()
+        .unwrap_or_default();
+    AudioMetadata {
+        path: path.to_string_lossy().to_string(),
+        size_bytes,
+        duration_secs: number(&probe["format"]["duration"]),
+        sample_rate: number(&stream["sample_rate"]).map(|r| r as u32),
+        channels: stream["channels"].as_u64().map(|c| c as u16),
+        bits_per_sample: stream["bits_per_sample"].as_u64().filter(|b| *b > 0).map(|b| b as u16),
+        header: HeaderStatus::Ok,
+        message: None,
+    }
+}
+
+/// Duration, size, and format of a recording, flagging truncated or invalid WAV headers
+#[tauri::command]
+pub async fn get_audio_metadata(app: tauri::AppHandle, path: String) -> Result<AudioMetadata, AppError> {
+    Ok(read_metadata(&paths::existing(&app, &path)?)?)
+}
+
+/// Clips for get_segment_audio go here, out of the recordings list
+const CLIP_DIR: &str = "clips";
+
+/// Clips are only needed while the frontend plays them
+const CLIP_MAX_AGE: std::time::Duration = std::time::Duration::from_secs(60 * 60);
+
+fn remove_old_clips(dir: &Path) {
+    let Ok(entries) = fs::read_dir(dir) else { return };
+    for entry in entries.flatten() {
+        let old = entry
+            .metadata()
+            .and_then(|m| m.modified())
+            .ok()
+            .and_then(|modified| modified.elapsed().ok())
+            .is_some_and(|age| age > CLIP_MAX_AGE);
+        if old {
+            let _ = fs::remove_file(entry.path());
+        }
+    }
+}
+
+/// Write `start_ms..end_ms` of a WAV (e.g. one transcript segment) to a temp clip for the frontend
+/// to play, returning its path. An end past the recording is cut to fit.
+#[tauri::command]
+pub async fn get_segment_audio(app: tauri::AppHandle, path: String, start_ms: u64, end_ms: u64) -> Result<String, AppError> {
+    let source = paths::existing(&app, &path)?;
+    let dir = get_cache_dir()?.join(CLIP_DIR);
+    fs::create_dir_all(&dir).map_err(|e| AppError::io("Failed to create the clips directory", e))?;
+    remove_old_clips(&dir);
+    let clip = dir.join(format!("{}-{}-{}.wav", unique_name("clip"), start_ms, end_ms));
+    let output = clip.clone();
+    tauri::async_runtime::spawn_blocking(move || extract_wav_span(&source, start_ms, end_ms, &output, |_, _| {}))
+        .await
+        .map_err(|e| format!("Clip task failed: {}", e))??;
+    Ok(clip.to_string_lossy().to_string())
+}
+
+/// Min/max peaks of a WAV in `buckets` equal slices, for drawing a waveform scrubber. A recording
+/// shorter than `buckets` frames gets one bucket per frame.
+#[tauri::command]
+pub async fn get_waveform_peaks(app: tauri::AppHandle, path: String, buckets: usize) -> Result<Vec<WaveformPeak>, AppError> {
+    if !(1..=MAX_WAVEFORM_BUCKETS).contains(&buckets) {
+        return Err(format!("buckets must be between 1 and {}", MAX_WAVEFORM_BUCKETS).into());
+    }
+    let source = paths::existing(&app, &path)?;
+    Ok(tauri::async_runtime::spawn_blocking(move || waveform_peaks(&source, buckets))
+        .await
+        .map_err(|e| format!("Waveform task failed: {}", e))??)
+}
+
+pub fn read_metadata(path: &Path) -> Result<AudioMetadata, String> {
+    let size_bytes = fs::metadata(path)
+        .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?
+        .len();
+
+    let mut magic = [0u8; 4];
+    let is_riff = fs::File::open(path).and_then(|mut f| f.read_exact(&mut magic)).is_ok() && &magic == b"RIFF";
+    let is_wav = is_riff || path.extension().map(|e| e.eq_ignore_ascii_case("wav")).unwrap_or(false);
+
+    Ok(if is_wav { wav_metadata(path, size_bytes) } else { ffprobe_metadata(path, size_bytes) })
+}
+
+/// Read a 16-bit PCM WAV as mono samples in [-1.0, 1.0], averaging channels
+pub fn read_pcm_samples(path: &Path) -> Result<(Vec<f32>, WavInfo), String> {
+    let (file, info) = open_wav(path)?;
+    if info.audio_format != 1 || info.bits_per_sample != 16 || info.channels == 0 {
+        return Err(format!(
+            "Unsupported WAV encoding (format {}, {} bits); expected 16-bit PCM",
+            info.audio_format, info.bits_per_sample
+        ));
+    }
+
+    // Recorders still writing a file leave the data size at 0 (or 0xFFFFFFFF), so read to EOF then
+    let mut bytes = Vec::new();
+    let limit = if info.data_len == 0 || info.data_len == u32::MAX as u64 { u64::MAX } else { info.data_len };
+    file.take(limit)
+        .read_to_end(&mut bytes)
+        .map_err(|e| format!("Failed to read audio samples: {}", e))?;
+
+    let channels = info.channels as usize;
+    let samples = bytes
+        .chunks_exact(2 * channels)
+        .map(|frame| {
+            let sum: f32 = frame
+                .chunks_exact(2)
+                .map(|s| i16::from_le_bytes([s[0], s[1]]) as f32 / i16::MAX as f32)
+                .sum();
+            sum / channels as f32
+        })
+        .collect();
+    Ok((samples, info))
+}
+
+/// Format tag of WAVE_FORMAT_EXTENSIBLE headers, which hound and ffmpeg write past 16 bits or 2 channels
+const FORMAT_EXTENSIBLE: u16 = 0xFFFE;
+const FORMAT_PCM: u16 = 1;
+const FORMAT_FLOAT: u16 = 3;
+
+/// A canonical 44-byte PCM header for `info.data_len` bytes of samples
+fn wav_header(info: &WavInfo) -> [u8; 44] {
+    let block_align = info.channels * (info.bits_per_sample / 8);
+    let byte_rate = info.sample_rate * block_align as u32;
+    let data_len = info.data_len as u32;
+    let mut header = [0u8; 44];
+    header[0..4].copy_from_slice(b"RIFF");
+    header[4..8].copy_from_slice(&(36 + data_len + data_len % 2).to_le_bytes());
+    header[8..16].copy_from_slice(b"WAVEfmt ");
+    header[16..20].copy_from_slice(&16u32.to_le_bytes());
+    // The recorders only write integer samples in extensible files, which a plain header describes
+    let format = if info.audio_format == FORMAT_EXTENSIBLE { FORMAT_PCM } else { info.audio_format };
+    header[20..22].copy_from_slice(&format.to_le_bytes());
+    header[22..24].copy_from_slice(&info.channels.to_le_bytes());
+    header[24..28].copy_from_slice(&info.sample_rate.to_le_bytes());
+    header[28..32].copy_from_slice(&byte_rate.to_le_bytes());
+    header[32..34].copy_from_slice(&block_align.to_le_bytes());
+    header[34..36].copy_from_slice(&info.bits_per_sample.to_le_bytes());
+    header[36..40].copy_from_slice(b"data");
+    header[40..44].copy_from_slice(&data_len.to_le_bytes());
+    header
+}
+
+/// Join WAVs end to end into `output` under a fresh header, calling `progress` with the number
+/// joined so far after each. They must all share one PCM format, as a live session's chunks do;
+/// the file only appears at `output` once it's complete.
+pub fn concat_wavs(inputs: &[PathBuf], output: &Path, mut progress: impl FnMut(usize)) -> Result<WavInfo, String> {
+    let tmp = output.with_extension("wav.part");
+    let write_err = |e: std::io::Error| format!("Failed to write {}: {}", tmp.display(), e);
+    let mut out = std::io::BufWriter::new(fs::File::create(&tmp).map_err(write_err)?);
+    out.write_all(&[0u8; 44]).map_err(write_err)?;
+
+    let mut format: Option<WavInfo> = None;
+    let mut data_len = 0u64;
+    for (done, input) in inputs.iter().enumerate() {
+        let (file, info) = open_wav(input).map_err(|e| format!("{}: {}", input.display(), e))?;
+        let layout = |i: &WavInfo| (i.audio_format, i.channels, i.sample_rate, i.bits_per_sample);
+        match &format {
+            Some(first) if layout(first) != layout(&info) => {
+                let _ = fs::remove_file(&tmp);
+                return Err(format!("{} isn't in the same format as the files before it", input.display()));
+            }
+            Some(_) => {}
+            None => format = Some(info.clone()),
+        }
+        // An unfinalized chunk's data runs to EOF
+        let limit = if info.data_len == 0 || info.data_len == u32::MAX as u64 { u64::MAX } else { info.data_len };
+        data_len += std::io::copy(&mut file.take(limit), &mut out).map_err(write_err)?;
+        progress(done + 1);
+    }
+
+    let Some(mut info) = format else {
+        let _ = fs::remove_file(&tmp);
+        return Err("No audio to join".to_string());
+    };
+    if data_len > (u32::MAX - 36) as u64 {
+        let _ = fs::remove_file(&tmp);
+        return Err("The joined audio is too long for a WAV file".to_string());
+    }
+    info.data_len = data_len;
+    if data_len % 2 == 1 {
+        out.write_all(&[0]).map_err(write_err)?;
+    }
+    let mut file = out.into_inner().map_err(|e| write_err(e.into_error()))?;
+    file.seek(SeekFrom::Start(0))
+        .and_then(|_| file.write_all(&wav_header(&info)))
+        .and_then(|_| file.sync_all())
+        .map_err(write_err)?;
+    fs::rename(&tmp, output).map_err(|e| format!("Failed to replace {}: {}", output.display(), e))?;
+    Ok(info)
+}
+
+/// Open a PCM WAV for reading its samples, with `data_len` cut to the bytes actually present
+/// (recorders still writing a file, or killed, leave the header's size unset or too big)
+fn open_pcm(path: &Path) -> Result<(BufReader<fs::File>, WavInfo), String> {
+    let (mut file, mut info) = open_wav(path)?;
+    let supported = matches!(
+        (info.audio_format, info.bits_per_sample),
+        (FORMAT_PCM | FORMAT_EXTENSIBLE, 8 | 16 | 24 | 32) | (FORMAT_FLOAT, 32)
+    );
+    if !supported || info.channels == 0 || info.sample_rate == 0 {
+        return Err(format!(
+            "Unsupported WAV encoding (format {}, {} bits); expected PCM",
+            info.audio_format, info.bits_per_sample
+        ));
+    }
+    let data_start = file.stream_position().map_err(|e| format!("Failed to read WAV header: {}", e))?;
+    let file_len = file.metadata().map_err(|e| format!("Failed to read {}: {}", path.display(), e))?.len();
+    let available = file_len.saturating_sub(data_start);
+    if PLACEHOLDER_SIZES.contains(&info.data_len) || info.data_len > available {
+        info.data_len = available;
+    }
+    let frame = info.frame_bytes();
+    info.data_len -= info.data_len % frame;
+    Ok((BufReader::new(file), info))
+}
+
+/// Format of a WAV whose samples extract_wav_span and waveform_peaks can read
+pub fn pcm_info(path: &Path) -> Result<WavInfo, String> {
+    open_pcm(path).map(|(_, info)| info)
+}
+
+/// One sample as a float in [-1.0, 1.0]
+fn decode_sample(bytes: &[u8], info: &WavInfo) -> f32 {
+    match (info.audio_format, bytes.len()) {
+        (FORMAT_FLOAT, _) => f32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]),
+        // 8-bit WAV is unsigned
+        (_, 1) => (bytes[0] as f32 - 128.0) / 128.0,
+        (_, 2) => i16::from_le_bytes([bytes[0], bytes[1]]) as f32 / 32768.0,
+        (_, 3) => i32::from_le_bytes([0, bytes[0], bytes[1], bytes[2]]) as f32 / 2_147_483_648.0,
+        _ => i32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]) as f32 / 2_147_483_648.0,
+    }
+}
+
+/// The frames `start_ms..end_ms` span, checked against the file's length; an end past it is cut to fit
+fn frame_range(info: &WavInfo, start_ms: u64, end_ms: u64) -> Result<(u64, u64), String> {
+    let frames = info.data_len / info.frame_bytes();
+    let duration_ms = frames * 1000 / info.sample_rate as u64;
+    if start_ms >= end_ms {
+        return Err(format!("The span {}–{} ms is empty", start_ms, end_ms));
+    }
+    if start_ms >= duration_ms {
+        return Err(format!("{} ms is past the end of the recording ({} ms)", start_ms, duration_ms));
+    }
+    let to_frame = |ms: u64| (ms.saturating_mul(info.sample_rate as u64) / 1000).min(frames);
+    Ok((to_frame(start_ms), to_frame(end_ms)))
+}
+
+/// Copy the samples from `start_ms` to `end_ms` into a new WAV at `output`, keeping the source's
+/// format, whatever its sample rate or depth. `progress` gets the bytes copied so far and the total.
+pub fn extract_wav_span(
+    path: &Path,
+    start_ms: u64,
+    end_ms: u64,
+    output: &Path,
+    mut progress: impl FnMut(u64, u64),
+) -> Result<WavInfo, String> {
+    let (mut reader, mut info) = open_pcm(path)?;
+    let (start, end) = frame_range(&info, start_ms, end_ms)?;
+    let frame = info.frame_bytes();
+    reader
+        .seek_relative((start * frame) as i64)
+        .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+    info.data_len = (end - start) * frame;
+
+    let write_err = |e: std::io::Error| format!("Failed to write {}: {}", output.display(), e);
+    let mut out = std::io::BufWriter::new(fs::File::create(output).map_err(write_err)?);
+    out.write_all(&wav_header(&info)).map_err(write_err)?;
+    let mut block = vec![0u8; 1 << 20];
+    let mut copied = 0u64;
+    while copied < info.data_len {
+        let len = ((info.data_len - copied) as usize).min(block.len());
+        reader
+            .read_exact(&mut block[..len])
+            .map_err(|_| format!("{} ended before the span did", path.display()))?;
+        out.write_all(&block[..len]).map_err(write_err)?;
+        copied += len as u64;
+        progress(copied, info.data_len);
+    }
+    if info.data_len % 2 == 1 {
+        out.write_all(&[0]).map_err(write_err)?;
+    }
+    out.flush().map_err(write_err)?;
+    Ok(info)
+}
+
+/// Write one sample given as a float in [-1.0, 1.0], clamping anything past full scale
+fn encode_sample(value: f32, info: &WavInfo, out: &mut Vec<u8>) {
+    let value = value.clamp(-1.0, 1.0);
+    match (info.audio_format, info.bits_per_sample) {
+        (FORMAT_FLOAT, _) => out.extend_from_slice(&value.to_le_bytes()),
+        (_, 8) => out.push((value * 127.0 + 128.0).round() as u8),
+        (_, 16) => out.extend_from_slice(&((value * 32767.0).round() as i16).to_le_bytes()),
+        (_, 24) => out.extend_from_slice(&((value * 8_388_607.0).round() as i32).to_le_bytes()[..3]),
+        _ => out.extend_from_slice(&((value as f64 * 2_147_483_647.0).round() as i32).to_le_bytes()),
+    }
+}
+
+/// Feed the sample data to `f` a block of whole frames at a time
+fn read_blocks(
+    reader: &mut BufReader<fs::File>,
+    info: &WavInfo,
+    mut f: impl FnMut(&[u8]) -> Result<(), String>,
+) -> Result<(), String> {
+    let mut block = vec![0u8; info.frame_bytes() as usize * 4096];
+    let mut remaining = info.data_len;
+    while remaining > 0 {
+        let len = (remaining as usize).min(block.len());
+        reader.read_exact(&mut block[..len]).map_err(|e| format!("Failed to read audio samples: {}", e))?;
+        f(&block[..len])?;
+        remaining -= len as u64;
+    }
+    Ok(())
+}
+
+fn to_dbfs(linear: f64) -> f32 {
+    (20.0 * linear.max(1e-6).log10()) as f32
+}
+
+/// Loudest sample and average (RMS) level of a recording, in dBFS
+#[derive(Serialize, Clone, Copy, Debug)]
+pub struct Levels {
+    pub peak_dbfs: f32,
+    pub rms_dbfs: f32,
+}
+
+pub fn wav_levels(path: &Path) -> Result<Levels, String> {
+    let (mut reader, info) = open_pcm(path)?;
+    let sample_bytes = (info.bits_per_sample / 8) as usize;
+    let (mut peak, mut sum_squares, mut count) = (0f64, 0f64, 0u64);
+    read_blocks(&mut reader, &info, |block| {
+        for sample in block.chunks_exact(sample_bytes) {
+            let value = decode_sample(sample, &info) as f64;
+            peak = peak.max(value.abs());
+            sum_squares += value * value;
+            count += 1;
+        }
+        Ok(())
+    })?;
+    let rms = if count == 0 { 0.0 } else { (sum_squares / count as f64).sqrt() };
+    Ok(Levels { peak_dbfs: to_dbfs(peak), rms_dbfs: to_dbfs(rms) })
+}
+
+/// Copy a WAV to `output` with every sample scaled by `gain_db`, in the same format
+pub fn apply_wav_gain(path: &Path, output: &Path, gain_db: f32) -> Result<WavInfo, String> {
+    let (mut reader, info) = open_pcm(path)?;
+    let sample_bytes = (info.bits_per_sample / 8) as usize;
+    let factor = 10f32.powf(gain_db / 20.0);
+    let write_err = |e: std::io::Error| format!("Failed to write {}: {}", output.display(), e);
+    let mut out = std::io::BufWriter::new(fs::File::create(output).map_err(write_err)?);
+    out.write_all(&wav_header(&info)).map_err(write_err)?;
+    let mut scaled = Vec::new();
+    read_blocks(&mut reader, &info, |block| {
+        scaled.clear();
+        for sample in block.chunks_exact(sample_bytes) {
+            encode_sample(decode_sample(sample, &info) * factor, &info, &mut scaled);
+        }
+        out.write_all(&scaled).map_err(write_err)
+    })?;
+    if info.data_len % 2 == 1 {
+        out.write_all(&[0]).map_err(write_err)?;
+    }
+    out.flush().map_err(write_err)?;
+    Ok(info)
+}
+
+/// More peaks than any scrubber has pixels for
+const MAX_WAVEFORM_BUCKETS: usize = 100_000;
+
+/// Lowest and highest sample in one slice of a waveform, across channels
+#[derive(Serialize, Clone, Copy, Debug)]
+pub struct WaveformPeak {
+    pub min: f32,
+    pub max: f32,
+}
+
+/// Split the recording into `buckets` equal slices and find each one's peaks
+pub fn waveform_peaks(path: &Path, buckets: usize) -> Result<Vec<WaveformPeak>, String> {
+    let (mut reader, info) = open_pcm(path)?;
+    let frame = info.frame_bytes() as usize;
+    let sample_bytes = (info.bits_per_sample / 8) as usize;
+    let frames = info.data_len / frame as u64;
+    let buckets = buckets.min(frames as usize);
+    let mut peaks = vec![WaveformPeak { min: 0.0, max: 0.0 }; buckets];
+    if buckets == 0 {
+        return Ok(peaks);
+    }
+
+    let mut block = vec![0u8; frame * 4096];
+    let mut index = 0u64;
+    while index < frames {
+        let count = ((frames - index) as usize).min(4096);
+        reader
+            .read_exact(&mut block[..count * frame])
+            .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+        for bytes in block[..count * frame].chunks_exact(frame) {
+            let peak = &mut peaks[(index * buckets as u64 / frames) as usize];
+            for sample in bytes.chunks_exact(sample_bytes) {
+                let value = decode_sample(sample, &info);
+                peak.min = peak.min.min(value);
+                peak.max = peak.max.max(value);
+            }
+            index += 1;
+        }
+    }
+    Ok(peaks)
+}
+
+static TEMP_COUNTER: AtomicU64 = AtomicU64::new(0);
+
+/// `<prefix>-<millis>-<pid>-<n>`, unique even for temp files made in the same millisecond or by
+/// a second instance sharing the cache dir
+pub fn unique_name(prefix: &str) -> String {
+    let millis = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_millis())
+        .unwrap_or(0);
+    let n = TEMP_COUNTER.fetch_add(1, Ordering::Relaxed);
+    format!("{}-{}-{}-{}", prefix, millis, std::process::id(), n)
+}
+
+/// Audio ready to hand to whisper; a converted temp copy is deleted on drop
+pub struct PreparedAudio {
+    pub path: PathBuf,
+    temp: bool,
+}
+
+impl PreparedAudio {
+    /// A temp file that's deleted along with this
+    pub fn temp(path: PathBuf) -> Self {
+        PreparedAudio { path, temp: true }
+    }
+}
+
+impl Drop for PreparedAudio {
+    fn drop(&mut self) {
+        if self.temp {
+            let _ = fs::remove_file(&self.path);
+        }
+    }
+}
+
+/// Probe a media file's duration in seconds with ffprobe, if available
+pub fn probe_duration_secs(path: &Path) -> Option<f64> {
+    let output = Command::new("ffprobe")
+        .arg("-v").arg("error")
+        .arg("-show_entries").arg("format=duration")
+        .arg("-of").arg("default=noprint_wrappers=1:nokey=1")
+        .arg(path)
+        .output()
+        .ok()?;
+    String::from_utf8_lossy(&output.stdout).trim().parse().ok()
+}
+
+/// Convert anything whisper can't read directly into a 16 kHz mono WAV temp file
+pub async fn prepare_audio_for_transcription(
+    app: &tauri::AppHandle,
+    input_path: &str,
+) -> Result<PreparedAudio, String> {
+    let input = PathBuf::from(input_path);
+    let is_wav = input
+        .extension()
+        .map(|e| e.eq_ignore_ascii_case("wav"))
+        .unwrap_or(false);
+
+    if is_wav {
+        if let Ok(info) = read_wav_info(&input) {
+            if info.is_whisper_ready() {
+                return Ok(PreparedAudio { path: input, temp: false });
+            }
+        }
+    }
+
+    if !has_ffmpeg() {
+        return Err(format!(
+            "ffmpeg is required to convert '{}' for transcription. Install ffmpeg (e.g. `sudo apt install ffmpeg`) and try again.",
+            input.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default()
+        ));
+    }
+
+    let output = get_cache_dir()?.join(format!("{}.wav", unique_name("convert")));
+    // Construct the guard before converting so a failed run still removes the partial file
+    let prepared = PreparedAudio { path: output.clone(), temp: true };
+
+    let app = app.clone();
+    let source = input_path.to_string();
+    tauri::async_runtime::spawn_blocking(move || convert_to_whisper_wav(&app, &source, &output))
+        .await
+        .map_err(|e| format!("Conversion task failed: {}", e))??;
+
+    Ok(prepared)
+}
+
+/// Split the first two channels of a recording into separate 16 kHz mono WAV temp files (left, right)
+pub async fn split_stereo_channels(input_path: &str) -> Result<(PreparedAudio, PreparedAudio), String> {
+    if !has_ffmpeg() {
+        return Err("ffmpeg is required to split stereo channels. Install ffmpeg (e.g. `sudo apt install ffmpeg`) and try again.".to_string());
+    }
+
+    // convert- keeps them out of the recordings list, like other transcription temp files
+    let name = unique_name("convert-split");
+    let cache_dir = get_cache_dir()?;
+    let left = PreparedAudio { path: cache_dir.join(format!("{}-left.wav", name)), temp: true };
+    let right = PreparedAudio { path: cache_dir.join(format!("{}-right.wav", name)), temp: true };
+
+    let (input, left_path, right_path) = (input_path.to_string(), left.path.clone(), right.path.clone());
+    let result = tauri::async_runtime::spawn_blocking(move || {
+        let sample_rate = WHISPER_SAMPLE_RATE.to_string();
+        Command::new("ffmpeg")
+            .arg("-hide_banner")
+            .arg("-loglevel").arg("error")
+            .arg("-y")
+            .arg("-i").arg(&input)
+            .arg("-filter_complex").arg("[0:a]asplit[a][b];[a]pan=mono|c0=c0[left];[b]pan=mono|c0=c1[right]")
+            .arg("-map").arg("[left]")
+            .arg("-ar").arg(&sample_rate)
+            .arg("-c:a").arg("pcm_s16le")
+            .arg(&left_path)
+            .arg("-map").arg("[right]")
+            .arg("-ar").arg(&sample_rate)
+            .arg("-c:a").arg("pcm_s16le")
+            .arg(&right_path)
+            .output()
+    })
+    .await
+    .map_err(|e| format!("Channel split task failed: {}", e))?
+    .map_err(|e| format!("Failed to start ffmpeg: {}", e))?;
+    if !result.status.success() {
+        return Err(format!("ffmpeg channel split failed: {}", String::from_utf8_lossy(&result.stderr).trim()));
+    }
+    Ok((left, right))
+}
+
+pub fn convert_to_whisper_wav(app: &tauri::AppHandle, input: &str, output: &Path) -> Result<(), String> {
+    let duration = probe_duration_secs(Path::new(input));
+    let _ = app.emit("audio-convert-progress", serde_json::json!({
+        "path": input,
+        "status": "started",
+        "percent": 0.0,
+    }));
+
+    let mut child = Command::new("ffmpeg")
+        .arg("-hide_banner")
+        .arg("-loglevel").arg("error")
+        .arg("-y")
+        .arg("-i").arg(input)
+        .arg("-ar").arg(WHISPER_SAMPLE_RATE.to_string())
+        .arg("-ac").arg("1")
+        .arg("-c:a").arg("pcm_s16le")
+        .arg("-progress").arg("pipe:1")
+        .arg("-nostats")
+        .arg(output)
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| format!("Failed to start ffmpeg: {}", e))?;
+
+    if let Some(stdout) = child.stdout.take() {
+        for line in BufReader::new(stdout).lines().map_while(Result::ok) {
+            // -progress emits key=value lines; out_time_us tracks how far we've converted
+            if let Some(us) = line.strip_prefix("out_time_us=").and_then(|v| v.trim().parse::<u64>().ok()) {
+                let secs = us as f64 / 1_000_000.0;
+                let percent = duration.map(|d| (secs / d * 100.0).min(100.0)).unwrap_or(0.0);
+                let _ = app.emit("audio-convert-progress", serde_json::json!({
+                    "path": input,
+                    "status": "converting",
+                    "seconds": secs,
+                    "percent": percent,
+                }));
+            }
+        }
+    }
+
+    let result = child.wait_with_output()
+        .map_err(|e| format!("Failed to wait for ffmpeg: {}", e))?;
+    if !result.status.success() {
+        let stderr = String::from_utf8_lossy(&result.stderr);
+        return Err(format!("ffmpeg conversion failed: {}", stderr.trim()));
+    }
+
+    let _ = app.emit("audio-convert-progress", serde_json::json!({
+        "path": input,
+        "status": "complete",
+        "percent": 100.0,
+    }));
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A 16 kHz mono WAV of `samples` zero samples whose header claims `riff_size` and `data_len`
+    fn write_wav(name: &str, samples: usize, riff_size: u32, data_len: u32) -> PathBuf {
+        let info = WavInfo { audio_format: FORMAT_PCM, channels: 1, sample_rate: 16000, bits_per_sample: 16, data_len: 0 };
+        let mut header = wav_header(&info);
+        header[4..8].copy_from_slice(&riff_size.to_le_bytes());
+        header[40..44].copy_from_slice(&data_len.to_le_bytes());
+        let path = std::env::temp_dir().join(format!("{}.wav", unique_name(name)));
+        let mut bytes = header.to_vec();
+        bytes.resize(44 + samples * 2, 0);
+        fs::write(&path, bytes).unwrap();
+        path
+    }
+
+    fn header_sizes(path: &Path) -> (u32, u32) {
+        let bytes = fs::read(path).unwrap();
+        let field = |at: usize| u32::from_le_bytes(bytes[at..at + 4].try_into().unwrap());
+        (field(4), field(40))
+    }
+
+    #[test]
+    fn repairs_zeroed_sizes_from_a_killed_recorder() {
+        let path = write_wav("repair-zeroed", 1600, 0, 0);
+        assert!(repair_wav_header(&path).unwrap());
+        assert_eq!(header_sizes(&path), (36 + 3200, 3200));
+        assert_eq!(read_wav_info(&path).unwrap().duration_secs(), 0.1);
+        // Already correct now, so a second pass leaves it alone
+        assert!(!repair_wav_header(&path).unwrap());
+        fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn repairs_placeholder_and_oversized_lengths() {
+        let path = write_wav("repair-placeholder", 800, 0xFFFF_FFFF, 0x7FFF_FFFF);
+        assert!(repair_wav_header(&path).unwrap());
+        assert_eq!(header_sizes(&path), (36 + 1600, 1600));
+        fs::remove_file(path).unwrap();
+
+        // Cut off mid-write: the header promises more data than the file holds
+        let path = write_wav("repair-oversized", 800, 36 + 32000, 32000);
+        assert!(repair_wav_header(&path).unwrap());
+        assert_eq!(header_sizes(&path), (36 + 1600, 1600));
+        fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn leaves_a_shorter_data_size_alone() {
+        // Data smaller than the rest of the file may just be followed by other chunks
+        let path = write_wav("repair-trailing", 800, 36 + 1600, 1000);
+        assert!(!repair_wav_header(&path).unwrap());
+        assert_eq!(header_sizes(&path), (36 + 1600, 1000));
+        fs::remove_file(path).unwrap();
+    }
+}
@@ -0,0 +1,234 @@
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use tauri::Emitter;
+
+use crate::notifications::{self, NotificationKind};
+use crate::error::AppError;
+use crate::{paths, transcribe_audio_internal, transcript, whisper};
+
+/// Extensions picked up when no pattern is given
+const AUDIO_EXTENSIONS: &[&str] = &["wav", "mp3", "m4a", "flac", "ogg", "opus", "webm", "aac"];
+
+// Only one batch runs at a time; cancel_batch flips `cancelled` and the loop stops after the current file
+pub struct BatchState {
+    running: AtomicBool,
+    cancelled: AtomicBool,
+}
+
+impl BatchState {
+    pub fn new() -> Self {
+        BatchState {
+            running: AtomicBool::new(false),
+            cancelled: AtomicBool::new(false),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct BatchProgress {
+    pub done: usize,
+    pub failed: usize,
+    pub skipped: usize,
+    pub remaining: usize,
+    pub current: Option<String>,
+    pub error: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct BatchSummary {
+    pub total: usize,
+    pub done: usize,
+    pub failed: usize,
+    pub skipped: usize,
+    pub cancelled: bool,
+    pub failures: Vec<BatchFailure>,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct BatchFailure {
+    pub path: String,
+    pub error: String,
+}
+
+/// Case-insensitive glob match supporting `*` and `?`
+fn wildcard_match(pattern: &str, name: &str) -> bool {
+    let p: Vec<char> = pattern.to_lowercase().chars().collect();
+    let n: Vec<char> = name.to_lowercase().chars().collect();
+    let (mut pi, mut ni) = (0, 0);
+    let mut backtrack: Option<(usize, usize)> = None;
+
+    while ni < n.len() {
+        if pi < p.len() && (p[pi] == '?' || p[pi] == n[ni]) {
+            pi += 1;
+            ni += 1;
+        } else if pi < p.len() && p[pi] == '*' {
+            backtrack = Some((pi, ni));
+            pi += 1;
+        } else if let Some((star_pi, star_ni)) = backtrack {
+            pi = star_pi + 1;
+            ni = star_ni + 1;
+            backtrack = Some((star_pi, star_ni + 1));
+        } else {
+            return false;
+        }
+    }
+    p[pi..].iter().all(|c| *c == '*')
+}
+
+fn matches_pattern(path: &Path, pattern: Option<&str>) -> bool {
+    let name = path.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+    match pattern {
+        Some(pattern) => wildcard_match(pattern, &name),
+        None => path
+            .extension()
+            .map(|e| AUDIO_EXTENSIONS.iter().any(|ext| e.eq_ignore_ascii_case(ext)))
+            .unwrap_or(false),
+    }
+}
+
+/// A transcript is up to date when it was written after the source last changed
+fn is_up_to_date(source: &Path, transcript: &Path) -> bool {
+    let modified = |p: &Path| fs::metadata(p).and_then(|m| m.modified()).ok();
+    match (modified(source), modified(transcript)) {
+        (Some(src), Some(out)) => out >= src,
+        _ => false,
+    }
+}
+
+async fn transcribe_one(
+    app: &tauri::AppHandle,
+    source: &Path,
+    params: &whisper::WhisperParams,
+    write_srt: bool,
+) -> Result<(), String> {
+    let source_str = source.to_string_lossy().to_string();
+    let txt_path = source.with_extension("txt");
+
+    if write_srt {
+        let output = whisper::run_whisper(app, &source_str, params, &whisper::OutputMode::Timestamped, None).await?;
+        let segments = transcript::parse_whisper_segments(&output.stdout);
+        fs::write(&txt_path, transcript::to_txt(&segments))
+            .map_err(|e| format!("Failed to write {}: {}", txt_path.display(), e))?;
+        let srt_path = source.with_extension("srt");
+        fs::write(&srt_path, transcript::to_srt(&segments))
+            .map_err(|e| format!("Failed to write {}: {}", srt_path.display(), e))?;
+    } else {
+        let text = transcribe_audio_internal(app, &source_str, params).await?;
+        fs::write(&txt_path, format!("{}\n", text))
+            .map_err(|e| format!("Failed to write {}: {}", txt_path.display(), e))?;
+    }
+    Ok(())
+}
+
+/// Transcribe every matching audio file in a directory, writing a .txt (and optionally .srt) beside each
+#[tauri::command]
+pub async fn transcribe_directory(
+    app: tauri::AppHandle,
+    state: tauri::State<'_, BatchState>,
+    dir: String,
+    pattern: Option<String>,
+    write_srt: Option<bool>,
+    model: Option<String>,
+    language: Option<String>,
+) -> Result<BatchSummary, AppError> {
+    let dir_path = paths::existing(&app, &dir)?;
+    if !dir_path.is_dir() {
+        return Err(format!("Not a directory: {}", dir).into());
+    }
+    if state.running.swap(true, Ordering::SeqCst) {
+        return Err("A batch transcription is already running".into());
+    }
+    state.cancelled.store(false, Ordering::SeqCst);
+
+    let pattern = pattern.map(|p| p.trim().to_string()).filter(|p| !p.is_empty());
+    let write_srt = write_srt.unwrap_or(false);
+    let params = whisper::WhisperParams {
+        model,
+        language: whisper::normalize_language(language),
+        ..Default::default()
+    }
+    .with_settings(&app);
+
+    let mut files: Vec<PathBuf> = match fs::read_dir(&dir_path) {
+        Ok(entries) => entries
+            .flatten()
+            .map(|e| e.path())
+            .filter(|p| p.is_file() && matches_pattern(p, pattern.as_deref()))
+            .collect(),
+        Err(e) => {
+            state.running.store(false, Ordering::SeqCst);
+            return Err(format!("Failed to read directory: {}", e).into());
+        }
+    };
+    files.sort();
+
+    let mut summary = BatchSummary {
+        total: files.len(),
+        done: 0,
+        failed: 0,
+        skipped: 0,
+        cancelled: false,
+        failures: Vec::new(),
+    };
+
+    for (i, file) in files.iter().enumerate() {
+        if state.cancelled.load(Ordering::SeqCst) {
+            summary.cancelled = true;
+            break;
+        }
+
+        let current = file.to_string_lossy().to_string();
+        let mut error = None;
+        let txt_fresh = is_up_to_date(file, &file.with_extension("txt"));
+        let srt_fresh = !write_srt || is_up_to_date(file, &file.with_extension("srt"));
+
+        if txt_fresh && srt_fresh {
+            summary.skipped += 1;
+        } else {
+            match transcribe_one(&app, file, &params, write_srt).await {
+                Ok(()) => summary.done += 1,
+                Err(e) => {
+                    summary.failed += 1;
+                    summary.failures.push(BatchFailure { path: current.clone(), error: e.clone() });
+                    error = Some(e);
+                }
+            }
+        }
+
+        let _ = app.emit("batch-progress", BatchProgress {
+            done: summary.done,
+            failed: summary.failed,
+            skipped: summary.skipped,
+            remaining: files.len() - i - 1,
+            current: Some(current),
+            error,
+        });
+    }
+
+    state.running.store(false, Ordering::SeqCst);
+    let title = if summary.cancelled { "Batch transcription cancelled" } else { "Batch transcription finished" };
+    let mut body = format!(
+        "{}: {} transcribed, {} failed, {} skipped",
+        notifications::display_name(&dir),
+        summary.done,
+        summary.failed,
+        summary.skipped
+    );
+    if let Some(failure) = summary.failures.first() {
+        body.push_str(&format!("\n{}: {}", notifications::display_name(&failure.path), notifications::first_line(&failure.error)));
+    }
+    notifications::notify(&app, NotificationKind::Batch, title, &body, Some(dir));
+    Ok(summary)
+}
+
+/// Stop the running batch once the file currently being transcribed finishes
+#[tauri::command]
+pub async fn cancel_batch(state: tauri::State<'_, BatchState>) -> Result<bool, String> {
+    if !state.running.load(Ordering::SeqCst) {
+        return Ok(false);
+    }
+    state.cancelled.store(true, Ordering::SeqCst);
+    Ok(true)
+}
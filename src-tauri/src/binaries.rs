@@ -0,0 +1,341 @@
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::sync::Mutex;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use tauri::Manager;
+
+use crate::{downloads, get_binaries_dir, get_config_dir, installed_binary_path, llama, BinaryStatus};
+
+/// How long a `--version` probe may run before it's killed
+const VERSION_TIMEOUT: Duration = Duration::from_secs(2);
+
+const GITHUB_API: &str = "https://api.github.com/repos";
+
+const WHISPER_REPO: &str = "ggerganov/whisper.cpp";
+
+/// Latest-release lookups are reused for this long to stay clear of GitHub's rate limit
+const RELEASE_CACHE_TTL_SECS: u64 = 60 * 60;
+
+const RELEASE_CACHE_FILE: &str = "release-cache.json";
+
+/// What check_binary_updates needs from a GitHub release
+#[derive(Serialize, Deserialize, Clone, Debug)]
+struct ReleaseSummary {
+    tag: String,
+    html_url: String,
+    assets: Vec<(String, String)>,
+}
+
+/// Latest releases keyed by repo, as saved in the config dir
+#[derive(Serialize, Deserialize, Default)]
+struct ReleaseCache {
+    fetched_at: u64,
+    releases: HashMap<String, ReleaseSummary>,
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct UpdateInfo {
+    pub name: String,
+    pub installed_version: Option<String>,
+    pub latest_version: String,
+    pub update_available: bool,
+    pub download_url: String,
+    pub changelog_url: String,
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct BinaryUpdates {
+    pub updates: Vec<UpdateInfo>,
+    /// Set when GitHub couldn't be reached and the releases come from an expired cache
+    pub stale: bool,
+    pub checked_at: u64,
+}
+
+// Versions probed per binary path, invalidated when the file's mtime changes
+pub struct BinaryVersionCache {
+    entries: Mutex<HashMap<PathBuf, (SystemTime, Option<String>)>>,
+}
+
+impl BinaryVersionCache {
+    pub fn new() -> Self {
+        BinaryVersionCache { entries: Mutex::new(HashMap::new()) }
+    }
+}
+
+#[cfg(unix)]
+pub fn is_executable(path: &Path) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+    fs::metadata(path).map(|m| m.is_file() && m.permissions().mode() & 0o111 != 0).unwrap_or(false)
+}
+
+#[cfg(not(unix))]
+pub fn is_executable(path: &Path) -> bool {
+    path.is_file()
+}
+
+/// A command running `binary`; one installed in the binaries dir also finds the shared libraries
+/// installed beside it, as dynamically linked prebuilt archives ship them
+pub fn command(binary: &Path) -> Command {
+    let mut cmd = Command::new(binary);
+    #[cfg(any(target_os = "linux", target_os = "macos"))]
+    if let Some(dir) = binary.parent().filter(|dir| get_binaries_dir().is_ok_and(|b| dir.starts_with(b))) {
+        let var = if cfg!(target_os = "macos") { "DYLD_LIBRARY_PATH" } else { "LD_LIBRARY_PATH" };
+        let mut paths = vec![dir.to_path_buf()];
+        paths.extend(std::env::var_os(var).iter().flat_map(std::env::split_paths));
+        if let Ok(joined) = std::env::join_paths(paths) {
+            cmd.env(var, joined);
+        }
+    }
+    cmd
+}
+
+/// Run `binary arg` and return stdout and stderr together, or None if it didn't exit in time
+pub fn run_with_timeout(binary: &Path, arg: &str) -> Option<String> {
+    let mut child = command(binary)
+        .arg(arg)
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .ok()?;
+    let deadline = Instant::now() + VERSION_TIMEOUT;
+    while child.try_wait().ok()?.is_none() {
+        if Instant::now() >= deadline {
+            let _ = child.kill();
+            let _ = child.wait();
+            return None;
+        }
+        std::thread::sleep(Duration::from_millis(50));
+    }
+    let output = child.wait_with_output().ok()?;
+    Some(format!("{}\n{}", String::from_utf8_lossy(&output.stdout), String::from_utf8_lossy(&output.stderr)))
+}
+
+fn looks_like_version(token: &str) -> bool {
+    let token = token.trim_start_matches('v');
+    !token.is_empty()
+        && token.starts_with(|c: char| c.is_ascii_digit())
+        && token.chars().all(|c| c.is_ascii_digit() || c == '.')
+}
+
+/// Pull the version out of `--version`/`--help` output: "version: 6550 (abc)", "whisper.cpp v1.8.2", ...
+pub fn parse_version(output: &str) -> Option<String> {
+    let tokens = |line: &str| -> Vec<String> {
+        line.split(|c: char| c.is_whitespace() || matches!(c, '(' | ')' | ',' | ':' | '='))
+            .filter(|t| !t.is_empty())
+            .map(|t| t.trim_start_matches('v').trim_end_matches('.').to_string())
+            .collect()
+    };
+    let labelled = output
+        .lines()
+        .filter(|line| line.to_lowercase().contains("version"))
+        .find_map(|line| tokens(line).into_iter().find(|t| looks_like_version(t)));
+    // Without a "version" label only dotted numbers count; help text is full of bare defaults like "4"
+    labelled.or_else(|| {
+        output.lines().find_map(|line| tokens(line).into_iter().find(|t| looks_like_version(t) && t.contains('.')))
+    })
+}
+
+/// Installed version of `binary`, probing it only when it's new or has changed on disk
+pub fn binary_version(app: &tauri::AppHandle, binary: &Path) -> Option<String> {
+    let mtime = fs::metadata(binary).and_then(|m| m.modified()).ok()?;
+    let cache = app.state::<BinaryVersionCache>();
+    if let Some((cached_mtime, version)) = cache.entries.lock().unwrap().get(binary) {
+        if *cached_mtime == mtime {
+            return version.clone();
+        }
+    }
+
+    // whisper-cli has no --version flag, but --help prints what it knows about its build to stderr
+    let version = ["--version", "--help"]
+        .iter()
+        .filter_map(|arg| run_with_timeout(binary, arg))
+        .find_map(|output| parse_version(&output));
+    cache.entries.lock().unwrap().insert(binary.to_path_buf(), (mtime, version.clone()));
+    version
+}
+
+pub fn binary_status(app: &tauri::AppHandle, binary_name: String) -> Result<BinaryStatus, String> {
+    let binary_path = installed_binary_path(&binary_name)?;
+    let installed = binary_path.exists();
+    let is_executable = installed && is_executable(&binary_path);
+
+    Ok(BinaryStatus {
+        version: if is_executable { binary_version(app, &binary_path) } else { None },
+        name: binary_name,
+        installed,
+        path: if installed { Some(binary_path.to_string_lossy().to_string()) } else { None },
+        is_executable,
+        sha256: None,
+    })
+}
+
+fn sha256_file(path: &Path) -> Result<String, String> {
+    let mut file = fs::File::open(path).map_err(|e| format!("Failed to open {}: {}", path.display(), e))?;
+    let mut hasher = Sha256::new();
+    std::io::copy(&mut file, &mut hasher).map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+    Ok(hex::encode(hasher.finalize()))
+}
+
+/// check_binary_status plus the binary's SHA-256, for spotting a corrupted download
+#[tauri::command]
+pub async fn verify_binary(app: tauri::AppHandle, binary_name: String) -> Result<BinaryStatus, String> {
+    tauri::async_runtime::spawn_blocking(move || {
+        let mut status = binary_status(&app, binary_name)?;
+        if let Some(path) = &status.path {
+            status.sha256 = Some(sha256_file(Path::new(path))?);
+        }
+        Ok(status)
+    })
+    .await
+    .map_err(|e| format!("Verify task failed: {}", e))?
+}
+
+/// Fetch `releases/<which>` of a GitHub repo, e.g. "latest" or "tags/b6550"
+pub async fn fetch_release(repo: &str, which: &str) -> Result<serde_json::Value, String> {
+    let body = downloads::http_client()?
+        .get(format!("{}/{}/releases/{}", GITHUB_API, repo, which))
+        .header(reqwest::header::USER_AGENT, "last-gen-notes")
+        .send()
+        .await
+        .map_err(|e| format!("Failed to look up {} release: {}", repo, e))?
+        .error_for_status()
+        .map_err(|e| format!("Failed to look up {} release: {}", repo, e))?
+        .text()
+        .await
+        .map_err(|e| format!("Failed to read {} release info: {}", repo, e))?;
+    serde_json::from_str(&body).map_err(|e| format!("Failed to parse {} release info: {}", repo, e))
+}
+
+async fn fetch_latest(repo: &str) -> Result<ReleaseSummary, String> {
+    let release = fetch_release(repo, "latest").await?;
+    let tag = release["tag_name"].as_str().ok_or_else(|| format!("{} release has no tag", repo))?;
+    let assets = release["assets"]
+        .as_array()
+        .map(|assets| {
+            assets
+                .iter()
+                .filter_map(|a| Some((a["name"].as_str()?.to_string(), a["browser_download_url"].as_str()?.to_string())))
+                .collect()
+        })
+        .unwrap_or_default();
+    Ok(ReleaseSummary {
+        tag: tag.to_string(),
+        html_url: release["html_url"].as_str().unwrap_or_default().to_string(),
+        assets,
+    })
+}
+
+fn now_secs() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0)
+}
+
+fn load_release_cache() -> Option<ReleaseCache> {
+    let raw = fs::read_to_string(get_config_dir().ok()?.join(RELEASE_CACHE_FILE)).ok()?;
+    serde_json::from_str(&raw).ok()
+}
+
+/// Latest releases of `repos`, from the cache while it's fresh; the flag is true when an expired
+/// cache had to stand in because GitHub couldn't be reached
+async fn latest_releases(repos: &[&str]) -> Result<(ReleaseCache, bool), String> {
+    let cached = load_release_cache();
+    if let Some(cache) = &cached {
+        let fresh = now_secs().saturating_sub(cache.fetched_at) < RELEASE_CACHE_TTL_SECS;
+        if fresh && repos.iter().all(|repo| cache.releases.contains_key(*repo)) {
+            return Ok((cached.unwrap(), false));
+        }
+    }
+
+    let mut releases = HashMap::new();
+    for repo in repos {
+        match fetch_latest(repo).await {
+            Ok(release) => {
+                releases.insert(repo.to_string(), release);
+            }
+            Err(e) => {
+                return match cached {
+                    Some(cache) => Ok((cache, true)),
+                    None => Err(e),
+                };
+            }
+        }
+    }
+
+    let cache = ReleaseCache { fetched_at: now_secs(), releases };
+    if let Ok(json) = serde_json::to_string_pretty(&cache) {
+        let _ = get_config_dir().map(|dir| fs::write(dir.join(RELEASE_CACHE_FILE), json));
+    }
+    Ok((cache, false))
+}
+
+/// Numeric parts of a version or tag: "v1.8.2" -> [1, 8, 2], llama.cpp's "b6550" -> [6550]
+fn version_parts(version: &str) -> Vec<u64> {
+    version
+        .trim_start_matches(['v', 'b'])
+        .split('.')
+        .map_while(|part| part.parse().ok())
+        .collect()
+}
+
+/// Release asset this platform's installer would download, by name
+fn platform_asset(name: &str, tag: &str) -> Option<String> {
+    match name {
+        "whisper-cli" if cfg!(target_os = "windows") => Some(
+            if cfg!(target_pointer_width = "64") { "whisper-bin-x64.zip" } else { "whisper-bin-Win32.zip" }.to_string(),
+        ),
+        "whisper-cli" if cfg!(target_os = "macos") => Some(format!("whisper-{}-xcframework.zip", tag)),
+        "llama-cli" => llama::asset_name(tag).ok(),
+        _ => None,
+    }
+}
+
+/// Compare installed whisper-cli and llama-cli versions against their latest GitHub releases
+#[tauri::command]
+pub async fn check_binary_updates(app: tauri::AppHandle) -> Result<BinaryUpdates, String> {
+    let binaries = [("whisper-cli", WHISPER_REPO), ("llama-cli", llama::REPO)];
+    let repos: Vec<&str> = binaries.iter().map(|(_, repo)| *repo).collect();
+    let (cache, stale) = latest_releases(&repos).await?;
+
+    let status_app = app.clone();
+    let installed = tauri::async_runtime::spawn_blocking(move || {
+        binaries
+            .iter()
+            .map(|(name, _)| binary_status(&status_app, name.to_string()).ok().and_then(|s| s.version))
+            .collect::<Vec<_>>()
+    })
+    .await
+    .map_err(|e| format!("Version check failed: {}", e))?;
+
+    let updates = binaries
+        .iter()
+        .zip(installed)
+        .filter_map(|((name, repo), installed_version)| {
+            let release = cache.releases.get(*repo)?;
+            let latest = version_parts(&release.tag);
+            let update_available = installed_version
+                .as_deref()
+                .map(|v| {
+                    let current = version_parts(v);
+                    !current.is_empty() && latest > current
+                })
+                .unwrap_or(false);
+            let download_url = platform_asset(name, &release.tag)
+                .and_then(|asset| release.assets.iter().find(|(n, _)| *n == asset).map(|(_, url)| url.clone()))
+                .unwrap_or_else(|| release.html_url.clone());
+            Some(UpdateInfo {
+                name: name.to_string(),
+                installed_version,
+                latest_version: release.tag.trim_start_matches('v').to_string(),
+                update_available,
+                download_url,
+                changelog_url: release.html_url.clone(),
+            })
+        })
+        .collect();
+
+    Ok(BinaryUpdates { updates, stale, checked_at: cache.fetched_at })
+}
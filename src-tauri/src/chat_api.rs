@@ -0,0 +1,136 @@
+use futures_util::StreamExt;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Instant;
+use tauri::Emitter;
+
+use crate::downloads;
+use crate::error::AppError;
+use crate::settings::AppSettings;
+use crate::summarize::{self, LlamaRun, SummarizeProgress, SummaryToken};
+
+/// An OpenAI-compatible chat completions API: our llama-server, or the user's remote one
+#[derive(Clone, Debug)]
+pub struct Endpoint {
+    /// For log lines and error messages
+    pub name: &'static str,
+    /// API root that /chat/completions is appended to
+    pub base_url: String,
+    pub api_key: Option<String>,
+    /// Sent as `model`; llama-server ignores it, most hosted APIs require it
+    pub model: Option<String>,
+}
+
+impl Endpoint {
+    /// The endpoint configured for the remote backend
+    pub fn remote(settings: &AppSettings) -> Result<Endpoint, AppError> {
+        let base_url = settings.remote_base_url.clone().ok_or(AppError::SetupRequired {
+            step: "remote".to_string(),
+            message: "Set a base URL for the remote summarization backend in Settings".to_string(),
+        })?;
+        Ok(Endpoint {
+            name: "remote endpoint",
+            base_url: base_url.trim_end_matches('/').to_string(),
+            api_key: settings.remote_api_key.clone().filter(|key| !key.is_empty()),
+            model: settings.remote_model.clone(),
+        })
+    }
+}
+
+/// Remote requests go through the download proxy, if one is configured
+pub fn remote_client() -> Result<reqwest::Client, String> {
+    downloads::DownloadSettings::load().client()
+}
+
+/// Text delta of one streamed chat completion chunk, or None for `[DONE]` and chunks without content
+fn sse_delta(line: &str) -> Option<String> {
+    let data = line.strip_prefix("data:")?.trim();
+    if data == "[DONE]" {
+        return None;
+    }
+    let chunk: serde_json::Value = serde_json::from_str(data).ok()?;
+    chunk["choices"][0]["delta"]["content"].as_str().map(str::to_string)
+}
+
+/// Send `prompt` as a streamed chat completion, emitting summary-token events for its deltas when `stream` is set.
+/// Stops reading, which makes the server abandon the request, once `cancelled` is set.
+pub async fn stream_chat(
+    app: &tauri::AppHandle,
+    client: &reqwest::Client,
+    endpoint: &Endpoint,
+    run: &LlamaRun,
+    prompt: &str,
+    stream: bool,
+    cancelled: &AtomicBool,
+) -> Result<String, AppError> {
+    let mut body = serde_json::json!({
+        "messages": [{ "role": "user", "content": prompt }],
+        "max_tokens": run.max_tokens,
+        "temperature": run.temperature,
+        "stream": true,
+    });
+    if let Some(model) = &endpoint.model {
+        body["model"] = serde_json::Value::String(model.clone());
+    }
+    let mut request = client
+        .post(format!("{}/chat/completions", endpoint.base_url))
+        .header("Content-Type", "application/json")
+        .body(body.to_string());
+    if let Some(key) = &endpoint.api_key {
+        request = request.bearer_auth(key);
+    }
+
+    let response = request
+        .send()
+        .await
+        .map_err(|e| AppError::Network(format!("Couldn't reach the {}: {}", endpoint.name, e)))?;
+    if !response.status().is_success() {
+        let status = response.status();
+        let text = response.text().await.unwrap_or_default();
+        return Err(AppError::ProcessFailed {
+            cmd: endpoint.base_url.clone(),
+            stderr: text.clone(),
+            message: format!("The {} returned {}: {}", endpoint.name, status, text),
+        });
+    }
+
+    let started = Instant::now();
+    let mut last_progress = started;
+    let mut summary = String::new();
+    // Split on newlines before decoding, so a character split across chunks is never cut
+    let mut pending: Vec<u8> = Vec::new();
+    let mut body = response.bytes_stream();
+    while let Some(chunk) = body.next().await {
+        if cancelled.load(Ordering::Relaxed) {
+            break;
+        }
+        let chunk = chunk.map_err(|e| AppError::Network(format!("Lost the connection to the {}: {}", endpoint.name, e)))?;
+        pending.extend_from_slice(&chunk);
+        while let Some(newline) = pending.iter().position(|&b| b == b'\n') {
+            let line: Vec<u8> = pending.drain(..=newline).collect();
+            let Some(mut delta) = sse_delta(String::from_utf8_lossy(&line).trim()) else {
+                continue;
+            };
+            if summary.is_empty() {
+                delta = delta.trim_start().to_string();
+            }
+            if delta.is_empty() {
+                continue;
+            }
+            summary.push_str(&delta);
+            if stream {
+                let _ = app.emit("summary-token", SummaryToken { delta });
+            }
+        }
+        if last_progress.elapsed() >= summarize::HEARTBEAT_INTERVAL {
+            last_progress = Instant::now();
+            let _ = app.emit("summarize-progress", SummarizeProgress {
+                tokens: summary.split_whitespace().count(),
+                elapsed_secs: started.elapsed().as_secs_f32(),
+            });
+        }
+    }
+
+    let summary = summary.trim_end().to_string();
+    log::info!("{} finished ({} chars)", endpoint.name, summary.len());
+    Ok(summary)
+}
@@ -0,0 +1,182 @@
+use serde::Serialize;
+use std::path::PathBuf;
+use std::time::Duration;
+use tauri::Manager;
+
+use crate::{commit_labelled, events, live_queue, notes, session, transcribe_audio_internal, whisper, ChunkedRecorderState};
+
+/// Background attempts after the first failure, waiting 2, 4 and then 8 seconds before each
+const MAX_RETRIES: u32 = 3;
+const BASE_BACKOFF_SECS: u64 = 2;
+
+/// What stands in for a failed chunk's text in the stitched live transcript
+const PLACEHOLDER_PREFIX: &str = "[transcription failed";
+
+/// A live chunk whose transcription failed, kept until it's transcribed again
+#[derive(Serialize, Clone)]
+pub struct FailedChunk {
+    pub index: usize,
+    pub path: String,
+    pub start_secs: u64,
+    pub end_secs: u64,
+    pub error: String,
+    pub attempts: u32,
+    /// Still being retried in the background
+    pub retrying: bool,
+    #[serde(skip)]
+    session_id: u64,
+    #[serde(skip)]
+    params: whisper::WhisperParams,
+}
+
+fn format_clock(secs: u64) -> String {
+    format!("{:02}:{:02}:{:02}", secs / 3600, secs / 60 % 60, secs % 60)
+}
+
+fn placeholder(start_secs: u64, end_secs: u64) -> String {
+    format!("{} {}–{}]", PLACEHOLDER_PREFIX, format_clock(start_secs), format_clock(end_secs))
+}
+
+/// Put the chunk's text where its placeholder is, if the session it belongs to is still the current one.
+/// Once that session has stopped and been saved as a note, the note's copy of the placeholder is replaced too.
+fn fill_slot(app: &tauri::AppHandle, state: &ChunkedRecorderState, chunk: &FailedChunk, text: &str) {
+    if *state.session_id.lock() != chunk.session_id {
+        return;
+    }
+    state.transcripts.lock().retried(chunk.index, text);
+    if *state.active.lock() {
+        return;
+    }
+    let note_id = PathBuf::from(&chunk.path).parent().and_then(session::read_manifest).and_then(|m| m.note_id);
+    if let Some(id) = note_id {
+        patch_note(app, id, &placeholder(chunk.start_secs, chunk.end_secs), text.trim());
+    }
+}
+
+fn patch_note(app: &tauri::AppHandle, id: u64, placeholder: &str, text: &str) {
+    let transcript = match notes::get(app, id) {
+        Ok(note) if note.transcript.contains(placeholder) => note.transcript.replacen(placeholder, text, 1),
+        Ok(_) => return,
+        Err(e) => {
+            log::warn!("Couldn't fill in a retried chunk in note {}: {}", id, e);
+            return;
+        }
+    };
+    let patch = notes::NotePatch { transcript: Some(transcript), ..Default::default() };
+    if let Err(e) = notes::update(app, id, patch) {
+        log::warn!("Couldn't fill in a retried chunk in note {}: {}", id, e);
+    }
+}
+
+/// Transcribe a failed chunk once more, replacing its placeholder and dropping it from the list on success
+async fn transcribe_again(app: &tauri::AppHandle, chunk: &FailedChunk) -> Result<String, String> {
+    let text = transcribe_audio_internal(app, &chunk.path, &chunk.params).await?;
+    let state = app.state::<ChunkedRecorderState>();
+    fill_slot(app, &state, chunk, &text);
+    state.failed_chunks.lock().retain(|c| !(c.index == chunk.index && c.session_id == chunk.session_id));
+    if let Some(dir) = PathBuf::from(&chunk.path).parent() {
+        if let Err(e) = session::record_chunk(dir, chunk.index, &text) {
+            events::emit(app, "live-recording-error", e);
+        }
+    }
+    events::emit(app, "live-transcript-chunk", serde_json::json!({
+        "chunk": chunk.index,
+        "text": text,
+        "path": chunk.path,
+        "retried": true,
+    }));
+    Ok(text)
+}
+
+/// Hold a placeholder for a chunk whose transcription failed and retry it with exponential backoff
+/// in the background; if every retry fails it stays in get_failed_chunks for retranscribe_chunk
+pub fn chunk_failed(
+    app: &tauri::AppHandle,
+    index: usize,
+    path: &str,
+    start_secs: u64,
+    segment_len: u64,
+    params: &whisper::WhisperParams,
+    error: String,
+) {
+    let state = app.state::<ChunkedRecorderState>();
+    let mut chunk = FailedChunk {
+        index,
+        path: path.to_string(),
+        start_secs,
+        end_secs: start_secs + segment_len,
+        error,
+        attempts: 1,
+        retrying: true,
+        session_id: *state.session_id.lock(),
+        params: params.clone(),
+    };
+    state.transcripts.lock().failed(index, placeholder(chunk.start_secs, chunk.end_secs));
+    if let Some(dir) = PathBuf::from(path).parent() {
+        commit_labelled(app, &state.transcripts, dir);
+    }
+    state.failed_chunks.lock().push(chunk.clone());
+
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        for retry in 0..MAX_RETRIES {
+            tokio::time::sleep(Duration::from_secs(BASE_BACKOFF_SECS << retry)).await;
+            chunk.attempts += 1;
+            match live_queue::run_retry(&app, transcribe_again(&app, &chunk)).await {
+                Ok(_) => {
+                    log::info!("Live chunk {} transcribed on attempt {}", chunk.index, chunk.attempts);
+                    return;
+                }
+                Err(e) => {
+                    log::warn!("Retrying live chunk {} failed (attempt {}): {}", chunk.index, chunk.attempts, e);
+                    chunk.error = e;
+                }
+            }
+        }
+
+        let state = app.state::<ChunkedRecorderState>();
+        let mut failed = state.failed_chunks.lock();
+        if let Some(entry) = failed.iter_mut().find(|c| c.index == chunk.index && c.session_id == chunk.session_id) {
+            entry.attempts = chunk.attempts;
+            entry.error = chunk.error.clone();
+            entry.retrying = false;
+        }
+        events::emit(
+            &app,
+            "live-recording-error",
+            format!("Chunk {} couldn't be transcribed after {} attempts: {}", chunk.index, chunk.attempts, chunk.error),
+        );
+    });
+}
+
+/// Live chunks whose transcription failed in the current or last session, oldest first
+#[tauri::command]
+pub async fn get_failed_chunks(state: tauri::State<'_, ChunkedRecorderState>) -> Result<Vec<FailedChunk>, String> {
+    let mut failed = state.failed_chunks.lock().clone();
+    failed.sort_by_key(|c| c.index);
+    Ok(failed)
+}
+
+/// Transcribe a failed live chunk again, returning its text
+#[tauri::command]
+pub async fn retranscribe_chunk(app: tauri::AppHandle, index: usize) -> Result<String, String> {
+    let chunk = {
+        let state = app.state::<ChunkedRecorderState>();
+        let failed = state.failed_chunks.lock();
+        let chunk = failed.iter().find(|c| c.index == index).ok_or_else(|| format!("Chunk {} hasn't failed", index))?;
+        if chunk.retrying {
+            return Err(format!("Chunk {} is still being retried", index));
+        }
+        chunk.clone()
+    };
+    let result = transcribe_again(&app, &chunk).await;
+    if let Err(e) = &result {
+        let state = app.state::<ChunkedRecorderState>();
+        let mut failed = state.failed_chunks.lock();
+        if let Some(entry) = failed.iter_mut().find(|c| c.index == index) {
+            entry.attempts += 1;
+            entry.error = e.clone();
+        }
+    }
+    result
+}
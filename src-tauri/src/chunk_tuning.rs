@@ -0,0 +1,72 @@
+use std::time::Duration;
+
+/// A chunk's text should appear within this multiple of its length of when it started recording
+const LATENCY_TARGET: f32 = 1.5;
+
+/// Under this share of the latency budget, chunks are shortened to bring text in sooner
+const SHRINK_BELOW: f32 = 0.5;
+
+/// Weight of the newest measurement in the smoothed latency ratio
+const SMOOTHING: f32 = 0.5;
+
+const GROW_FACTOR: f32 = 1.25;
+const SHRINK_FACTOR: f32 = 0.8;
+
+/// Picks each live chunk's length from how quickly earlier chunks came back. Every whisper run pays
+/// a fixed cost to load the model, so a slow machine keeps up better with fewer, longer chunks,
+/// and a fast one can afford short ones for snappier text.
+pub struct SegmentTuner {
+    adaptive: bool,
+    min: u64,
+    max: u64,
+    current: u64,
+    /// Smoothed latency over the latency budget; above 1 chunks come back too late
+    ratio: Option<f32>,
+}
+
+impl SegmentTuner {
+    /// A tuner that always returns `segment_secs`
+    pub fn fixed(segment_secs: u64) -> Self {
+        SegmentTuner { adaptive: false, min: segment_secs, max: segment_secs, current: segment_secs, ratio: None }
+    }
+
+    /// Start at `segment_secs` and adapt within `min..=max`
+    pub fn adaptive(segment_secs: u64, min: u64, max: u64) -> Self {
+        SegmentTuner { adaptive: true, min, max, current: segment_secs.clamp(min, max), ratio: None }
+    }
+
+    /// Length in seconds for the next chunk
+    pub fn current(&self) -> u64 {
+        self.current
+    }
+
+    /// Note that a `segment_secs` chunk's text arrived `latency` after the chunk finished recording
+    /// (queueing included), and adjust the length of the chunks after it
+    pub fn observe(&mut self, segment_secs: u64, latency: Duration) {
+        if !self.adaptive {
+            return;
+        }
+        let budget = (LATENCY_TARGET - 1.0) * segment_secs.max(1) as f32;
+        let ratio = latency.as_secs_f32() / budget;
+        let smoothed = self.ratio.map_or(ratio, |previous| previous * (1.0 - SMOOTHING) + ratio * SMOOTHING);
+        self.ratio = Some(smoothed);
+
+        let next = if smoothed > 1.0 {
+            (self.current as f32 * GROW_FACTOR).ceil() as u64
+        } else if smoothed < SHRINK_BELOW {
+            (self.current as f32 * SHRINK_FACTOR).floor() as u64
+        } else {
+            self.current
+        }
+        .clamp(self.min, self.max);
+        if next != self.current {
+            log::info!(
+                "Live chunks came back at {:.2}x their latency budget; next chunks will be {}s (was {}s)",
+                smoothed,
+                next,
+                self.current
+            );
+            self.current = next;
+        }
+    }
+}
@@ -0,0 +1,151 @@
+use regex::Regex;
+use std::sync::LazyLock;
+use tauri_plugin_clipboard_manager::ClipboardExt;
+
+use crate::{notes, transcript};
+
+/// Larger copies can stall the clipboard owner (and whatever's pasted into) for a long time
+const MAX_CLIPBOARD_BYTES: usize = 2 * 1024 * 1024;
+
+/// Inline whisper timestamps, "[00:01:02.500]" or "[00:01:02.500 --> 00:01:05.000]"
+static TIMESTAMP_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"\[\d{2}:\d{2}:\d{2}(?:[.,]\d{3})?(?:\s*-->\s*\d{2}:\d{2}:\d{2}(?:[.,]\d{3})?)?\]\s*").unwrap()
+});
+
+static NUMBERED_RE: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"^\d+[.)]\s+").unwrap());
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum ClipboardFormat {
+    Text,
+    /// Lists become HTML lists, with the text itself as the plain-text alternative
+    Html,
+}
+
+impl ClipboardFormat {
+    fn parse(format: Option<&str>) -> Result<Self, String> {
+        match format.map(str::to_lowercase).as_deref() {
+            None | Some("text") | Some("plain") => Ok(ClipboardFormat::Text),
+            Some("html") if cfg!(any(target_os = "linux", target_os = "windows")) => Ok(ClipboardFormat::Html),
+            Some("html") => Err("Copying as HTML is only supported on Linux and Windows".to_string()),
+            Some(other) => Err(format!("Unsupported clipboard format '{}' (expected text or html)", other)),
+        }
+    }
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;")
+}
+
+/// Paragraphs, bullet lists ("- ", "* ", "• ") and numbered lists as HTML, so summaries keep their
+/// structure when pasted into an email
+fn to_html(text: &str) -> String {
+    #[derive(PartialEq)]
+    enum Block {
+        None,
+        Paragraph,
+        Bullets,
+        Numbered,
+    }
+    let close = |block: &Block, html: &mut String| match block {
+        Block::Paragraph => html.push_str("</p>"),
+        Block::Bullets => html.push_str("</ul>"),
+        Block::Numbered => html.push_str("</ol>"),
+        Block::None => {}
+    };
+
+    let mut html = String::new();
+    let mut block = Block::None;
+    for line in text.lines().map(str::trim) {
+        let (next, content) = if line.is_empty() {
+            (Block::None, "")
+        } else if let Some(item) = ["- ", "* ", "• "].iter().find_map(|p| line.strip_prefix(p)) {
+            (Block::Bullets, item)
+        } else if let Some(marker) = NUMBERED_RE.find(line) {
+            (Block::Numbered, &line[marker.end()..])
+        } else {
+            (Block::Paragraph, line)
+        };
+
+        if next != block {
+            close(&block, &mut html);
+            match next {
+                Block::Paragraph => html.push_str("<p>"),
+                Block::Bullets => html.push_str("<ul>"),
+                Block::Numbered => html.push_str("<ol>"),
+                Block::None => {}
+            }
+        } else if next == Block::Paragraph {
+            html.push_str("<br>");
+        }
+        match next {
+            Block::Bullets | Block::Numbered => html.push_str(&format!("<li>{}</li>", escape_html(content))),
+            Block::Paragraph => html.push_str(&escape_html(content)),
+            Block::None => {}
+        }
+        block = next;
+    }
+    close(&block, &mut html);
+    html
+}
+
+/// Put `text` on the clipboard, returning its length in characters
+async fn write(app: &tauri::AppHandle, text: String, format: ClipboardFormat) -> Result<usize, String> {
+    if text.len() > MAX_CLIPBOARD_BYTES {
+        return Err(format!(
+            "That's {:.1} MB of text, more than the clipboard's {} MB limit; export it to a file instead",
+            text.len() as f64 / (1024.0 * 1024.0),
+            MAX_CLIPBOARD_BYTES / (1024 * 1024)
+        ));
+    }
+    let chars = text.chars().count();
+    let app = app.clone();
+    // Setting the clipboard waits on the display server, so it stays off the async runtime
+    tauri::async_runtime::spawn_blocking(move || match format {
+        ClipboardFormat::Text => app.clipboard().write_text(text),
+        ClipboardFormat::Html => app.clipboard().write_html(to_html(&text), Some(text)),
+    })
+    .await
+    .map_err(|e| format!("Clipboard task failed: {}", e))?
+    .map_err(|e| format!("Failed to copy to the clipboard: {}", e))?;
+    Ok(chars)
+}
+
+/// Copy text to the clipboard as "text" or, on Linux and Windows, "html"
+#[tauri::command]
+pub async fn copy_to_clipboard(app: tauri::AppHandle, text: String, format: String) -> Result<(), String> {
+    write(&app, text, ClipboardFormat::parse(Some(&format))?).await.map(|_| ())
+}
+
+/// Copy a note's "transcript", "summary" or "both", returning how many characters were copied.
+/// Timestamps are stripped unless `keep_timestamps` is set, which also timestamps each segment
+/// of notes that have them.
+#[tauri::command]
+pub async fn copy_note(
+    app: tauri::AppHandle,
+    note_id: u64,
+    what: String,
+    keep_timestamps: Option<bool>,
+    format: Option<String>,
+) -> Result<usize, String> {
+    let format = ClipboardFormat::parse(format.as_deref())?;
+    let note = notes::get(&app, note_id)?;
+    let transcript = if !keep_timestamps.unwrap_or(false) {
+        TIMESTAMP_RE.replace_all(note.transcript.trim(), "").to_string()
+    } else if !note.segments.is_empty() {
+        transcript::to_timestamped_txt(&note.segments)
+    } else {
+        note.transcript.trim().to_string()
+    };
+    let summary = note.summary.as_deref().map(str::trim).filter(|s| !s.is_empty());
+
+    let text = match what.as_str() {
+        "transcript" => transcript,
+        "summary" => summary.ok_or_else(|| format!("Note {} has no summary", note_id))?.to_string(),
+        "both" => match summary {
+            Some(summary) => format!("Summary\n\n{}\n\nTranscript\n\n{}", summary, transcript),
+            None => transcript,
+        },
+        other => return Err(format!("Unknown part '{}' (expected transcript, summary, or both)", other)),
+    };
+    write(&app, text, format).await
+}
@@ -0,0 +1,176 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use tauri::Manager;
+
+use crate::audio::{self, PreparedAudio, WHISPER_SAMPLE_RATE};
+use crate::{events, get_cache_dir, has_ffmpeg, models, processes};
+
+/// ffmpeg filter audio goes through before whisper sees it
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Hash, Debug, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum NoiseReduction {
+    #[default]
+    Off,
+    /// FFT denoiser, good for steady noise like HVAC hum
+    Afftdn,
+    /// RNNoise, better with keyboard clatter; needs a .rnnn model in the models dir
+    Arnndn,
+}
+
+impl NoiseReduction {
+    fn filter(self) -> &'static str {
+        match self {
+            NoiseReduction::Off => "",
+            NoiseReduction::Afftdn => "afftdn",
+            NoiseReduction::Arnndn => "arnndn",
+        }
+    }
+}
+
+// Whether this ffmpeg has each filter, checked once; a missing one is warned about once and then
+// skipped, so live chunks don't each fail on it
+pub struct DenoiseState {
+    available: Mutex<HashMap<NoiseReduction, bool>>,
+    warned_no_model: AtomicBool,
+}
+
+impl DenoiseState {
+    pub fn new() -> Self {
+        DenoiseState { available: Mutex::new(HashMap::new()), warned_no_model: AtomicBool::new(false) }
+    }
+}
+
+fn has_filter(name: &str) -> bool {
+    Command::new("ffmpeg")
+        .arg("-hide_banner")
+        .arg("-filters")
+        .output()
+        .map(|out| {
+            String::from_utf8_lossy(&out.stdout)
+                .lines()
+                .any(|line| line.split_whitespace().nth(1) == Some(name))
+        })
+        .unwrap_or(false)
+}
+
+/// Check for the filter the first time it's used, sending `noise-reduction-unavailable` if it's missing
+fn available(app: &tauri::AppHandle, mode: NoiseReduction) -> bool {
+    let state = app.state::<DenoiseState>();
+    let mut available = state.available.lock().unwrap();
+    if let Some(&known) = available.get(&mode) {
+        return known;
+    }
+    let found = has_ffmpeg() && has_filter(mode.filter());
+    available.insert(mode, found);
+    if !found {
+        let message = if has_ffmpeg() {
+            format!("This ffmpeg has no {} filter; transcribing without noise reduction", mode.filter())
+        } else {
+            "Noise reduction needs ffmpeg; transcribing without it".to_string()
+        };
+        log::warn!("{}", message);
+        events::emit(app, "noise-reduction-unavailable", serde_json::json!({ "filter": mode.filter(), "message": message }));
+    }
+    found
+}
+
+/// The first RNNoise model (.rnnn) in the models dir
+fn rnnoise_model() -> Option<PathBuf> {
+    let mut found: Vec<PathBuf> = fs::read_dir(models::get_models_dir().ok()?)
+        .ok()?
+        .flatten()
+        .map(|e| e.path())
+        .filter(|p| p.extension().is_some_and(|e| e.eq_ignore_ascii_case("rnnn")))
+        .collect();
+    found.sort();
+    found.into_iter().next()
+}
+
+/// arnndn with `model`. Filter options are ':'-separated, so the path is quoted; a quote in it
+/// closes the quoting, adds an escaped quote and reopens it.
+fn arnndn_filter(model: &Path) -> String {
+    let model = model.to_string_lossy().replace('\\', "/").replace('\'', "'\\''");
+    format!("arnndn=m='{}'", model)
+}
+
+/// The filtergraph for `mode`; arnndn without a model falls back to afftdn, warning once. Blocking.
+fn filtergraph(app: &tauri::AppHandle, mode: NoiseReduction) -> Option<String> {
+    if mode == NoiseReduction::Arnndn {
+        match rnnoise_model() {
+            Some(model) => return available(app, mode).then(|| arnndn_filter(&model)),
+            None => {
+                if !app.state::<DenoiseState>().warned_no_model.swap(true, Ordering::Relaxed) {
+                    let message = format!(
+                        "arnndn needs an RNNoise model; download '{}' from the model manager. Using afftdn instead",
+                        models::RNNOISE_MODELS[0].name
+                    );
+                    log::warn!("{}", message);
+                    events::emit(app, "noise-reduction-unavailable", serde_json::json!({ "filter": "arnndn", "message": message }));
+                }
+                return filtergraph(app, NoiseReduction::Afftdn);
+            }
+        }
+    }
+    available(app, mode).then(|| mode.filter().to_string())
+}
+
+/// A denoised temp copy of `input` (already whisper-ready) for whisper to read instead, or None to
+/// use `input` as it is: when noise reduction is off, ffmpeg can't do it, or it failed on this file
+pub async fn prepare(app: &tauri::AppHandle, input: &Path, mode: NoiseReduction) -> Option<PreparedAudio> {
+    if mode == NoiseReduction::Off {
+        return None;
+    }
+    // convert- keeps it out of the recordings list, like other transcription temp files
+    let denoised = PreparedAudio::temp(get_cache_dir().ok()?.join(format!("{}.wav", audio::unique_name("convert-denoise"))));
+
+    let (task_app, source, output) = (app.clone(), input.to_path_buf(), denoised.path.clone());
+    let result = tauri::async_runtime::spawn_blocking(move || {
+        let filter = filtergraph(&task_app, mode)?;
+        Some(processes::output(
+            &task_app,
+            Command::new("ffmpeg")
+                .arg("-hide_banner")
+                .arg("-loglevel").arg("error")
+                .arg("-y")
+                .arg("-i").arg(&source)
+                .arg("-af").arg(&filter)
+                .arg("-ar").arg(WHISPER_SAMPLE_RATE.to_string())
+                .arg("-ac").arg("1")
+                .arg("-c:a").arg("pcm_s16le")
+                .arg(&output),
+        ))
+    })
+    .await;
+    match result {
+        Ok(None) => None,
+        Ok(Some(Ok(out))) if out.status.success() => Some(denoised),
+        Ok(Some(Ok(out))) => {
+            log::warn!("Noise reduction failed on {}: {}", input.display(), String::from_utf8_lossy(&out.stderr).trim());
+            None
+        }
+        Ok(Some(Err(e))) => {
+            log::warn!("Failed to start ffmpeg for noise reduction: {}", e);
+            None
+        }
+        Err(e) => {
+            log::warn!("Noise reduction task failed: {}", e);
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn quotes_the_model_path_for_arnndn() {
+        assert_eq!(arnndn_filter(Path::new("/models/C:sh.rnnn")), "arnndn=m='/models/C:sh.rnnn'");
+        assert_eq!(arnndn_filter(Path::new("/home/o'brien/sh.rnnn")), r"arnndn=m='/home/o'\''brien/sh.rnnn'");
+    }
+}
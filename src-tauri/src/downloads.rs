@@ -0,0 +1,322 @@
+use futures_util::StreamExt;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+use tauri::{Emitter, Manager};
+use tokio::io::AsyncWriteExt;
+
+use crate::error::AppError;
+use crate::{emit_progress, get_config_dir, models};
+
+pub const WHISPER_RELEASES_URL: &str = "https://github.com/ggerganov/whisper.cpp/releases/download";
+
+const SETTINGS_FILE: &str = "download-settings.json";
+
+/// Mirrors and proxy used for whisper and model downloads; unset fields use the canonical hosts
+#[derive(Serialize, Deserialize, Clone, Default, Debug)]
+pub struct DownloadSettings {
+    /// Replaces https://github.com/ggerganov/whisper.cpp/releases/download
+    pub whisper_mirror: Option<String>,
+    /// Replaces https://huggingface.co/ggerganov/whisper.cpp/resolve/main
+    pub models_mirror: Option<String>,
+    /// http://, https:// or socks5:// proxy for every download
+    pub proxy: Option<String>,
+}
+
+impl DownloadSettings {
+    pub fn load() -> DownloadSettings {
+        get_config_dir()
+            .ok()
+            .and_then(|dir| fs::read_to_string(dir.join(SETTINGS_FILE)).ok())
+            .and_then(|raw| serde_json::from_str(&raw).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn whisper_base(&self) -> &str {
+        self.whisper_mirror.as_deref().unwrap_or(WHISPER_RELEASES_URL).trim_end_matches('/')
+    }
+
+    pub fn models_base(&self) -> &str {
+        self.models_mirror.as_deref().unwrap_or(models::HF_BASE_URL).trim_end_matches('/')
+    }
+
+    /// HTTP client going through the configured proxy, if any
+    pub fn client(&self) -> Result<reqwest::Client, String> {
+        let mut builder = reqwest::Client::builder();
+        if let Some(proxy) = &self.proxy {
+            builder = builder.proxy(
+                reqwest::Proxy::all(proxy.as_str()).map_err(|e| format!("Invalid proxy '{}': {}", proxy, e))?,
+            );
+        }
+        builder.build().map_err(|e| format!("Failed to create HTTP client: {}", e))
+    }
+}
+
+/// Client for downloads and release lookups, honouring the saved proxy
+pub fn http_client() -> Result<reqwest::Client, String> {
+    DownloadSettings::load().client()
+}
+
+/// One endpoint checked by test_download_connectivity
+#[derive(Serialize, Clone, Debug)]
+pub struct ConnectivityResult {
+    pub name: String,
+    pub url: String,
+    pub reachable: bool,
+    pub status: Option<u16>,
+    pub latency_ms: Option<u64>,
+    pub error: Option<String>,
+}
+
+// Cancel flags of in-flight downloads, keyed by the id sent in `download-started`
+pub struct DownloadState {
+    active: Mutex<HashMap<String, Arc<AtomicBool>>>,
+}
+
+impl DownloadState {
+    pub fn new() -> Self {
+        DownloadState { active: Mutex::new(HashMap::new()) }
+    }
+}
+
+/// Payload of `download-started`, emitted before any bytes are fetched
+#[derive(Serialize, Clone)]
+struct DownloadStarted {
+    download_id: String,
+    name: String,
+}
+
+/// A registered download; dropping it unregisters the id however the download ends
+pub struct ActiveDownload {
+    pub id: String,
+    cancel: Arc<AtomicBool>,
+    app: tauri::AppHandle,
+}
+
+impl ActiveDownload {
+    pub fn start(window: &tauri::Window, name: &str) -> ActiveDownload {
+        let ts = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_millis())
+            .unwrap_or(0);
+        let id = format!("{}-{}", name, ts);
+        let cancel = Arc::new(AtomicBool::new(false));
+        let app = window.app_handle().clone();
+        app.state::<DownloadState>().active.lock().unwrap().insert(id.clone(), cancel.clone());
+        let _ = window.emit("download-started", DownloadStarted { download_id: id.clone(), name: name.to_string() });
+        ActiveDownload { id, cancel, app }
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancel.load(Ordering::Relaxed)
+    }
+
+    /// Remove the partial file and report the cancellation; returns the error for the command to return
+    pub fn cancelled(&self, window: &tauri::Window, partial: &Path, downloaded: u64, total: Option<u64>) -> AppError {
+        let _ = std::fs::remove_file(partial);
+        emit_progress(window, &self.id, downloaded, total, "Cancelled");
+        AppError::Cancelled("Download cancelled".to_string())
+    }
+}
+
+impl Drop for ActiveDownload {
+    fn drop(&mut self) {
+        self.app.state::<DownloadState>().active.lock().unwrap().remove(&self.id);
+    }
+}
+
+/// Stream `url` to `dest`, resuming a partial file there, and check its SHA-256 when one is known.
+/// Returns the bytes downloaded and the total size when the server reported one.
+pub async fn fetch_verified(
+    window: &tauri::Window,
+    download: &ActiveDownload,
+    url: &str,
+    dest: &Path,
+    expected_sha256: Option<&str>,
+) -> Result<(u64, Option<u64>), AppError> {
+    // A partial archive left by an interrupted download is resumed rather than refetched
+    let existing = fs::metadata(dest).map(|m| m.len()).unwrap_or(0);
+    log::info!("Downloading {} to {}{}", url, dest.display(), if existing > 0 { " (resuming)" } else { "" });
+    emit_progress(window, &download.id, existing, None, if existing > 0 { "Resuming download..." } else { "Starting download..." });
+    
+    let client = http_client()?;
+    let mut request = client.get(url);
+    if existing > 0 {
+        request = request.header(reqwest::header::RANGE, format!("bytes={}-", existing));
+    }
+    let response = request
+        .send()
+        .await
+        .map_err(|e| format!("Download request failed: {}", e))?;
+    
+    let status = response.status();
+    let (mut downloaded, total_size, append) = if status == reqwest::StatusCode::PARTIAL_CONTENT {
+        // The content length only covers the remaining bytes
+        (existing, response.content_length().map(|len| len + existing), true)
+    } else if status == reqwest::StatusCode::RANGE_NOT_SATISFIABLE && existing > 0 {
+        // The partial file already holds everything the server has
+        (existing, Some(existing), true)
+    } else if status.is_success() {
+        if existing > 0 {
+            emit_progress(window, &download.id, 0, response.content_length(), "Server doesn't support resuming; restarting download...");
+        }
+        (0, response.content_length(), false)
+    } else {
+        log::error!("Downloading {} failed with HTTP {}", url, status);
+        return Err(format!("Download failed with HTTP {}", status).into());
+    };
+    
+    let mut hasher = Sha256::new();
+    if append {
+        // Feed the bytes already on disk through the hasher so the final checksum covers the whole file
+        let mut partial = fs::File::open(dest)
+            .map_err(|e| AppError::io("Failed to read partial download", e))?;
+        std::io::copy(&mut partial, &mut hasher)
+            .map_err(|e| AppError::io("Failed to read partial download", e))?;
+    }
+    
+    if status != reqwest::StatusCode::RANGE_NOT_SATISFIABLE {
+        let mut file = if append {
+            tokio::fs::OpenOptions::new()
+                .append(true)
+                .open(dest)
+                .await
+                .map_err(|e| AppError::io("Failed to open partial file", e))?
+        } else {
+            tokio::fs::File::create(dest)
+                .await
+                .map_err(|e| AppError::io("Failed to create file", e))?
+        };
+        
+        let mut stream = response.bytes_stream();
+        while let Some(chunk) = stream.next().await {
+            if download.is_cancelled() {
+                drop(file);
+                return Err(download.cancelled(window, dest, downloaded, total_size));
+            }
+            let chunk = chunk.map_err(|e| format!("Download stream error: {}", e))?;
+            
+            file.write_all(&chunk)
+                .await
+                .map_err(|e| AppError::io("Failed to write chunk", e))?;
+            
+            hasher.update(&chunk);
+            downloaded += chunk.len() as u64;
+            
+            let percent = total_size.map(|t| (downloaded as f32 / t as f32) * 100.0).unwrap_or(0.0);
+            emit_progress(window, &download.id, downloaded, total_size, &format!("Downloading... {:.1}%", percent));
+        }
+        
+        file.flush().await.map_err(|e| AppError::io("Failed to flush file", e))?;
+    }
+    
+    let Some(expected_sha256) = expected_sha256 else {
+        log::info!("Downloaded {} ({} bytes, unverified)", url, downloaded);
+        return Ok((downloaded, total_size));
+    };
+    
+    // Verify SHA256
+    emit_progress(window, &download.id, downloaded, total_size, "Verifying checksum...");
+    let hash = hex::encode(hasher.finalize());
+    
+    if !hash.eq_ignore_ascii_case(expected_sha256) {
+        log::error!("Checksum mismatch for {}: expected {}, got {}", url, expected_sha256, hash);
+        fs::remove_file(dest).ok();
+        return Err(AppError::ChecksumMismatch { expected: expected_sha256.to_string(), actual: hash });
+    }
+    
+    log::info!("Downloaded {} ({} bytes, SHA-256 verified)", url, downloaded);
+    Ok((downloaded, total_size))
+}
+
+/// Stop an in-flight download; it deletes its partial file before its command returns
+#[tauri::command]
+pub async fn cancel_download(state: tauri::State<'_, DownloadState>, download_id: String) -> Result<(), String> {
+    match state.active.lock().unwrap().get(&download_id) {
+        Some(cancel) => {
+            cancel.store(true, Ordering::Relaxed);
+            Ok(())
+        }
+        None => Err(format!("No download in progress with id '{}'", download_id)),
+    }
+}
+
+fn clean_setting(value: Option<String>) -> Option<String> {
+    value.map(|v| v.trim().to_string()).filter(|v| !v.is_empty())
+}
+
+#[tauri::command]
+pub async fn get_download_settings() -> Result<DownloadSettings, String> {
+    Ok(DownloadSettings::load())
+}
+
+/// Save the download mirrors and proxy; empty values go back to the canonical hosts and no proxy
+#[tauri::command]
+pub async fn set_download_settings(
+    whisper_mirror: Option<String>,
+    models_mirror: Option<String>,
+    proxy: Option<String>,
+) -> Result<DownloadSettings, String> {
+    let settings = DownloadSettings {
+        whisper_mirror: clean_setting(whisper_mirror),
+        models_mirror: clean_setting(models_mirror),
+        proxy: clean_setting(proxy),
+    };
+    for mirror in [&settings.whisper_mirror, &settings.models_mirror].into_iter().flatten() {
+        reqwest::Url::parse(mirror).map_err(|e| format!("Invalid mirror URL '{}': {}", mirror, e))?;
+    }
+    // Fails on a malformed proxy before it's saved
+    settings.client()?;
+
+    let json = serde_json::to_string_pretty(&settings)
+        .map_err(|e| format!("Failed to serialize download settings: {}", e))?;
+    fs::write(get_config_dir()?.join(SETTINGS_FILE), json)
+        .map_err(|e| format!("Failed to save download settings: {}", e))?;
+    Ok(settings)
+}
+
+/// HEAD a known file on each configured download host and time the response
+#[tauri::command]
+pub async fn test_download_connectivity() -> Result<Vec<ConnectivityResult>, String> {
+    let settings = DownloadSettings::load();
+    let client = settings.client()?;
+    let endpoints = [
+        ("whisper", format!("{}/v1.8.2/whisper-bin-x64.zip", settings.whisper_base())),
+        ("models", format!("{}/{}", settings.models_base(), models::WHISPER_MODELS[0].file_name)),
+    ];
+
+    let mut results = Vec::new();
+    for (name, url) in endpoints {
+        let started = Instant::now();
+        let response = client
+            .head(&url)
+            .timeout(std::time::Duration::from_secs(10))
+            .send()
+            .await;
+        let latency_ms = started.elapsed().as_millis() as u64;
+        results.push(match response {
+            Ok(response) => ConnectivityResult {
+                name: name.to_string(),
+                url,
+                reachable: response.status().is_success(),
+                status: Some(response.status().as_u16()),
+                latency_ms: Some(latency_ms),
+                error: None,
+            },
+            Err(e) => ConnectivityResult {
+                name: name.to_string(),
+                url,
+                reachable: false,
+                status: None,
+                latency_ms: None,
+                error: Some(e.to_string()),
+            },
+        });
+    }
+    Ok(results)
+}
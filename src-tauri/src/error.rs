@@ -0,0 +1,126 @@
+use serde::ser::SerializeStruct;
+use serde::{Serialize, Serializer};
+
+/// Error returned by commands, sent to the frontend as `{code, message, details}`.
+/// `message` is the same text the command used to return as a plain string.
+#[derive(Debug, thiserror::Error)]
+pub enum AppError {
+    #[error("{name} not found. Searched: {}", searched.join(", "))]
+    BinaryNotFound { name: String, searched: Vec<String> },
+
+    #[error("{message}")]
+    ModelNotFound { model: String, message: String },
+
+    /// A recording or other exclusive operation is already running
+    #[error("{0}")]
+    RecorderBusy(String),
+
+    #[error("{message}")]
+    ProcessFailed { cmd: String, stderr: String, message: String },
+
+    /// A request to a summarization endpoint couldn't be made or was cut off
+    #[error("{0}")]
+    Network(String),
+
+    #[error("{context}: {source}")]
+    Io { context: String, source: std::io::Error },
+
+    #[error("Checksum mismatch! Expected: {expected}, Got: {actual}")]
+    ChecksumMismatch { expected: String, actual: String },
+
+    #[error("{0}")]
+    Cancelled(String),
+
+    /// Loading the model would likely get the app OOM-killed; retry with `force` to load it anyway
+    #[error("Not enough memory for this model: it needs about {required_mb} MB but only {available_mb} MB is available")]
+    InsufficientMemory { required_mb: u64, available_mb: u64 },
+
+    /// Something the setup wizard installs is missing; `step` is the run_setup_step to run
+    #[error("{message}")]
+    SetupRequired { step: String, message: String },
+
+    /// A path from the frontend that isn't a plain file name where one is expected, or is outside
+    /// the app's folders and the ones the user picked
+    #[error("{message}")]
+    PathNotAllowed { path: String, message: String },
+
+    /// Anything not yet given its own variant
+    #[error("{0}")]
+    Other(String),
+}
+
+impl AppError {
+    pub fn io(context: impl Into<String>, source: std::io::Error) -> AppError {
+        AppError::Io { context: context.into(), source }
+    }
+
+    pub fn code(&self) -> &'static str {
+        match self {
+            AppError::BinaryNotFound { .. } => "binary_not_found",
+            AppError::ModelNotFound { .. } => "model_not_found",
+            AppError::RecorderBusy(_) => "recorder_busy",
+            AppError::ProcessFailed { .. } => "process_failed",
+            AppError::Network(_) => "network",
+            AppError::Io { source, .. } if is_disk_full(source) => "disk_full",
+            AppError::Io { .. } => "io",
+            AppError::ChecksumMismatch { .. } => "checksum_mismatch",
+            AppError::Cancelled(_) => "cancelled",
+            AppError::InsufficientMemory { .. } => "insufficient_memory",
+            AppError::SetupRequired { .. } => "setup_required",
+            AppError::PathNotAllowed { .. } => "path_not_allowed",
+            AppError::Other(_) => "other",
+        }
+    }
+
+    fn details(&self) -> serde_json::Value {
+        match self {
+            AppError::BinaryNotFound { name, searched } => serde_json::json!({ "name": name, "searched": searched }),
+            AppError::ModelNotFound { model, .. } => serde_json::json!({ "model": model }),
+            AppError::ProcessFailed { cmd, stderr, .. } => serde_json::json!({ "cmd": cmd, "stderr": stderr }),
+            AppError::Io { context, source } => serde_json::json!({ "context": context, "kind": format!("{:?}", source.kind()) }),
+            AppError::ChecksumMismatch { expected, actual } => serde_json::json!({ "expected": expected, "actual": actual }),
+            AppError::SetupRequired { step, .. } => serde_json::json!({ "step": step }),
+            AppError::PathNotAllowed { path, .. } => serde_json::json!({ "path": path }),
+            AppError::InsufficientMemory { required_mb, available_mb } => {
+                serde_json::json!({ "required_mb": required_mb, "available_mb": available_mb })
+            }
+            AppError::RecorderBusy(_) | AppError::Network(_) | AppError::Cancelled(_) | AppError::Other(_) => {
+                serde_json::Value::Null
+            }
+        }
+    }
+}
+
+/// ENOSPC on unix, ERROR_DISK_FULL on Windows
+fn is_disk_full(error: &std::io::Error) -> bool {
+    error.kind() == std::io::ErrorKind::StorageFull
+}
+
+impl Serialize for AppError {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut state = serializer.serialize_struct("AppError", 3)?;
+        state.serialize_field("code", self.code())?;
+        state.serialize_field("message", &self.to_string())?;
+        state.serialize_field("details", &self.details())?;
+        state.end()
+    }
+}
+
+impl From<String> for AppError {
+    fn from(message: String) -> Self {
+        AppError::Other(message)
+    }
+}
+
+impl From<&str> for AppError {
+    fn from(message: &str) -> Self {
+        AppError::Other(message.to_string())
+    }
+}
+
+/// Lets helpers that still return `Result<_, String>` call migrated ones with `?`
+impl From<AppError> for String {
+    fn from(error: AppError) -> Self {
+        error.to_string()
+    }
+}
@@ -0,0 +1,70 @@
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::VecDeque;
+use std::sync::Mutex;
+use tauri::{Emitter, Manager};
+
+/// Events kept for replay; at 10 s chunks that's well over an hour of live transcript
+const MAX_EVENTS: usize = 2000;
+
+/// An event as it was emitted, for replay_session_events
+#[derive(Serialize, Clone)]
+pub struct SessionEvent {
+    pub seq: u64,
+    pub event: String,
+    pub payload: Value,
+}
+
+struct EventLog {
+    last_seq: u64,
+    events: VecDeque<SessionEvent>,
+}
+
+// Recent recording-session events, so a reloaded webview can catch up on what it missed
+pub struct SessionEventState {
+    log: Mutex<EventLog>,
+}
+
+impl SessionEventState {
+    pub fn new() -> Self {
+        SessionEventState { log: Mutex::new(EventLog { last_seq: 0, events: VecDeque::new() }) }
+    }
+}
+
+/// Forget the previous session's events. Sequence numbers keep counting, so a `since_seq` the
+/// frontend held from before still only returns newer events.
+pub fn start_session(app: &tauri::AppHandle) {
+    app.state::<SessionEventState>().log.lock().unwrap().events.clear();
+}
+
+/// Emit a session event and keep it for replay. Object payloads gain a `seq` field; other payloads
+/// (plain error strings) are wrapped as `{ seq, message }` so every event carries its sequence number.
+pub fn emit<S: Serialize>(app: &tauri::AppHandle, event: &str, payload: S) {
+    let mut payload = serde_json::to_value(payload).unwrap_or(Value::Null);
+    let state = app.state::<SessionEventState>();
+    // Held while emitting so events reach the webview in sequence order
+    let mut log = state.log.lock().unwrap();
+    log.last_seq += 1;
+    let seq = log.last_seq;
+    match payload.as_object_mut() {
+        Some(fields) => {
+            fields.insert("seq".to_string(), seq.into());
+        }
+        None => payload = serde_json::json!({ "seq": seq, "message": payload }),
+    }
+    let _ = app.emit(event, &payload);
+    if log.events.len() == MAX_EVENTS {
+        log.events.pop_front();
+    }
+    log.events.push_back(SessionEvent { seq, event: event.to_string(), payload });
+}
+
+/// Session events emitted after `since_seq`, oldest first; 0 returns everything still kept
+#[tauri::command]
+pub async fn replay_session_events(
+    state: tauri::State<'_, SessionEventState>,
+    since_seq: u64,
+) -> Result<Vec<SessionEvent>, String> {
+    let log = state.log.lock().unwrap();
+    Ok(log.events.iter().filter(|e| e.seq > since_seq).cloned().collect())
+}
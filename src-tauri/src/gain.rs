@@ -0,0 +1,152 @@
+use serde::Serialize;
+use std::path::Path;
+use std::process::Command;
+
+use crate::audio::{self, Levels, PreparedAudio};
+use crate::error::AppError;
+use crate::{get_cache_dir, has_ffmpeg, paths, processes, trim};
+
+/// Recordings quieter than this on average get auto_gain
+const QUIET_RMS_DBFS: f32 = -30.0;
+
+/// Average level auto_gain brings a quiet recording up to
+const TARGET_RMS_DBFS: f32 = -20.0;
+
+/// Gain never pushes the loudest sample past this, so nothing clips
+const PEAK_CEILING_DBFS: f32 = -1.0;
+
+/// Less gain than this isn't worth copying the file for
+const MIN_GAIN_DB: f32 = 1.0;
+
+/// Parse "mean_volume: -27.3 dB" style lines from ffmpeg's volumedetect filter
+fn volumedetect_value(stderr: &str, key: &str) -> Option<f32> {
+    stderr
+        .lines()
+        .find_map(|line| line.split_once(key).map(|(_, rest)| rest))
+        .and_then(|rest| rest.trim().trim_end_matches("dB").trim().parse().ok())
+}
+
+/// Levels of a PCM WAV read directly, or of anything else through ffmpeg's volumedetect
+fn measure(app: &tauri::AppHandle, path: &Path) -> Result<Levels, String> {
+    if let Ok(levels) = audio::wav_levels(path) {
+        return Ok(levels);
+    }
+    if !has_ffmpeg() {
+        return Err(format!("ffmpeg is required to measure the level of {}", path.display()));
+    }
+    let mut cmd = Command::new("ffmpeg");
+    cmd.arg("-hide_banner")
+        .arg("-nostats")
+        .arg("-i").arg(path)
+        .arg("-af").arg("volumedetect")
+        .arg("-f").arg("null")
+        .arg("-");
+    let output = processes::output(app, &mut cmd)
+        .map_err(|e| format!("Failed to start ffmpeg: {}", e))?;
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    match (volumedetect_value(&stderr, "max_volume:"), volumedetect_value(&stderr, "mean_volume:")) {
+        (Some(peak_dbfs), Some(rms_dbfs)) if output.status.success() => Ok(Levels { peak_dbfs, rms_dbfs }),
+        _ => Err(format!("ffmpeg couldn't measure the level of {}: {}", path.display(), stderr.trim())),
+    }
+}
+
+/// Gain that brings the average level to `target_rms_dbfs` without the peak passing the ceiling
+fn gain_for(levels: &Levels, target_rms_dbfs: f32) -> f32 {
+    (target_rms_dbfs - levels.rms_dbfs).min(PEAK_CEILING_DBFS - levels.peak_dbfs)
+}
+
+/// Write `source` scaled by `gain_db` to `output`: sample by sample for PCM WAV, otherwise with
+/// ffmpeg's volume filter. Blocking.
+fn write_gained(app: &tauri::AppHandle, source: &Path, output: &Path, gain_db: f32) -> Result<(), String> {
+    let is_wav = output.extension().is_some_and(|e| e.eq_ignore_ascii_case("wav"));
+    if is_wav && audio::pcm_info(source).is_ok() {
+        return audio::apply_wav_gain(source, output, gain_db).map(|_| ());
+    }
+    if !has_ffmpeg() {
+        return Err(format!("ffmpeg is required to change the level of {}", source.display()));
+    }
+    let mut cmd = Command::new("ffmpeg");
+    cmd.arg("-hide_banner")
+        .arg("-loglevel").arg("error")
+        .arg("-y")
+        .arg("-i").arg(source)
+        .arg("-af").arg(format!("volume={:.2}dB", gain_db))
+        .arg(output);
+    let output_result = processes::output(app, &mut cmd)
+        .map_err(|e| format!("Failed to start ffmpeg: {}", e))?;
+    if !output_result.status.success() {
+        return Err(format!(
+            "ffmpeg couldn't change the level of {}: {}",
+            source.display(),
+            String::from_utf8_lossy(&output_result.stderr).trim()
+        ));
+    }
+    Ok(())
+}
+
+/// For transcribe_audio's auto_gain: a louder temp copy of a quiet recording and the gain applied,
+/// or None, without copying anything, when it's already loud enough. Blocking.
+pub fn auto_gain(app: &tauri::AppHandle, path: &Path) -> Result<Option<(PreparedAudio, f32)>, String> {
+    let levels = measure(app, path)?;
+    if levels.rms_dbfs >= QUIET_RMS_DBFS {
+        return Ok(None);
+    }
+    let gain_db = gain_for(&levels, TARGET_RMS_DBFS);
+    if gain_db < MIN_GAIN_DB {
+        log::info!("{} is quiet but its peaks leave no room for gain", path.display());
+        return Ok(None);
+    }
+    // convert- keeps it out of the recordings list, like other transcription temp files
+    let gained = PreparedAudio::temp(get_cache_dir()?.join(format!("{}.wav", audio::unique_name("convert-gain"))));
+    write_gained(app, path, &gained.path, gain_db)?;
+    log::info!(
+        "Raised {} by {:.1} dB (RMS {:.1} dBFS, peak {:.1} dBFS)",
+        path.display(),
+        gain_db,
+        levels.rms_dbfs,
+        levels.peak_dbfs
+    );
+    Ok(Some((gained, gain_db)))
+}
+
+#[derive(Serialize)]
+pub struct Normalized {
+    pub path: String,
+    pub gain_db: f32,
+    /// Levels of the source before the gain
+    pub levels: Levels,
+}
+
+/// Bring a recording's average level to `target_dbfs` (RMS), limited so its peaks stay below
+/// -1 dBFS, writing the result to `out_path` (default `<name>-normalized` beside it)
+#[tauri::command]
+pub async fn normalize_audio(
+    app: tauri::AppHandle,
+    path: String,
+    target_dbfs: f32,
+    out_path: Option<String>,
+) -> Result<Normalized, AppError> {
+    if !(-60.0..=0.0).contains(&target_dbfs) {
+        return Err(format!("target_dbfs must be between -60 and 0, not {}", target_dbfs).into());
+    }
+    let source = paths::existing(&app, &path)?;
+    let output = match out_path {
+        Some(out) => paths::writable(&app, &out)?,
+        None => trim::sibling(&source, "-normalized"),
+    };
+    trim::check_output(&source, &output, false)?;
+
+    let written = output.clone();
+    let (levels, gain_db) = tauri::async_runtime::spawn_blocking(move || {
+        let levels = measure(&app, &source)?;
+        let gain_db = gain_for(&levels, target_dbfs);
+        if let Err(e) = write_gained(&app, &source, &written, gain_db) {
+            let _ = std::fs::remove_file(&written);
+            return Err(e);
+        }
+        Ok::<_, String>((levels, gain_db))
+    })
+    .await
+    .map_err(|e| format!("Normalize task failed: {}", e))??;
+    Ok(Normalized { path: output.to_string_lossy().to_string(), gain_db, levels })
+}
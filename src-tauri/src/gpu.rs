@@ -0,0 +1,195 @@
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use std::process::Command;
+use std::sync::OnceLock;
+
+use crate::recorder;
+
+/// PCI vendor IDs as vulkaninfo prints them
+const VENDOR_IDS: &[(&str, &str)] = &[("nvidia", "0x10de"), ("amd", "0x1002"), ("intel", "0x8086")];
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct GpuInfo {
+    /// "nvidia", "amd", "intel", "apple" or "other"
+    pub vendor: String,
+    pub model: String,
+    /// Compute APIs whisper.cpp could use on this GPU: "cuda", "vulkan", "opencl", "metal"
+    pub capabilities: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct GpuStatus {
+    #[serde(default)]
+    pub gpus: Vec<GpuInfo>,
+    /// "cpu", "cuda", "vulkan", "openvino" or "metal"
+    #[serde(default = "default_backend")]
+    pub recommended_backend: String,
+    // Fields the frontend read before multi-GPU detection
+    pub gpu_name: String,
+    pub is_iris_xe: bool,
+    pub status: String,
+}
+
+fn default_backend() -> String {
+    "cpu".to_string()
+}
+
+fn vendor_of(name: &str) -> &'static str {
+    let lower = name.to_lowercase();
+    if lower.contains("nvidia") {
+        "nvidia"
+    } else if lower.contains("amd") || lower.contains("advanced micro devices") || lower.contains("ati ") || lower.contains("radeon") {
+        "amd"
+    } else if lower.contains("intel") {
+        "intel"
+    } else if lower.contains("apple") {
+        "apple"
+    } else {
+        "other"
+    }
+}
+
+fn is_iris_xe(gpu: &GpuInfo) -> bool {
+    gpu.vendor == "intel" && gpu.model.to_lowercase().contains("iris xe")
+}
+
+/// Intel GPUs OpenVINO supports well enough to prefer over the CPU
+fn suits_openvino(gpu: &GpuInfo) -> bool {
+    let model = gpu.model.to_lowercase();
+    gpu.vendor == "intel" && ["iris xe", "arc", "alder lake", "tiger lake", "dg1"].iter().any(|m| model.contains(m))
+}
+
+fn command_stdout(cmd: &mut Command) -> Option<String> {
+    let output = cmd.output().ok()?;
+    output.status.success().then(|| String::from_utf8_lossy(&output.stdout).to_string())
+}
+
+/// Display adapters from `lspci`, e.g. "00:02.0 VGA compatible controller: Intel Corporation Alder Lake-P [Iris Xe Graphics]"
+fn linux_gpu_names() -> Vec<String> {
+    let Some(stdout) = command_stdout(&mut Command::new("lspci")) else {
+        return Vec::new();
+    };
+    stdout
+        .lines()
+        .filter(|line| ["VGA compatible controller", "3D controller", "Display controller"].iter().any(|class| line.contains(class)))
+        .filter_map(|line| line.split_once(": ").map(|(_, name)| name.trim().to_string()))
+        .collect()
+}
+
+fn macos_gpu_names() -> Vec<String> {
+    let Some(stdout) = command_stdout(Command::new("system_profiler").arg("SPDisplaysDataType")) else {
+        return Vec::new();
+    };
+    stdout
+        .lines()
+        .filter_map(|line| line.trim().strip_prefix("Chipset Model:").map(|name| name.trim().to_string()))
+        .collect()
+}
+
+fn windows_gpu_names() -> Vec<String> {
+    let mut cmd = Command::new("powershell");
+    cmd.arg("-NoProfile").arg("-Command").arg("Get-CimInstance Win32_VideoController | Select-Object -ExpandProperty Name");
+    let Some(stdout) = command_stdout(&mut cmd) else {
+        return Vec::new();
+    };
+    stdout.lines().map(str::trim).filter(|l| !l.is_empty()).map(str::to_string).collect()
+}
+
+/// Vendors with a Vulkan device, from `vulkaninfo --summary`
+fn vulkan_vendors() -> Vec<&'static str> {
+    let Some(stdout) = command_stdout(Command::new("vulkaninfo").arg("--summary")) else {
+        return Vec::new();
+    };
+    VENDOR_IDS
+        .iter()
+        .filter(|(_, id)| stdout.lines().any(|line| line.contains("vendorID") && line.contains(id)))
+        .map(|(vendor, _)| *vendor)
+        .collect()
+}
+
+fn detect_gpus() -> Vec<GpuInfo> {
+    let names = if cfg!(target_os = "linux") {
+        linux_gpu_names()
+    } else if cfg!(target_os = "macos") {
+        macos_gpu_names()
+    } else if cfg!(target_os = "windows") {
+        windows_gpu_names()
+    } else {
+        Vec::new()
+    };
+
+    let vulkan = vulkan_vendors();
+    let has_cuda = recorder::has_tool("nvidia-smi");
+    // ROCm's kernel driver; present when an AMD GPU can run OpenCL/HIP compute
+    let has_kfd = Path::new("/dev/kfd").exists();
+
+    names
+        .into_iter()
+        .map(|name| {
+            let vendor = vendor_of(&name);
+            let mut capabilities = Vec::new();
+            if vendor == "nvidia" && has_cuda {
+                capabilities.push("cuda".to_string());
+            }
+            if vulkan.contains(&vendor) {
+                capabilities.push("vulkan".to_string());
+            }
+            if vendor == "amd" && has_kfd {
+                capabilities.push("opencl".to_string());
+            }
+            if vendor == "apple" {
+                capabilities.push("metal".to_string());
+            }
+            GpuInfo { vendor: vendor.to_string(), model: name, capabilities }
+        })
+        .collect()
+}
+
+fn recommended_backend(gpus: &[GpuInfo]) -> &'static str {
+    let has = |capability: &str| gpus.iter().any(|gpu| gpu.capabilities.iter().any(|c| c == capability));
+    if has("cuda") {
+        "cuda"
+    } else if has("metal") {
+        "metal"
+    } else if gpus.iter().any(|gpu| gpu.vendor != "intel" && gpu.capabilities.iter().any(|c| c == "vulkan")) {
+        "vulkan"
+    } else if gpus.iter().any(suits_openvino) {
+        "openvino"
+    } else if has("vulkan") {
+        "vulkan"
+    } else {
+        "cpu"
+    }
+}
+
+/// recommended_backend for this machine, detected once per run since GPUs don't come and go
+pub fn recommended() -> &'static str {
+    static RECOMMENDED: OnceLock<&'static str> = OnceLock::new();
+    RECOMMENDED.get_or_init(|| recommended_backend(&detect_gpus()))
+}
+
+/// Detect NVIDIA, AMD, Intel and Apple GPUs and which whisper.cpp backend suits them best
+#[tauri::command]
+pub async fn detect_gpu() -> Result<GpuStatus, String> {
+    let gpus = tauri::async_runtime::spawn_blocking(detect_gpus)
+        .await
+        .map_err(|e| format!("GPU detection failed: {}", e))?;
+    let recommended = recommended_backend(&gpus);
+
+    // The most capable GPU stands in for the single-GPU fields
+    let primary = gpus.iter().max_by_key(|gpu| gpu.capabilities.len());
+    let gpu_name = primary.map(|gpu| gpu.model.clone()).unwrap_or_else(|| "Unknown GPU".to_string());
+    let status = match (recommended, primary) {
+        ("cpu", Some(_)) => "GPU detected, but no usable compute backend found; using CPU".to_string(),
+        ("cpu", None) => "No GPU detected; using CPU".to_string(),
+        (backend, _) => format!("Ready ({})", backend),
+    };
+
+    Ok(GpuStatus {
+        is_iris_xe: gpus.iter().any(is_iris_xe),
+        recommended_backend: recommended.to_string(),
+        gpus,
+        gpu_name,
+        status,
+    })
+}
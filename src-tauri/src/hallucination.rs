@@ -0,0 +1,134 @@
+use std::path::Path;
+use std::sync::Mutex;
+use tauri::Manager;
+
+use crate::transcript::TranscriptSegment;
+use crate::vad;
+
+/// A line may repeat this many times in a row before further copies are dropped
+const MAX_REPEATED_LINES: usize = 2;
+
+/// Mean RMS below which a chunk counts as low-energy and known phrases are dropped
+const LOW_ENERGY_RMS: f32 = 0.02;
+
+/// Phrases whisper tends to invent on quiet audio (compared after normalization)
+const DEFAULT_FILTERS: &[&str] = &[
+    "thank you",
+    "thanks for watching",
+    "thank you for watching",
+    "thank you so much for watching",
+    "please subscribe",
+    "subtitles by the amara org community",
+    "you",
+    "bye",
+];
+
+pub struct HallucinationState {
+    pub phrases: Mutex<Vec<String>>,
+}
+
+impl HallucinationState {
+    pub fn new() -> Self {
+        HallucinationState {
+            phrases: Mutex::new(DEFAULT_FILTERS.iter().map(|p| normalize(p)).collect()),
+        }
+    }
+}
+
+/// Lowercase, drop punctuation, and collapse whitespace so "Thank you." matches "thank you"
+fn normalize(text: &str) -> String {
+    text.chars()
+        .map(|c| if c.is_alphanumeric() || c.is_whitespace() { c.to_ascii_lowercase() } else { ' ' })
+        .collect::<String>()
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Remove [BLANK_AUDIO], (music), and similar bracketed non-speech annotations
+fn strip_annotations(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut closing: Vec<char> = Vec::new();
+    for c in text.chars() {
+        match c {
+            '[' => closing.push(']'),
+            '(' => closing.push(')'),
+            c if closing.last() == Some(&c) => {
+                closing.pop();
+            }
+            c if closing.is_empty() => out.push(c),
+            _ => {}
+        }
+    }
+    out.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// Drop annotations, runs of repeated lines, and (on low-energy audio) known hallucination phrases
+pub fn filter_segments(segments: Vec<TranscriptSegment>, phrases: &[String], low_energy: bool) -> Vec<TranscriptSegment> {
+    let mut kept: Vec<TranscriptSegment> = Vec::with_capacity(segments.len());
+    let mut last_line = String::new();
+    let mut repeats = 0;
+
+    for mut segment in segments {
+        let text = strip_annotations(&segment.text);
+        let key = normalize(&text);
+        if key.is_empty() {
+            continue;
+        }
+        if low_energy && phrases.contains(&key) {
+            continue;
+        }
+
+        if key == last_line {
+            repeats += 1;
+            if repeats >= MAX_REPEATED_LINES {
+                continue;
+            }
+        } else {
+            last_line = key;
+            repeats = 0;
+        }
+
+        segment.text = text;
+        kept.push(segment);
+    }
+    kept
+}
+
+/// Line-oriented filter for plain (untimestamped) whisper output
+pub fn filter_text(text: &str, phrases: &[String], low_energy: bool) -> String {
+    let segments = text
+        .lines()
+        .map(|line| TranscriptSegment { text: line.to_string(), ..Default::default() })
+        .collect();
+    filter_segments(segments, phrases, low_energy)
+        .into_iter()
+        .map(|s| s.text)
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Current filter phrases plus whether the audio at `path` is quiet enough to apply them
+pub fn filter_context(app: &tauri::AppHandle, path: &Path) -> (Vec<String>, bool) {
+    let phrases = app.state::<HallucinationState>().phrases.lock().unwrap().clone();
+    let vad_options = app.state::<vad::VadState>().options.lock().unwrap().clone();
+    let low_energy = vad::analyze_silence(path, &vad_options)
+        .map(|r| r.mean_rms < LOW_ENERGY_RMS)
+        .unwrap_or(false);
+    (phrases, low_energy)
+}
+
+/// Add phrases to the hallucination filter list, returning the full list
+#[tauri::command]
+pub async fn set_hallucination_filters(
+    state: tauri::State<'_, HallucinationState>,
+    phrases: Vec<String>,
+) -> Result<Vec<String>, String> {
+    let mut current = state.phrases.lock().unwrap();
+    for phrase in phrases.iter().map(|p| normalize(p)).filter(|p| !p.is_empty()) {
+        if !current.contains(&phrase) {
+            current.push(phrase);
+        }
+    }
+    Ok(current.clone())
+}
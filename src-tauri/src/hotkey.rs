@@ -0,0 +1,79 @@
+use tauri::Manager;
+use tauri_plugin_global_shortcut::{GlobalShortcutExt, Shortcut, ShortcutState};
+
+use crate::{events, recorder_status, settings, ChunkedRecorderState};
+
+/// Parse an accelerator like "CommandOrControl+Shift+R"
+pub fn parse(accelerator: &str) -> Result<Shortcut, String> {
+    accelerator
+        .parse::<Shortcut>()
+        .map_err(|e| format!("Invalid hotkey '{}': {}", accelerator, e))
+}
+
+/// Start a live recording with the saved settings, or stop the one in progress, and tell the
+/// frontend which way it went
+async fn toggle_recording(app: tauri::AppHandle) {
+    // The last session's chunks are still draining; starting another now would clear them
+    if recorder_status::current(&app).live == recorder_status::LivePhase::Transcribing {
+        log::info!("Ignoring the recording hotkey while the last live session is transcribing");
+        return;
+    }
+    let active = *app.state::<ChunkedRecorderState>().active.lock();
+    let result = recorder_status::toggle_live(&app).await;
+    if let Err(e) = &result {
+        log::error!("Recording hotkey failed to {} recording: {}", if active { "stop" } else { "start" }, e);
+    }
+    let recording = *app.state::<ChunkedRecorderState>().active.lock();
+    events::emit(&app, "hotkey-recording-toggled", serde_json::json!({
+        "recording": recording,
+        "error": result.err(),
+    }));
+}
+
+/// Register `shortcut` with the OS; fails if it's taken by another application
+fn register(app: &tauri::AppHandle, shortcut: Shortcut) -> Result<(), String> {
+    app.global_shortcut()
+        .on_shortcut(shortcut, |app, _, event| {
+            if event.state == ShortcutState::Pressed {
+                tauri::async_runtime::spawn(toggle_recording(app.clone()));
+            }
+        })
+        .map_err(|e| format!("Couldn't register hotkey '{}': {}", shortcut, e))
+}
+
+/// Re-register the saved hotkey at startup; a hotkey that's now taken is logged and left saved
+pub fn restore(app: &tauri::AppHandle) {
+    let Some(accelerator) = settings::current(app).recording_hotkey else { return };
+    if let Err(e) = parse(&accelerator).and_then(|shortcut| register(app, shortcut)) {
+        log::warn!("Recording hotkey not restored: {}", e);
+    }
+}
+
+/// Set the global hotkey that starts and stops live recording, replacing any previous one
+#[tauri::command]
+pub async fn register_recording_hotkey(app: tauri::AppHandle, accelerator: String) -> Result<(), String> {
+    let shortcut = parse(&accelerator)?;
+    let previous = settings::current(&app).recording_hotkey.and_then(|a| parse(&a).ok());
+    let shortcuts = app.global_shortcut();
+    if previous != Some(shortcut) || !shortcuts.is_registered(shortcut) {
+        // The old hotkey stays in place if the new one can't be registered
+        register(&app, shortcut)?;
+        if let Some(previous) = previous.filter(|p| *p != shortcut) {
+            let _ = shortcuts.unregister(previous);
+        }
+    }
+    settings::set_recording_hotkey(&app, Some(accelerator))
+}
+
+/// Remove the recording hotkey
+#[tauri::command]
+pub async fn unregister_recording_hotkey(app: tauri::AppHandle) -> Result<(), String> {
+    if let Some(shortcut) = settings::current(&app).recording_hotkey.and_then(|a| parse(&a).ok()) {
+        if app.global_shortcut().is_registered(shortcut) {
+            app.global_shortcut()
+                .unregister(shortcut)
+                .map_err(|e| format!("Couldn't unregister hotkey: {}", e))?;
+        }
+    }
+    settings::set_recording_hotkey(&app, None)
+}
@@ -0,0 +1,132 @@
+use serde::Serialize;
+use std::fs;
+use std::path::{Path, PathBuf};
+use tauri::{Emitter, Manager};
+
+use crate::{audio, get_library_dir, note_export, notes, paths, transcribe_audio_detailed};
+
+#[derive(Serialize, Clone)]
+pub struct ImportedFile {
+    pub source: String,
+    /// Where the copy now lives in the library directory
+    pub path: Option<String>,
+    pub note_id: Option<u64>,
+    /// Why this file wasn't imported; the rest of the batch carries on
+    pub error: Option<String>,
+}
+
+#[derive(Serialize, Clone)]
+struct ImportProgress {
+    source: String,
+    index: usize,
+    total: usize,
+    /// "imported", "failed", "transcribing", "transcribed" or "transcription_failed"
+    status: &'static str,
+    note_id: Option<u64>,
+    error: Option<String>,
+}
+
+/// Check that `path` is audio we can work with, returning its duration when known
+fn validate(path: &Path) -> Result<Option<f64>, String> {
+    if !path.is_file() {
+        return Err("Not a file".to_string());
+    }
+    fs::File::open(path).map_err(|e| format!("Can't read the file: {}", e))?;
+    let metadata = audio::read_metadata(path)?;
+    match metadata.header {
+        audio::HeaderStatus::Ok | audio::HeaderStatus::Truncated => Ok(metadata.duration_secs),
+        _ => Err(metadata.message.unwrap_or_else(|| "Not a supported audio file".to_string())),
+    }
+}
+
+/// Copy one file into the library directory, out of reach of the cache and its retention cleanup.
+/// A real copy, not a hard link, so trimming the note's audio never touches the original.
+fn copy_in(source: &Path) -> Result<PathBuf, String> {
+    let name = source.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+    let dest = note_export::unique_path(get_library_dir()?.join(note_export::sanitize_file_name(&name)));
+    fs::copy(source, &dest).map_err(|e| format!("Failed to copy into the library directory: {}", e))?;
+    Ok(dest)
+}
+
+fn import_file(app: &tauri::AppHandle, source: &Path) -> Result<(PathBuf, notes::Note), String> {
+    let duration_secs = validate(source)?;
+    let dest = copy_in(source)?;
+    let note = notes::insert(app, notes::NewNote {
+        title: source.file_stem().map(|s| s.to_string_lossy().to_string()).unwrap_or_default(),
+        audio_path: Some(dest.to_string_lossy().to_string()),
+        duration_secs,
+        ..Default::default()
+    });
+    match note {
+        Ok(note) => Ok((dest, note)),
+        Err(e) => {
+            let _ = fs::remove_file(&dest);
+            Err(e)
+        }
+    }
+}
+
+/// Transcribe an imported file through the job queue and fill in its note
+async fn transcribe_into_note(window: tauri::Window, progress: ImportProgress, audio_path: String, note_id: u64) {
+    let _ = window.emit("import-progress", ImportProgress { status: "transcribing", ..progress.clone() });
+    let result = transcribe_audio_detailed(window.clone(), audio_path, None, None, None, None, None, None)
+        .await
+        .map_err(|e| e.to_string())
+        .and_then(|result| {
+            notes::update(window.app_handle(), note_id, notes::NotePatch {
+                transcript: Some(result.text),
+                segments: Some(result.segments),
+                ..Default::default()
+            })
+        });
+    let (status, error) = match result {
+        Ok(_) => ("transcribed", None),
+        Err(e) => {
+            log::error!("Transcribing imported note {} failed: {}", note_id, e);
+            ("transcription_failed", Some(e))
+        }
+    };
+    let _ = window.emit("import-progress", ImportProgress { status, error, ..progress });
+}
+
+/// Copy audio files into the library directory and create a note for each, optionally queueing
+/// their transcription; files that can't be imported are reported without stopping the others
+#[tauri::command]
+pub async fn import_audio(
+    window: tauri::Window,
+    paths: Vec<String>,
+    transcribe_now: bool,
+) -> Result<Vec<ImportedFile>, String> {
+    let total = paths.len();
+    let mut imported = Vec::with_capacity(total);
+    for (index, source) in paths.into_iter().enumerate() {
+        let app = window.app_handle().clone();
+        let result = match paths::existing(&app, &source) {
+            Ok(path) => tauri::async_runtime::spawn_blocking(move || import_file(&app, &path))
+                .await
+                .map_err(|e| format!("Import task failed: {}", e))
+                .and_then(|result| result),
+            Err(e) => Err(e.to_string()),
+        };
+
+        let mut progress = ImportProgress { source: source.clone(), index, total, status: "imported", note_id: None, error: None };
+        match result {
+            Ok((dest, note)) => {
+                log::info!("Imported {} as note {}", source, note.id);
+                progress.note_id = Some(note.id);
+                let _ = window.emit("import-progress", progress.clone());
+                let dest = dest.to_string_lossy().to_string();
+                if transcribe_now {
+                    tauri::async_runtime::spawn(transcribe_into_note(window.clone(), progress, dest.clone(), note.id));
+                }
+                imported.push(ImportedFile { source, path: Some(dest), note_id: Some(note.id), error: None });
+            }
+            Err(e) => {
+                log::warn!("Couldn't import {}: {}", source, e);
+                let _ = window.emit("import-progress", ImportProgress { status: "failed", error: Some(e.clone()), ..progress });
+                imported.push(ImportedFile { source, path: None, note_id: None, error: Some(e) });
+            }
+        }
+    }
+    Ok(imported)
+}
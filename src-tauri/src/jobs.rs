@@ -0,0 +1,197 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use tauri::{Emitter, Manager};
+
+use crate::error::AppError;
+use crate::processes;
+
+/// How many finished jobs we keep around for get_transcription_jobs
+const MAX_FINISHED_JOBS: usize = 50;
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug)]
+#[serde(rename_all = "lowercase")]
+pub enum JobStatus {
+    Pending,
+    Running,
+    Completed,
+    Failed,
+    Cancelled,
+}
+
+impl JobStatus {
+    fn is_finished(self) -> bool {
+        matches!(self, JobStatus::Completed | JobStatus::Failed | JobStatus::Cancelled)
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct TranscriptionJob {
+    pub id: String,
+    pub audio_path: String,
+    pub status: JobStatus,
+    pub error: Option<String>,
+    pub created_at: u64,
+}
+
+// Queued/running transcription jobs and the whisper PIDs they own
+pub struct TranscriptionJobState {
+    jobs: Mutex<Vec<TranscriptionJob>>,
+    pids: Mutex<HashMap<String, u32>>,
+    // Held for the duration of a job so submissions run one at a time, in order
+    run_lock: tokio::sync::Mutex<()>,
+    next_id: AtomicU64,
+}
+
+impl TranscriptionJobState {
+    pub fn new() -> Self {
+        TranscriptionJobState {
+            jobs: Mutex::new(Vec::new()),
+            pids: Mutex::new(HashMap::new()),
+            run_lock: tokio::sync::Mutex::new(()),
+            next_id: AtomicU64::new(1),
+        }
+    }
+
+    fn status(&self, job_id: &str) -> Option<JobStatus> {
+        self.jobs.lock().unwrap().iter().find(|j| j.id == job_id).map(|j| j.status)
+    }
+
+    /// Record the whisper child for a running job so it can be cancelled. A job cancelled before
+    /// its child started has it killed here instead, returning false.
+    pub fn register_pid(&self, job_id: &str, pid: u32) -> bool {
+        self.pids.lock().unwrap().insert(job_id.to_string(), pid);
+        // cancel_transcription marks the job before it looks for a PID, so one of the two sees the other
+        if self.is_cancelled(job_id) {
+            if self.pids.lock().unwrap().remove(job_id).is_some() {
+                terminate_pid(pid);
+            }
+            return false;
+        }
+        true
+    }
+
+    pub fn unregister_pid(&self, job_id: &str) {
+        self.pids.lock().unwrap().remove(job_id);
+    }
+
+    pub fn is_cancelled(&self, job_id: &str) -> bool {
+        self.status(job_id) == Some(JobStatus::Cancelled)
+    }
+
+    fn set_status(&self, app: &tauri::AppHandle, job_id: &str, status: JobStatus, error: Option<String>) {
+        let mut jobs = self.jobs.lock().unwrap();
+        let Some(job) = jobs.iter_mut().find(|j| j.id == job_id) else {
+            return;
+        };
+        // A cancelled job stays cancelled even if whisper reports a failure on the way out
+        if job.status == JobStatus::Cancelled {
+            return;
+        }
+        job.status = status;
+        job.error = error;
+        let _ = app.emit("transcription-job-update", job.clone());
+
+        // Trim the oldest finished jobs so the history doesn't grow forever
+        let finished = jobs.iter().filter(|j| j.status.is_finished()).count();
+        if finished > MAX_FINISHED_JOBS {
+            let mut to_drop = finished - MAX_FINISHED_JOBS;
+            jobs.retain(|j| {
+                if to_drop > 0 && j.status.is_finished() {
+                    to_drop -= 1;
+                    false
+                } else {
+                    true
+                }
+            });
+        }
+    }
+}
+
+/// Terminate a process by PID without relying on a child handle
+pub fn terminate_pid(pid: u32) {
+    if let Err(e) = processes::signal(pid, processes::Signal::Term) {
+        // Usually just a process that already exited
+        log::debug!("Couldn't terminate process {}: {}", pid, e);
+    }
+}
+
+/// Add a pending transcription job, returning its id for cancel_transcription. Callers hand
+/// the id to the frontend before run_job starts it.
+pub fn queue(app: &tauri::AppHandle, audio_path: &str) -> Result<String, AppError> {
+    let state = app.state::<TranscriptionJobState>();
+    let created_at = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map_err(|e| AppError::Other(format!("time error: {}", e)))?
+        .as_millis() as u64;
+    let job_id = format!("job-{}-{}", created_at, state.next_id.fetch_add(1, Ordering::SeqCst));
+    let job = TranscriptionJob {
+        id: job_id.clone(),
+        audio_path: audio_path.to_string(),
+        status: JobStatus::Pending,
+        error: None,
+        created_at,
+    };
+    state.jobs.lock().unwrap().push(job.clone());
+    let _ = app.emit("transcription-job-update", job);
+    Ok(job_id)
+}
+
+/// Run a queued job once every earlier job has finished
+pub async fn run_job<T, F, Fut>(app: &tauri::AppHandle, job_id: String, run: F) -> Result<T, AppError>
+where
+    F: FnOnce(String) -> Fut,
+    Fut: Future<Output = Result<T, AppError>>,
+{
+    let state = app.state::<TranscriptionJobState>();
+    let _turn = state.run_lock.lock().await;
+    if state.is_cancelled(&job_id) {
+        return Err(AppError::Cancelled("Transcription cancelled".to_string()));
+    }
+    state.set_status(app, &job_id, JobStatus::Running, None);
+
+    let result = run(job_id.clone()).await;
+    state.unregister_pid(&job_id);
+
+    if state.is_cancelled(&job_id) {
+        return Err(AppError::Cancelled("Transcription cancelled".to_string()));
+    }
+    match &result {
+        Ok(_) => state.set_status(app, &job_id, JobStatus::Completed, None),
+        Err(e) => state.set_status(app, &job_id, JobStatus::Failed, Some(e.to_string())),
+    }
+    result
+}
+
+/// Cancel a pending or running transcription job, killing its whisper process
+#[tauri::command]
+pub async fn cancel_transcription(
+    app: tauri::AppHandle,
+    state: tauri::State<'_, TranscriptionJobState>,
+    job_id: String,
+) -> Result<String, String> {
+    match state.status(&job_id) {
+        None => return Err(format!("Unknown transcription job '{}'", job_id)),
+        Some(status) if status.is_finished() => {
+            return Err(format!("Transcription job '{}' already finished", job_id));
+        }
+        Some(_) => {}
+    }
+
+    state.set_status(&app, &job_id, JobStatus::Cancelled, None);
+    if let Some(pid) = state.pids.lock().unwrap().remove(&job_id) {
+        terminate_pid(pid);
+    }
+
+    Ok(job_id)
+}
+
+/// List pending, running, and recently finished transcription jobs
+#[tauri::command]
+pub async fn get_transcription_jobs(
+    state: tauri::State<'_, TranscriptionJobState>,
+) -> Result<Vec<TranscriptionJob>, String> {
+    Ok(state.jobs.lock().unwrap().clone())
+}
@@ -0,0 +1,246 @@
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io::{Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use tauri::{Emitter, Manager};
+
+use crate::{audio, native_recorder, processes, recorder};
+
+/// How often the meter emits `audio-level`
+const METER_INTERVAL_MS: u64 = 100;
+
+/// Floor reported for digital silence instead of -inf
+const SILENCE_DBFS: f32 = -96.0;
+
+const MIC_TEST_SECS: u64 = 3;
+
+/// test_microphone gives up if the recorder hasn't finished by then
+const MIC_TEST_TIMEOUT_SECS: u64 = 6;
+
+/// Peak below this over the whole test most likely means a muted or wrong input
+const LIKELY_SILENT_PEAK_DBFS: f32 = -50.0;
+
+/// Samples at or above this magnitude count as clipped
+const CLIP_LEVEL: f32 = 0.999;
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug)]
+pub struct AudioLevel {
+    pub peak_dbfs: f32,
+    pub rms_dbfs: f32,
+}
+
+// Latest level seen by whichever recording is active, for get_current_audio_level polling
+pub struct AudioLevelState {
+    pub current: Mutex<Option<AudioLevel>>,
+}
+
+fn to_dbfs(amplitude: f32) -> f32 {
+    if amplitude <= 0.0 {
+        SILENCE_DBFS
+    } else {
+        (20.0 * amplitude.log10()).max(SILENCE_DBFS)
+    }
+}
+
+/// Peak and RMS level of samples in [-1.0, 1.0]
+pub fn level_of(samples: &[f32]) -> Option<AudioLevel> {
+    if samples.is_empty() {
+        return None;
+    }
+    let peak = samples.iter().fold(0.0f32, |acc, s| acc.max(s.abs()));
+    let rms = (samples.iter().map(|s| s * s).sum::<f32>() / samples.len() as f32).sqrt();
+    Some(AudioLevel { peak_dbfs: to_dbfs(peak), rms_dbfs: to_dbfs(rms) })
+}
+
+/// Where the meter reads samples from
+pub enum LevelSource {
+    /// Tail the WAV an external recorder is writing; the closure returns the current file
+    WavFile(Box<dyn Fn() -> Option<PathBuf> + Send>),
+    /// Samples the native recorder's capture callback set aside for metering
+    Native(Arc<Mutex<Vec<f32>>>),
+}
+
+/// Offset of the sample data in a WAV header, found by scanning for the "data" chunk id
+fn data_offset(file: &mut fs::File) -> Option<u64> {
+    let mut head = vec![0u8; 4096];
+    let n = file.read(&mut head).ok()?;
+    head[..n].windows(4).position(|w| w == b"data").map(|pos| pos as u64 + 8)
+}
+
+// Follows a growing 16- or 24-bit WAV and yields levels for bytes appended since the last read
+struct WavTail {
+    path: PathBuf,
+    offset: u64,
+    bytes_per_sample: usize,
+}
+
+fn decode_sample(bytes: &[u8]) -> f32 {
+    match bytes {
+        [a, b, c] => (i32::from_le_bytes([0, *a, *b, *c]) >> 8) as f32 / 8_388_607.0,
+        [a, b] => i16::from_le_bytes([*a, *b]) as f32 / i16::MAX as f32,
+        _ => 0.0,
+    }
+}
+
+impl WavTail {
+    fn read_new(&mut self) -> Option<AudioLevel> {
+        let mut file = fs::File::open(&self.path).ok()?;
+        if self.offset == 0 {
+            self.offset = data_offset(&mut file)?;
+            self.bytes_per_sample = audio::read_wav_info(&self.path)
+                .map(|info| if info.bits_per_sample == 24 { 3 } else { 2 })
+                .unwrap_or(2);
+        }
+        let len = file.metadata().ok()?.len();
+        if len <= self.offset {
+            return None;
+        }
+        file.seek(SeekFrom::Start(self.offset)).ok()?;
+        let mut bytes = Vec::new();
+        file.take(len - self.offset).read_to_end(&mut bytes).ok()?;
+        // Leave a trailing partial sample for next time so samples stay aligned
+        let usable = bytes.len() - bytes.len() % self.bytes_per_sample;
+        self.offset += usable as u64;
+        let samples: Vec<f32> = bytes[..usable].chunks_exact(self.bytes_per_sample).map(decode_sample).collect();
+        level_of(&samples)
+    }
+}
+
+/// Newest chunk-NNNN.wav in a live session dir, i.e. the one currently being recorded
+pub fn latest_chunk(dir: &Path) -> Option<PathBuf> {
+    fs::read_dir(dir)
+        .ok()?
+        .flatten()
+        .filter_map(|e| {
+            let name = e.file_name().to_string_lossy().to_string();
+            let index: usize = name.strip_prefix("chunk-")?.strip_suffix(".wav")?.parse().ok()?;
+            Some((index, e.path()))
+        })
+        .max_by_key(|(index, _)| *index)
+        .map(|(_, path)| path)
+}
+
+/// Emit `audio-level` every ~100ms until `is_active` turns false, then clear the polled level
+pub fn start_meter(
+    app: tauri::AppHandle,
+    source: LevelSource,
+    is_active: impl Fn(&tauri::AppHandle) -> bool + Send + 'static,
+) {
+    std::thread::spawn(move || {
+        let mut tail: Option<WavTail> = None;
+        loop {
+            std::thread::sleep(std::time::Duration::from_millis(METER_INTERVAL_MS));
+            if !is_active(&app) {
+                break;
+            }
+
+            let level = match &source {
+                LevelSource::Native(shared) => {
+                    let samples = std::mem::take(&mut *shared.lock().unwrap());
+                    level_of(&samples)
+                }
+                LevelSource::WavFile(current_path) => {
+                    let Some(path) = current_path() else { continue };
+                    if tail.as_ref().map(|t| t.path != path).unwrap_or(true) {
+                        tail = Some(WavTail { path, offset: 0, bytes_per_sample: 2 });
+                    }
+                    tail.as_mut().and_then(WavTail::read_new)
+                }
+            };
+
+            if let Some(level) = level {
+                *app.state::<AudioLevelState>().current.lock().unwrap() = Some(level);
+                let _ = app.emit("audio-level", level);
+            }
+        }
+        *app.state::<AudioLevelState>().current.lock().unwrap() = None;
+    });
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct MicTestResult {
+    pub backend: String,
+    pub device: Option<String>,
+    pub duration_secs: f64,
+    pub peak_dbfs: f32,
+    pub rms_dbfs: f32,
+    pub clipped_percent: f32,
+    pub likely_silent: bool,
+}
+
+/// Record MIC_TEST_SECS into `path`, giving up after MIC_TEST_TIMEOUT_SECS
+fn record_mic_test(
+    app: &tauri::AppHandle,
+    backend: recorder::RecorderBackend,
+    device: Option<&str>,
+    path: &Path,
+) -> Result<(), String> {
+    if backend == recorder::RecorderBackend::Native {
+        let recording = native_recorder::NativeRecording::start(device, native_recorder::NativeTarget::File(path.to_path_buf()))?;
+        std::thread::sleep(std::time::Duration::from_secs(MIC_TEST_SECS));
+        return recording.stop().map(|_| ());
+    }
+
+    let mut child = processes::spawn(app, &mut recorder::capture_command(
+        backend,
+        device,
+        path,
+        Some(MIC_TEST_SECS),
+        &recorder::RecordingProfile::default(),
+    )?)
+        .map_err(|e| format!("Failed to start {} recorder: {}", backend.as_str(), e))?;
+    let deadline = std::time::Instant::now() + std::time::Duration::from_secs(MIC_TEST_TIMEOUT_SECS);
+    loop {
+        if child.try_wait().map_err(|e| format!("Failed to wait for recorder: {}", e))?.is_some() {
+            app.state::<processes::ProcessRegistry>().unregister(child.id());
+            return Ok(());
+        }
+        if std::time::Instant::now() >= deadline {
+            let _ = child.kill();
+            let _ = processes::wait(app, &mut child);
+            return Err(format!("{} recorder did not finish within {}s", backend.as_str(), MIC_TEST_TIMEOUT_SECS));
+        }
+        std::thread::sleep(std::time::Duration::from_millis(100));
+    }
+}
+
+/// Record a few seconds from the selected input and report its levels; the recording is discarded
+#[tauri::command]
+pub async fn test_microphone(
+    app: tauri::AppHandle,
+    device: Option<String>,
+    backend: Option<String>,
+) -> Result<MicTestResult, String> {
+    let backend = recorder::resolve_backend(backend.as_deref())?;
+    let device = recorder::select_device(&app, device, backend)?;
+    let path = std::env::temp_dir().join(format!("{}.wav", audio::unique_name("last-gen-notes-mic-test")));
+
+    tauri::async_runtime::spawn_blocking(move || {
+        let recorded = record_mic_test(&app, backend, device.as_deref(), &path)
+            .and_then(|_| audio::read_pcm_samples(&path));
+        let _ = fs::remove_file(&path);
+        let (samples, info) = recorded?;
+        let level = level_of(&samples).ok_or("The recorder produced no audio")?;
+        let clipped = samples.iter().filter(|s| s.abs() >= CLIP_LEVEL).count();
+        Ok(MicTestResult {
+            backend: backend.as_str().to_string(),
+            device,
+            duration_secs: samples.len() as f64 / info.sample_rate.max(1) as f64,
+            peak_dbfs: level.peak_dbfs,
+            rms_dbfs: level.rms_dbfs,
+            clipped_percent: clipped as f32 * 100.0 / samples.len() as f32,
+            likely_silent: level.peak_dbfs < LIKELY_SILENT_PEAK_DBFS,
+        })
+    })
+    .await
+    .map_err(|e| format!("Mic test task failed: {}", e))?
+}
+
+/// Most recent input level, or None when nothing is recording
+#[tauri::command]
+pub async fn get_current_audio_level(
+    state: tauri::State<'_, AudioLevelState>,
+) -> Result<Option<AudioLevel>, String> {
+    Ok(*state.current.lock().unwrap())
+}
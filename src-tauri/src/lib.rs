@@ -1,37 +1,93 @@
 use serde::{Deserialize, Serialize};
-use sha2::{Sha256, Digest};
+use std::collections::{HashMap, HashSet};
 use std::fs;
-use std::path::PathBuf;
-use sysinfo::System;
-use tauri::Emitter;
-use tokio::io::AsyncWriteExt;
+use std::path::{Path, PathBuf};
+use tauri::{Emitter, Manager};
 use std::process::Command as StdCommand;
 use std::process::Child as StdChild;
 use std::sync::Mutex;
 
-#[derive(Serialize, Deserialize)]
-struct GpuStatus {
-    gpu_name: String,
-    is_iris_xe: bool,
-    status: String,
-}
-
-#[derive(Serialize, Deserialize)]
-struct PowerStatus {
-    power_now_mw: f64,
-    power_now_w: f64,
-}
+use error::AppError;
+
+mod actions;
+mod archive;
+mod audio;
+mod batch;
+mod binaries;
+mod chat_api;
+mod chunk_retry;
+mod chunk_tuning;
+mod clipboard;
+mod denoise;
+mod downloads;
+mod error;
+mod events;
+mod gain;
+mod gpu;
+mod hallucination;
+mod hotkey;
+mod import;
+mod jobs;
+mod levels;
+mod live_queue;
+mod live_summary;
+mod live_transcript;
+mod limits;
+mod llama;
+mod llama_server;
+mod logging;
+mod models;
+mod native_recorder;
+mod note_export;
+mod note_templates;
+mod notes;
+mod notifications;
+mod paths;
+mod power;
+mod processes;
+mod prompts;
+mod recorder;
+mod recorder_lock;
+mod recorder_status;
+mod recordings;
+mod retranscribe;
+mod retention;
+mod session;
+mod settings;
+mod setup;
+mod sleep_inhibit;
+mod stereo;
+mod summarize;
+mod telemetry;
+mod thermal;
+mod transcript;
+mod trim;
+mod tray;
+mod vad;
+mod watchdog;
+mod whisper;
+mod whisper_build;
 
 #[derive(Serialize, Deserialize)]
 struct MicPortalStatus {
     portal_running: bool,
     portal_gtk_running: bool,
     pipewire_running: bool,
+    pipewire_pulse_running: bool,
+    wireplumber_running: bool,
+    default_source: Option<String>,
+    /// "RUNNING", "IDLE" or "SUSPENDED", as pactl reports it
+    default_source_state: Option<String>,
+    /// Whether a 200ms probe capture delivered audio; None when not requested or ffmpeg is missing
+    capture_ok: Option<bool>,
+    // macOS only: whether the app may use the microphone (None when it couldn't be determined)
+    mic_permission: Option<bool>,
     message: String,
 }
 
 #[derive(Serialize, Deserialize, Clone)]
 pub struct DownloadProgress {
+    pub download_id: String,
     pub downloaded: u64,
     pub total: Option<u64>,
     pub percent: f32,
@@ -44,27 +100,160 @@ pub struct BinaryStatus {
     pub installed: bool,
     pub path: Option<String>,
     pub version: Option<String>,
+    pub is_executable: bool,
+    // Only filled in by verify_binary, since hashing a large binary on every poll is wasteful
+    pub sha256: Option<String>,
 }
 
 // Shared recorder state for long-running system recordings
 struct RecorderProcess {
-    child: StdChild,
+    handle: RecorderHandle,
+    // The first file, which identifies the recording
     path: PathBuf,
+    // Every file recorded to, the last being written now; the watchdog adds one per restart
+    parts: Vec<PathBuf>,
+    plan: recorder::CapturePlan,
+    backend: recorder::RecorderBackend,
+    archive: Option<recorder::ArchiveFormat>,
+    // The recorder writes the archive itself; otherwise it's encoded from the WAV on stop
+    archive_direct: bool,
+    // What an external recorder has printed, for errors if it leaves no audio
+    stderr: Option<recorder::StderrTail>,
+}
+
+enum RecorderHandle {
+    Process(StdChild),
+    Native(native_recorder::NativeRecording),
+    /// The watchdog took the failed recorder out to stop it and hasn't started the next one yet
+    Restarting,
 }
 
 struct RecorderState {
-    current: Mutex<Option<RecorderProcess>>,
+    current: parking_lot::Mutex<Option<RecorderProcess>>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct RecorderModeStatus {
+    mode: String,
+    backend: Option<recorder::RecorderBackend>,
+    /// Whether the machine is being kept awake for a recording
+    sleep_inhibited: bool,
 }
 
-// Live chunked recording state (30s segments)
+// Live chunked recording state (30s segments). Recorder state uses parking_lot locks, which don't
+// poison, so a panic in one chunk task can't leave every later start or stop failing.
 use std::sync::Arc;
 
+/// How long stop_live_recording waits for the last chunk to be transcribed
+const LIVE_DRAIN_TIMEOUT_SECS: u64 = 120;
+
 struct ChunkedRecorderState {
-    active: Arc<Mutex<bool>>,
-    chunk_index: Arc<Mutex<usize>>,
-    base_dir: Arc<Mutex<Option<PathBuf>>>,
-    transcripts: Arc<Mutex<Vec<String>>>,
-    ffmpeg_pid: Arc<Mutex<Option<u32>>>,
+    active: Arc<parking_lot::Mutex<bool>>,
+    paused: Arc<parking_lot::Mutex<bool>>,
+    chunk_index: Arc<parking_lot::Mutex<usize>>,
+    base_dir: Arc<parking_lot::Mutex<Option<PathBuf>>>,
+    transcripts: Arc<parking_lot::Mutex<live_transcript::LiveTranscript>>,
+    // The segmenting ffmpeg, kept so stop can ask it to quit and finalize the last segment
+    ffmpeg: Arc<parking_lot::Mutex<Option<StdChild>>>,
+    // Resolves once the recording loop has handed off the chunks left after the recorder stopped
+    drained: Arc<parking_lot::Mutex<Option<tokio::sync::oneshot::Receiver<()>>>>,
+    // Bumped on every start so a stale limits guard can't stop the next session
+    session_id: Arc<parking_lot::Mutex<u64>>,
+    native: Arc<parking_lot::Mutex<Option<native_recorder::NativeRecording>>>,
+    backend: Arc<parking_lot::Mutex<Option<recorder::RecorderBackend>>>,
+    // Chunks whose transcription failed, being retried or waiting for retranscribe_chunk
+    failed_chunks: Arc<parking_lot::Mutex<Vec<chunk_retry::FailedChunk>>>,
+}
+
+impl ChunkedRecorderState {
+    fn new() -> Self {
+        ChunkedRecorderState {
+            active: Arc::new(parking_lot::Mutex::new(false)),
+            paused: Arc::new(parking_lot::Mutex::new(false)),
+            chunk_index: Arc::new(parking_lot::Mutex::new(0)),
+            base_dir: Arc::new(parking_lot::Mutex::new(None)),
+            transcripts: Arc::new(parking_lot::Mutex::new(live_transcript::LiveTranscript::default())),
+            ffmpeg: Arc::new(parking_lot::Mutex::new(None)),
+            drained: Arc::new(parking_lot::Mutex::new(None)),
+            session_id: Arc::new(parking_lot::Mutex::new(0)),
+            native: Arc::new(parking_lot::Mutex::new(None)),
+            backend: Arc::new(parking_lot::Mutex::new(None)),
+            failed_chunks: Arc::new(parking_lot::Mutex::new(Vec::new())),
+        }
+    }
+
+    /// Reset the per-session state for a session recording into `dir`, returning the sender the
+    /// recording loop signals once it has drained and the new session's id
+    fn reset_for_session(
+        &self,
+        backend: recorder::RecorderBackend,
+        dir: PathBuf,
+    ) -> (tokio::sync::oneshot::Sender<()>, u64) {
+        *self.paused.lock() = false;
+        *self.backend.lock() = Some(backend);
+        *self.chunk_index.lock() = 0;
+        *self.base_dir.lock() = Some(dir);
+        self.transcripts.lock().clear();
+        self.failed_chunks.lock().clear();
+        let (drained_tx, drained_rx) = tokio::sync::oneshot::channel();
+        *self.drained.lock() = Some(drained_rx);
+        let mut id = self.session_id.lock();
+        *id += 1;
+        (drained_tx, *id)
+    }
+
+    /// Mark a new session active, failing if one already is. `prepare` runs first under the same lock
+    /// and returns the directory the session records into.
+    fn begin_session(
+        &self,
+        backend: recorder::RecorderBackend,
+        prepare: impl FnOnce() -> Result<PathBuf, AppError>,
+    ) -> Result<(PathBuf, tokio::sync::oneshot::Sender<()>, u64), AppError> {
+        let mut active = self.active.lock();
+        if *active {
+            return Err(AppError::RecorderBusy("Live recording already in progress".to_string()));
+        }
+        let dir = prepare()?;
+        *active = true;
+        let (drained_tx, session_id) = self.reset_for_session(backend, dir.clone());
+        Ok((dir, drained_tx, session_id))
+    }
+
+    /// Stop the recorders, with `stop_ffmpeg` closing out a segment recorder, then mark the session
+    /// inactive and wait up to `timeout` for its recording loop to drain. False if it didn't.
+    /// The recorders are taken out of the state first, so stopping them doesn't hold any of its locks.
+    async fn end_session(
+        &self,
+        stop_ffmpeg: impl FnOnce(StdChild) + Send + 'static,
+        timeout: tokio::time::Duration,
+    ) -> Result<bool, String> {
+        let (ffmpeg, native) = (self.ffmpeg.lock().take(), self.native.lock().take());
+        tauri::async_runtime::spawn_blocking(move || {
+            if let Some(child) = ffmpeg {
+                stop_ffmpeg(child);
+            }
+            if let Some(recording) = native {
+                if let Err(e) = recording.stop() {
+                    log::error!("native recorder stop failed: {}", e);
+                }
+            }
+        })
+        .await
+        .map_err(|e| format!("Failed to stop recorder: {}", e))?;
+
+        *self.active.lock() = false;
+        let drained = self.drained.lock().take();
+        Ok(match drained {
+            Some(drained) => tokio::time::timeout(timeout, drained).await.is_ok(),
+            None => true,
+        })
+    }
+}
+
+// Resolved whisper-cli location, cached so live chunks don't re-stat every candidate
+struct WhisperState {
+    resolved: Mutex<Option<PathBuf>>,
+    override_path: Mutex<Option<PathBuf>>,
 }
 
 /// Get the app data directory for storing binaries
@@ -80,113 +269,234 @@ fn get_binaries_dir() -> Result<PathBuf, String> {
     Ok(data_dir)
 }
 
-/// Check if a binary is installed and valid
+/// Get the app cache directory used for recordings and temp files
+fn get_cache_dir() -> Result<PathBuf, String> {
+    let cache_dir = dirs::cache_dir()
+        .ok_or("Could not find cache directory")?
+        .join("last-gen-notes");
+
+    fs::create_dir_all(&cache_dir)
+        .map_err(|e| format!("Failed to create cache directory: {}", e))?;
+
+    Ok(cache_dir)
+}
+
+/// Get the app data directory for the notes store and other persisted app data
+fn get_data_dir() -> Result<PathBuf, String> {
+    let data_dir = dirs::data_local_dir()
+        .ok_or("Could not find local data directory")?
+        .join("last-gen-notes");
+
+    fs::create_dir_all(&data_dir)
+        .map_err(|e| format!("Failed to create data directory: {}", e))?;
+
+    Ok(data_dir)
+}
+
+/// Get the directory imported audio is copied into; it belongs to the notes, so retention never scans it
+fn get_library_dir() -> Result<PathBuf, String> {
+    let library_dir = get_data_dir()?.join("library");
+
+    fs::create_dir_all(&library_dir)
+        .map_err(|e| format!("Failed to create library directory: {}", e))?;
+
+    Ok(library_dir)
+}
+
+/// Get the app config directory for persisted user settings
+fn get_config_dir() -> Result<PathBuf, String> {
+    let config_dir = dirs::config_dir()
+        .ok_or("Could not find config directory")?
+        .join("last-gen-notes");
+
+    fs::create_dir_all(&config_dir)
+        .map_err(|e| format!("Failed to create config directory: {}", e))?;
+
+    Ok(config_dir)
+}
+
+/// File holding the user's explicit whisper-cli override path
+fn whisper_override_file() -> Result<PathBuf, String> {
+    Ok(get_config_dir()?.join("whisper-binary-path"))
+}
+
+/// Load the persisted whisper-cli override, if any
+fn load_whisper_override() -> Option<PathBuf> {
+    let file = whisper_override_file().ok()?;
+    let content = fs::read_to_string(file).ok()?;
+    let trimmed = content.trim();
+    if trimmed.is_empty() {
+        None
+    } else {
+        Some(PathBuf::from(trimmed))
+    }
+}
+
+/// Look up a binary on PATH via `which`
+fn which_binary(name: &str) -> Option<PathBuf> {
+    let output = StdCommand::new("which").arg(name).output().ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    let path = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    if path.is_empty() {
+        None
+    } else {
+        Some(PathBuf::from(path))
+    }
+}
+
+/// Resolve whisper-cli: binaries dir, then the user override, then the bundled sidecar, then PATH
+fn find_whisper_binary(override_path: Option<&PathBuf>) -> Result<PathBuf, AppError> {
+    let exe_name = if cfg!(target_os = "windows") { "whisper-cli.exe" } else { "whisper-cli" };
+    let mut searched: Vec<String> = Vec::new();
+
+    let mut candidates: Vec<PathBuf> = Vec::new();
+    if let Ok(binaries_dir) = get_binaries_dir() {
+        candidates.push(binaries_dir.join(exe_name));
+        // Windows release zips extract into a Release/ subfolder
+        candidates.push(binaries_dir.join("Release").join(exe_name));
+        // Older whisper.cpp releases named the binary `main`
+        candidates.push(binaries_dir.join(if cfg!(target_os = "windows") { "main.exe" } else { "main" }));
+    }
+    if let Some(p) = override_path {
+        candidates.push(p.clone());
+    }
+    // Bundled sidecar (externalBin) is placed alongside the app executable
+    if let Some(exe_dir) = std::env::current_exe().ok().and_then(|p| p.parent().map(|d| d.to_path_buf())) {
+        candidates.push(exe_dir.join(exe_name));
+    }
+
+    for candidate in candidates {
+        if candidate.is_file() {
+            return Ok(candidate);
+        }
+        searched.push(candidate.to_string_lossy().to_string());
+    }
+
+    if let Some(p) = which_binary("whisper-cli") {
+        return Ok(p);
+    }
+    searched.push("PATH (which whisper-cli)".to_string());
+
+    Err(AppError::BinaryNotFound { name: "whisper-cli".to_string(), searched })
+}
+
+/// Resolve whisper-cli using the cached path when it is still valid
+fn resolve_whisper_binary(app: &tauri::AppHandle) -> Result<PathBuf, AppError> {
+    let state = app.state::<WhisperState>();
+    if let Some(cached) = state.resolved.lock().unwrap().clone() {
+        if cached.is_file() {
+            return Ok(cached);
+        }
+    }
+
+    let override_path = state.override_path.lock().unwrap().clone();
+    let resolved = find_whisper_binary(override_path.as_ref())?;
+    *state.resolved.lock().unwrap() = Some(resolved.clone());
+    Ok(resolved)
+}
+
+/// Persist an explicit whisper-cli path that survives restarts (empty string clears it)
 #[tauri::command]
-async fn check_binary_status(binary_name: String) -> Result<BinaryStatus, String> {
+async fn set_whisper_binary_path(
+    state: tauri::State<'_, WhisperState>,
+    path: String,
+) -> Result<String, String> {
+    let file = whisper_override_file()?;
+    let trimmed = path.trim();
+
+    if trimmed.is_empty() {
+        if file.exists() {
+            fs::remove_file(&file)
+                .map_err(|e| format!("Failed to clear whisper override: {}", e))?;
+        }
+        *state.override_path.lock().unwrap() = None;
+        *state.resolved.lock().unwrap() = None;
+        return Ok("Whisper binary override cleared".to_string());
+    }
+
+    let binary = PathBuf::from(trimmed);
+    if !binary.is_file() {
+        return Err(format!("Whisper binary not found at: {}", binary.display()));
+    }
+
+    fs::write(&file, trimmed)
+        .map_err(|e| format!("Failed to save whisper override: {}", e))?;
+    *state.override_path.lock().unwrap() = Some(binary.clone());
+    // Drop the cache so the next transcription re-resolves with the override in place
+    *state.resolved.lock().unwrap() = None;
+
+    Ok(binary.to_string_lossy().to_string())
+}
+
+/// Where a downloaded binary lives in the binaries dir, whether or not it's installed
+fn installed_binary_path(binary_name: &str) -> Result<PathBuf, String> {
     let binaries_dir = get_binaries_dir()?;
     
-    let binary_path = if cfg!(target_os = "windows") {
+    Ok(if binary_name == "llama-cli" {
+        // download_llama keeps llama-cli in its own subdirectory with its libraries
+        llama::installed_binary(&binaries_dir).unwrap_or_else(|| binaries_dir.join("llama").join(binary_name))
+    } else if cfg!(target_os = "windows") {
         binaries_dir.join(format!("{}.exe", binary_name))
     } else {
-        binaries_dir.join(&binary_name)
-    };
-    
-    let installed = binary_path.exists();
-    
-    Ok(BinaryStatus {
-        name: binary_name,
-        installed,
-        path: if installed { Some(binary_path.to_string_lossy().to_string()) } else { None },
-        version: None, // Could run --version to get this
+        binaries_dir.join(binary_name)
     })
 }
 
+/// Check if a binary is installed and valid, with its version
+#[tauri::command]
+async fn check_binary_status(app: tauri::AppHandle, binary_name: String) -> Result<BinaryStatus, String> {
+    tauri::async_runtime::spawn_blocking(move || binaries::binary_status(&app, binary_name))
+        .await
+        .map_err(|e| format!("Status task failed: {}", e))?
+}
+
 /// Download whisper.cpp binary from GitHub releases
 #[tauri::command]
-async fn download_whisper(window: tauri::Window) -> Result<String, String> {
+async fn download_whisper(window: tauri::Window) -> Result<String, AppError> {
     let binaries_dir = get_binaries_dir()?;
     
     // Determine platform-specific download
-    let (download_url, expected_sha256, archive_name) = if cfg!(target_os = "windows") {
+    let (release_path, expected_sha256, archive_name) = if cfg!(target_os = "windows") {
         if cfg!(target_arch = "x86_64") {
             (
-                "https://github.com/ggerganov/whisper.cpp/releases/download/v1.8.2/whisper-bin-x64.zip",
+                "v1.8.2/whisper-bin-x64.zip",
                 "b1514ebc099765e39fa37eb780b92a140a94c86bb0b3b3d98226b38825979732",
                 "whisper-bin-x64.zip"
             )
         } else {
             (
-                "https://github.com/ggerganov/whisper.cpp/releases/download/v1.8.2/whisper-bin-Win32.zip",
+                "v1.8.2/whisper-bin-Win32.zip",
                 "49244b4d13cc95f2f27a0098809a8514a835929fa0d24d1a8db6b9073650ba96",
                 "whisper-bin-Win32.zip"
             )
         }
     } else if cfg!(target_os = "linux") {
-        // Linux - we'll need to build from source or use a custom release
-        // For now, point to a hypothetical Linux release
-        return Err("Linux binary not available from official releases. Please build from source or use whisper.cpp AppImage.".to_string());
+        // There are no official Linux release binaries; use a self-hosted build or compile one
+        return whisper_build::download_prebuilt(&window).await;
     } else if cfg!(target_os = "macos") {
         (
-            "https://github.com/ggerganov/whisper.cpp/releases/download/v1.8.2/whisper-v1.8.2-xcframework.zip",
+            "v1.8.2/whisper-v1.8.2-xcframework.zip",
             "3ffeec1df254d908f01ee3d87bf0aedb8fbc8f29cbf50dc8702741bb85381385",
             "whisper-xcframework.zip"
         )
     } else {
-        return Err("Unsupported platform".to_string());
+        return Err("Unsupported platform".into());
     };
     
+    // A mirror serves the same files, and they're still checked against the canonical checksums
+    let settings = downloads::DownloadSettings::load();
+    let download_url = format!("{}/{}", settings.whisper_base(), release_path);
     let archive_path = binaries_dir.join(archive_name);
+    let download = downloads::ActiveDownload::start(&window, "whisper");
     
-    // Download with progress
-    emit_progress(&window, 0, None, "Starting download...");
-    
-    let client = reqwest::Client::new();
-    let response = client.get(download_url)
-        .send()
-        .await
-        .map_err(|e| format!("Download request failed: {}", e))?;
-    
-    let total_size = response.content_length();
-    let mut downloaded: u64 = 0;
-    
-    let mut file = tokio::fs::File::create(&archive_path)
-        .await
-        .map_err(|e| format!("Failed to create file: {}", e))?;
-    
-    let mut stream = response.bytes_stream();
-    use futures_util::StreamExt;
-    
-    let mut hasher = Sha256::new();
-    
-    while let Some(chunk) = stream.next().await {
-        let chunk = chunk.map_err(|e| format!("Download stream error: {}", e))?;
-        
-        file.write_all(&chunk)
-            .await
-            .map_err(|e| format!("Failed to write chunk: {}", e))?;
-        
-        hasher.update(&chunk);
-        downloaded += chunk.len() as u64;
-        
-        let percent = total_size.map(|t| (downloaded as f32 / t as f32) * 100.0).unwrap_or(0.0);
-        emit_progress(&window, downloaded, total_size, &format!("Downloading... {:.1}%", percent));
-    }
-    
-    file.flush().await.map_err(|e| format!("Failed to flush file: {}", e))?;
-    drop(file);
-    
-    // Verify SHA256
-    emit_progress(&window, downloaded, total_size, "Verifying checksum...");
-    let hash = hex::encode(hasher.finalize());
-    
-    if hash != expected_sha256 {
-        fs::remove_file(&archive_path).ok();
-        return Err(format!("Checksum mismatch! Expected: {}, Got: {}", expected_sha256, hash));
-    }
+    let (downloaded, total_size) =
+        downloads::fetch_verified(&window, &download, &download_url, &archive_path, Some(expected_sha256)).await?;
     
     // Extract archive
-    emit_progress(&window, downloaded, total_size, "Extracting...");
-    extract_zip(&archive_path, &binaries_dir)?;
+    emit_progress(&window, &download.id, downloaded, total_size, "Extracting...");
+    archive::extract_archive(&archive_path, &binaries_dir)?;
     
     // Clean up archive
     fs::remove_file(&archive_path).ok();
@@ -206,46 +516,15 @@ async fn download_whisper(window: tauri::Window) -> Result<String, String> {
         }
     }
     
-    emit_progress(&window, downloaded, total_size, "Complete!");
+    emit_progress(&window, &download.id, downloaded, total_size, "Complete!");
     
     Ok(binaries_dir.to_string_lossy().to_string())
 }
 
-fn extract_zip(archive_path: &PathBuf, dest_dir: &PathBuf) -> Result<(), String> {
-    let file = fs::File::open(archive_path)
-        .map_err(|e| format!("Failed to open archive: {}", e))?;
-    
-    let mut archive = zip::ZipArchive::new(file)
-        .map_err(|e| format!("Failed to read zip archive: {}", e))?;
-    
-    for i in 0..archive.len() {
-        let mut file = archive.by_index(i)
-            .map_err(|e| format!("Failed to read archive entry: {}", e))?;
-        
-        let outpath = match file.enclosed_name() {
-            Some(path) => dest_dir.join(path),
-            None => continue,
-        };
-        
-        if file.name().ends_with('/') {
-            fs::create_dir_all(&outpath).ok();
-        } else {
-            if let Some(p) = outpath.parent() {
-                fs::create_dir_all(p).ok();
-            }
-            let mut outfile = fs::File::create(&outpath)
-                .map_err(|e| format!("Failed to create extracted file: {}", e))?;
-            std::io::copy(&mut file, &mut outfile)
-                .map_err(|e| format!("Failed to extract file: {}", e))?;
-        }
-    }
-    
-    Ok(())
-}
-
-fn emit_progress(window: &tauri::Window, downloaded: u64, total: Option<u64>, status: &str) {
+fn emit_progress(window: &tauri::Window, download_id: &str, downloaded: u64, total: Option<u64>, status: &str) {
     let percent = total.map(|t| (downloaded as f32 / t as f32) * 100.0).unwrap_or(0.0);
     let _ = window.emit("download-progress", DownloadProgress {
+        download_id: download_id.to_string(),
         downloaded,
         total,
         percent,
@@ -277,186 +556,205 @@ fn greet(name: &str) -> String {
     format!("Hello, {}! You've been greeted from Rust!", name)
 }
 
-/// Detects Intel GPU and checks if it's Iris Xe
+/// Get the full path to a recording file in app cache
 #[tauri::command]
-async fn detect_gpu() -> Result<GpuStatus, String> {
-    let mut sys = System::new_all();
-    sys.refresh_all();
-
-    // Try to read GPU info from /sys/class/drm on Linux
-    let gpu_name = if cfg!(target_os = "linux") {
-        read_linux_gpu_name().unwrap_or_else(|_| "Unknown GPU".to_string())
-    } else {
-        "Unknown GPU (non-Linux)".to_string()
-    };
-
-    let is_iris_xe = gpu_name.to_lowercase().contains("iris xe");
-    let status = if is_iris_xe {
-        "Ready".to_string()
-    } else if gpu_name.to_lowercase().contains("intel") {
-        "Intel GPU detected, but not Iris Xe".to_string()
-    } else {
-        "Not Ready - Intel Iris Xe not detected".to_string()
-    };
-
-    Ok(GpuStatus {
-        gpu_name,
-        is_iris_xe,
-        status,
-    })
+async fn get_recording_path(filename: String) -> Result<String, AppError> {
+    let path = paths::in_cache_dir(&filename, recordings::RECORDING_EXTENSIONS)?;
+    Ok(path.to_string_lossy().to_string())
 }
 
-/// Read GPU name from Linux using lspci
-fn read_linux_gpu_name() -> Result<String, std::io::Error> {
-    // Use lspci to get GPU info directly
-    use std::process::Command;
-    
-    let output = Command::new("lspci")
-        .arg("-v")
-        .output();
-    
-    if let Ok(output) = output {
-        let stdout = String::from_utf8_lossy(&output.stdout);
-        
-        // Look for VGA controller line
-        for line in stdout.lines() {
-            if line.contains("VGA") && line.contains("Intel") {
-                // Extract the GPU name after the colon
-                if let Some(name_part) = line.split(':').nth(2) {
-                    let gpu_name = name_part.trim().to_string();
-                    
-                    // Check if it's Iris Xe (Alder Lake-P, Tiger Lake, etc.)
-                    if gpu_name.contains("Iris Xe") || 
-                       gpu_name.contains("Alder Lake") || 
-                       gpu_name.contains("Tiger Lake") ||
-                       gpu_name.contains("DG1") {
-                        return Ok(gpu_name);
-                    }
-                    
-                    return Ok(gpu_name);
-                }
+/// Stop everything we're recording and signal any other recorder child we spawned (blocking)
+fn stop_all_recorders_now(app: &tauri::AppHandle) -> recordings::CleanupSummary {
+    let mut summary = recordings::CleanupSummary::default();
+    let registry = app.state::<processes::ProcessRegistry>();
+
+    let current = app.state::<RecorderState>().current.lock().take();
+    if let Some(proc) = current {
+        match proc.handle {
+            // Native recordings are owned by us, so stop them directly rather than signalling
+            RecorderHandle::Native(recording) => {
+                let _ = recording.stop();
+                summary.recordings_stopped += 1;
+            }
+            RecorderHandle::Process(mut child) => {
+                recorder::stop_process(&mut child, proc.backend);
+                registry.unregister(child.id());
+                summary.processes_signalled += 1;
             }
+            // The watchdog is stopping the old recorder and won't start another once this is gone
+            RecorderHandle::Restarting => {}
         }
     }
-    
-    // Fallback to sysinfo
-    Ok("Intel Integrated Graphics".to_string())
-}
 
-/// Monitor battery power consumption on Linux
-#[tauri::command]
-async fn get_power_status() -> Result<PowerStatus, String> {
-    if cfg!(target_os = "linux") {
-        let power_now_path = "/sys/class/power_supply/BAT0/power_now";
-        
-        match fs::read_to_string(power_now_path) {
-            Ok(content) => {
-                let power_uw: f64 = content
-                    .trim()
-                    .parse()
-                    .map_err(|e| format!("Failed to parse power value: {}", e))?;
-                
-                // Convert from microwatts to milliwatts and watts
-                let power_now_mw = power_uw / 1000.0;
-                let power_now_w = power_uw / 1_000_000.0;
-
-                Ok(PowerStatus {
-                    power_now_mw,
-                    power_now_w,
-                })
-            }
-            Err(e) => Err(format!("Failed to read power status: {}", e)),
+    let live = app.state::<ChunkedRecorderState>();
+    let was_paused = std::mem::replace(&mut *live.paused.lock(), false);
+    let ffmpeg = live.ffmpeg.lock().take();
+    if let Some(mut child) = ffmpeg {
+        if was_paused && !cfg!(target_os = "windows") {
+            continue_stopped(&child);
         }
-    } else {
-        Err("Power monitoring only supported on Linux".to_string())
+        recorder::quit_ffmpeg(&mut child);
+        registry.unregister(child.id());
+        summary.processes_signalled += 1;
+    }
+    let native = live.native.lock().take();
+    if let Some(recording) = native {
+        let _ = recording.stop();
+        summary.recordings_stopped += 1;
     }
+    *live.active.lock() = false;
+    recorder_status::transition(app, recorder_status::Transition::SystemStopped);
+    recorder_status::transition(app, recorder_status::Transition::LiveFinished);
+
+    // Whatever is left (e.g. a per-chunk arecord) is signalled by PID; nothing we didn't spawn
+    summary.processes_signalled += registry.terminate_all();
+    summary
 }
 
-/// Get the full path to a recording file in app cache
+/// Stop all recorders this app started, leaving every file in place
 #[tauri::command]
-async fn get_recording_path(filename: String) -> Result<String, String> {
-    let cache_dir = dirs::cache_dir()
-        .ok_or("Could not find cache directory")?
-        .join("last-gen-notes");
-    
-    fs::create_dir_all(&cache_dir)
-        .map_err(|e| format!("Failed to create cache directory: {}", e))?;
-    
-    let path = cache_dir.join(&filename);
-    Ok(path.to_string_lossy().to_string())
+async fn stop_all_recorders(app: tauri::AppHandle) -> Result<recordings::CleanupSummary, String> {
+    tauri::async_runtime::spawn_blocking(move || stop_all_recorders_now(&app))
+        .await
+        .map_err(|e| format!("Failed to stop recorders: {}", e))
 }
 
-/// Cleanup helper: kill recorder processes and clear cached wav chunks
+/// Cleanup helper: stop recorders, clear the live-session cache and the WAV recordings.
+/// Kept for older frontends; new code should call stop_all_recorders / clear_* directly.
 #[tauri::command]
-async fn cleanup_recorders_and_cache() -> Result<String, String> {
-    // Kill ffmpeg/arecord best-effort
-    let _ = StdCommand::new("pkill").arg("ffmpeg").output();
-    let _ = StdCommand::new("pkill").arg("arecord").output();
-
-    let cache_base = dirs::cache_dir()
-        .ok_or("Could not find cache directory")?
-        .join("last-gen-notes");
-
-    // Remove live-session wavs and sys-recording wavs
-    let live_dir = cache_base.join("live-session");
-    if live_dir.exists() {
-        let _ = std::fs::remove_dir_all(&live_dir);
-        let _ = std::fs::create_dir_all(&live_dir);
-    }
-    if cache_base.exists() {
-        for entry in std::fs::read_dir(&cache_base).unwrap_or_else(|_| std::fs::read_dir("/dev/null").unwrap()) {
-            if let Ok(ent) = entry {
-                let path = ent.path();
-                if let Some(ext) = path.extension() {
-                    if ext == "wav" {
-                        let _ = std::fs::remove_file(&path);
-                    }
-                }
-            }
-        }
-    }
-
-    Ok("Cache cleared and recorder processes signaled".to_string())
+async fn cleanup_recorders_and_cache(app: tauri::AppHandle) -> Result<String, String> {
+    // The live-session cache is shared, so only the owning instance may clear it
+    recorder_lock::ensure_owner_async().await?;
+    let stopped = stop_all_recorders(app.clone()).await?;
+    let live = recordings::clear_live_session_cache(app.clone()).await?;
+    let cleared = recordings::clear_wav_recordings(&app)?;
+
+    Ok(format!(
+        "Cache cleared and recorder processes signaled ({} processes, {} files removed)",
+        stopped.processes_signalled,
+        live.files_removed.len() + cleared.files_removed.len()
+    ))
 }
 
-/// Check if the Linux portals/pipewire needed for mic prompts are running
+/// Check the Linux portals, pipewire session and default source needed for capture, optionally recording a
+/// 200ms probe (`probe_capture`); on macOS, check mic permission
 #[tauri::command]
-async fn check_mic_portal() -> Result<MicPortalStatus, String> {
-    fn is_running(name: &str) -> bool {
-        StdCommand::new("pgrep").arg(name).output().map(|o| o.status.success()).unwrap_or(false)
+async fn check_mic_portal(probe_capture: Option<bool>) -> Result<MicPortalStatus, String> {
+    use recorder::is_process_running as is_running;
+
+    if cfg!(target_os = "macos") {
+        let permission = tauri::async_runtime::spawn_blocking(recorder::probe_mic_permission)
+            .await
+            .map_err(|e| format!("Microphone check failed: {}", e))?;
+        let message = match permission {
+            Some(true) => "Microphone access granted",
+            Some(false) => "Microphone access denied; allow it in System Settings > Privacy & Security > Microphone",
+            None => "Couldn't determine microphone access (is ffmpeg installed?)",
+        };
+        return Ok(MicPortalStatus {
+            portal_running: false,
+            portal_gtk_running: false,
+            pipewire_running: false,
+            pipewire_pulse_running: false,
+            wireplumber_running: false,
+            default_source: None,
+            default_source_state: None,
+            capture_ok: None,
+            mic_permission: permission,
+            message: message.to_string(),
+        });
     }
 
     let portal = is_running("xdg-desktop-portal");
     let portal_gtk = is_running("xdg-desktop-portal-gtk");
     let pipewire = is_running("pipewire");
+    let pipewire_pulse = is_running("pipewire-pulse");
+    let wireplumber = is_running("wireplumber");
+    let probe = probe_capture.unwrap_or(false);
+    let (source, capture_ok) = tauri::async_runtime::spawn_blocking(move || {
+        let source = recorder::default_source();
+        let capture_ok = if probe && source.is_some() { recorder::probe_default_capture() } else { None };
+        (source, capture_ok)
+    })
+    .await
+    .map_err(|e| format!("Microphone check failed: {}", e))?;
+    let source_name = source.as_ref().map(|s| s.name.clone());
+    let source_state = source.as_ref().and_then(|s| s.state.clone());
 
-    let message = if portal && pipewire {
-        "Portal and pipewire are running".to_string()
-    } else {
+    // Most specific problem first: missing processes, then the session manager, then the source itself
+    let message = if !portal || !pipewire {
         let mut missing = vec![];
         if !portal { missing.push("xdg-desktop-portal"); }
         if !portal_gtk { missing.push("xdg-desktop-portal-gtk"); }
         if !pipewire { missing.push("pipewire"); }
         format!("Missing: {}", missing.join(", "))
+    } else if !wireplumber && !is_running("pipewire-media-session") {
+        "pipewire is running but wireplumber isn't, so no devices are set up; run `systemctl --user restart wireplumber`".to_string()
+    } else if source.is_none() && !pipewire_pulse {
+        "pipewire-pulse isn't running, so the default source can't be found; install or start pipewire-pulse".to_string()
+    } else if source.is_none() {
+        "No default audio source; pick an input device in pavucontrol or your sound settings".to_string()
+    } else {
+        let name = source_name.as_deref().unwrap_or_default();
+        match capture_ok {
+            Some(false) if source_state.as_deref() == Some("SUSPENDED") => {
+                format!("Default source {} is suspended and delivered no audio; check pavucontrol", name)
+            }
+            Some(false) => format!("No audio arrived from default source {}; check it isn't muted in pavucontrol", name),
+            Some(true) => format!("Capturing from {}", name),
+            None => format!("Portal and pipewire are running; default source is {}", name),
+        }
     };
 
     Ok(MicPortalStatus {
         portal_running: portal,
         portal_gtk_running: portal_gtk,
         pipewire_running: pipewire,
+        pipewire_pulse_running: pipewire_pulse,
+        wireplumber_running: wireplumber,
+        default_source: source_name,
+        default_source_state: source_state,
+        capture_ok,
+        mic_permission: None,
         message,
     })
 }
 
-/// Record audio via system arecord for 10 seconds and return the file path
+/// Longest one-shot recording record_system_audio accepts
+const MAX_ONE_SHOT_SECS: u64 = 600;
+
+// Cancel flag of the running record_system_audio call, if any
+struct OneShotState {
+    cancel: Mutex<Option<Arc<std::sync::atomic::AtomicBool>>>,
+}
+
+enum OneShotCapture {
+    Native(native_recorder::NativeRecording),
+    Process(StdChild, recorder::StderrTail),
+}
+
+/// Record for a fixed time (default 10s), emitting `recording-countdown` each second; returns the file path
 #[tauri::command]
-async fn record_system_audio() -> Result<String, String> {
+async fn record_system_audio(
+    app: tauri::AppHandle,
+    device: Option<String>,
+    backend: Option<String>,
+    duration_secs: Option<u64>,
+) -> Result<String, AppError> {
+    recorder_lock::ensure_owner_async().await?;
+    let duration = duration_secs.unwrap_or(10);
+    if !(1..=MAX_ONE_SHOT_SECS).contains(&duration) {
+        return Err(format!("Recording duration must be between 1 and {} seconds (got {})", MAX_ONE_SHOT_SECS, duration).into());
+    }
+    let backend = recorder::resolve_backend(backend.or(settings::current(&app).backend).as_deref())?;
+    let device = recorder::select_device(&app, device, backend)?;
+
     // Ensure cache dir exists
     let cache_dir = dirs::cache_dir()
         .ok_or("Could not find cache directory")?
         .join("last-gen-notes");
     fs::create_dir_all(&cache_dir)
-        .map_err(|e| format!("Failed to create cache directory: {}", e))?;
+        .map_err(|e| AppError::io("Failed to create cache directory", e))?;
 
     // Simple unique filename using system time
     let ts = std::time::SystemTime::now()
@@ -465,35 +763,156 @@ async fn record_system_audio() -> Result<String, String> {
         .as_secs();
     let outfile = cache_dir.join(format!("sys-recording-{}.wav", ts));
 
-    // arecord command: 16-bit PCM, mono, 16kHz, duration 10s
-    let status = StdCommand::new("arecord")
-        .arg("-f").arg("S16_LE")
-        .arg("-r").arg("16000")
-        .arg("-c").arg("1")
-        .arg("-d").arg("10")
-        .arg(outfile.to_string_lossy().to_string())
-        .status()
-        .map_err(|e| format!("Failed to start arecord: {}", e))?;
+    let cancel = Arc::new(std::sync::atomic::AtomicBool::new(false));
+    {
+        let one_shot = app.state::<OneShotState>();
+        let mut slot = one_shot.cancel.lock().unwrap();
+        if slot.is_some() {
+            return Err(AppError::RecorderBusy("A one-shot recording is already running".to_string()));
+        }
+        *slot = Some(cancel.clone());
+    }
+    recorder_status::transition(&app, recorder_status::Transition::OneShotStarted);
+
+    let recording_flag = Arc::new(std::sync::atomic::AtomicBool::new(true));
+    let meter_flag = recording_flag.clone();
+    let meter_active = move |_: &tauri::AppHandle| meter_flag.load(std::sync::atomic::Ordering::Relaxed);
+    let result = run_one_shot(&app, backend, device.as_deref(), &outfile, duration, &cancel, meter_active).await;
+    recording_flag.store(false, std::sync::atomic::Ordering::Relaxed);
+    *app.state::<OneShotState>().cancel.lock().unwrap() = None;
+    recorder_status::transition(&app, recorder_status::Transition::OneShotStopped);
+
+    if cancel.load(std::sync::atomic::Ordering::Relaxed) {
+        let _ = fs::remove_file(&outfile);
+        return Err(AppError::Cancelled("Recording cancelled".to_string()));
+    }
+    result?;
+    retention::enforce_in_background(app.clone());
+    Ok(outfile.to_string_lossy().to_string())
+}
 
-    if !status.success() {
-        return Err("arecord did not complete successfully".to_string());
+async fn run_one_shot(
+    app: &tauri::AppHandle,
+    backend: recorder::RecorderBackend,
+    device: Option<&str>,
+    outfile: &Path,
+    duration: u64,
+    cancel: &std::sync::atomic::AtomicBool,
+    meter_active: impl Fn(&tauri::AppHandle) -> bool + Send + 'static,
+) -> Result<(), AppError> {
+    let mut capture = if backend == recorder::RecorderBackend::Native {
+        let recording = native_recorder::NativeRecording::start(
+            device,
+            native_recorder::NativeTarget::File(outfile.to_path_buf()),
+        )?;
+        levels::start_meter(app.clone(), levels::LevelSource::Native(recording.meter_buffer()), meter_active);
+        OneShotCapture::Native(recording)
+    } else {
+        // 16-bit PCM, mono, 16kHz; the recorder stops itself after `duration`
+        let mut command = recorder::capture_command(backend, device, outfile, Some(duration), &recorder::RecordingProfile::default())?;
+        let mut child = processes::spawn(app, command.stderr(std::process::Stdio::piped()))
+            .map_err(|e| format!("Failed to start {} recorder: {}", backend.as_str(), e))?;
+        let stderr = recorder::StderrTail::capture(&mut child);
+        let meter_path = outfile.to_path_buf();
+        levels::start_meter(app.clone(), levels::LevelSource::WavFile(Box::new(move || Some(meter_path.clone()))), meter_active);
+        OneShotCapture::Process(child, stderr)
+    };
+
+    let started = std::time::Instant::now();
+    let mut last_reported = None;
+    loop {
+        let elapsed = started.elapsed().as_secs();
+        if last_reported != Some(elapsed) && elapsed <= duration {
+            last_reported = Some(elapsed);
+            let _ = app.emit("recording-countdown", serde_json::json!({
+                "remaining_secs": duration - elapsed,
+                "duration_secs": duration,
+            }));
+        }
+
+        let cancelled = cancel.load(std::sync::atomic::Ordering::Relaxed);
+        match &mut capture {
+            OneShotCapture::Native(_) if cancelled || elapsed >= duration => break,
+            OneShotCapture::Process(child, _) if cancelled => {
+                // The partial file is deleted, so there's no header worth finalizing
+                let _ = child.kill();
+                let _ = processes::wait(app, child);
+                return Ok(());
+            }
+            OneShotCapture::Process(child, stderr) => {
+                if let Some(status) = child.try_wait().map_err(|e| format!("Failed to wait for recorder: {}", e))? {
+                    app.state::<processes::ProcessRegistry>().unregister(child.id());
+                    if !status.success() {
+                        let stderr = stderr.clone();
+                        let failure = tauri::async_runtime::spawn_blocking(move || {
+                            stderr.failure(backend, format!("{} recorder did not complete successfully", backend.as_str()))
+                        })
+                        .await
+                        .map_err(|e| format!("Failed to read recorder output: {}", e))?;
+                        log::error!("One-shot recording failed ({}): {}", status, failure);
+                        return Err(failure);
+                    }
+                    return Ok(());
+                }
+            }
+            _ => {}
+        }
+        tokio::time::sleep(tokio::time::Duration::from_millis(100)).await;
     }
 
-    Ok(outfile.to_string_lossy().to_string())
+    if let OneShotCapture::Native(recording) = capture {
+        // Joins the writer thread, so keep it off the async runtime
+        tauri::async_runtime::spawn_blocking(move || recording.stop())
+            .await
+            .map_err(|e| format!("Failed to stop recorder: {}", e))??;
+    }
+    Ok(())
+}
+
+/// Abort the running record_system_audio call and delete its partial file
+#[tauri::command]
+async fn cancel_one_shot_recording(state: tauri::State<'_, OneShotState>) -> Result<(), String> {
+    match state.cancel.lock().unwrap().as_ref() {
+        Some(cancel) => {
+            cancel.store(true, std::sync::atomic::Ordering::Relaxed);
+            Ok(())
+        }
+        None => Err("No one-shot recording in progress".into()),
+    }
 }
 
 /// Start long system recording (until stopped). Returns output path.
 #[tauri::command]
-async fn start_system_recording(state: tauri::State<'_, RecorderState>) -> Result<String, String> {
-    if state.current.lock().unwrap().is_some() {
-        return Err("Recording already in progress".into());
+async fn start_system_recording(
+    app: tauri::AppHandle,
+    state: tauri::State<'_, RecorderState>,
+    device: Option<String>,
+    backend: Option<String>,
+    capture_source: Option<String>,
+    max_duration_secs: Option<u64>,
+    archive_format: Option<String>,
+) -> Result<String, AppError> {
+    recorder_lock::ensure_owner_async().await?;
+    if state.current.lock().is_some() {
+        return Err(AppError::RecorderBusy("Recording already in progress".to_string()));
+    }
+    let backend = recorder::resolve_backend(backend.or(settings::current(&app).backend).as_deref())?;
+    let source = recorder::CaptureSource::parse(capture_source.as_deref())?;
+    let archive = recorder::ArchiveFormat::parse(archive_format.as_deref())?;
+    let device = recorder::select_device(&app, device, backend)?;
+    let profile = *app.state::<recorder::RecordingProfileState>().profile.lock().unwrap();
+    let plan = recorder::CapturePlan::new(backend, source, device)?.with_profile(profile).with_archive(archive);
+    let archive_direct = plan.writes_archive_directly();
+    if let Some(format) = archive.filter(|f| !archive_direct && !f.can_encode()) {
+        return Err(format!("Writing a {} archive needs ffmpeg or {}", format.extension(), format.encoder_tool()).into());
     }
 
     let cache_dir = dirs::cache_dir()
         .ok_or("Could not find cache directory")?
         .join("last-gen-notes");
     fs::create_dir_all(&cache_dir)
-        .map_err(|e| format!("Failed to create cache directory: {}", e))?;
+        .map_err(|e| AppError::io("Failed to create cache directory", e))?;
+    limits::check_free_space(&app, &cache_dir)?;
 
     let ts = std::time::SystemTime::now()
         .duration_since(std::time::UNIX_EPOCH)
@@ -501,124 +920,602 @@ async fn start_system_recording(state: tauri::State<'_, RecorderState>) -> Resul
         .as_secs();
     let outfile = cache_dir.join(format!("sys-recording-{}.wav", ts));
 
-    let child = StdCommand::new("arecord")
-        .arg("-f").arg("S16_LE")
-        .arg("-r").arg("16000")
-        .arg("-c").arg("1")
-        .arg(outfile.to_string_lossy().to_string())
-        .spawn()
-        .map_err(|e| format!("Failed to start arecord: {}", e))?;
+    let (handle, stderr) = if backend == recorder::RecorderBackend::Native {
+        let recording = native_recorder::NativeRecording::start_with_profile(
+            plan.mic.as_deref(),
+            native_recorder::NativeTarget::File(outfile.clone()),
+            profile,
+        )?;
+        (RecorderHandle::Native(recording), None)
+    } else {
+        let mut child = processes::spawn(&app, plan.command(&outfile, None)?.stderr(std::process::Stdio::piped()))
+            .map_err(|e| format!("Failed to start {} recorder: {}", backend.as_str(), e))?;
+        let stderr = recorder::StderrTail::capture(&mut child);
+        (RecorderHandle::Process(child), Some(stderr))
+    };
+
+    let level_source = match &handle {
+        RecorderHandle::Native(recording) => levels::LevelSource::Native(recording.meter_buffer()),
+        RecorderHandle::Process(_) | RecorderHandle::Restarting => {
+            // Follows the watchdog onto later parts
+            let (meter_app, recording) = (app.clone(), outfile.clone());
+            levels::LevelSource::WavFile(Box::new(move || {
+                let state = meter_app.state::<RecorderState>();
+                let current = state.current.lock();
+                current.as_ref().filter(|p| p.path == recording).and_then(|p| p.parts.last().cloned())
+            }))
+        }
+    };
+    let watched = matches!(handle, RecorderHandle::Process(_));
+    *state.current.lock() = Some(RecorderProcess {
+        handle,
+        path: outfile.clone(),
+        parts: vec![outfile.clone()],
+        plan,
+        backend,
+        archive,
+        archive_direct,
+        stderr,
+    });
 
-    *state.current.lock().unwrap() = Some(RecorderProcess { child, path: outfile.clone() });
+    // Meter until this particular recording is stopped
+    let metered = outfile.clone();
+    levels::start_meter(app.clone(), level_source, move |app| {
+        app.state::<RecorderState>().current.lock().as_ref().map(|p| p.path == metered).unwrap_or(false)
+    });
+    limits::start_guard(app.clone(), limits::GuardedRecording::System(outfile.clone()), cache_dir, max_duration_secs);
+    if watched {
+        watchdog::start(&app, outfile.clone());
+    }
+    log::info!("System recording started with {} ({:?}) to {}", backend.as_str(), source, outfile.display());
+    recorder_status::transition(&app, recorder_status::Transition::SystemStarted);
     Ok(outfile.to_string_lossy().to_string())
 }
 
-/// Stop long system recording. Returns path to recorded file.
+#[derive(Serialize, Deserialize)]
+struct SystemRecording {
+    // The first part with audio in it
+    path: String,
+    // Across every part
+    duration_secs: f64,
+    // The recorder left placeholder sizes in the WAV header and they were rewritten
+    header_repaired: bool,
+    // Compressed copy next to the WAV, when start_system_recording asked for one; later parts
+    // have theirs next to them
+    archive_path: Option<String>,
+    archive_error: Option<String>,
+    // Every file with audio, in order; more than one when the watchdog restarted a failed recorder
+    parts: Vec<String>,
+}
+
+/// Make sure the archive for `wav` exists, encoding it now if the recorder couldn't write it,
+/// then bring a profile-quality WAV down to whisper's format
+fn finish_archive(
+    app: &tauri::AppHandle,
+    wav: &Path,
+    format: recorder::ArchiveFormat,
+    direct: bool,
+) -> Result<PathBuf, String> {
+    let archive = format.archive_path(wav);
+    if !direct {
+        let output = processes::output(app, &mut format.encode_command(wav, &archive)?)
+            .map_err(|e| format!("Failed to start {} encoder: {}", format.extension(), e))?;
+        if !output.status.success() {
+            return Err(format!(
+                "Encoding the {} archive failed: {}",
+                format.extension(),
+                String::from_utf8_lossy(&output.stderr).trim()
+            ));
+        }
+        let whisper_ready = audio::read_wav_info(wav).map(|info| info.is_whisper_ready()).unwrap_or(true);
+        if !whisper_ready && has_ffmpeg() {
+            let converted = wav.with_file_name(format!(
+                "convert-{}",
+                wav.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default()
+            ));
+            audio::convert_to_whisper_wav(app, &wav.to_string_lossy(), &converted)?;
+            fs::rename(&converted, wav).map_err(|e| format!("Failed to replace {}: {}", wav.display(), e))?;
+        }
+    }
+    let has_audio = fs::metadata(&archive).map(|m| m.len() > 0).unwrap_or(false);
+    if !has_audio {
+        return Err(format!("The {} archive wasn't written", format.extension()));
+    }
+    Ok(archive)
+}
+
+/// Stop long system recording. Returns the recorded file and its duration.
 #[tauri::command]
-async fn stop_system_recording(state: tauri::State<'_, RecorderState>) -> Result<String, String> {
+async fn stop_system_recording(app: tauri::AppHandle) -> Result<SystemRecording, AppError> {
+    finish_system_recording(&app).await
+}
+
+/// Finalize the current system recording; shared by stop_system_recording and the limits guard
+async fn finish_system_recording(app: &tauri::AppHandle) -> Result<SystemRecording, AppError> {
+    let state = app.state::<RecorderState>();
     let proc = {
-        let mut guard = state.current.lock().unwrap();
+        let mut guard = state.current.lock();
         guard.take()
     };
     
-    if let Some(mut proc) = proc {
-        // Try to send SIGTERM via pkill (more reliable than child.kill on some systems)
-        let pid = proc.child.id();
-        let _ = StdCommand::new("kill")
-            .arg("-TERM")
-            .arg(pid.to_string())
-            .output();
-        
-        // Wait for process to exit
-        let _ = proc.child.wait();
-        
-        // Give OS time to flush buffers and finalize the file
-        tokio::time::sleep(tokio::time::Duration::from_millis(300)).await;
+    if let Some(proc) = proc {
+        recorder_status::transition(app, recorder_status::Transition::SystemStopped);
+        // Stopping waits on the writer thread or the recorder's exit, which can take a while with a
+        // slow arecord, so it runs on the blocking pool rather than stalling the async runtime
+        let (handle, backend, stderr) = (proc.handle, proc.backend, proc.stderr.clone());
+        let stopped_pid = tauri::async_runtime::spawn_blocking(move || match handle {
+            // The writer finalizes the WAV header before stop returns
+            RecorderHandle::Native(recording) => recording.stop().map(|_| None),
+            RecorderHandle::Process(mut child) => {
+                // Signal (or on Windows, ask) the recorder to finish and wait for it to exit
+                recorder::stop_process(&mut child, backend);
+                if let Some(stderr) = &stderr {
+                    // Collected now so it's complete if the file turns out to be empty
+                    stderr.final_text();
+                }
+                Ok(Some(child.id()))
+            }
+            // The watchdog is stopping the failed recorder and sees the recording is gone before restarting it
+            RecorderHandle::Restarting => Ok(None),
+        })
+        .await
+        .map_err(|e| format!("Failed to stop recorder: {}", e))??;
+        if let Some(pid) = stopped_pid {
+            app.state::<processes::ProcessRegistry>().unregister(pid);
+            // Give OS time to flush buffers and finalize the file
+            tokio::time::sleep(tokio::time::Duration::from_millis(300)).await;
+        }
         
-        // Verify file exists and has content
-        match std::fs::metadata(&proc.path) {
-            Ok(meta) if meta.len() > 44 => {
-                // WAV header is 44 bytes; file is valid if larger
-                return Ok(proc.path.to_string_lossy().to_string());
+        // A killed recorder can leave placeholder sizes that make players see a zero-length file
+        let invalid = |part: &Path, reason: String| {
+            let message = format!("Recording file invalid or empty at {}: {}", part.display(), reason);
+            match &proc.stderr {
+                Some(stderr) => stderr.failure(backend, message),
+                None => AppError::Other(message),
             }
-            _ => {
-                return Err(format!(
-                    "Recording file invalid or empty at: {}",
-                    proc.path.display()
-                ));
+        };
+        // A part the watchdog gave up on may hold nothing; that's only an error if they all do
+        let mut parts = Vec::new();
+        let mut header_repaired = false;
+        let mut duration_secs = 0.0;
+        let mut last_error = None;
+        for part in &proc.parts {
+            let checked = audio::repair_wav_header(part).and_then(|repaired| Ok((repaired, audio::read_wav_info(part)?)));
+            let error = match checked {
+                Ok((repaired, info)) if info.data_len > 0 => {
+                    header_repaired |= repaired;
+                    duration_secs += info.duration_secs();
+                    parts.push(part.clone());
+                    continue;
+                }
+                Ok(_) => invalid(part, "no audio was recorded".to_string()),
+                Err(e) => invalid(part, e),
+            };
+            if proc.parts.len() > 1 {
+                log::warn!("Leaving {} out of the recording: {}", part.display(), error);
             }
+            last_error = Some(error);
+        }
+        if parts.is_empty() {
+            let error = last_error.unwrap_or_else(|| invalid(&proc.path, "no audio was recorded".to_string()));
+            log::error!("System recording {} is empty: {}", proc.path.display(), error);
+            return Err(error);
         }
+        log::info!("System recording stopped: {} ({} parts, {:.1}s)", proc.path.display(), parts.len(), duration_secs);
+
+        let (archive_path, archive_error) = match proc.archive {
+            Some(format) => {
+                let (app, wavs, direct) = (app.clone(), parts.clone(), proc.archive_direct);
+                let finished = tauri::async_runtime::spawn_blocking(move || {
+                    wavs.iter().map(|wav| finish_archive(&app, wav, format, direct)).collect::<Vec<_>>()
+                })
+                .await
+                .map_err(|e| format!("Archive task failed: {}", e))?;
+                let mut archives = Vec::new();
+                let mut errors = Vec::new();
+                for (wav, result) in parts.iter().zip(finished) {
+                    match result {
+                        Ok(path) => archives.push(path.to_string_lossy().to_string()),
+                        Err(e) => {
+                            log::warn!("Archiving {} failed: {}", wav.display(), e);
+                            errors.push(e);
+                        }
+                    }
+                }
+                (archives.into_iter().next(), (!errors.is_empty()).then(|| errors.join("; ")))
+            }
+            None => (None, None),
+        };
+
+        retention::enforce_in_background(app.clone());
+        return Ok(SystemRecording {
+            path: parts[0].to_string_lossy().to_string(),
+            duration_secs,
+            header_repaired,
+            archive_path,
+            archive_error,
+            parts: parts.iter().map(|p| p.to_string_lossy().to_string()).collect(),
+        });
     }
     Err("No recording in progress".into())
 }
 
-/// Transcribe audio file using whisper-cli
+#[derive(Serialize)]
+struct TranscribeAudioResult {
+    /// The run's history entry; cancelling mid-run takes the same id from the transcribe-start event
+    job_id: String,
+    transcript: String,
+}
+
+/// Transcribe audio file using whisper-cli, optionally with a specific model (e.g. "base.en").
+/// Returns once the run ends, so the job id comes back with the transcript; the transcribe-start
+/// event carries it as soon as the job is queued, for cancel_transcription.
 #[tauri::command]
-async fn transcribe_audio(window: tauri::Window, audio_path: String) -> Result<String, String> {
-    // Emit start debug with file size if possible
+#[allow(clippy::too_many_arguments)]
+async fn transcribe_audio(
+    window: tauri::Window,
+    audio_path: String,
+    model: Option<String>,
+    language: Option<String>,
+    translate: Option<bool>,
+    initial_prompt: Option<String>,
+    options: Option<whisper::TranscriptionOptions>,
+    force: Option<bool>,
+    auto_gain: Option<bool>,
+) -> Result<TranscribeAudioResult, AppError> {
+    let audio_path = paths::existing(window.app_handle(), &audio_path)?.to_string_lossy().to_string();
+    let params = whisper::WhisperParams {
+        model,
+        language: whisper::normalize_language(language),
+        translate: translate.unwrap_or(false),
+        initial_prompt: whisper::normalize_prompt(initial_prompt),
+        options,
+        force: force.unwrap_or(false),
+        diarize: false,
+    }
+    .with_settings(window.app_handle());
+
+    // Quiet recordings are transcribed from a louder temp copy; loud enough ones are used as they are
+    let gained = if auto_gain.unwrap_or(false) {
+        let (app, source) = (window.app_handle().clone(), PathBuf::from(&audio_path));
+        tauri::async_runtime::spawn_blocking(move || gain::auto_gain(&app, &source))
+            .await
+            .map_err(|e| format!("Gain task failed: {}", e))??
+    } else {
+        None
+    };
+    let whisper_path = gained.as_ref().map(|(g, _)| g.path.to_string_lossy().to_string()).unwrap_or(audio_path.clone());
+
+    // Emit start debug with file size if possible; job_id is what cancel_transcription takes
+    let job_id = jobs::queue(window.app_handle(), &audio_path)?;
     let size = std::fs::metadata(&audio_path).map(|m| m.len()).unwrap_or(0);
+    let backend = whisper::backend_for_run(window.app_handle(), &audio_path).await;
     let _ = window.emit("transcribe-start", serde_json::json!({
         "path": audio_path.clone(),
+        "job_id": job_id,
         "size": size,
+        "translate": params.translate,
+        "backend": backend,
+        "gain_db": gained.as_ref().map(|(_, gain_db)| gain_db),
     }));
 
-    match transcribe_audio_internal(&audio_path).await {
-        Ok(text) => {
+    let app = window.app_handle();
+    let params_ref = &params;
+    let whisper_ref = whisper_path.as_str();
+    let result = jobs::run_job(app, job_id.clone(), |job_id| async move {
+        whisper::run_whisper(app, whisper_ref, params_ref, &whisper::OutputMode::Plain, Some(&job_id)).await
+    })
+    .await;
+
+    match result {
+        Ok(output) => {
+            notifications::transcription_finished(app, &audio_path, None);
             let _ = window.emit("transcribe-complete", serde_json::json!({
                 "path": audio_path,
                 "ok": true,
+                "language": output.detected_language,
+                "stats": output.stats,
             }));
-            Ok(text)
+            Ok(TranscribeAudioResult { job_id, transcript: output.stdout.trim().to_string() })
         }
         Err(e) => {
+            notifications::transcription_finished(app, &audio_path, Some(e.to_string()));
             let _ = window.emit("transcribe-complete", serde_json::json!({
                 "path": audio_path,
                 "ok": false,
-                "error": e,
+                "error": e.to_string(),
             }));
             Err(e)
         }
     }
 }
 
+/// Transcribe audio file with segment timestamps for timeline display and subtitle export
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+async fn transcribe_audio_detailed(
+    window: tauri::Window,
+    audio_path: String,
+    model: Option<String>,
+    language: Option<String>,
+    translate: Option<bool>,
+    initial_prompt: Option<String>,
+    force: Option<bool>,
+    diarize: Option<bool>,
+) -> Result<transcript::TranscriptResult, AppError> {
+    let audio_path = paths::existing(window.app_handle(), &audio_path)?.to_string_lossy().to_string();
+    let params = whisper::WhisperParams {
+        model,
+        language: whisper::normalize_language(language),
+        translate: translate.unwrap_or(false),
+        initial_prompt: whisper::normalize_prompt(initial_prompt),
+        options: None,
+        force: force.unwrap_or(false),
+        diarize: diarize.unwrap_or(false),
+    }
+    .with_settings(window.app_handle());
+
+    let job_id = jobs::queue(window.app_handle(), &audio_path)?;
+    let size = std::fs::metadata(&audio_path).map(|m| m.len()).unwrap_or(0);
+    let backend = whisper::backend_for_run(window.app_handle(), &audio_path).await;
+    let _ = window.emit("transcribe-start", serde_json::json!({
+        "path": audio_path.clone(),
+        "job_id": job_id,
+        "size": size,
+        "translate": params.translate,
+        "backend": backend,
+    }));
+
+    let app = window.app_handle();
+    let result = transcribe_detailed_internal(app, job_id, &audio_path, &params).await;
+
+    notifications::transcription_finished(app, &audio_path, result.as_ref().err().map(|e| e.to_string()));
+    let _ = window.emit("transcribe-complete", serde_json::json!({
+        "path": audio_path,
+        "ok": result.is_ok(),
+        "error": result.as_ref().err().map(|e| e.to_string()),
+        "stats": result.as_ref().ok().map(|(_, stats)| stats),
+    }));
+    result.map(|(result, _)| result)
+}
+
+/// Run the queued job `job_id` as a timestamped transcription, filtering hallucinations when the
+/// options ask for it
+async fn transcribe_detailed_internal(
+    app: &tauri::AppHandle,
+    job_id: String,
+    audio_path: &str,
+    params: &whisper::WhisperParams,
+) -> Result<(transcript::TranscriptResult, telemetry::RunStats), AppError> {
+    jobs::run_job(app, job_id, |job_id| async move {
+        whisper::run_whisper(app, audio_path, params, &whisper::OutputMode::Timestamped, Some(&job_id))
+            .await
+            .map(|output| {
+                let mut segments = transcript::parse_whisper_segments(&output.stdout);
+                if params.diarize {
+                    transcript::assign_speakers(&mut segments, 0);
+                }
+                let mut result = if whisper::effective_options(app, params).filter_hallucinations.unwrap_or(false) {
+                    let raw_text = transcript::TranscriptResult::from_segments(segments.clone()).text;
+                    let (phrases, low_energy) = hallucination::filter_context(app, Path::new(audio_path));
+                    let mut filtered = transcript::TranscriptResult::from_segments(
+                        hallucination::filter_segments(segments, &phrases, low_energy),
+                    );
+                    if filtered.text != raw_text {
+                        filtered.raw_text = Some(raw_text);
+                    }
+                    filtered
+                } else {
+                    transcript::TranscriptResult::from_segments(segments)
+                };
+                result.language = output.detected_language.or(params.language.clone());
+                (result, output.stats)
+            })
+    })
+    .await
+}
+
+/// Transcribe with whisper's full JSON output for per-word timestamps and confidence
+#[tauri::command]
+async fn transcribe_audio_json(
+    window: tauri::Window,
+    audio_path: String,
+    model: Option<String>,
+    language: Option<String>,
+    translate: Option<bool>,
+    initial_prompt: Option<String>,
+    force: Option<bool>,
+) -> Result<transcript::DetailedTranscript, AppError> {
+    let audio_path = paths::existing(window.app_handle(), &audio_path)?.to_string_lossy().to_string();
+    let params = whisper::WhisperParams {
+        model,
+        language: whisper::normalize_language(language),
+        translate: translate.unwrap_or(false),
+        initial_prompt: whisper::normalize_prompt(initial_prompt),
+        options: None,
+        force: force.unwrap_or(false),
+        diarize: false,
+    }
+    .with_settings(window.app_handle());
+
+    let job_id = jobs::queue(window.app_handle(), &audio_path)?;
+    let size = std::fs::metadata(&audio_path).map(|m| m.len()).unwrap_or(0);
+    let backend = whisper::backend_for_run(window.app_handle(), &audio_path).await;
+    let _ = window.emit("transcribe-start", serde_json::json!({
+        "path": audio_path.clone(),
+        "job_id": job_id,
+        "size": size,
+        "translate": params.translate,
+        "backend": backend,
+    }));
+
+    let output_base = get_cache_dir()?.join(audio::unique_name("whisper-json"));
+
+    let app = window.app_handle();
+    let (path_ref, params_ref, base_ref) = (audio_path.as_str(), &params, &output_base);
+    let result = jobs::run_job(app, job_id, |job_id| async move {
+        let mode = whisper::OutputMode::JsonFull { output_base: base_ref.clone() };
+        let output = whisper::run_whisper(app, path_ref, params_ref, &mode, Some(&job_id)).await?;
+        Ok((transcript::take_whisper_json(base_ref, path_ref)?, output.stats))
+    })
+    .await;
+
+    notifications::transcription_finished(app, &audio_path, result.as_ref().err().map(|e| e.to_string()));
+    let _ = window.emit("transcribe-complete", serde_json::json!({
+        "path": audio_path,
+        "ok": result.is_ok(),
+        "error": result.as_ref().err().map(|e| e.to_string()),
+        "stats": result.as_ref().ok().map(|(_, stats)| stats),
+    }));
+    result.map(|(result, _)| result)
+}
+
+#[derive(Serialize)]
+struct TranscribeAndSummarizeResult {
+    transcript: String,
+    transcript_path: String,
+    summary: Option<String>,
+    summary_path: Option<String>,
+    /// Why there's no summary; the transcript is still returned and saved
+    summary_error: Option<AppError>,
+    /// Set when the result was saved as a note
+    note_id: Option<u64>,
+}
+
+/// Transcribe a recording and summarize the transcript, saving both beside it as .txt and .summary.md,
+/// and as a note when `save_note` is set
+#[tauri::command]
+async fn transcribe_and_summarize(
+    window: tauri::Window,
+    audio_path: String,
+    template_name: Option<String>,
+    save_note: Option<bool>,
+) -> Result<TranscribeAndSummarizeResult, AppError> {
+    let transcript = transcribe_audio(window.clone(), audio_path.clone(), None, None, None, None, None, None, None)
+        .await?
+        .transcript;
+    let source = Path::new(&audio_path);
+    let transcript_path = source.with_extension("txt");
+    fs::write(&transcript_path, format!("{}\n", transcript))
+        .map_err(|e| AppError::io(format!("Failed to write {}", transcript_path.display()), e))?;
+
+    let mut result = TranscribeAndSummarizeResult {
+        transcript: transcript.clone(),
+        transcript_path: transcript_path.to_string_lossy().to_string(),
+        summary: None,
+        summary_path: None,
+        summary_error: None,
+        note_id: None,
+    };
+    let app = window.app_handle().clone();
+    match summarize::summarize_text_llama(app, transcript, None, None, None, template_name, None).await {
+        Ok(summary) => {
+            let summary_path = source.with_extension("summary.md");
+            match fs::write(&summary_path, format!("{}\n", summary)) {
+                Ok(()) => result.summary_path = Some(summary_path.to_string_lossy().to_string()),
+                Err(e) => result.summary_error = Some(AppError::io(format!("Failed to write {}", summary_path.display()), e)),
+            }
+            result.summary = Some(summary);
+        }
+        Err(e) => {
+            log::error!("Summarizing {} failed: {}", audio_path, e);
+            result.summary_error = Some(e);
+        }
+    }
+    if save_note.unwrap_or(false) {
+        let note = notes::NewNote {
+            audio_path: Some(audio_path.clone()),
+            transcript: result.transcript.clone(),
+            summary: result.summary.clone(),
+            duration_secs: audio::read_wav_info(source).ok().map(|info| info.duration_secs()),
+            ..Default::default()
+        };
+        let note = notes::insert(window.app_handle(), notes::with_title(window.app_handle(), note).await)?;
+        result.note_id = Some(note.id);
+    }
+    Ok(result)
+}
+
+/// Undo what start_live_recording set up once it marked the session active, when a later step fails.
+/// Clearing `active` also ends the limits guard and the level meter.
+fn abort_live_start(app: &tauri::AppHandle, state: &ChunkedRecorderState) {
+    *state.active.lock() = false;
+    live_queue::cancel(app);
+    live_summary::stop(app);
+    thermal::end_session(app);
+    recorder_status::transition(app, recorder_status::Transition::LiveFinished);
+}
+
 /// Start live chunked recording (default 30s segments with auto-transcription)
 #[tauri::command]
+#[allow(clippy::too_many_arguments)]
 fn start_live_recording(
     state: tauri::State<'_, ChunkedRecorderState>,
     app: tauri::AppHandle,
     preferred_recorder: Option<String>,
     segment_seconds: Option<u64>,
-) -> Result<String, String> {
+    model: Option<String>,
+    language: Option<String>,
+    translate: Option<bool>,
+    initial_prompt: Option<String>,
+    device: Option<String>,
+    backend: Option<String>,
+    capture_source: Option<String>,
+    max_duration_secs: Option<u64>,
+    rolling_summary_interval_chunks: Option<usize>,
+    diarize: Option<bool>,
+    adaptive_segments: Option<bool>,
+) -> Result<String, AppError> {
     let _ = preferred_recorder; // Mark parameter as intentionally used
-    let mut active = state.active.lock().unwrap();
-    if *active {
-        return Err("Live recording already in progress".into());
+    recorder_lock::ensure_owner()?;
+    if *state.active.lock() {
+        return Err(AppError::RecorderBusy("Live recording already in progress".to_string()));
     }
-    
-    let cache_dir = dirs::cache_dir()
-        .ok_or("Could not find cache directory")?
-        .join("last-gen-notes")
-        .join("live-session");
-    if cache_dir.exists() {
-        let _ = fs::remove_dir_all(&cache_dir);
-    }
-    fs::create_dir_all(&cache_dir)
-        .map_err(|e| format!("Failed to create cache directory: {}", e))?;
-    
-    *active = true;
-    *state.chunk_index.lock().unwrap() = 0;
-    *state.base_dir.lock().unwrap() = Some(cache_dir.clone());
-    state.transcripts.lock().unwrap().clear();
-    drop(active);
+    // Probe before taking the active lock so a bad device fails here instead of producing empty chunks
+    let backend = recorder::resolve_backend(backend.or(settings::current(&app).backend).as_deref())?;
+    let source = recorder::CaptureSource::parse(capture_source.as_deref())?;
+    let device = recorder::select_device(&app, device, backend)?;
+    let plan = recorder::CapturePlan::new(backend, source, device)?;
+    let (cache_dir, drained_tx, session_id) = state.begin_session(backend, || {
+        let cache_dir = session::live_session_dir()?;
+        if retranscribe::is_pinned(&app, &cache_dir) {
+            return Err(AppError::RecorderBusy("The last live session is still being retranscribed".to_string()));
+        }
+        if cache_dir.exists() {
+            let _ = fs::remove_dir_all(&cache_dir);
+        }
+        fs::create_dir_all(&cache_dir)
+            .map_err(|e| format!("Failed to create cache directory: {}", e))?;
+        limits::check_free_space(&app, &cache_dir)?;
+        Ok(cache_dir)
+    })?;
+    live_queue::start(&app, settings::current(&app).live_concurrency);
+    events::start_session(&app);
+    live_summary::start(&app, rolling_summary_interval_chunks);
+    thermal::start_session(&app);
+    recorder_status::transition(&app, recorder_status::Transition::LiveStarted);
     
     // Clone Arc references for the background task
     let active_clone = state.active.clone();
+    let paused_clone = state.paused.clone();
     let chunk_index_clone = state.chunk_index.clone();
     let base_dir_clone = state.base_dir.clone();
     let transcripts_clone = state.transcripts.clone();
     
+    let params = whisper::WhisperParams {
+        model,
+        language: whisper::normalize_language(language),
+        translate: translate.unwrap_or(false),
+        initial_prompt: whisper::normalize_prompt(initial_prompt),
+        options: None,
+        force: false,
+        diarize: diarize.unwrap_or(false),
+    }
+    .with_settings(&app);
+
     // Clamp segment length to a safe range to avoid overly short or long files
-    let segment_len = segment_seconds.unwrap_or(10).max(5).min(60);
+    let segment_len = segment_seconds.unwrap_or(settings::current(&app).segment_seconds).clamp(5, 60);
+    let adaptive = adaptive_segments.unwrap_or(settings::current(&app).adaptive_segments);
 
     // Decide method: prefer arecord for reliability; use ffmpeg only if explicitly requested
     let prefer = preferred_recorder.unwrap_or_else(|| "auto".to_string());
@@ -629,17 +1526,72 @@ fn start_live_recording(
         _ => false,  // "auto" defaults to arecord (more reliable); ffmpeg has timing issues
     };
 
-    if use_ffmpeg {
+    let use_native = backend == recorder::RecorderBackend::Native;
+    let live_meter_active = |app: &tauri::AppHandle| *app.state::<ChunkedRecorderState>().active.lock();
+    if !use_native {
+        let meter_dir = cache_dir.clone();
+        levels::start_meter(
+            app.clone(),
+            levels::LevelSource::WavFile(Box::new(move || levels::latest_chunk(&meter_dir))),
+            live_meter_active,
+        );
+    }
+    let mode = if use_native { "native" } else if use_ffmpeg { "ffmpeg" } else { "arecord" };
+    // The segment muxer and the native recorder cut chunks at a length fixed when they start
+    if adaptive && mode != "arecord" {
+        log::info!("Adaptive segment length isn't available with the {} recorder; using {}s chunks", mode, segment_len);
+    }
+    if let Err(e) = session::start_session(&cache_dir, segment_len, mode, &params) {
+        abort_live_start(&app, &state);
+        return Err(e.into());
+    }
+    limits::start_guard(app.clone(), limits::GuardedRecording::Live(session_id), cache_dir.clone(), max_duration_secs);
+
+    if use_native {
+        // Chunks are rotated in-process on exact sample boundaries, so the segment watcher applies as-is
+        let recording = native_recorder::NativeRecording::start(
+            plan.mic.as_deref(),
+            native_recorder::NativeTarget::Segments {
+                dir: cache_dir.clone(),
+                segment_secs: segment_len,
+                start_index: 0,
+            },
+        );
+        match recording {
+            Ok(recording) => {
+                levels::start_meter(app.clone(), levels::LevelSource::Native(recording.meter_buffer()), live_meter_active);
+                *state.native.lock() = Some(recording);
+            }
+            Err(e) => {
+                abort_live_start(&app, &state);
+                return Err(e.into());
+            }
+        }
+        events::emit(&app, "live-recorder-mode", "native");
+
+        tauri::async_runtime::spawn(async move {
+            let _ = chunked_recording_loop_ffmpeg(
+                active_clone,
+                chunk_index_clone,
+                base_dir_clone,
+                transcripts_clone,
+                app,
+                segment_len,
+                params,
+                None
+            ).await;
+            let _ = drained_tx.send(());
+        });
+    } else if use_ffmpeg {
         let base_dir_for_ff = cache_dir.clone();
-        let pid_holder = state.ffmpeg_pid.clone();
+        let child_holder = state.ffmpeg.clone();
         // spawn ffmpeg process once to segment into files
         // Use -segment_time to create exact-length segments
         // Note: ffmpeg may create partial first segment before filling up to segment_time
-        let child = StdCommand::new("ffmpeg")
-            .arg("-hide_banner")
+        let mut segmenter = StdCommand::new("ffmpeg");
+        segmenter.arg("-hide_banner")
             .arg("-loglevel").arg("error")
-            .arg("-f").arg("alsa")
-            .arg("-i").arg("default")
+            .args(plan.ffmpeg_input_args())
             .arg("-ac").arg("1")
             .arg("-ar").arg("16000")
             .arg("-f").arg("segment")
@@ -647,12 +1599,21 @@ fn start_live_recording(
             .arg("-reset_timestamps").arg("1")
             .arg("-segment_start_number").arg("0")
             .arg(base_dir_for_ff.join("chunk-%04d.wav").to_string_lossy().to_string())
-            .spawn()
-            .map_err(|e| format!("Failed to start ffmpeg: {}", e))?;
-        *pid_holder.lock().unwrap() = Some(child.id());
+            // Kept open so stop can send "q" and ffmpeg closes out the final segment properly
+            .stdin(std::process::Stdio::piped())
+            .stderr(std::process::Stdio::piped());
+        let mut child = match processes::spawn(&app, &mut segmenter) {
+            Ok(child) => child,
+            Err(e) => {
+                abort_live_start(&app, &state);
+                return Err(format!("Failed to start ffmpeg: {}", e).into());
+            }
+        };
+        let stderr = recorder::StderrTail::stream(&app, &mut child, "ffmpeg");
+        *child_holder.lock() = Some(child);
 
         // Emit recorder mode to frontend
-        let _ = app.emit("live-recorder-mode", "ffmpeg");
+        events::emit(&app, "live-recorder-mode", "ffmpeg");
 
         // Spawn background task to watch and transcribe segments
         tauri::async_runtime::spawn(async move {
@@ -662,112 +1623,328 @@ fn start_live_recording(
                 base_dir_clone,
                 transcripts_clone,
                 app,
-                segment_len
+                segment_len,
+                params,
+                Some(stderr)
             ).await;
+            let _ = drained_tx.send(());
         });
     } else {
-        // Fallback to arecord per-chunk
-        let _ = app.emit("live-recorder-mode", "arecord");
+        // Fallback to one recorder process per chunk
+        let tuner = if adaptive {
+            let current = settings::current(&app);
+            chunk_tuning::SegmentTuner::adaptive(segment_len, current.min_segment_seconds, current.max_segment_seconds)
+        } else {
+            chunk_tuning::SegmentTuner::fixed(segment_len)
+        };
+        events::emit(&app, "live-recorder-mode", "arecord");
         tauri::async_runtime::spawn(async move {
             let _ = chunked_recording_loop(
                 active_clone,
+                paused_clone,
                 chunk_index_clone,
                 base_dir_clone,
                 transcripts_clone,
                 app,
-                segment_len
+                tuner,
+                plan,
+                params
             ).await;
+            let _ = drained_tx.send(());
         });
     }
     
+    log::info!("Live recording started with {} ({}s segments) in {}", backend.as_str(), segment_len, cache_dir.display());
     Ok(cache_dir.to_string_lossy().to_string())
 }
-/// Get current recorder mode (ffmpeg/arecord/inactive)
+fn live_recorder_mode(state: &ChunkedRecorderState) -> &'static str {
+    if !*state.active.lock() {
+        "inactive"
+    } else if *state.paused.lock() {
+        "paused"
+    } else if state.native.lock().is_some() {
+        "native"
+    } else if state.ffmpeg.lock().is_some() {
+        "ffmpeg"
+    } else {
+        // The per-chunk loop, named for the backend it had before any other
+        "arecord"
+    }
+}
+
+/// Get current live recorder mode (ffmpeg/arecord/paused/inactive; native for the cpal recorder)
 #[tauri::command]
 async fn get_recorder_mode(state: tauri::State<'_, ChunkedRecorderState>) -> Result<String, String> {
-    if *state.active.lock().unwrap() {
-        if state.ffmpeg_pid.lock().unwrap().is_some() {
-            Ok("ffmpeg".to_string())
-        } else {
-            Ok("arecord".to_string())
+    Ok(live_recorder_mode(&state).to_string())
+}
+
+/// Get current live recorder mode (ffmpeg/arecord/native/paused/inactive), capture backend, and
+/// whether sleep is inhibited
+#[tauri::command]
+async fn get_recorder_status(
+    state: tauri::State<'_, ChunkedRecorderState>,
+    inhibitor: tauri::State<'_, sleep_inhibit::SleepInhibitState>,
+) -> Result<RecorderModeStatus, String> {
+    let mode = live_recorder_mode(&state);
+    Ok(RecorderModeStatus {
+        mode: mode.to_string(),
+        backend: if mode == "inactive" { None } else { *state.backend.lock() },
+        sleep_inhibited: inhibitor.is_held(),
+    })
+}
+
+#[derive(Serialize)]
+struct LiveRecordingResult {
+    transcript: String,
+    /// Set when the transcript was saved as a note
+    note_id: Option<u64>,
+    /// The session's chunks joined into one recording, unless that was turned off or failed
+    recording_path: Option<String>,
+}
+
+/// Stop live chunked recording; returns the transcript once the final chunk has been transcribed,
+/// saving it as a note when `save_note` is set (with a generated title if none is given). Unless
+/// `join_chunks` is false the chunks are joined into one session recording, replacing all but the
+/// ones still waiting on a transcription.
+#[tauri::command]
+async fn stop_live_recording(
+    app: tauri::AppHandle,
+    save_note: Option<bool>,
+    title: Option<String>,
+    join_chunks: Option<bool>,
+) -> Result<LiveRecordingResult, AppError> {
+    let transcript = finish_live_recording(&app).await?;
+    let session_dir = app.state::<ChunkedRecorderState>().base_dir.lock().clone();
+    let mut result = LiveRecordingResult { transcript, note_id: None, recording_path: None };
+    // Measured before joining, which removes the chunks
+    let recorded_secs = session_dir.as_deref().map(session::recorded_secs);
+    if let Some(dir) = session_dir.clone().filter(|_| join_chunks.unwrap_or(true)) {
+        // Failed chunks are still being retried, and retranscribe_chunk reads them later
+        let state = app.state::<ChunkedRecorderState>();
+        let next_index = *state.chunk_index.lock();
+        let unfinished: Vec<usize> = state
+            .transcripts
+            .lock()
+            .listing(next_index)
+            .into_iter()
+            .filter(|c| matches!(c.status, live_transcript::ChunkStatus::Failed | live_transcript::ChunkStatus::Pending))
+            .map(|c| c.index)
+            .collect();
+        let app_for_join = app.clone();
+        let joined = tauri::async_runtime::spawn_blocking(move || {
+            let joined = session::join_chunks(&app_for_join, &dir)?;
+            session::remove_chunk_wavs(&dir, &unfinished);
+            Ok::<_, String>(joined)
+        })
+        .await
+        .map_err(|e| format!("Joining the session's chunks failed: {}", e))?;
+        match joined {
+            Ok(path) => result.recording_path = Some(path.to_string_lossy().to_string()),
+            Err(e) => log::error!("Couldn't join the live session's chunks: {}", e),
         }
-    } else {
-        Ok("inactive".to_string())
+    }
+    if save_note.unwrap_or(false) {
+        let transcripts = app.state::<ChunkedRecorderState>().transcripts.clone();
+        // Background retries may have filled failed chunks since the drain
+        result.transcript = transcripts.lock().joined();
+        let note = notes::NewNote {
+            title: title.unwrap_or_default(),
+            // Unjoined chunks are cleared when the next session starts, so only a joined recording lasts
+            audio_path: result.recording_path.clone(),
+            transcript: result.transcript.clone(),
+            duration_secs: recorded_secs,
+            ..Default::default()
+        };
+        let note = notes::insert(&app, notes::with_title(&app, note).await)?;
+        if let Some(dir) = &session_dir {
+            if let Err(e) = session::set_note(dir, note.id) {
+                log::warn!("Couldn't link the live session to note {}: {}", note.id, e);
+            }
+        }
+        // Retries finishing from here on patch the note themselves; catch any that landed before it was linked
+        let latest = transcripts.lock().joined();
+        if latest != note.transcript {
+            let patch = notes::NotePatch { transcript: Some(latest.clone()), ..Default::default() };
+            if let Err(e) = notes::update(&app, note.id, patch) {
+                log::warn!("Couldn't fill in retried chunks in note {}: {}", note.id, e);
+            }
+            result.transcript = latest;
+        }
+        result.note_id = Some(note.id);
+    }
+    Ok(result)
+}
+
+/// End the live session and drain its last chunk; shared by stop_live_recording and the limits guard
+async fn finish_live_recording(app: &tauri::AppHandle) -> Result<String, String> {
+    let state = app.state::<ChunkedRecorderState>();
+    if !*state.active.lock() {
+        return Err("No live recording in progress".into());
+    }
+    recorder_status::transition(app, recorder_status::Transition::LiveStopping);
+    let was_paused = std::mem::replace(&mut *state.paused.lock(), false);
+
+    // Let the recorder close out its current segment before the watcher is told to drain
+    let app_for_stop = app.clone();
+    let stop_ffmpeg = move |mut child: StdChild| {
+        // A stopped process won't read its stdin until it's continued
+        if was_paused && !cfg!(target_os = "windows") {
+            continue_stopped(&child);
+        }
+        recorder::quit_ffmpeg(&mut child);
+        app_for_stop.state::<processes::ProcessRegistry>().unregister(child.id());
+    };
+    let timeout = tokio::time::Duration::from_secs(LIVE_DRAIN_TIMEOUT_SECS);
+    if !state.end_session(stop_ffmpeg, timeout).await? {
+        log::warn!("Timed out transcribing the final live chunk; cancelling it");
+        live_queue::cancel(app);
+    }
+    live_queue::drain(app, tokio::time::Duration::from_secs(LIVE_DRAIN_TIMEOUT_SECS)).await;
+    // Diarized chunks still waiting on one that never finished get their speakers now
+    state.transcripts.lock().finish_labelling();
+    if let Some(dir) = state.base_dir.lock().clone() {
+        commit_labelled(app, &state.transcripts, &dir);
+    }
+    live_summary::finish(app).await;
+
+    let transcripts = state.transcripts.lock();
+    log::info!("Live recording stopped after {} transcribed chunks", transcripts.len());
+    recorder_status::transition(app, recorder_status::Transition::LiveFinished);
+    Ok(transcripts.joined())
+}
+
+/// Continue a paused ffmpeg so it can read the quit request on its stdin
+fn continue_stopped(child: &std::process::Child) {
+    if let Err(e) = processes::signal(child.id(), processes::Signal::Cont) {
+        log::warn!("Couldn't continue the paused ffmpeg recorder: {}", e);
     }
 }
 
-/// Stop live chunked recording
+/// Pause live recording without ending the session; transcripts and chunk numbering are kept
 #[tauri::command]
-fn stop_live_recording(state: tauri::State<'_, ChunkedRecorderState>) -> Result<String, String> {
-    let mut active = state.active.lock().unwrap();
-    if !*active {
+fn pause_live_recording(app: tauri::AppHandle, state: tauri::State<'_, ChunkedRecorderState>) -> Result<(), String> {
+    if !*state.active.lock() {
         return Err("No live recording in progress".into());
     }
-    *active = false;
-    drop(active);
+    let mut paused = state.paused.lock();
+    if *paused {
+        return Err("Live recording is already paused".into());
+    }
+    // ffmpeg is frozen in place so its segment counter carries on after resume;
+    // the arecord loop just stops starting new chunks
+    if let Some(child) = state.ffmpeg.lock().as_ref() {
+        if cfg!(target_os = "windows") {
+            return Err("Pausing the ffmpeg segment recorder isn't supported on Windows; use the chunked recorder".into());
+        }
+        processes::signal(child.id(), processes::Signal::Stop)
+            .map_err(|e| format!("Failed to pause the ffmpeg recorder: {}", e))?;
+    }
+    if let Some(recording) = state.native.lock().as_ref() {
+        recording.set_paused(true);
+    }
+    *paused = true;
+    events::emit(&app, "live-recorder-mode", "paused");
+    recorder_status::transition(&app, recorder_status::Transition::LivePaused);
+    Ok(())
+}
 
-    // If ffmpeg is running, terminate it (non-blocking to avoid deadlock)
-    if let Some(pid) = *state.ffmpeg_pid.lock().unwrap() {
-        let _ = StdCommand::new("kill").arg("-TERM").arg(pid.to_string()).output();
-        *state.ffmpeg_pid.lock().unwrap() = None;
-        // Background loop will check active flag and exit cleanly
+/// Resume a paused live recording
+#[tauri::command]
+fn resume_live_recording(app: tauri::AppHandle, state: tauri::State<'_, ChunkedRecorderState>) -> Result<(), String> {
+    if !*state.active.lock() {
+        return Err("No live recording in progress".into());
     }
-    
-    let transcripts = state.transcripts.lock().unwrap().clone();
-    Ok(transcripts.join(" "))
+    let mut paused = state.paused.lock();
+    if !*paused {
+        return Err("Live recording is not paused".into());
+    }
+    let ffmpeg_pid = state.ffmpeg.lock().as_ref().map(|child| child.id());
+    if let Some(pid) = ffmpeg_pid {
+        processes::signal(pid, processes::Signal::Cont)
+            .map_err(|e| format!("Failed to resume the ffmpeg recorder: {}", e))?;
+    }
+    let native = state.native.lock();
+    if let Some(recording) = native.as_ref() {
+        recording.set_paused(false);
+    }
+    *paused = false;
+    let mode = if native.is_some() { "native" } else if ffmpeg_pid.is_some() { "ffmpeg" } else { "arecord" };
+    events::emit(&app, "live-recorder-mode", mode);
+    recorder_status::transition(&app, recorder_status::Transition::LiveResumed);
+    Ok(())
 }
 
-/// Get accumulated live transcripts
+/// Get the live session's chunks in order, with ones recorded but not transcribed yet marked pending
 #[tauri::command]
-async fn get_live_transcripts(state: tauri::State<'_, ChunkedRecorderState>) -> Result<Vec<String>, String> {
-    Ok(state.transcripts.lock().unwrap().clone())
+async fn get_live_transcripts(
+    state: tauri::State<'_, ChunkedRecorderState>,
+) -> Result<Vec<live_transcript::ChunkTranscript>, String> {
+    let next_index = *state.chunk_index.lock();
+    Ok(state.transcripts.lock().listing(next_index))
 }
 
 /// Chunked recording loop - records 30s segments and transcribes each
+#[allow(clippy::too_many_arguments)]
 async fn chunked_recording_loop(
-    active: Arc<Mutex<bool>>,
-    chunk_index: Arc<Mutex<usize>>,
-    base_dir: Arc<Mutex<Option<PathBuf>>>,
-    transcripts: Arc<Mutex<Vec<String>>>,
+    active: Arc<parking_lot::Mutex<bool>>,
+    paused: Arc<parking_lot::Mutex<bool>>,
+    chunk_index: Arc<parking_lot::Mutex<usize>>,
+    base_dir: Arc<parking_lot::Mutex<Option<PathBuf>>>,
+    transcripts: Arc<parking_lot::Mutex<live_transcript::LiveTranscript>>,
     app: tauri::AppHandle,
-    segment_len: u64,
+    tuner: chunk_tuning::SegmentTuner,
+    plan: recorder::CapturePlan,
+    params: whisper::WhisperParams,
 ) -> Result<(), String> {
+    let tuner = Arc::new(parking_lot::Mutex::new(tuner));
+    // Where the next chunk starts in the session; chunk lengths vary when the tuner is adaptive
+    let mut start_secs = 0u64;
     loop {
-        let is_active = *active.lock().unwrap();
+        let is_active = *active.lock();
         if !is_active {
             break;
         }
+        if *paused.lock() {
+            tokio::time::sleep(tokio::time::Duration::from_millis(200)).await;
+            continue;
+        }
         
         let chunk_idx = {
-            let mut idx = chunk_index.lock().unwrap();
+            let mut idx = chunk_index.lock();
             let current = *idx;
             *idx += 1;
             current
         };
         
-        let base_dir_path = base_dir.lock().unwrap().clone()
+        let base_dir_path = base_dir.lock().clone()
             .ok_or("Base dir not set")?;
         let chunk_file = base_dir_path.join(format!("chunk-{:04}.wav", chunk_idx));
         
         // Record chunk: add 3 seconds to capture leading context from previous chunk
         // This ensures we don't lose content at chunk boundaries
+        let segment_len = tuner.lock().current();
+        let chunk_start = start_secs;
         let record_duration = segment_len + 3;
-        let output = StdCommand::new("arecord")
-            .arg("-f").arg("S16_LE")
-            .arg("-r").arg("16000")
-            .arg("-c").arg("1")
-            .arg("-d").arg(record_duration.to_string())
-            .arg(chunk_file.to_string_lossy().to_string())
-            .output()
+        let output = processes::output(&app, &mut plan.command(&chunk_file, Some(record_duration))?)
             .map_err(|e| format!("Failed to record chunk: {}", e))?;
         
         if !output.status.success() {
-            let _ = app.emit("live-recording-error", "Chunk recording failed");
+            let stderr = logging::stderr_tail(&output.stderr);
+            log::error!("Recording live chunk {} with {} failed: {}", chunk_idx, plan.backend.as_str(), stderr);
+            events::emit(&app, "live-recording-error", match stderr.lines().last() {
+                Some(last) => format!("Chunk recording failed: {}", last),
+                None => "Chunk recording failed".to_string(),
+            });
             break;
         }
 
         // Note: We record segment_len + 3 seconds to capture startup delay and previous context.
         // Do NOT trim - all audio is needed to avoid gaps in transcription.
+        // The session clock follows what was actually recorded, so failed-chunk placeholders don't drift.
+        let recorded_secs = audio::read_wav_info(&chunk_file)
+            .map(|info| info.duration_secs().round() as u64)
+            .unwrap_or(0);
+        start_secs += recorded_secs;
         
         // Verify chunk file and spawn transcription in background to avoid blocking
         if chunk_file.exists() {
@@ -775,28 +1952,77 @@ async fn chunked_recording_loop(
             let chunk_path = chunk_file.to_string_lossy().to_string();
             let transcripts_clone = transcripts.clone();
             let app_clone = app.clone();
+            let session_dir = base_dir_path.clone();
+            let params_clone = thermal::chunk_params(&app, params.with_context(live_context(&transcripts).as_deref()));
+            let tuner = tuner.clone();
+            let recorded = std::time::Instant::now();
+
+            if vad::should_skip_chunk(&app, &chunk_file) {
+                transcripts.lock().silent(chunk_idx);
+                commit_labelled(&app, &transcripts, &base_dir_path);
+                let _ = session::record_chunk(&base_dir_path, chunk_idx, "");
+                events::emit(&app, "live-transcript-chunk", serde_json::json!({
+                    "chunk": chunk_idx,
+                    "text": "",
+                    "path": chunk_path,
+                    "size": size,
+                    "segment_secs": segment_len,
+                    "silent": true
+                }));
+                continue;
+            }
             
-            // Spawn transcription in background so we can immediately start next recording
-            tauri::async_runtime::spawn(async move {
-                match transcribe_audio_internal(&chunk_path).await {
-                    Ok(text) => {
-                        transcripts_clone.lock().unwrap().push(text.clone());
-                        let _ = app_clone.emit("live-transcript-chunk", serde_json::json!({
+            // Spawn transcription in background so we can immediately start next recording;
+            // the live queue keeps slow transcriptions from piling up whisper processes
+            let task = tauri::async_runtime::spawn(async move {
+                let result = live_queue::run(&app_clone, async {
+                    let started = std::time::Instant::now();
+                    let result = if params_clone.diarize {
+                        transcribe_chunk_diarized(&app_clone, &chunk_path, &params_clone).await.map(LiveText::Diarized)
+                    } else {
+                        transcribe_audio_internal(&app_clone, &chunk_path, &params_clone).await.map(LiveText::Plain)
+                    };
+                    thermal::chunk_transcribed(&app_clone, chunk_idx, started.elapsed(), segment_len, &params_clone);
+                    result
+                })
+                .await;
+                // Time spent queued behind earlier chunks counts, since that's how late the text shows up
+                tuner.lock().observe(segment_len, recorded.elapsed());
+                match result {
+                    Ok(LiveText::Diarized(segments)) => {
+                        transcripts_clone.lock().diarized(chunk_idx, segments);
+                        commit_labelled(&app_clone, &transcripts_clone, &session_dir);
+                    }
+                    Ok(LiveText::Plain(text)) => {
+                        push_live_transcript(&app_clone, &transcripts_clone, chunk_idx, &text);
+                        commit_labelled(&app_clone, &transcripts_clone, &session_dir);
+                        if let Err(e) = session::record_chunk(&session_dir, chunk_idx, &text) {
+                            events::emit(&app_clone, "live-recording-error", e);
+                        }
+                        events::emit(&app_clone, "live-transcript-chunk", serde_json::json!({
                             "chunk": chunk_idx,
                             "text": text,
                             "path": chunk_path,
-                            "size": size
+                            "size": size,
+                            "segment_secs": segment_len
                         }));
                     }
                     Err(e) => {
-                        let _ = app_clone.emit("live-recording-error", format!("Transcription error: {}", e));
+                        log::error!("Transcribing live chunk {} failed: {}", chunk_idx, e);
+                        events::emit(&app_clone, "live-recording-error", format!("Transcription error: {}", e));
+                        chunk_retry::chunk_failed(&app_clone, chunk_idx, &chunk_path, chunk_start, recorded_secs, &params_clone, e);
                     }
                 }
             });
+            live_queue::track(&app, task);
+        } else {
+            // Nothing was recorded, so diarized chunks after it shouldn't wait on it for their speakers
+            transcripts.lock().silent(chunk_idx);
+            commit_labelled(&app, &transcripts, &base_dir_path);
         }
         
         // Check if still active after processing
-        if !*active.lock().unwrap() {
+        if !*active.lock() {
             break;
         }
     }
@@ -804,275 +2030,498 @@ async fn chunked_recording_loop(
     Ok(())
 }
 
-/// Gapless recording watcher using ffmpeg's segment muxer
+/// How the segmenting ffmpeg exited, if it died on its own; stop takes it out of the state before
+/// quitting it, so a normal shutdown never shows up here
+fn segmenter_exit(app: &tauri::AppHandle) -> Option<std::process::ExitStatus> {
+    let state = app.state::<ChunkedRecorderState>();
+    let mut holder = state.ffmpeg.lock();
+    let status = holder.as_mut()?.try_wait().ok()??;
+    if let Some(child) = holder.take() {
+        app.state::<processes::ProcessRegistry>().unregister(child.id());
+    }
+    Some(status)
+}
+
+/// Segment files in a live session directory as (index, path, size), plus the highest index the
+/// recorder has started, counting the native recorder's in-progress ".wav.part" files
+fn scan_segments(dir: &Path) -> (Vec<(usize, PathBuf, u64)>, Option<usize>) {
+    let mut segments = Vec::new();
+    let mut newest = None;
+    for entry in fs::read_dir(dir).into_iter().flatten().flatten() {
+        let name = entry.file_name().to_string_lossy().to_string();
+        let Some(rest) = name.strip_prefix("chunk-") else { continue };
+        let (number, complete) = match rest.strip_suffix(".wav") {
+            Some(number) => (number, true),
+            None => match rest.strip_suffix(".wav.part") {
+                Some(number) => (number, false),
+                None => continue,
+            },
+        };
+        let Ok(index) = number.parse::<usize>() else { continue };
+        newest = newest.max(Some(index));
+        if complete {
+            let size = entry.metadata().map(|m| m.len()).unwrap_or(0);
+            segments.push((index, entry.path(), size));
+        }
+    }
+    segments.sort_by_key(|(index, _, _)| *index);
+    (segments, newest)
+}
+
+/// Gapless recording watcher for segmented recorders (ffmpeg's segment muxer or the native recorder).
+/// It transcribes whichever segments are complete rather than waiting on each index in turn, so a
+/// skipped or late segment doesn't hold up the ones after it.
+#[allow(clippy::too_many_arguments)]
 async fn chunked_recording_loop_ffmpeg(
-    active: Arc<Mutex<bool>>,
-    chunk_index: Arc<Mutex<usize>>,
-    base_dir: Arc<Mutex<Option<PathBuf>>>,
-    transcripts: Arc<Mutex<Vec<String>>>,
+    active: Arc<parking_lot::Mutex<bool>>,
+    chunk_index: Arc<parking_lot::Mutex<usize>>,
+    base_dir: Arc<parking_lot::Mutex<Option<PathBuf>>>,
+    transcripts: Arc<parking_lot::Mutex<live_transcript::LiveTranscript>>,
     app: tauri::AppHandle,
     segment_len: u64,
+    params: whisper::WhisperParams,
+    stderr: Option<recorder::StderrTail>,
 ) -> Result<(), String> {
+    let mut processed = HashSet::new();
+    // Sizes from the previous poll; a segment counts as complete once its size holds still
+    let mut last_sizes: HashMap<usize, u64> = HashMap::new();
+    let mut recorder_died = false;
     loop {
-        if !*active.lock().unwrap() { break; }
-
-        let next_idx = {
-            let idx = chunk_index.lock().unwrap();
-            let current = *idx;
-            current
-        };
-
-        let base_dir_path = base_dir.lock().unwrap().clone().ok_or("Base dir not set")?;
-        let chunk_file = base_dir_path.join(format!("chunk-{next_idx:04}.wav"));
-
-        // Wait until the segment file appears and has reasonable data
-        // WAV header is 44 bytes; skip obviously incomplete segments
-        let mut waited_ms = 0u64;
-        loop {
-            if !*active.lock().unwrap() { return Ok(()); }
-            
-            let file_size = std::fs::metadata(&chunk_file).map(|m| m.len()).unwrap_or(0);
-            // Accept file if it exists and is larger than WAV header + minimal audio
-            if chunk_file.exists() && file_size > 1000 {
-                break;
+        // Read before scanning: stop waits for the recorder to finalize before clearing `active`,
+        // so once it's clear every segment on disk is complete
+        let stopping = !*active.lock();
+        if !stopping && !recorder_died {
+            if let Some(status) = segmenter_exit(&app) {
+                recorder_died = true;
+                let detail = stderr.as_ref().map(|s| s.text()).unwrap_or_default();
+                log::error!("ffmpeg segment recorder exited unexpectedly ({}): {}", status, detail);
+                let message = if detail.is_empty() {
+                    format!("ffmpeg stopped recording unexpectedly ({})", status)
+                } else {
+                    format!("ffmpeg stopped recording unexpectedly ({}): {}", status, detail)
+                };
+                events::emit(&app, "live-recording-error", message);
             }
-            
-            tokio::time::sleep(tokio::time::Duration::from_millis(200)).await;
-            waited_ms += 200;
-            if waited_ms > (segment_len + 10) * 1000 {
-                // Timeout; move to next chunk
-                let mut idx = chunk_index.lock().unwrap();
-                *idx += 1;
-                break;
+        }
+        let finished = stopping || recorder_died;
+
+        let base_dir_path = base_dir.lock().clone().ok_or("Base dir not set")?;
+        let (segments, newest) = scan_segments(&base_dir_path);
+        let mut sizes = HashMap::new();
+        let mut ready = Vec::new();
+        for (index, path, size) in segments {
+            if processed.contains(&index) {
+                continue;
+            }
+            let settled = finished || last_sizes.get(&index) == Some(&size);
+            let writing = !finished && Some(index) == newest;
+            sizes.insert(index, size);
+            if !settled || writing {
+                continue;
+            }
+            // WAV header is 44 bytes; a segment this small never got any audio
+            if size > 1000 {
+                ready.push((index, path, size));
+            } else {
+                log::warn!("Skipping live segment {} with no audio ({} bytes)", index, size);
+                processed.insert(index);
+                transcripts.lock().silent(index);
+                commit_labelled(&app, &transcripts, &base_dir_path);
             }
         }
+        last_sizes = sizes;
 
-        // Transcribe if file exists and has content
-        if !chunk_file.exists() {
+        if ready.is_empty() {
+            if finished {
+                return Ok(());
+            }
+            tokio::time::sleep(tokio::time::Duration::from_millis(200)).await;
             continue;
         }
 
-        // Transcribe
-        let chunk_path = chunk_file.to_string_lossy().to_string();
-        let size = std::fs::metadata(&chunk_file).map(|m| m.len()).unwrap_or(0);
-        match transcribe_audio_internal(&chunk_path).await {
-            Ok(text) => {
-                transcripts.lock().unwrap().push(text.clone());
-                let _ = app.emit("live-transcript-chunk", serde_json::json!({
-                    "chunk": next_idx,
-                    "text": text,
+        for (index, chunk_file, size) in ready {
+            processed.insert(index);
+            {
+                let mut next = chunk_index.lock();
+                *next = (*next).max(index + 1);
+            }
+            let chunk_path = chunk_file.to_string_lossy().to_string();
+            let chunk_params = thermal::chunk_params(&app, params.with_context(live_context(&transcripts).as_deref()));
+            if vad::should_skip_chunk(&app, &chunk_file) {
+                transcripts.lock().silent(index);
+                commit_labelled(&app, &transcripts, &base_dir_path);
+                let _ = session::record_chunk(&base_dir_path, index, "");
+                events::emit(&app, "live-transcript-chunk", serde_json::json!({
+                    "chunk": index,
+                    "text": "",
                     "path": chunk_path,
-                    "size": size
+                    "size": size,
+                    "segment_secs": segment_len,
+                    "silent": true
                 }));
-                // advance index after processing
-                let mut idx = chunk_index.lock().unwrap();
-                *idx += 1;
+                continue;
             }
-            Err(e) => {
-                let _ = app.emit("live-recording-error", format!("Transcription error: {}", e));
+            let result = live_queue::run(&app, async {
+                let started = std::time::Instant::now();
+                let result = if chunk_params.diarize {
+                    transcribe_chunk_diarized(&app, &chunk_path, &chunk_params).await.map(LiveText::Diarized)
+                } else {
+                    transcribe_audio_internal(&app, &chunk_path, &chunk_params).await.map(LiveText::Plain)
+                };
+                thermal::chunk_transcribed(&app, index, started.elapsed(), segment_len, &chunk_params);
+                result
+            })
+            .await;
+            match result {
+                Ok(LiveText::Diarized(segments)) => {
+                    transcripts.lock().diarized(index, segments);
+                    commit_labelled(&app, &transcripts, &base_dir_path);
+                }
+                Ok(LiveText::Plain(text)) => {
+                    push_live_transcript(&app, &transcripts, index, &text);
+                    commit_labelled(&app, &transcripts, &base_dir_path);
+                    if let Err(e) = session::record_chunk(&base_dir_path, index, &text) {
+                        events::emit(&app, "live-recording-error", e);
+                    }
+                    events::emit(&app, "live-transcript-chunk", serde_json::json!({
+                        "chunk": index,
+                        "text": text,
+                        "path": chunk_path,
+                        "size": size,
+                        "segment_secs": segment_len
+                    }));
+                }
+                Err(e) => {
+                    log::error!("Transcribing live chunk {} failed: {}", index, e);
+                    events::emit(&app, "live-recording-error", format!("Transcription error: {}", e));
+                    // Retried in the background so the chunks behind it aren't held up
+                    chunk_retry::chunk_failed(&app, index, &chunk_path, index as u64 * segment_len, segment_len, &chunk_params, e);
+                }
             }
         }
     }
-    Ok(())
 }
 
-fn has_ffmpeg() -> bool {
-    StdCommand::new("which").arg("ffmpeg").output().map(|o| o.status.success()).unwrap_or(false)
+/// A live chunk's transcription: plain text, or diarized segments that get their speakers once the
+/// chunks before them are in
+enum LiveText {
+    Plain(String),
+    Diarized(Vec<transcript::TranscriptSegment>),
 }
 
-/// Internal transcription helper (shared logic)
-async fn transcribe_audio_internal(audio_path: &str) -> Result<String, String> {
-    use std::process::Command;
-    
-    // Verify file exists and has minimum size
-    let file_path = std::path::PathBuf::from(audio_path);
-    if !file_path.exists() {
-        return Err(format!("Audio file not found: {}", audio_path));
-    }
-    
-    let file_size = std::fs::metadata(&file_path)
-        .map_err(|e| format!("Failed to stat file: {}", e))?
-        .len();
-    
-    if file_size < 100 {
-        return Err(format!("Audio file too small ({} bytes). Recording may have failed.", file_size));
+/// Record and announce the diarized chunks labelled since the last call. Labelling waits on earlier
+/// chunks, so this runs after any chunk settles, whether transcribed, silent or failed.
+pub(crate) fn commit_labelled(
+    app: &tauri::AppHandle,
+    transcripts: &parking_lot::Mutex<live_transcript::LiveTranscript>,
+    session_dir: &Path,
+) {
+    let labelled = transcripts.lock().take_labelled();
+    if labelled.is_empty() {
+        return;
     }
-    
-    // Try multiple possible whisper binary locations
-    let whisper_candidates = [
-        "/home/cwas/Desktop/last-gen-notes/src-tauri/binaries/whisper-cli-x86_64-unknown-linux-gnu",
-        "/home/cwas/Desktop/last-gen-notes/src-tauri/target/debug/whisper-cli",
-    ];
-    
-    let whisper_path = whisper_candidates.iter()
-        .find(|p| std::path::Path::new(p).exists())
-        .ok_or("Whisper binary not found in known locations")?;
-    
-    let exe_dir = std::env::current_exe()
-        .map_err(|e| format!("Failed to get exe path: {}", e))?
-        .parent()
-        .ok_or("Failed to get parent directory")?
-        .to_path_buf();
-    
-    // Prefer tiny model for speed, fall back to base
-    let model_candidates = vec![
-        exe_dir.join("../../../models/ggml-tiny.en.bin"),
-        exe_dir.join("models/ggml-tiny.en.bin"),
-        PathBuf::from("/home/cwas/Desktop/last-gen-notes/models/ggml-tiny.en.bin"),
-        exe_dir.join("../../../models/ggml-base.en.bin"),
-        exe_dir.join("models/ggml-base.en.bin"),
-        PathBuf::from("/home/cwas/Desktop/last-gen-notes/models/ggml-base.en.bin"),
-    ];
-    
-    let model_path = model_candidates.iter()
-        .find(|p| p.exists())
-        .ok_or("Model not found")?;
-    
-    // Use 4 threads for faster transcription on multicore CPUs
-    let num_threads = std::thread::available_parallelism()
-        .map(|p| p.get().min(4))
-        .unwrap_or(2);
-    
-    let output = Command::new(whisper_path)
-        .arg("-m")
-        .arg(model_path)
-        .arg("-f")
-        .arg(audio_path)
-        .arg("-t")
-        .arg(num_threads.to_string())
-        .arg("--no-timestamps")
-        .output()
-        .map_err(|e| format!("Failed to run whisper-cli: {}", e))?;
-    
-    if !output.status.success() {
-        let stderr = String::from_utf8_lossy(&output.stderr);
-        let stdout = String::from_utf8_lossy(&output.stdout);
-        let msg = if !stderr.is_empty() { 
-            stderr.to_string() 
-        } else if !stdout.is_empty() { 
-            stdout.to_string() 
-        } else { 
-            format!("Unknown error (exit code: {:?})", output.status.code())
-        };
-        eprintln!("Whisper error: {}", msg);
-        return Err(format!("Whisper failed: {}", msg));
+    for chunk in labelled {
+        if let Err(e) = session::record_chunk(session_dir, chunk.index, &chunk.text) {
+            events::emit(app, "live-recording-error", e);
+        }
+        events::emit(app, "live-transcript-chunk", serde_json::json!({
+            "chunk": chunk.index,
+            "text": chunk.text,
+        }));
     }
-    
-    let result = String::from_utf8_lossy(&output.stdout).to_string();
-    Ok(result.trim().to_string())
+    live_summary::chunk_transcribed(app, transcripts);
 }
 
-/// Summarize text using a local llama.cpp CLI binary and a provided or default model path
-#[tauri::command]
-fn summarize_text_llama(
-    text: String,
-    model_path: Option<String>,
-    max_tokens: Option<u32>,
-    temperature: Option<f32>,
-) -> Result<String, String> {
-    let llama_candidates = [
-        "/home/cwas/Desktop/last-gen-notes/src-tauri/binaries/llama-cli",
-        "/home/cwas/Desktop/last-gen-notes/src-tauri/target/debug/llama-cli",
-        "/usr/bin/llama-cli",
-    ];
-
-    let llama_path = llama_candidates
-        .iter()
-        .find(|p| std::path::Path::new(p).exists())
-        .ok_or("llama-cli binary not found in known locations")?;
-
-    // Determine model path: prefer provided, else look in models directory
-    let model = if let Some(mp) = model_path {
-        std::path::PathBuf::from(mp)
-    } else {
-        // Try common locations
-        let exe_dir = std::env::current_exe()
-            .map_err(|e| format!("Failed to get exe path: {}", e))?
-            .parent()
-            .ok_or("Failed to get parent directory")?
-            .to_path_buf();
-        let candidates = vec![
-            exe_dir.join("../../../models/llm.gguf"),
-            exe_dir.join("models/llm.gguf"),
-            PathBuf::from("/home/cwas/Desktop/last-gen-notes/models/llm.gguf"),
-        ];
-        candidates
-            .into_iter()
-            .find(|p| p.exists())
-            .ok_or("Model gguf not found; provide model_path in Settings")?
-    };
+/// The most recent chunk text to carry over as prompt context, skipping failed-chunk placeholders
+fn live_context(transcripts: &parking_lot::Mutex<live_transcript::LiveTranscript>) -> Option<String> {
+    transcripts.lock().last_text()
+}
 
-    let ntok = max_tokens.unwrap_or(256);
-    let temp = temperature.unwrap_or(0.7);
-    // Use logical CPUs if available via env or fallback to 4
-    let threads = std::thread::available_parallelism()
-        .map(|n| n.get())
-        .unwrap_or(4)
-        .to_string();
-
-    let prompt = format!(
-        "You are a concise note-taking assistant. Summarize the following transcript into clear bullet points with timestamps if present, avoiding speculation.\n\nTranscript:\n{}\n\nSummary:",
-        text
-    );
-
-    let output = StdCommand::new(llama_path)
-        .arg("-m").arg(&model)
-        .arg("-p").arg(&prompt)
-        .arg("-n").arg(ntok.to_string())
-        .arg("--temp").arg(format!("{:.2}", temp))
-        .arg("-t").arg(&threads)
-        .output()
-        .map_err(|e| format!("Failed to run llama-cli: {}", e))?;
+/// Store a chunk's text with any words repeated from the previous chunk's tail trimmed off,
+/// then start a rolling summary if one is due
+fn push_live_transcript(
+    app: &tauri::AppHandle,
+    transcripts: &parking_lot::Mutex<live_transcript::LiveTranscript>,
+    index: usize,
+    text: &str,
+) {
+    transcripts.lock().transcribed(index, text);
+    live_summary::chunk_transcribed(app, transcripts);
+}
 
-    if !output.status.success() {
-        let stderr = String::from_utf8_lossy(&output.stderr);
-        let stdout = String::from_utf8_lossy(&output.stdout);
-        let msg = if !stderr.is_empty() { stderr.to_string() } else { stdout.to_string() };
-        return Err(format!("llama-cli failed: {}", msg));
+fn has_ffmpeg() -> bool {
+    recorder::has_tool("ffmpeg")
+}
+
+/// Internal transcription helper (shared logic)
+async fn transcribe_audio_internal(
+    app: &tauri::AppHandle,
+    audio_path: &str,
+    params: &whisper::WhisperParams,
+) -> Result<String, String> {
+    let output = whisper::run_whisper(app, audio_path, params, &whisper::OutputMode::Plain, None).await?;
+    if whisper::effective_options(app, params).filter_hallucinations.unwrap_or(false) {
+        let (phrases, low_energy) = hallucination::filter_context(app, Path::new(audio_path));
+        return Ok(hallucination::filter_text(output.stdout.trim(), &phrases, low_energy).trim().to_string());
     }
+    Ok(output.stdout.trim().to_string())
+}
 
-    let mut result = String::from_utf8_lossy(&output.stdout).to_string();
-    // Best-effort trimming
-    result = result.trim().to_string();
-    Ok(result)
+/// Transcribe a live chunk with speaker turns; its speakers are labelled when it's committed in chunk order
+async fn transcribe_chunk_diarized(
+    app: &tauri::AppHandle,
+    audio_path: &str,
+    params: &whisper::WhisperParams,
+) -> Result<Vec<transcript::TranscriptSegment>, String> {
+    let output = whisper::run_whisper(app, audio_path, params, &whisper::OutputMode::Timestamped, None).await?;
+    let mut segments = transcript::parse_whisper_segments(&output.stdout);
+    if whisper::effective_options(app, params).filter_hallucinations.unwrap_or(false) {
+        let (phrases, low_energy) = hallucination::filter_context(app, Path::new(audio_path));
+        segments = hallucination::filter_segments(segments, &phrases, low_energy);
+    }
+    Ok(segments)
 }
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
+    logging::init();
+    let settings = settings::SettingsState::load();
+    let saved_device = settings.settings.lock().unwrap().device.clone();
     tauri::Builder::default()
+        // Registered first so a second launch hands over to this one before setting anything up
+        .plugin(tauri_plugin_single_instance::init(|app, _args, _cwd| tray::show_main_window(app)))
         .plugin(tauri_plugin_opener::init())
         .plugin(tauri_plugin_shell::init())
         .plugin(tauri_plugin_fs::init())
         .plugin(tauri_plugin_os::init())
         .plugin(tauri_plugin_dialog::init())
-        .manage(RecorderState { current: Mutex::new(None) })
-        .manage(ChunkedRecorderState {
-            active: Arc::new(Mutex::new(false)),
-            chunk_index: Arc::new(Mutex::new(0)),
-            base_dir: Arc::new(Mutex::new(None)),
-            transcripts: Arc::new(Mutex::new(Vec::new())),
-            ffmpeg_pid: Arc::new(Mutex::new(None)),
+        .plugin(tauri_plugin_global_shortcut::Builder::new().build())
+        .plugin(tauri_plugin_clipboard_manager::init())
+        .manage(RecorderState { current: parking_lot::Mutex::new(None) })
+        .manage(processes::ProcessRegistry::new())
+        .manage(limits::RecordingLimitsState::new())
+        .manage(OneShotState { cancel: Mutex::new(None) })
+        .manage(downloads::DownloadState::new())
+        .manage(binaries::BinaryVersionCache::new())
+        .manage(jobs::TranscriptionJobState::new())
+        .manage(models::ModelUseState::new())
+        .manage(summarize::SummarizationState::new())
+        .manage(live_summary::LiveSummaryState::new())
+        .manage(thermal::LivePaceState::new())
+        .manage(live_queue::LiveQueueState::new())
+        .manage(retranscribe::RetranscribeState::new())
+        .manage(denoise::DenoiseState::new())
+        .manage(events::SessionEventState::new())
+        .manage(recorder_status::RecorderStatusState::new())
+        .manage(sleep_inhibit::SleepInhibitState::new())
+        .manage(notifications::NotificationState::new())
+        .manage(notes::NotesState::load())
+        .manage(llama_server::LlamaServerState::new())
+        .manage(batch::BatchState::new())
+        .manage(levels::AudioLevelState { current: Mutex::new(None) })
+        .manage(recorder::AudioDeviceState { device: Mutex::new(saved_device) })
+        .manage(settings)
+        .manage(recorder::RecordingProfileState { profile: Mutex::new(recorder::RecordingProfile::default()) })
+        .manage(hallucination::HallucinationState::new())
+        .manage(vad::VadState { options: Mutex::new(Default::default()) })
+        .manage(whisper::TranscriptionOptionsState { options: Mutex::new(Default::default()) })
+        .manage(whisper::GpuSupportCache::new())
+        .manage(WhisperState {
+            resolved: Mutex::new(None),
+            override_path: Mutex::new(load_whisper_override()),
+        })
+        .manage(ChunkedRecorderState::new())
+        .setup(|app| {
+            recorder_lock::claim();
+            retention::enforce_in_background(app.handle().clone());
+            hotkey::restore(app.handle());
+            tray::init(app.handle());
+            Ok(())
         })
         .invoke_handler(tauri::generate_handler![
             greet,
-            detect_gpu,
-            get_power_status,
+            gpu::detect_gpu,
+            power::get_power_status,
+            telemetry::get_performance_history,
+            thermal::get_thermal_status,
+            notes::save_note,
+            notes::get_note,
+            notes::list_notes,
+            notes::update_note,
+            notes::delete_note,
+            notes::search_notes,
+            notes::set_note_tags,
+            notes::list_tags,
+            notes::rename_tag,
+            summarize::generate_note_title,
+            note_export::export_note,
+            note_export::export_note_markdown,
+            note_templates::list_note_templates,
+            note_templates::save_note_template,
+            note_templates::delete_note_template,
+            import::import_audio,
+            stereo::transcribe_stereo_split,
+            chunk_retry::get_failed_chunks,
+            chunk_retry::retranscribe_chunk,
+            retranscribe::retranscribe_session,
+            events::replay_session_events,
+            hotkey::register_recording_hotkey,
+            hotkey::unregister_recording_hotkey,
+            clipboard::copy_to_clipboard,
+            clipboard::copy_note,
             check_binary_status,
+            binaries::verify_binary,
+            binaries::check_binary_updates,
             download_whisper,
+            whisper_build::set_whisper_download_source,
+            whisper_build::build_whisper_from_source,
+            llama::download_llama,
+            llama::get_summarization_status,
+            downloads::cancel_download,
+            downloads::get_download_settings,
+            downloads::set_download_settings,
+            downloads::test_download_connectivity,
+            models::download_model,
+            models::check_model_status,
+            models::list_models,
+            models::delete_model,
+            models::get_models_dir,
+            models::verify_model,
+            models::can_run_model,
+            setup::get_setup_status,
+            setup::run_setup_step,
+            settings::get_settings,
+            settings::update_settings,
+            logging::get_recent_logs,
+            logging::get_log_path,
             get_binary_path,
+            set_whisper_binary_path,
             check_mic_portal,
+            recorder::list_audio_devices,
+            recorder::list_monitor_sources,
+            recorder::set_recording_profile,
+            levels::get_current_audio_level,
+            levels::test_microphone,
             record_system_audio,
+            cancel_one_shot_recording,
             start_system_recording,
             stop_system_recording,
             start_live_recording,
             stop_live_recording,
+            pause_live_recording,
+            resume_live_recording,
             get_live_transcripts,
+            session::recover_live_session,
             get_recording_path,
+            recordings::list_recordings,
+            recordings::delete_recording,
+            recordings::rename_recording,
+            retention::set_retention_policy,
             transcribe_audio,
-            summarize_text_llama,
+            transcribe_audio_detailed,
+            transcribe_audio_json,
+            transcript::export_transcript,
+            jobs::cancel_transcription,
+            jobs::get_transcription_jobs,
+            whisper::set_transcription_options,
+            batch::transcribe_directory,
+            batch::cancel_batch,
+            vad::set_vad_options,
+            vad::analyze_audio_silence,
+            hallucination::set_hallucination_filters,
+            audio::get_audio_metadata,
+            audio::get_segment_audio,
+            audio::get_waveform_peaks,
+            gain::normalize_audio,
+            trim::trim_audio,
+            trim::split_audio,
+            limits::set_min_free_space,
+            summarize::summarize_text_llama,
+            summarize::summarize_long_text,
+            transcribe_and_summarize,
+            summarize::cancel_summarization,
+            actions::extract_action_items,
+            llama_server::start_llama_server,
+            llama_server::stop_llama_server,
+            prompts::list_prompt_templates,
+            prompts::save_prompt_template,
+            prompts::delete_prompt_template,
             get_recorder_mode,
+            get_recorder_status,
+            stop_all_recorders,
+            recorder_lock::force_takeover_recorders,
+            recordings::clear_live_session_cache,
+            recordings::clear_recordings,
             cleanup_recorders_and_cache
         ])
-        .run(tauri::generate_context!())
-        .expect("error while running tauri application");
+        .build(tauri::generate_context!())
+        .expect("error while building tauri application")
+        .run(|app, event| {
+            // Don't leave our recorders running after the window closes
+            if let tauri::RunEvent::Exit = event {
+                app.state::<processes::ProcessRegistry>().terminate_all();
+                sleep_inhibit::release(app);
+                recorder_lock::release();
+            }
+        });
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    /// Runs begin_session and end_session, the halves of starting and stopping a live session that
+    /// take the state's locks, 100 times over with a stand-in recorder process and a watcher task
+    /// taking the same locks the recording loops do
+    #[test]
+    fn starts_and_stops_live_sessions_repeatedly_without_deadlocking() {
+        let state = Arc::new(ChunkedRecorderState::new());
+        tauri::async_runtime::block_on(async {
+            for round in 0..100 {
+                {
+                    let (_, drained_tx, _) = state
+                        .begin_session(recorder::RecorderBackend::Alsa, || Ok(std::env::temp_dir()))
+                        .unwrap_or_else(|e| panic!("round {} started while the last session was still active: {}", round, e));
+                    *state.ffmpeg.lock() = Some(StdCommand::new("sleep").arg("30").spawn().unwrap());
+                    assert!(state.begin_session(recorder::RecorderBackend::Alsa, || Ok(std::env::temp_dir())).is_err());
+
+                    let (active, chunk_index, transcripts) =
+                        (state.active.clone(), state.chunk_index.clone(), state.transcripts.clone());
+                    tauri::async_runtime::spawn(async move {
+                        while *active.lock() {
+                            let index = {
+                                let mut next = chunk_index.lock();
+                                *next += 1;
+                                *next - 1
+                            };
+                            transcripts.lock().transcribed(index, "chunk text");
+                            tokio::time::sleep(Duration::from_millis(1)).await;
+                        }
+                        let _ = drained_tx.send(());
+                    });
+                }
+
+                // A chunk task panicking mid-update mustn't leave the next stop or start stuck
+                if round % 10 == 0 {
+                    let transcripts = state.transcripts.clone();
+                    let _ = std::thread::spawn(move || {
+                        let _guard = transcripts.lock();
+                        panic!("chunk task panicked");
+                    })
+                    .join();
+                }
+
+                let stop_ffmpeg = |mut child: StdChild| {
+                    child.kill().unwrap();
+                    child.wait().unwrap();
+                };
+                let drained = state.end_session(stop_ffmpeg, Duration::from_secs(5)).await.unwrap();
+                assert!(drained, "round {}'s recording loop never drained", round);
+                assert!(state.drained.lock().is_none());
+            }
+        });
+        assert_eq!(*state.session_id.lock(), 100);
+        assert!(state.ffmpeg.lock().is_none());
+    }
 }
@@ -0,0 +1,162 @@
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use tauri::Manager;
+
+use crate::notifications::{self, NotificationKind};
+use crate::{events, finish_live_recording, finish_system_recording, ChunkedRecorderState, RecorderState};
+
+/// How often free space on the cache partition is checked while recording
+const DISK_CHECK_INTERVAL_SECS: u64 = 30;
+
+const DEFAULT_MIN_FREE_MB: u64 = 500;
+
+// Free space (MB) recordings must leave on the cache partition before they're stopped
+pub struct RecordingLimitsState {
+    pub min_free_mb: Mutex<u64>,
+}
+
+impl RecordingLimitsState {
+    pub fn new() -> Self {
+        RecordingLimitsState { min_free_mb: Mutex::new(DEFAULT_MIN_FREE_MB) }
+    }
+}
+
+#[derive(Serialize, Clone, Copy, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum AutoStopReason {
+    MaxDuration,
+    DiskFull,
+}
+
+/// Payload of `recording-auto-stopped`; `output` is the WAV path (system) or transcript (live)
+#[derive(Serialize, Clone)]
+struct AutoStopped {
+    recording: &'static str,
+    reason: AutoStopReason,
+    message: String,
+    output: Option<String>,
+    error: Option<String>,
+}
+
+/// The recording a guard watches; it exits quietly once that recording has been stopped
+pub enum GuardedRecording {
+    System(PathBuf),
+    Live(u64),
+}
+
+impl GuardedRecording {
+    fn kind(&self) -> &'static str {
+        match self {
+            GuardedRecording::System(_) => "system",
+            GuardedRecording::Live(_) => "live",
+        }
+    }
+
+    fn is_current(&self, app: &tauri::AppHandle) -> bool {
+        match self {
+            GuardedRecording::System(path) => app
+                .state::<RecorderState>()
+                .current
+                .lock()
+                .as_ref()
+                .map(|p| p.path == *path)
+                .unwrap_or(false),
+            GuardedRecording::Live(session_id) => {
+                let state = app.state::<ChunkedRecorderState>();
+                let active = *state.active.lock();
+                active && *state.session_id.lock() == *session_id
+            }
+        }
+    }
+
+    fn is_paused(&self, app: &tauri::AppHandle) -> bool {
+        match self {
+            GuardedRecording::System(_) => false,
+            GuardedRecording::Live(_) => *app.state::<ChunkedRecorderState>().paused.lock(),
+        }
+    }
+}
+
+/// Available space on the partition holding `path` (statvfs on Unix, via sysinfo)
+fn free_space_mb(path: &Path) -> Option<u64> {
+    let disks = sysinfo::Disks::new_with_refreshed_list();
+    disks
+        .list()
+        .iter()
+        .filter(|d| path.starts_with(d.mount_point()))
+        .max_by_key(|d| d.mount_point().as_os_str().len())
+        .map(|d| d.available_space() / (1024 * 1024))
+}
+
+fn low_on_space(app: &tauri::AppHandle, dir: &Path) -> Option<String> {
+    let min_free_mb = *app.state::<RecordingLimitsState>().min_free_mb.lock().unwrap();
+    let free = free_space_mb(dir)?;
+    (free < min_free_mb).then(|| format!("Only {} MB free on the recording disk (minimum {} MB)", free, min_free_mb))
+}
+
+/// Refuse to start a recording on a disk that's already below the free-space minimum
+pub fn check_free_space(app: &tauri::AppHandle, dir: &Path) -> Result<(), String> {
+    match low_on_space(app, dir) {
+        Some(message) => Err(message),
+        None => Ok(()),
+    }
+}
+
+/// Stop `recording` after `max_duration_secs` (paused time excluded) or when the disk runs low,
+/// through the same finalization path as a manual stop
+pub fn start_guard(app: tauri::AppHandle, recording: GuardedRecording, dir: PathBuf, max_duration_secs: Option<u64>) {
+    let max_duration_secs = max_duration_secs.filter(|secs| *secs > 0);
+    tauri::async_runtime::spawn(async move {
+        let mut elapsed = 0u64;
+        loop {
+            tokio::time::sleep(tokio::time::Duration::from_secs(1)).await;
+            if !recording.is_current(&app) {
+                return;
+            }
+            if recording.is_paused(&app) {
+                continue;
+            }
+            elapsed += 1;
+
+            let stop = match max_duration_secs {
+                Some(max) if elapsed >= max => {
+                    Some((AutoStopReason::MaxDuration, format!("Reached the maximum duration of {}s", max)))
+                }
+                _ if elapsed.is_multiple_of(DISK_CHECK_INTERVAL_SECS) => {
+                    low_on_space(&app, &dir).map(|message| (AutoStopReason::DiskFull, message))
+                }
+                _ => None,
+            };
+            let Some((reason, message)) = stop else { continue };
+
+            let result = match recording {
+                GuardedRecording::System(_) => finish_system_recording(&app).await.map(|r| r.path).map_err(String::from),
+                GuardedRecording::Live(_) => finish_live_recording(&app).await,
+            };
+            let body = match &result {
+                Ok(output) => format!("{}. Saved {}", message, notifications::display_name(output)),
+                Err(e) => format!("{}. Stopping failed: {}", message, notifications::first_line(e)),
+            };
+            notifications::notify(&app, NotificationKind::AutoStop, "Recording stopped", &body, result.as_ref().ok().cloned());
+            events::emit(&app, "recording-auto-stopped", AutoStopped {
+                recording: recording.kind(),
+                reason,
+                message,
+                output: result.as_ref().ok().cloned(),
+                error: result.err(),
+            });
+            return;
+        }
+    });
+}
+
+/// Set the free-space floor (MB) below which recordings are automatically stopped
+#[tauri::command]
+pub async fn set_min_free_space(
+    state: tauri::State<'_, RecordingLimitsState>,
+    min_free_mb: u64,
+) -> Result<u64, String> {
+    *state.min_free_mb.lock().unwrap() = min_free_mb;
+    Ok(min_free_mb)
+}
@@ -0,0 +1,140 @@
+use std::collections::HashSet;
+use std::future::Future;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tauri::async_runtime::JoinHandle;
+use tauri::Manager;
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+
+use crate::{events, jobs};
+use crate::recorder_status::{self, Transition};
+
+tokio::task_local! {
+    // Set while a live chunk is transcribed, so its whisper process is registered for drain to kill
+    static LIVE_CHUNK: ();
+}
+
+/// Chunks that may wait for a free transcription slot before the backlog is reported
+const BACKLOG_REPORT_DEPTH: usize = 2;
+
+// Limits how many live chunks are transcribed at once, so a slow model can't pile up whisper processes
+pub struct LiveQueueState {
+    // Replaced on every session start; closed on stop so queued chunks give up instead of spawning whisper
+    permits: Mutex<Arc<Semaphore>>,
+    waiting: AtomicUsize,
+    // Background chunk transcriptions still running or queued, awaited by drain
+    tasks: Mutex<Vec<JoinHandle<()>>>,
+    // Whisper processes transcribing live chunks, killed if drain runs out of time
+    pids: Mutex<HashSet<u32>>,
+}
+
+impl LiveQueueState {
+    pub fn new() -> Self {
+        LiveQueueState {
+            permits: Mutex::new(Arc::new(Semaphore::new(1))),
+            waiting: AtomicUsize::new(0),
+            tasks: Mutex::new(Vec::new()),
+            pids: Mutex::new(HashSet::new()),
+        }
+    }
+}
+
+fn report_backlog(app: &tauri::AppHandle, waiting: usize) {
+    events::emit(app, "transcription-backlog", serde_json::json!({ "waiting": waiting }));
+}
+
+/// Open the queue for a new live session with `concurrency` slots
+pub fn start(app: &tauri::AppHandle, concurrency: usize) {
+    let state = app.state::<LiveQueueState>();
+    state.permits.lock().unwrap().close();
+    *state.permits.lock().unwrap() = Arc::new(Semaphore::new(concurrency.max(1)));
+    state.waiting.store(0, Ordering::SeqCst);
+    state.tasks.lock().unwrap().clear();
+}
+
+/// Run a live transcription once a slot is free. `transcribe` isn't polled, so no whisper process
+/// starts, until then; fails without running it if the session stopped while it was queued.
+pub async fn run<T, F>(app: &tauri::AppHandle, transcribe: F) -> Result<T, String>
+where
+    F: Future<Output = Result<T, String>>,
+{
+    let _permit = acquire(app).await.ok_or("Live recording stopped before this chunk was transcribed")?;
+    LIVE_CHUNK.scope((), transcribe).await
+}
+
+/// Like run, except that once the session has stopped and closed the queue `transcribe` runs
+/// straight away rather than giving up; retries of failed chunks carry on after stop
+pub async fn run_retry<T, F>(app: &tauri::AppHandle, transcribe: F) -> Result<T, String>
+where
+    F: Future<Output = Result<T, String>>,
+{
+    let _permit = acquire(app).await;
+    LIVE_CHUNK.scope((), transcribe).await
+}
+
+/// Note a whisper process started for a live chunk; processes started outside run aren't tracked
+pub fn register_pid(app: &tauri::AppHandle, pid: u32) {
+    if LIVE_CHUNK.try_with(|_| ()).is_ok() {
+        app.state::<LiveQueueState>().pids.lock().unwrap().insert(pid);
+    }
+}
+
+pub fn unregister_pid(app: &tauri::AppHandle, pid: u32) {
+    app.state::<LiveQueueState>().pids.lock().unwrap().remove(&pid);
+}
+
+/// Wait for a transcription slot, or None if the queue was closed first
+async fn acquire(app: &tauri::AppHandle) -> Option<OwnedSemaphorePermit> {
+    let state = app.state::<LiveQueueState>();
+    let permits = state.permits.lock().unwrap().clone();
+    let waiting = state.waiting.fetch_add(1, Ordering::SeqCst) + 1;
+    recorder_status::transition(app, Transition::Backlog(waiting));
+    if waiting > BACKLOG_REPORT_DEPTH {
+        report_backlog(app, waiting);
+    }
+    let permit = permits.acquire_owned().await;
+    let waiting = state.waiting.fetch_sub(1, Ordering::SeqCst) - 1;
+    recorder_status::transition(app, Transition::Backlog(waiting));
+    // Let the frontend know once the backlog it was told about has cleared
+    if waiting >= BACKLOG_REPORT_DEPTH {
+        report_backlog(app, waiting);
+    }
+    permit.ok()
+}
+
+/// Keep a background chunk transcription so stop can wait for it
+pub fn track(app: &tauri::AppHandle, task: JoinHandle<()>) {
+    let state = app.state::<LiveQueueState>();
+    let mut tasks = state.tasks.lock().unwrap();
+    tasks.retain(|t| !t.inner().is_finished());
+    tasks.push(task);
+}
+
+/// Wait up to `timeout` for queued and running chunk transcriptions. After that the queue is closed,
+/// the remaining tasks aborted and their whisper processes killed, so nothing is left running.
+pub async fn drain(app: &tauri::AppHandle, timeout: Duration) {
+    let state = app.state::<LiveQueueState>();
+    let tasks: Vec<JoinHandle<()>> = std::mem::take(&mut *state.tasks.lock().unwrap());
+    let aborts: Vec<_> = tasks.iter().map(|t| t.inner().abort_handle()).collect();
+    let mut all = std::pin::pin!(futures_util::future::join_all(tasks));
+    if tokio::time::timeout(timeout, &mut all).await.is_err() {
+        log::warn!("Timed out waiting for queued live chunks; cancelling the rest");
+        for abort in &aborts {
+            abort.abort();
+        }
+        cancel(app);
+        all.await;
+    }
+    state.permits.lock().unwrap().close();
+}
+
+/// Close the queue and kill the live chunk transcriptions still running. Aborting their tasks isn't
+/// enough, since whisper runs on a blocking thread until its process exits.
+pub fn cancel(app: &tauri::AppHandle) {
+    let state = app.state::<LiveQueueState>();
+    state.permits.lock().unwrap().close();
+    for pid in state.pids.lock().unwrap().drain() {
+        jobs::terminate_pid(pid);
+    }
+}
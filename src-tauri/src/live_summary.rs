@@ -0,0 +1,103 @@
+use serde::Serialize;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use tauri::Manager;
+
+use crate::error::AppError;
+use crate::events;
+use crate::live_transcript::LiveTranscript;
+use crate::summarize::{self, CancelHandle, Session};
+
+/// A rolling summary in flight, with the handle that cancels it and no other summary
+struct RollingTask {
+    handle: tauri::async_runtime::JoinHandle<()>,
+    cancel: CancelHandle,
+}
+
+// Rolling summary settings for the live session, and the summary task in flight
+pub struct LiveSummaryState {
+    interval_chunks: Mutex<Option<usize>>,
+    task: Mutex<Option<RollingTask>>,
+    running: Arc<AtomicBool>,
+}
+
+impl LiveSummaryState {
+    pub fn new() -> Self {
+        LiveSummaryState {
+            interval_chunks: Mutex::new(None),
+            task: Mutex::new(None),
+            running: Arc::new(AtomicBool::new(false)),
+        }
+    }
+}
+
+#[derive(Serialize, Clone)]
+struct LiveSummaryUpdated {
+    summary: String,
+    /// Transcribed chunks the summary covers
+    chunks: usize,
+}
+
+/// Summarize the live transcript every `interval_chunks` transcribed chunks for the session being started
+pub fn start(app: &tauri::AppHandle, interval_chunks: Option<usize>) {
+    *app.state::<LiveSummaryState>().interval_chunks.lock().unwrap() = interval_chunks.filter(|&n| n > 0);
+}
+
+/// Stop scheduling rolling summaries for a session that failed to start, before any chunk could start one
+pub fn stop(app: &tauri::AppHandle) {
+    *app.state::<LiveSummaryState>().interval_chunks.lock().unwrap() = None;
+}
+
+/// Called after each chunk's text is stored; starts a summary when one is due, unless the last is still running
+pub fn chunk_transcribed(app: &tauri::AppHandle, transcripts: &parking_lot::Mutex<LiveTranscript>) {
+    let state = app.state::<LiveSummaryState>();
+    let Some(interval) = *state.interval_chunks.lock().unwrap() else {
+        return;
+    };
+    let (chunks, text) = {
+        let transcripts = transcripts.lock();
+        if transcripts.is_empty() || !transcripts.len().is_multiple_of(interval) {
+            return;
+        }
+        (transcripts.len(), transcripts.joined())
+    };
+    if state.running.swap(true, Ordering::Relaxed) {
+        log::info!("Skipping the rolling summary at chunk {}; the previous one is still running", chunks);
+        return;
+    }
+
+    let cancel = CancelHandle::new();
+    let (task_app, task_cancel, running) = (app.clone(), cancel.clone(), state.running.clone());
+    let handle = tauri::async_runtime::spawn(async move {
+        // Windowed like summarize_long_text, since a long session outgrows one llama context
+        let result = async {
+            let session = Session::begin_with(&task_app, &task_cancel)?;
+            summarize::summarize_long(&task_app, &session, &text, None, false).await
+        }
+        .await;
+        match result {
+            Ok(summary) => {
+                events::emit(&task_app, "live-summary-updated", LiveSummaryUpdated { summary, chunks });
+            }
+            Err(e @ AppError::Cancelled(_)) => log::info!("Rolling summary at chunk {} stopped: {}", chunks, e),
+            Err(e) => log::error!("Rolling summary at chunk {} failed: {}", chunks, e),
+        }
+        running.store(false, Ordering::Relaxed);
+    });
+    *state.task.lock().unwrap() = Some(RollingTask { handle, cancel });
+}
+
+/// Cancel the in-flight rolling summary, if any, and wait for it to finish; used when the live session stops.
+/// Only that summary is cancelled, not one the user started meanwhile.
+pub async fn finish(app: &tauri::AppHandle) {
+    let state = app.state::<LiveSummaryState>();
+    *state.interval_chunks.lock().unwrap() = None;
+    let Some(task) = state.task.lock().unwrap().take() else {
+        return;
+    };
+    task.cancel.cancel(&app.state::<summarize::SummarizationState>());
+    // Cancelling kills its llama-cli or drops its request, so this returns promptly
+    if let Err(e) = task.handle.await {
+        log::error!("Rolling summary task failed: {}", e);
+    }
+}
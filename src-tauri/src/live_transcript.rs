@@ -0,0 +1,205 @@
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+use crate::transcript;
+
+#[derive(Serialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum ChunkStatus {
+    Transcribed,
+    /// Transcription failed; `text` is the placeholder until a retry fills it in
+    Failed,
+    /// Skipped by VAD, so it contributes no text
+    Silent,
+    /// Recorded or still recording, but not transcribed yet
+    Pending,
+}
+
+/// One live chunk's text
+#[derive(Serialize, Clone)]
+pub struct ChunkTranscript {
+    pub index: usize,
+    pub status: ChunkStatus,
+    pub text: String,
+}
+
+/// A live session's chunk texts keyed by chunk index, so the transcript reads in recording order
+/// however out of order the chunks finish transcribing
+#[derive(Default)]
+pub struct LiveTranscript {
+    chunks: BTreeMap<usize, ChunkTranscript>,
+    /// Diarized chunks waiting on earlier chunks before their speakers can be labelled
+    unlabelled: BTreeMap<usize, Vec<transcript::TranscriptSegment>>,
+    /// The first chunk not yet accounted for by speaker labelling
+    next_labelled: usize,
+    speakers: transcript::SpeakerTracker,
+    /// Chunks labelled since the last take_labelled
+    labelled: Vec<usize>,
+}
+
+impl LiveTranscript {
+    pub fn clear(&mut self) {
+        *self = Self::default();
+    }
+
+    fn set(&mut self, index: usize, status: ChunkStatus, text: String) {
+        self.chunks.insert(index, ChunkTranscript { index, status, text });
+        self.label_ready();
+    }
+
+    /// Store a chunk's text with any words repeated from the tail of the chunks before it trimmed off
+    pub fn transcribed(&mut self, index: usize, text: &str) {
+        let stitched = self.stitched(index, text);
+        self.set(index, ChunkStatus::Transcribed, stitched);
+    }
+
+    /// Hold a diarized chunk's segments until every chunk before it is in, so speakers are labelled
+    /// in recording order rather than in the order transcriptions finish
+    pub fn diarized(&mut self, index: usize, segments: Vec<transcript::TranscriptSegment>) {
+        self.unlabelled.insert(index, segments);
+        self.label_ready();
+    }
+
+    /// Label whatever diarized chunks are still waiting, skipping chunks that never came in; used at stop
+    pub fn finish_labelling(&mut self) {
+        if let Some(&last) = self.unlabelled.keys().next_back() {
+            while self.next_labelled <= last {
+                if !self.unlabelled.contains_key(&self.next_labelled) && !self.chunks.contains_key(&self.next_labelled) {
+                    self.next_labelled += 1;
+                }
+                self.label_ready();
+            }
+        }
+    }
+
+    /// Diarized chunks labelled since the last call, in chunk order
+    pub fn take_labelled(&mut self) -> Vec<ChunkTranscript> {
+        std::mem::take(&mut self.labelled).into_iter().filter_map(|index| self.chunks.get(&index).cloned()).collect()
+    }
+
+    fn label_ready(&mut self) {
+        loop {
+            let index = self.next_labelled;
+            if let Some(mut segments) = self.unlabelled.remove(&index) {
+                let text = self.speakers.label_chunk(&mut segments);
+                let stitched = self.stitched(index, &text);
+                self.chunks.insert(index, ChunkTranscript { index, status: ChunkStatus::Transcribed, text: stitched });
+                self.labelled.push(index);
+            } else if !self.chunks.contains_key(&index) {
+                return;
+            }
+            self.next_labelled += 1;
+        }
+    }
+
+    fn stitched(&self, index: usize, text: &str) -> String {
+        let mut before: Vec<String> = self
+            .chunks
+            .range(..index)
+            .rev()
+            .filter(|(_, c)| c.status == ChunkStatus::Transcribed)
+            .take(3)
+            .map(|(_, c)| c.text.clone())
+            .collect();
+        before.reverse();
+        transcript::stitch_chunk(&before, text)
+    }
+
+    pub fn failed(&mut self, index: usize, placeholder: String) {
+        self.set(index, ChunkStatus::Failed, placeholder);
+    }
+
+    pub fn silent(&mut self, index: usize) {
+        self.set(index, ChunkStatus::Silent, String::new());
+    }
+
+    /// Replace a failed chunk's placeholder with its text once a retry succeeds
+    pub fn retried(&mut self, index: usize, text: &str) {
+        if let Some(chunk) = self.chunks.get_mut(&index).filter(|c| c.status == ChunkStatus::Failed) {
+            chunk.status = ChunkStatus::Transcribed;
+            chunk.text = text.trim().to_string();
+        }
+    }
+
+    /// Chunks with text in the transcript, counting failed ones' placeholders
+    pub fn len(&self) -> usize {
+        self.chunks.values().filter(|c| matches!(c.status, ChunkStatus::Transcribed | ChunkStatus::Failed)).count()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// The latest transcribed chunk's text, to carry over as prompt context
+    pub fn last_text(&self) -> Option<String> {
+        self.chunks.values().rev().find(|c| c.status == ChunkStatus::Transcribed).map(|c| c.text.clone())
+    }
+
+    /// The whole transcript in chunk order, with placeholders where chunks failed
+    pub fn joined(&self) -> String {
+        self.chunks
+            .values()
+            .filter(|c| !c.text.is_empty())
+            .map(|c| c.text.as_str())
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+
+    /// Every chunk below `next_index` in order, with the ones not transcribed yet marked pending
+    pub fn listing(&self, next_index: usize) -> Vec<ChunkTranscript> {
+        let end = self.chunks.keys().next_back().map_or(0, |last| last + 1).max(next_index);
+        (0..end)
+            .map(|index| {
+                self.chunks.get(&index).cloned().unwrap_or(ChunkTranscript {
+                    index,
+                    status: ChunkStatus::Pending,
+                    text: String::new(),
+                })
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transcript::TranscriptSegment;
+
+    fn segments(texts: &[(&str, bool)]) -> Vec<TranscriptSegment> {
+        texts
+            .iter()
+            .map(|(text, speaker_turn)| TranscriptSegment { text: text.to_string(), speaker_turn: *speaker_turn, ..Default::default() })
+            .collect()
+    }
+
+    #[test]
+    fn labels_speakers_in_chunk_order_however_chunks_finish() {
+        let mut transcript = LiveTranscript::default();
+        transcript.diarized(1, segments(&[("second chunk", false)]));
+        assert!(transcript.take_labelled().is_empty());
+        transcript.diarized(0, segments(&[("first chunk", true)]));
+        let labelled = transcript.take_labelled();
+        assert_eq!(labelled.iter().map(|c| c.index).collect::<Vec<_>>(), vec![0, 1]);
+        assert_eq!(labelled[0].text, "[Speaker 1] first chunk");
+        assert_eq!(labelled[1].text, "[Speaker 2] second chunk");
+    }
+
+    #[test]
+    fn settled_chunks_release_the_diarized_ones_behind_them() {
+        let mut transcript = LiveTranscript::default();
+        transcript.diarized(2, segments(&[("after the gap", false)]));
+        transcript.silent(0);
+        assert!(transcript.take_labelled().is_empty());
+        transcript.failed(1, "[transcription failed]".into());
+        assert_eq!(transcript.take_labelled()[0].index, 2);
+    }
+
+    #[test]
+    fn finish_labels_chunks_waiting_on_one_that_never_came() {
+        let mut transcript = LiveTranscript::default();
+        transcript.diarized(1, segments(&[("orphan", false)]));
+        transcript.finish_labelling();
+        assert_eq!(transcript.take_labelled()[0].text, "[Speaker 1] orphan");
+        assert_eq!(transcript.joined(), "[Speaker 1] orphan");
+    }
+}
@@ -0,0 +1,157 @@
+use serde::Serialize;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::error::AppError;
+use crate::{archive, binaries, downloads, emit_progress, get_binaries_dir, models, recorder, settings};
+
+/// llama.cpp release download_llama installs
+const LLAMA_RELEASE: &str = "b6550";
+
+pub const REPO: &str = "ggml-org/llama.cpp";
+
+/// SHA-256 of each platform's LLAMA_RELEASE zip, pinned here so a download is checked against
+/// something other than the release it came from. Update together with LLAMA_RELEASE (from
+/// `sha256sum` of each asset); an asset missing here isn't downloaded at all.
+const PINNED_SHA256: &[(&str, &str)] = &[];
+
+const RELEASES_URL: &str = "https://github.com/ggml-org/llama.cpp/releases/download";
+
+/// Subdirectory of the binaries dir holding llama-cli and its shared libraries, apart from whisper's ggml libs
+const INSTALL_DIR: &str = "llama";
+
+fn binary_file_name() -> &'static str {
+    if cfg!(target_os = "windows") { "llama-cli.exe" } else { "llama-cli" }
+}
+
+/// Release zip of `tag` for this platform, e.g. llama-b6550-bin-ubuntu-x64.zip
+pub fn asset_name(tag: &str) -> Result<String, String> {
+    let platform = if cfg!(target_os = "windows") {
+        if cfg!(target_arch = "aarch64") { "win-cpu-arm64" } else { "win-cpu-x64" }
+    } else if cfg!(target_os = "macos") {
+        if cfg!(target_arch = "aarch64") { "macos-arm64" } else { "macos-x64" }
+    } else if cfg!(target_os = "linux") && cfg!(target_arch = "x86_64") {
+        "ubuntu-x64"
+    } else {
+        return Err("No prebuilt llama-cli for this platform; build llama.cpp from source and put llama-cli on PATH".to_string());
+    };
+    Ok(format!("llama-{}-bin-{}.zip", tag, platform))
+}
+
+/// llama-cli installed by download_llama; release zips nest it under build/bin on some platforms
+pub fn installed_binary(binaries_dir: &Path) -> Option<PathBuf> {
+    archive::find_file(&binaries_dir.join(INSTALL_DIR), binary_file_name(), 3)
+}
+
+/// The downloaded llama-cli, falling back to one on PATH
+pub fn resolve_binary() -> Result<PathBuf, AppError> {
+    if let Some(binary) = get_binaries_dir().ok().and_then(|dir| installed_binary(&dir)) {
+        return Ok(binary);
+    }
+    if recorder::has_tool("llama-cli") {
+        return Ok(PathBuf::from("llama-cli"));
+    }
+    Err(AppError::SetupRequired {
+        step: "llama".to_string(),
+        message: "llama-cli not found; download it from Settings or install llama.cpp".to_string(),
+    })
+}
+
+/// llama-server from the same release download as llama-cli, falling back to one on PATH
+pub fn resolve_server_binary() -> Result<PathBuf, AppError> {
+    let file_name = if cfg!(target_os = "windows") { "llama-server.exe" } else { "llama-server" };
+    if let Some(binary) = get_binaries_dir().ok().and_then(|dir| archive::find_file(&dir.join(INSTALL_DIR), file_name, 3)) {
+        return Ok(binary);
+    }
+    if recorder::has_tool("llama-server") {
+        return Ok(PathBuf::from("llama-server"));
+    }
+    Err(AppError::SetupRequired {
+        step: "llama".to_string(),
+        message: "llama-server not found; download llama.cpp from Settings or install it".to_string(),
+    })
+}
+
+/// What summarize_text_llama would run with when called without a model_path
+#[derive(Serialize, Debug)]
+pub struct SummarizationStatus {
+    pub ready: bool,
+    pub binary: Option<String>,
+    pub binary_version: Option<String>,
+    pub model: Option<String>,
+    pub model_source: Option<models::LlamaModelSource>,
+    /// Why it isn't ready; SetupRequired errors name the setup step that fixes them
+    pub errors: Vec<AppError>,
+}
+
+/// Report which llama-cli and model summaries would use, for the Settings screen
+#[tauri::command]
+pub async fn get_summarization_status(app: tauri::AppHandle) -> Result<SummarizationStatus, String> {
+    let saved_model = settings::current(&app).llama_model_path;
+    tauri::async_runtime::spawn_blocking(move || {
+        let mut errors = Vec::new();
+        let binary = resolve_binary().map_err(|e| errors.push(e)).ok();
+        let model = models::resolve_llama_model(None, saved_model.as_deref()).map_err(|e| errors.push(e)).ok();
+        SummarizationStatus {
+            ready: errors.is_empty(),
+            binary_version: binary.as_deref().and_then(|b| binaries::binary_version(&app, b)),
+            binary: binary.map(|b| b.to_string_lossy().to_string()),
+            model_source: model.as_ref().map(|(_, source)| *source),
+            model: model.map(|(path, _)| path.to_string_lossy().to_string()),
+            errors,
+        }
+    })
+    .await
+    .map_err(|e| format!("Summarization check failed: {}", e))
+}
+
+/// The pinned SHA-256 of a release asset
+fn pinned_sha256(name: &str) -> Result<&'static str, AppError> {
+    PINNED_SHA256
+        .iter()
+        .find(|(asset, _)| *asset == name)
+        .map(|(_, sha256)| *sha256)
+        .ok_or_else(|| AppError::SetupRequired {
+            step: "llama".to_string(),
+            message: format!(
+                "No pinned checksum for {}, so it can't be verified; install llama.cpp yourself and put llama-cli on PATH",
+                name
+            ),
+        })
+}
+
+/// Download llama-cli from the llama.cpp GitHub releases
+#[tauri::command]
+pub async fn download_llama(window: tauri::Window) -> Result<String, AppError> {
+    let binaries_dir = get_binaries_dir()?;
+    let asset = asset_name(LLAMA_RELEASE)?;
+    let sha256 = pinned_sha256(&asset)?;
+    let download = downloads::ActiveDownload::start(&window, "llama-cli");
+
+    let url = format!("{}/{}/{}", RELEASES_URL, LLAMA_RELEASE, asset);
+    let archive_path = binaries_dir.join(&asset);
+    let (downloaded, total_size) = downloads::fetch_verified(&window, &download, &url, &archive_path, Some(sha256)).await?;
+
+    emit_progress(&window, &download.id, downloaded, total_size, "Extracting...");
+    let install_dir = binaries_dir.join(INSTALL_DIR);
+    // Libraries left from an older release would be loaded alongside the new binary
+    let _ = fs::remove_dir_all(&install_dir);
+    archive::extract_archive(&archive_path, &install_dir)?;
+    fs::remove_file(&archive_path).ok();
+
+    let binary = installed_binary(&binaries_dir).ok_or("llama-cli wasn't found in the downloaded archive")?;
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let mut perms = fs::metadata(&binary)
+            .map_err(|e| format!("Failed to get permissions: {}", e))?
+            .permissions();
+        perms.set_mode(0o755);
+        fs::set_permissions(&binary, perms)
+            .map_err(|e| format!("Failed to set permissions: {}", e))?;
+    }
+
+    emit_progress(&window, &download.id, downloaded, total_size, "Complete!");
+    Ok(binary.to_string_lossy().to_string())
+}
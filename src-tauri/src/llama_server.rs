@@ -0,0 +1,181 @@
+use serde::Serialize;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::{Child, Command, Stdio};
+use std::sync::atomic::AtomicBool;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tauri::Manager;
+
+use crate::error::AppError;
+use crate::summarize::LlamaRun;
+use crate::{chat_api, jobs, llama, logging, models, processes, settings};
+
+const DEFAULT_PORT: u16 = 8089;
+
+/// Loading a multi-gigabyte model can take a while on a slow disk
+const STARTUP_TIMEOUT: Duration = Duration::from_secs(180);
+
+const HEALTH_POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// llama-server's stderr goes here, in the app log dir, since nothing reads it while the server runs
+const SERVER_LOG_FILE: &str = "llama-server.log";
+
+struct LlamaServer {
+    child: Child,
+    port: u16,
+    model: PathBuf,
+}
+
+// The llama-server we started, if any. It's spawned through the process registry, so app exit kills it too.
+pub struct LlamaServerState {
+    server: Mutex<Option<LlamaServer>>,
+}
+
+impl LlamaServerState {
+    pub fn new() -> Self {
+        LlamaServerState { server: Mutex::new(None) }
+    }
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct LlamaServerInfo {
+    pub pid: u32,
+    pub port: u16,
+    pub model: String,
+    pub url: String,
+}
+
+fn base_url(port: u16) -> String {
+    format!("http://127.0.0.1:{}", port)
+}
+
+/// Client for talking to our own server; never goes through the download proxy
+fn local_client() -> Result<reqwest::Client, String> {
+    reqwest::Client::builder()
+        .no_proxy()
+        .build()
+        .map_err(|e| format!("Failed to create HTTP client: {}", e))
+}
+
+/// Base URL of the running server if it has `model` loaded and hasn't exited
+pub fn url_for(app: &tauri::AppHandle, model: &Path) -> Option<String> {
+    let state = app.state::<LlamaServerState>();
+    let mut server = state.server.lock().unwrap();
+    let running = server.as_mut()?;
+    if running.child.try_wait().map(|status| status.is_some()).unwrap_or(true) {
+        log::info!("llama-server exited on its own; summaries fall back to llama-cli");
+        app.state::<processes::ProcessRegistry>().unregister(running.child.id());
+        *server = None;
+        return None;
+    }
+    (running.model == model).then(|| base_url(running.port))
+}
+
+fn server_log_tail() -> String {
+    logging::get_log_dir()
+        .ok()
+        .and_then(|dir| fs::read(dir.join(SERVER_LOG_FILE)).ok())
+        .map(|log| logging::stderr_tail(&log))
+        .unwrap_or_default()
+}
+
+/// Start llama-server with a model loaded, returning once /health reports it ready
+#[tauri::command]
+pub async fn start_llama_server(
+    app: tauri::AppHandle,
+    model_path: Option<String>,
+    ctx_size: Option<u32>,
+    port: Option<u16>,
+    force: Option<bool>,
+) -> Result<LlamaServerInfo, AppError> {
+    let binary = llama::resolve_server_binary()?;
+    let settings = settings::current(&app);
+    let (model, _) = models::resolve_llama_model(model_path.as_deref(), settings.llama_model_path.as_deref())?;
+    models::check_memory(&model, models::ModelKind::Llama, force.unwrap_or(false))?;
+    let ctx_size = ctx_size.unwrap_or(settings.llama_ctx_size);
+    let port = port.unwrap_or(DEFAULT_PORT);
+
+    let log_file = logging::get_log_dir()
+        .and_then(|dir| fs::File::create(dir.join(SERVER_LOG_FILE)).map_err(|e| e.to_string()))
+        .map(Stdio::from)
+        .unwrap_or_else(|_| Stdio::null());
+    let mut cmd = Command::new(&binary);
+    cmd.arg("-m").arg(&model)
+        .arg("-c").arg(ctx_size.to_string())
+        .arg("--host").arg("127.0.0.1")
+        .arg("--port").arg(port.to_string())
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(log_file);
+
+    let pid = {
+        let state = app.state::<LlamaServerState>();
+        let mut server = state.server.lock().unwrap();
+        if server.is_some() {
+            return Err(AppError::RecorderBusy("llama-server is already running; stop it first".to_string()));
+        }
+        log::info!("Starting llama-server on port {} with {} (ctx {})", port, model.display(), ctx_size);
+        let child = processes::spawn(&app, &mut cmd).map_err(|e| AppError::io("Failed to start llama-server", e))?;
+        let pid = child.id();
+        *server = Some(LlamaServer { child, port, model: model.clone() });
+        pid
+    };
+
+    let client = local_client()?;
+    let health_url = format!("{}/health", base_url(port));
+    let started = Instant::now();
+    loop {
+        // 503 while the model is still loading, 200 once it can take requests
+        if let Ok(response) = client.get(&health_url).send().await {
+            if response.status().is_success() {
+                break;
+            }
+        }
+        let exited = url_for(&app, &model).is_none();
+        if exited || started.elapsed() > STARTUP_TIMEOUT {
+            let _ = stop_llama_server(app.clone()).await;
+            let message = if exited {
+                format!("llama-server exited during startup: {}", server_log_tail())
+            } else {
+                format!("llama-server didn't become ready within {}s", STARTUP_TIMEOUT.as_secs())
+            };
+            log::error!("{}", message);
+            return Err(AppError::ProcessFailed { cmd: binary.display().to_string(), stderr: server_log_tail(), message });
+        }
+        tokio::time::sleep(HEALTH_POLL_INTERVAL).await;
+    }
+
+    log::info!("llama-server ready after {:.1}s", started.elapsed().as_secs_f32());
+    Ok(LlamaServerInfo { pid, port, model: model.to_string_lossy().to_string(), url: base_url(port) })
+}
+
+/// Stop the server started by start_llama_server; summaries go back to spawning llama-cli
+#[tauri::command]
+pub async fn stop_llama_server(app: tauri::AppHandle) -> Result<(), String> {
+    let Some(mut server) = app.state::<LlamaServerState>().server.lock().unwrap().take() else {
+        return Err("llama-server isn't running".to_string());
+    };
+    jobs::terminate_pid(server.child.id());
+    let wait_app = app.clone();
+    tauri::async_runtime::spawn_blocking(move || processes::wait(&wait_app, &mut server.child))
+        .await
+        .map_err(|e| format!("Failed to stop llama-server: {}", e))?
+        .map_err(|e| format!("Failed to stop llama-server: {}", e))?;
+    log::info!("llama-server stopped");
+    Ok(())
+}
+
+/// Run `prompt` through the server's OpenAI-compatible chat endpoint
+pub async fn chat(
+    app: &tauri::AppHandle,
+    base_url: &str,
+    run: &LlamaRun,
+    prompt: &str,
+    stream: bool,
+    cancelled: &AtomicBool,
+) -> Result<String, AppError> {
+    log::info!("Sending {} chars to llama-server ({} tokens max)", prompt.len(), run.max_tokens);
+    let endpoint = chat_api::Endpoint { name: "llama-server", base_url: format!("{}/v1", base_url), api_key: None, model: None };
+    chat_api::stream_chat(app, &local_client()?, &endpoint, run, prompt, stream, cancelled).await
+}
@@ -0,0 +1,162 @@
+use std::fs;
+use std::io::Write;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+const LOG_FILE: &str = "app.log";
+
+/// app.log is moved to app.log.1 (replacing the previous one) once it grows past this
+const MAX_LOG_BYTES: u64 = 5 * 1024 * 1024;
+
+/// Upper bound on get_recent_logs so a diagnostics panel can't pull megabytes over IPC
+const MAX_RECENT_LINES: usize = 5000;
+
+/// Directory holding app.log and its rotated predecessor
+pub fn get_log_dir() -> Result<PathBuf, String> {
+    let log_dir = dirs::data_local_dir()
+        .ok_or("Could not find local data directory")?
+        .join("last-gen-notes")
+        .join("logs");
+
+    fs::create_dir_all(&log_dir)
+        .map_err(|e| format!("Failed to create log directory: {}", e))?;
+
+    Ok(log_dir)
+}
+
+struct LogFile {
+    path: PathBuf,
+    file: Option<fs::File>,
+    size: u64,
+}
+
+impl LogFile {
+    fn open(path: PathBuf) -> LogFile {
+        let file = fs::OpenOptions::new().create(true).append(true).open(&path).ok();
+        let size = fs::metadata(&path).map(|m| m.len()).unwrap_or(0);
+        LogFile { path, file, size }
+    }
+
+    fn write_line(&mut self, line: &str) {
+        if self.size + line.len() as u64 > MAX_LOG_BYTES {
+            self.file = None;
+            let _ = fs::rename(&self.path, self.path.with_extension("log.1"));
+            *self = LogFile::open(self.path.clone());
+        }
+        if let Some(file) = self.file.as_mut() {
+            if file.write_all(line.as_bytes()).is_ok() {
+                self.size += line.len() as u64;
+            }
+        }
+    }
+}
+
+/// `log` backend appending to app.log, and echoing to stderr in debug builds
+struct FileLogger {
+    file: Mutex<LogFile>,
+}
+
+impl log::Log for FileLogger {
+    fn enabled(&self, metadata: &log::Metadata) -> bool {
+        metadata.level() <= log::Level::Info
+    }
+
+    fn log(&self, record: &log::Record) {
+        if !self.enabled(record.metadata()) {
+            return;
+        }
+        let line = format!(
+            "{} {:<5} [{}] {}\n",
+            timestamp(SystemTime::now()),
+            record.level(),
+            record.target().trim_start_matches("last_gen_notes_lib::"),
+            record.args()
+        );
+        if cfg!(debug_assertions) {
+            eprint!("{}", line);
+        }
+        self.file.lock().unwrap().write_line(&line);
+    }
+
+    fn flush(&self) {
+        if let Some(file) = self.file.lock().unwrap().file.as_mut() {
+            let _ = file.flush();
+        }
+    }
+}
+
+/// Civil (year, month, day) from days since 1970-01-01 (Howard Hinnant's algorithm)
+pub fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let year = yoe + era * 400 + if month <= 2 { 1 } else { 0 };
+    (year, month, day)
+}
+
+/// UTC time as "2024-05-01T12:34:56.789Z"
+fn timestamp(time: SystemTime) -> String {
+    let since_epoch = time.duration_since(UNIX_EPOCH).unwrap_or_default();
+    let secs = since_epoch.as_secs();
+    let (days, day_secs) = ((secs / 86_400) as i64, secs % 86_400);
+    let (year, month, day) = civil_from_days(days);
+
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}.{:03}Z",
+        year,
+        month,
+        day,
+        day_secs / 3600,
+        day_secs % 3600 / 60,
+        day_secs % 60,
+        since_epoch.subsec_millis()
+    )
+}
+
+/// Install the file logger; logging stays off if the log dir can't be created
+pub fn init() {
+    let Ok(dir) = get_log_dir() else {
+        return;
+    };
+    let logger = FileLogger { file: Mutex::new(LogFile::open(dir.join(LOG_FILE))) };
+    if log::set_boxed_logger(Box::new(logger)).is_ok() {
+        log::set_max_level(log::LevelFilter::Info);
+    }
+}
+
+/// Whatever stderr a child process left, trimmed to its last lines for a log entry
+pub fn stderr_tail(stderr: &[u8]) -> String {
+    let text = String::from_utf8_lossy(stderr);
+    let lines: Vec<&str> = text.lines().filter(|l| !l.trim().is_empty()).collect();
+    lines[lines.len().saturating_sub(20)..].join("\n")
+}
+
+/// The last `lines` lines of the log, reaching into the rotated file when app.log is short
+#[tauri::command]
+pub async fn get_recent_logs(lines: usize) -> Result<Vec<String>, String> {
+    let lines = lines.min(MAX_RECENT_LINES);
+    let dir = get_log_dir()?;
+    let read = |name: &str| fs::read_to_string(dir.join(name)).unwrap_or_default();
+
+    let current = read(LOG_FILE);
+    let mut recent: Vec<String> = current.lines().map(str::to_string).collect();
+    if recent.len() < lines {
+        let rotated = read(&format!("{}.1", LOG_FILE));
+        let mut older: Vec<String> = rotated.lines().map(str::to_string).collect();
+        older.append(&mut recent);
+        recent = older;
+    }
+    let start = recent.len().saturating_sub(lines);
+    Ok(recent.split_off(start))
+}
+
+#[tauri::command]
+pub async fn get_log_path() -> Result<String, String> {
+    Ok(get_log_dir()?.join(LOG_FILE).to_string_lossy().to_string())
+}
@@ -0,0 +1,880 @@
+use serde::{Deserialize, Serialize};
+use sha1::{Digest, Sha1};
+use sha2::Sha256;
+use std::collections::HashMap;
+use std::fs;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use sysinfo::{MemoryRefreshKind, RefreshKind, System};
+use tauri::Manager;
+use tokio::io::AsyncWriteExt;
+
+use crate::error::AppError;
+use crate::{downloads, emit_progress, get_config_dir, paths};
+
+pub const HF_BASE_URL: &str = "https://huggingface.co/ggerganov/whisper.cpp/resolve/main";
+
+/// The tinydiarize models aren't in the main repo, so they're never fetched from a models mirror
+pub const TDRZ_BASE_URL: &str = "https://huggingface.co/akashmjn/tinydiarize-whisper.cpp/resolve/main";
+
+/// Registry name of the model diarized transcriptions fall back to
+pub const DEFAULT_TDRZ_MODEL: &str = "small.en-tdrz";
+
+/// Last-used timestamps of models, keyed by file name
+const USAGE_FILE: &str = "model-usage.json";
+
+/// How far an installed model may be from its registry size before it counts as truncated
+const SIZE_TOLERANCE: f64 = 0.05;
+
+/// Memory a loaded model takes beyond its file: (factor on the file size, fixed MB). Whisper adds compute
+/// buffers for the encoder and decoder; llama adds its KV cache and scratch space.
+const WHISPER_MEMORY_OVERHEAD: (f64, u64) = (1.2, 200);
+const LLAMA_MEMORY_OVERHEAD: (f64, u64) = (1.2, 512);
+
+/// A ggml whisper model we know how to download and verify
+pub struct WhisperModel {
+    pub name: &'static str,
+    pub file_name: &'static str,
+    pub size_mb: u64,
+    /// SHA-256 of the upstream file; checked in preference to the SHA-1 once pinned
+    pub sha256: Option<&'static str>,
+    /// SHA-1 as published in the whisper.cpp models table
+    pub sha1: Option<&'static str>,
+    /// Fine-tuned with tinydiarize, so whisper-cli can mark speaker turns with it
+    pub tdrz: bool,
+}
+
+/// No SHA-256 is pinned yet; each belongs here once copied from the `oid sha256:` line of the file's
+/// LFS pointer on huggingface. Until then downloads match the SHA-1 and also get a header check.
+pub const WHISPER_MODELS: &[WhisperModel] = &[
+    WhisperModel { name: "tiny.en", file_name: "ggml-tiny.en.bin", size_mb: 75, sha256: None, sha1: Some("c78c86eb1a8faa21b369bcd33207cc90d64ae9df"), tdrz: false },
+    WhisperModel { name: "tiny", file_name: "ggml-tiny.bin", size_mb: 75, sha256: None, sha1: Some("bd577a113a864445d4c299885e0cb97d4ba92b5f"), tdrz: false },
+    WhisperModel { name: "base.en", file_name: "ggml-base.en.bin", size_mb: 142, sha256: None, sha1: Some("137c40403d78fd54d454da0f9bd998f78703390c"), tdrz: false },
+    WhisperModel { name: "base", file_name: "ggml-base.bin", size_mb: 142, sha256: None, sha1: Some("465707469ff3a37a2b9b8d8f89f2f99de7299dac"), tdrz: false },
+    WhisperModel { name: "small.en", file_name: "ggml-small.en.bin", size_mb: 466, sha256: None, sha1: Some("db8a495a91d927739e50b3fc1cc4c6b8f6c2d022"), tdrz: false },
+    WhisperModel { name: "small", file_name: "ggml-small.bin", size_mb: 466, sha256: None, sha1: Some("55356645c2b361a969dfd0ef2c5a50d530afd8d5"), tdrz: false },
+    WhisperModel { name: "medium", file_name: "ggml-medium.bin", size_mb: 1463, sha256: None, sha1: Some("fd9727b6e1217c2f614f9b698455c4ffd82463b4"), tdrz: false },
+    WhisperModel { name: "large-v3", file_name: "ggml-large-v3.bin", size_mb: 2952, sha256: None, sha1: Some("ad82bf6a9043ceed055076d0fd39f5f186ff8062"), tdrz: false },
+    WhisperModel { name: "small.en-tdrz", file_name: "ggml-small.en-tdrz.bin", size_mb: 465, sha256: None, sha1: None, tdrz: true },
+];
+
+/// An RNNoise model for ffmpeg's arnndn filter, installed into the models dir by download_model
+pub struct RnnoiseModel {
+    pub name: &'static str,
+    pub file_name: &'static str,
+    pub url: &'static str,
+    pub size_mb: u64,
+    /// Not pinned yet, so downloads are checked by their header alone
+    pub sha256: Option<&'static str>,
+}
+
+/// The "somnolent hogwash" model from richardpl/rnnoise-models, trained on speech with general noise
+pub const RNNOISE_MODELS: &[RnnoiseModel] = &[RnnoiseModel {
+    name: "rnnoise-sh",
+    file_name: "sh.rnnn",
+    url: "https://raw.githubusercontent.com/richardpl/rnnoise-models/master/somnolent-hogwash-2018-09-01/sh.rnnn",
+    size_mb: 1,
+    sha256: None,
+}];
+
+/// First line of every .rnnn file, as ffmpeg's arnndn reads it
+const RNNOISE_HEADER: &[u8] = b"rnnoise-nu model file version";
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug)]
+#[serde(rename_all = "lowercase")]
+pub enum ModelKind {
+    Whisper,
+    Llama,
+}
+
+impl ModelKind {
+    /// Kind of a model file by extension; None for anything that isn't a model
+    fn of_file(path: &Path) -> Option<ModelKind> {
+        let name = path.file_name()?.to_string_lossy().to_lowercase();
+        if name.ends_with(".gguf") {
+            Some(ModelKind::Llama)
+        } else if name.ends_with(".bin") {
+            Some(ModelKind::Whisper)
+        } else {
+            None
+        }
+    }
+}
+
+/// A model file in the models dir, as reported by list_models
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct InstalledModel {
+    pub name: String,
+    pub path: String,
+    pub size_bytes: u64,
+    pub kind: ModelKind,
+    /// Unix seconds when a transcription or summary last used it
+    pub last_used: Option<u64>,
+}
+
+// Models held by running transcriptions and summaries, with how many jobs hold each
+pub struct ModelUseState {
+    in_use: Mutex<HashMap<PathBuf, usize>>,
+}
+
+impl ModelUseState {
+    pub fn new() -> Self {
+        ModelUseState { in_use: Mutex::new(HashMap::new()) }
+    }
+
+    fn is_in_use(&self, path: &Path) -> bool {
+        self.in_use.lock().unwrap().get(path).map(|n| *n > 0).unwrap_or(false)
+    }
+}
+
+/// Marks a model in use for as long as it's held, so delete_model refuses to remove it
+pub struct ModelLease {
+    path: PathBuf,
+    app: tauri::AppHandle,
+}
+
+impl ModelLease {
+    /// Take a lease on a resolved model and record it as just used
+    pub fn acquire(app: &tauri::AppHandle, path: &Path) -> ModelLease {
+        *app.state::<ModelUseState>().in_use.lock().unwrap().entry(path.to_path_buf()).or_insert(0) += 1;
+        mark_used(path);
+        ModelLease { path: path.to_path_buf(), app: app.clone() }
+    }
+}
+
+impl Drop for ModelLease {
+    fn drop(&mut self) {
+        let state = self.app.state::<ModelUseState>();
+        let mut in_use = state.in_use.lock().unwrap();
+        if let Some(count) = in_use.get_mut(&self.path) {
+            *count -= 1;
+            if *count == 0 {
+                in_use.remove(&self.path);
+            }
+        }
+    }
+}
+
+fn load_usage() -> HashMap<String, u64> {
+    get_config_dir()
+        .ok()
+        .and_then(|dir| fs::read_to_string(dir.join(USAGE_FILE)).ok())
+        .and_then(|raw| serde_json::from_str(&raw).ok())
+        .unwrap_or_default()
+}
+
+fn save_usage(usage: &HashMap<String, u64>) {
+    if let (Ok(dir), Ok(json)) = (get_config_dir(), serde_json::to_string_pretty(usage)) {
+        let _ = fs::write(dir.join(USAGE_FILE), json);
+    }
+}
+
+/// Record that a model was just used; only models in the models dir are tracked
+fn mark_used(path: &Path) {
+    let Ok(models_dir) = get_models_dir() else {
+        return;
+    };
+    if path.parent() != Some(models_dir.as_path()) {
+        return;
+    }
+    let Some(name) = path.file_name().map(|n| n.to_string_lossy().to_string()) else {
+        return;
+    };
+    let now = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    let mut usage = load_usage();
+    usage.insert(name, now);
+    save_usage(&usage);
+}
+
+/// Result of verify_model; `valid` is false if any check that ran failed
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct VerificationResult {
+    pub name: String,
+    pub path: String,
+    /// "ggml" or "gguf" when the header was recognised
+    pub format: Option<String>,
+    pub magic_ok: bool,
+    pub size_bytes: u64,
+    pub expected_size_bytes: Option<u64>,
+    pub size_ok: Option<bool>,
+    /// Against the registry's SHA-256, else its SHA-1; only computed on request, and None for
+    /// models without a published checksum
+    pub checksum_ok: Option<bool>,
+    pub valid: bool,
+    pub problems: Vec<String>,
+}
+
+/// File format named by a model's magic bytes: ggml (and its ggmf/ggjt revisions) or gguf
+pub fn model_format(path: &Path) -> Result<Option<&'static str>, String> {
+    let mut magic = [0u8; 4];
+    let read = fs::File::open(path)
+        .and_then(|mut f| f.read(&mut magic))
+        .map_err(|e| format!("Failed to open model: {}", e))?;
+    if read < 4 {
+        return Ok(None);
+    }
+    Ok(match &magic {
+        b"GGUF" => Some("gguf"),
+        // Written as a little-endian u32, so "ggml" lands on disk reversed
+        b"lmgg" | b"fmgg" | b"tjgg" => Some("ggml"),
+        _ => None,
+    })
+}
+
+/// Cheap pre-flight check that `path` starts with a ggml/gguf header
+pub fn check_model_header(path: &Path) -> Result<(), String> {
+    match model_format(path)? {
+        Some(_) => Ok(()),
+        None => Err(format!("Model file appears corrupted, re-download it ({})", path.display())),
+    }
+}
+
+/// Cheap check that `path` is an RNNoise model rather than, say, an HTML error page
+pub fn check_rnnoise_header(path: &Path) -> Result<(), String> {
+    let mut header = [0u8; RNNOISE_HEADER.len()];
+    let read = fs::File::open(path)
+        .and_then(|mut f| f.read(&mut header))
+        .map_err(|e| format!("Failed to open model: {}", e))?;
+    if header[..read] != *RNNOISE_HEADER {
+        return Err(format!("RNNoise model appears corrupted, re-download it ({})", path.display()));
+    }
+    Ok(())
+}
+
+/// Estimated MB of RAM needed to run a model of `size_bytes`
+fn required_memory_mb(size_bytes: u64, kind: ModelKind) -> u64 {
+    let (factor, fixed_mb) = match kind {
+        ModelKind::Whisper => WHISPER_MEMORY_OVERHEAD,
+        ModelKind::Llama => LLAMA_MEMORY_OVERHEAD,
+    };
+    (size_bytes as f64 / (1024.0 * 1024.0) * factor) as u64 + fixed_mb
+}
+
+/// RAM the OS could hand out right now, counting reclaimable cache
+fn available_memory_mb() -> u64 {
+    let sys = System::new_with_specifics(RefreshKind::new().with_memory(MemoryRefreshKind::new().with_ram()));
+    sys.available_memory() / (1024 * 1024)
+}
+
+/// Refuse to load a model that won't fit in available memory, unless `force` is set
+pub fn check_memory(path: &Path, kind: ModelKind, force: bool) -> Result<(), AppError> {
+    if force {
+        return Ok(());
+    }
+    let size = fs::metadata(path).map_err(|e| AppError::io("Failed to stat model", e))?.len();
+    let (required_mb, available_mb) = (required_memory_mb(size, kind), available_memory_mb());
+    // sysinfo reports 0 where it can't read memory stats; don't block on that
+    if available_mb > 0 && required_mb > available_mb {
+        log::warn!("Refusing to load {}: needs ~{} MB, {} MB available", path.display(), required_mb, available_mb);
+        return Err(AppError::InsufficientMemory { required_mb, available_mb });
+    }
+    Ok(())
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct ModelFit {
+    pub model: String,
+    pub kind: ModelKind,
+    pub required_mb: u64,
+    pub available_mb: u64,
+    pub fits: bool,
+}
+
+/// Whether a model fits in available memory: a whisper model name (installed or not) or a llama .gguf name or path
+#[tauri::command]
+pub async fn can_run_model(model_name: String) -> Result<ModelFit, AppError> {
+    let (kind, size) = match find_model(&model_name) {
+        Some(known) => {
+            let size = fs::metadata(get_models_dir()?.join(known.file_name))
+                .map(|m| m.len())
+                .unwrap_or(known.size_mb * 1024 * 1024);
+            (ModelKind::Whisper, size)
+        }
+        None => {
+            let path = explicit_llama_model(&model_name)?;
+            let kind = ModelKind::of_file(&path).unwrap_or(ModelKind::Llama);
+            (kind, fs::metadata(&path).map_err(|e| AppError::io("Failed to stat model", e))?.len())
+        }
+    };
+    let (required_mb, available_mb) = (required_memory_mb(size, kind), available_memory_mb());
+    Ok(ModelFit {
+        model: model_name,
+        kind,
+        required_mb,
+        available_mb,
+        fits: available_mb == 0 || required_mb <= available_mb,
+    })
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct ModelStatus {
+    pub name: String,
+    pub installed: bool,
+    pub path: Option<String>,
+    pub size_bytes: Option<u64>,
+    pub expected_size_mb: u64,
+}
+
+/// Look up a model by its short name ("base.en") or file name ("ggml-base.en.bin")
+pub fn find_model(name: &str) -> Option<&'static WhisperModel> {
+    WHISPER_MODELS.iter().find(|m| m.name == name || m.file_name == name)
+}
+
+pub fn find_rnnoise_model(name: &str) -> Option<&'static RnnoiseModel> {
+    RNNOISE_MODELS.iter().find(|m| m.name == name || m.file_name == name)
+}
+
+/// What download_model fetches, from either catalog
+struct DownloadSource {
+    name: &'static str,
+    file_name: &'static str,
+    url: String,
+    sha256: Option<&'static str>,
+    sha1: Option<&'static str>,
+    /// Checks the file when there's no checksum to compare
+    check_header: fn(&Path) -> Result<(), String>,
+}
+
+impl DownloadSource {
+    fn find(name: &str, settings: &downloads::DownloadSettings) -> Option<DownloadSource> {
+        if let Some(model) = find_model(name) {
+            let base = if model.tdrz { TDRZ_BASE_URL } else { settings.models_base() };
+            return Some(DownloadSource {
+                name: model.name,
+                file_name: model.file_name,
+                url: format!("{}/{}", base, model.file_name),
+                sha256: model.sha256,
+                sha1: model.sha1,
+                check_header: check_model_header,
+            });
+        }
+        let model = find_rnnoise_model(name)?;
+        Some(DownloadSource {
+            name: model.name,
+            file_name: model.file_name,
+            url: model.url.to_string(),
+            sha256: model.sha256,
+            sha1: None,
+            check_header: check_rnnoise_header,
+        })
+    }
+}
+
+/// Get the app data directory for storing models (sibling of the binaries dir)
+#[tauri::command]
+pub fn get_models_dir() -> Result<PathBuf, String> {
+    let models_dir = dirs::data_local_dir()
+        .ok_or("Could not find local data directory")?
+        .join("last-gen-notes")
+        .join("models");
+
+    fs::create_dir_all(&models_dir)
+        .map_err(|e| format!("Failed to create models directory: {}", e))?;
+
+    Ok(models_dir)
+}
+
+/// Where resolve_llama_model found the model
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum LlamaModelSource {
+    Parameter,
+    Settings,
+    ModelsDir,
+}
+
+/// An explicit model path; a bare file name refers to a model in the models dir
+fn explicit_llama_model(requested: &str) -> Result<PathBuf, AppError> {
+    let path = PathBuf::from(requested);
+    let path = if path.components().count() == 1 { get_models_dir()?.join(path) } else { path };
+    if !path.is_file() {
+        return Err(AppError::ModelNotFound {
+            model: requested.to_string(),
+            message: format!("Model '{}' not found", path.display()),
+        });
+    }
+    Ok(path)
+}
+
+/// Resolve the llama model to summarize with: the `model_path` parameter, then the saved
+/// setting, then the most recently used .gguf in the models dir
+pub fn resolve_llama_model(
+    model_path: Option<&str>,
+    saved_path: Option<&str>,
+) -> Result<(PathBuf, LlamaModelSource), AppError> {
+    let non_empty = |p: Option<&str>| p.map(str::trim).filter(|p| !p.is_empty()).map(str::to_string);
+    if let Some(requested) = non_empty(model_path) {
+        return Ok((explicit_llama_model(&requested)?, LlamaModelSource::Parameter));
+    }
+    if let Some(saved) = non_empty(saved_path) {
+        return Ok((explicit_llama_model(&saved)?, LlamaModelSource::Settings));
+    }
+
+    let models_dir = get_models_dir()?;
+    let usage = load_usage();
+    let newest = fs::read_dir(&models_dir)
+        .map_err(|e| format!("Failed to read models directory: {}", e))?
+        .flatten()
+        .map(|e| e.path())
+        .filter(|p| p.is_file() && ModelKind::of_file(p) == Some(ModelKind::Llama))
+        // Never-used models rank by name so the pick is stable
+        .max_by_key(|p| {
+            let name = p.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+            (usage.get(&name).copied().unwrap_or(0), std::cmp::Reverse(name))
+        });
+    match newest {
+        Some(path) => Ok((path, LlamaModelSource::ModelsDir)),
+        None => Err(AppError::SetupRequired {
+            step: "llama-model".to_string(),
+            message: format!(
+                "No llama model (.gguf) found; put one in {} or set llama_model_path in Settings",
+                models_dir.display()
+            ),
+        }),
+    }
+}
+
+/// Multilingual models in preference order when a non-English language is requested
+const MULTILINGUAL_PREFERENCE: &[&str] = &["base", "small", "tiny", "medium", "large-v3"];
+
+/// Picked when no model is requested: the small English models for speed, then any multilingual one
+const DEFAULT_PREFERENCE: &[&str] = &["tiny.en", "base.en", "small.en", "base", "small", "tiny", "medium", "large-v3"];
+
+fn is_english_only_file(path: &Path) -> bool {
+    path.file_name()
+        .map(|n| {
+            let name = n.to_string_lossy();
+            name.ends_with(".en.bin") || name.ends_with(".en-tdrz.bin")
+        })
+        .unwrap_or(false)
+}
+
+/// Resolve a whisper model by name against the models dir, or the default search order when None.
+/// `multilingual` rejects English-only (.en) models, which produce garbage for other languages
+/// and silently ignore --translate.
+pub fn resolve_whisper_model(model: Option<&str>, multilingual: bool) -> Result<PathBuf, AppError> {
+    let models_dir = get_models_dir()?;
+
+    let requested = match model.map(str::trim).filter(|m| !m.is_empty()) {
+        Some(name) => name,
+        None if multilingual => {
+            return MULTILINGUAL_PREFERENCE
+                .iter()
+                .filter_map(|name| find_model(name))
+                .map(|m| models_dir.join(m.file_name))
+                .find(|p| p.exists())
+                .ok_or_else(|| AppError::ModelNotFound {
+                    model: "base".to_string(),
+                    message: "No multilingual whisper model installed; only English (.en) models are available. Download 'base' (ggml-base.bin) to transcribe other languages or translate.".to_string(),
+                });
+        }
+        None => {
+            return DEFAULT_PREFERENCE
+                .iter()
+                .filter_map(|name| find_model(name))
+                .map(|m| models_dir.join(m.file_name))
+                .find(|p| p.exists())
+                .ok_or_else(|| AppError::ModelNotFound {
+                    model: "tiny.en".to_string(),
+                    message: format!("No whisper model installed in {}. Download 'tiny.en' to get started.", models_dir.display()),
+                });
+        }
+    };
+
+    let file_name = match find_model(requested) {
+        Some(known) => known.file_name.to_string(),
+        None if requested.ends_with(".bin") => requested.to_string(),
+        None => format!("ggml-{}.bin", requested),
+    };
+
+    // A name that isn't in the registry comes straight from the frontend
+    let path = models_dir.join(paths::file_name(&file_name, &["bin"])?);
+    if !path.exists() {
+        return Err(AppError::ModelNotFound {
+            model: requested.to_string(),
+            message: format!("Model '{}' is not installed (expected {}). Download it first.", requested, path.display()),
+        });
+    }
+    if multilingual && is_english_only_file(&path) {
+        let suggestion = requested.trim_start_matches("ggml-").trim_end_matches(".bin").trim_end_matches(".en");
+        return Err(AppError::ModelNotFound {
+            model: suggestion.to_string(),
+            message: format!(
+                "Model '{}' is English-only and can't transcribe other languages or translate. Download '{}' (ggml-{}.bin) instead.",
+                requested, suggestion, suggestion
+            ),
+        });
+    }
+    Ok(path)
+}
+
+/// Resolve a tinydiarize model for a diarized transcription: the requested model when it is one,
+/// otherwise the first installed tdrz model. tdrz models are English-only.
+pub fn resolve_diarize_model(model: Option<&str>, multilingual: bool) -> Result<PathBuf, AppError> {
+    if multilingual {
+        return Err(AppError::Other(
+            "Speaker diarization needs an English-only tinydiarize model, so it can't be combined with other languages or translation".to_string(),
+        ));
+    }
+    let requested = model.map(str::trim).filter(|m| !m.is_empty());
+    if let Some(name) = requested {
+        if find_model(name).is_some_and(|m| m.tdrz) || name.contains("tdrz") {
+            return resolve_whisper_model(Some(name), false);
+        }
+    }
+
+    let models_dir = get_models_dir()?;
+    WHISPER_MODELS
+        .iter()
+        .filter(|m| m.tdrz)
+        .map(|m| models_dir.join(m.file_name))
+        .find(|p| p.exists())
+        .ok_or_else(|| AppError::ModelNotFound {
+            model: DEFAULT_TDRZ_MODEL.to_string(),
+            message: format!(
+                "Speaker diarization needs a tinydiarize model{}. Download '{}' with download_model first.",
+                requested.map(|m| format!(" ('{}' isn't one)", m)).unwrap_or_default(),
+                DEFAULT_TDRZ_MODEL
+            ),
+        })
+}
+
+/// SHA-256 and SHA-1 of a model file, fed as it's downloaded so verifying needs no second pass
+#[derive(Default)]
+struct ModelHasher {
+    sha256: Sha256,
+    sha1: Sha1,
+}
+
+impl ModelHasher {
+    fn update(&mut self, bytes: &[u8]) {
+        self.sha256.update(bytes);
+        self.sha1.update(bytes);
+    }
+
+    /// Hash what's already on disk, e.g. the prefix of a resumed download. Blocking.
+    fn of_file(path: &Path) -> Result<Self, String> {
+        let mut file = fs::File::open(path)
+            .map_err(|e| format!("Failed to open model for hashing: {}", e))?;
+        let mut hasher = ModelHasher::default();
+        let mut buf = vec![0u8; 1024 * 1024];
+        loop {
+            let n = file.read(&mut buf)
+                .map_err(|e| format!("Failed to read model for hashing: {}", e))?;
+            if n == 0 {
+                break;
+            }
+            hasher.update(&buf[..n]);
+        }
+        Ok(hasher)
+    }
+
+    /// Compare against the registry's SHA-256 if it has one, else its SHA-1. None when the model
+    /// has no published checksum at all.
+    fn verify(self, sha256: Option<&str>, sha1: Option<&str>) -> Option<Result<(), AppError>> {
+        let (expected, actual) = match (sha256, sha1) {
+            (Some(expected), _) => (expected, hex::encode(self.sha256.finalize())),
+            (None, Some(expected)) => (expected, hex::encode(self.sha1.finalize())),
+            (None, None) => return None,
+        };
+        Some(if actual == expected {
+            Ok(())
+        } else {
+            Err(AppError::ChecksumMismatch { expected: expected.to_string(), actual })
+        })
+    }
+}
+
+/// Download a ggml whisper model from huggingface, or an RNNoise model for noise reduction,
+/// resuming a partial download if present
+#[tauri::command]
+pub async fn download_model(window: tauri::Window, model_name: String) -> Result<String, AppError> {
+    // Mirrored files are still checked against the registry's checksum
+    let settings = downloads::DownloadSettings::load();
+    let model = DownloadSource::find(&model_name, &settings)
+        .ok_or_else(|| format!("Unknown model '{}'", model_name))?;
+    let download = downloads::ActiveDownload::start(&window, model.name);
+
+    let models_dir = get_models_dir()?;
+    let final_path = models_dir.join(model.file_name);
+    if final_path.exists() {
+        emit_progress(&window, &download.id, 0, None, "Already installed");
+        return Ok(final_path.to_string_lossy().to_string());
+    }
+
+    let part_path = models_dir.join(format!("{}.part", model.file_name));
+    let existing = fs::metadata(&part_path).map(|m| m.len()).unwrap_or(0);
+    let url = &model.url;
+    log::info!("Downloading model {} from {}", model.name, url);
+
+    emit_progress(&window, &download.id, existing, None, "Starting download...");
+
+    let client = settings.client()?;
+    let mut request = client.get(url);
+    if existing > 0 {
+        request = request.header(reqwest::header::RANGE, format!("bytes={}-", existing));
+    }
+    let response = request
+        .send()
+        .await
+        .map_err(|e| format!("Download request failed: {}", e))?;
+
+    let status = response.status();
+    let (mut downloaded, total_size, append) = if status == reqwest::StatusCode::PARTIAL_CONTENT {
+        (existing, response.content_length().map(|len| len + existing), true)
+    } else if status == reqwest::StatusCode::RANGE_NOT_SATISFIABLE && existing > 0 {
+        // The partial file already holds everything the server has
+        (existing, Some(existing), true)
+    } else if status.is_success() {
+        (0, response.content_length(), false)
+    } else {
+        return Err(format!("Download failed with HTTP {}", status).into());
+    };
+
+    // A resumed download's prefix is hashed first, off the async runtime
+    let mut hasher = if append {
+        let prefix = part_path.clone();
+        tauri::async_runtime::spawn_blocking(move || ModelHasher::of_file(&prefix))
+            .await
+            .map_err(|e| format!("Hashing task failed: {}", e))??
+    } else {
+        ModelHasher::default()
+    };
+
+    if status != reqwest::StatusCode::RANGE_NOT_SATISFIABLE {
+        let mut file = if append {
+            tokio::fs::OpenOptions::new()
+                .append(true)
+                .open(&part_path)
+                .await
+                .map_err(|e| AppError::io("Failed to open partial file", e))?
+        } else {
+            tokio::fs::File::create(&part_path)
+                .await
+                .map_err(|e| AppError::io("Failed to create file", e))?
+        };
+
+        let mut stream = response.bytes_stream();
+        use futures_util::StreamExt;
+
+        while let Some(chunk) = stream.next().await {
+            if download.is_cancelled() {
+                drop(file);
+                return Err(download.cancelled(&window, &part_path, downloaded, total_size));
+            }
+            let chunk = chunk.map_err(|e| format!("Download stream error: {}", e))?;
+
+            file.write_all(&chunk)
+                .await
+                .map_err(|e| AppError::io("Failed to write chunk", e))?;
+            hasher.update(&chunk);
+
+            downloaded += chunk.len() as u64;
+
+            let percent = total_size.map(|t| (downloaded as f32 / t as f32) * 100.0).unwrap_or(0.0);
+            emit_progress(&window, &download.id, downloaded, total_size, &format!("Downloading... {:.1}%", percent));
+        }
+
+        file.flush().await.map_err(|e| AppError::io("Failed to flush file", e))?;
+    }
+
+    // The hash covers the whole file, including any resumed prefix
+    emit_progress(&window, &download.id, downloaded, total_size, "Verifying checksum...");
+    match hasher.verify(model.sha256, model.sha1) {
+        Some(Ok(())) if model.sha256.is_some() => {}
+        Some(Err(e)) => {
+            log::error!("Checksum mismatch for model {}: {}", model.name, e);
+            fs::remove_file(&part_path).ok();
+            return Err(e);
+        }
+        // SHA-1 alone is too weak to vouch for the file, so it has to look like a model as well
+        verified => {
+            match verified {
+                Some(_) => log::warn!("Model {} has only a SHA-1 pinned; checking its header too", model.name),
+                None => log::warn!("No published checksum for model {}; checking its header only", model.name),
+            }
+            if let Err(e) = (model.check_header)(&part_path) {
+                fs::remove_file(&part_path).ok();
+                return Err(e.into());
+            }
+        }
+    }
+
+    fs::rename(&part_path, &final_path)
+        .map_err(|e| AppError::io("Failed to finalize model file", e))?;
+
+    emit_progress(&window, &download.id, downloaded, total_size, "Complete!");
+    log::info!("Installed model {} at {}", model.name, final_path.display());
+
+    Ok(final_path.to_string_lossy().to_string())
+}
+
+/// Report which known whisper and RNNoise models are installed in the models dir
+#[tauri::command]
+pub async fn check_model_status() -> Result<Vec<ModelStatus>, String> {
+    let models_dir = get_models_dir()?;
+
+    let whisper = WHISPER_MODELS.iter().map(|m| (m.name, m.file_name, m.size_mb));
+    let rnnoise = RNNOISE_MODELS.iter().map(|m| (m.name, m.file_name, m.size_mb));
+    Ok(whisper
+        .chain(rnnoise)
+        .map(|(name, file_name, size_mb)| {
+            let path = models_dir.join(file_name);
+            let size = fs::metadata(&path).ok().map(|m| m.len());
+            ModelStatus {
+                name: name.to_string(),
+                installed: size.is_some(),
+                path: size.map(|_| path.to_string_lossy().to_string()),
+                size_bytes: size,
+                expected_size_mb: size_mb,
+            }
+        })
+        .collect())
+}
+
+/// List the whisper (.bin) and llama (.gguf) models in the models dir, largest first
+#[tauri::command]
+pub async fn list_models() -> Result<Vec<InstalledModel>, String> {
+    let models_dir = get_models_dir()?;
+    let usage = load_usage();
+
+    let mut models: Vec<InstalledModel> = fs::read_dir(&models_dir)
+        .map_err(|e| format!("Failed to read models directory: {}", e))?
+        .flatten()
+        .filter_map(|entry| {
+            let path = entry.path();
+            let kind = ModelKind::of_file(&path)?;
+            let metadata = entry.metadata().ok().filter(|m| m.is_file())?;
+            let name = entry.file_name().to_string_lossy().to_string();
+            Some(InstalledModel {
+                last_used: usage.get(&name).copied(),
+                name,
+                path: path.to_string_lossy().to_string(),
+                size_bytes: metadata.len(),
+                kind,
+            })
+        })
+        .collect();
+    models.sort_by_key(|m| std::cmp::Reverse(m.size_bytes));
+    Ok(models)
+}
+
+/// Delete a model from the models dir by file name, unless a running job is using it
+#[tauri::command]
+pub async fn delete_model(state: tauri::State<'_, ModelUseState>, name: String) -> Result<u64, String> {
+    let file_name = match find_model(&name) {
+        Some(known) => known.file_name.to_string(),
+        None => name.clone(),
+    };
+    if Path::new(&file_name).components().count() != 1 {
+        return Err(format!("'{}' is not a model file name", name));
+    }
+
+    let path = get_models_dir()?.join(&file_name);
+    if ModelKind::of_file(&path).is_none() || !path.is_file() {
+        return Err(format!("Model '{}' is not installed", name));
+    }
+    if state.is_in_use(&path) {
+        return Err(format!("Model '{}' is in use by a running transcription or summary", name));
+    }
+
+    let size = fs::metadata(&path).map(|m| m.len()).unwrap_or(0);
+    fs::remove_file(&path).map_err(|e| format!("Failed to delete model: {}", e))?;
+    let mut usage = load_usage();
+    if usage.remove(&file_name).is_some() {
+        save_usage(&usage);
+    }
+    Ok(size)
+}
+
+/// Check an installed model's header and size, and with `checksum` its hash against the registry
+#[tauri::command]
+pub async fn verify_model(name: String, checksum: Option<bool>) -> Result<VerificationResult, String> {
+    let known = find_model(&name);
+    let file_name = known.map(|m| m.file_name.to_string()).unwrap_or_else(|| name.clone());
+    if Path::new(&file_name).components().count() != 1 {
+        return Err(format!("'{}' is not a model file name", name));
+    }
+    let path = get_models_dir()?.join(&file_name);
+    let size_bytes = fs::metadata(&path)
+        .map_err(|_| format!("Model '{}' is not installed", name))?
+        .len();
+
+    tauri::async_runtime::spawn_blocking(move || {
+        let mut problems = Vec::new();
+        let format = model_format(&path)?;
+        if format.is_none() {
+            problems.push("Not a ggml/gguf file; it may be corrupted or an error page".to_string());
+        }
+
+        let expected_size_bytes = known.map(|m| m.size_mb * 1024 * 1024);
+        let size_ok = expected_size_bytes.map(|expected| {
+            (size_bytes as f64 - expected as f64).abs() <= expected as f64 * SIZE_TOLERANCE
+        });
+        if size_ok == Some(false) {
+            problems.push(format!(
+                "Size is {} MB but {} MB was expected; the download may be incomplete",
+                size_bytes / (1024 * 1024),
+                known.map(|m| m.size_mb).unwrap_or(0)
+            ));
+        }
+
+        let checksum_ok = match known {
+            Some(model) if checksum.unwrap_or(false) => {
+                let ok = ModelHasher::of_file(&path)?.verify(model.sha256, model.sha1).map(|result| result.is_ok());
+                if ok == Some(false) {
+                    problems.push("Checksum doesn't match the published one".to_string());
+                }
+                ok
+            }
+            _ => None,
+        };
+
+        Ok(VerificationResult {
+            name: file_name,
+            path: path.to_string_lossy().to_string(),
+            format: format.map(str::to_string),
+            magic_ok: format.is_some(),
+            size_bytes,
+            expected_size_bytes,
+            size_ok,
+            checksum_ok,
+            valid: problems.is_empty(),
+            problems,
+        })
+    })
+    .await
+    .map_err(|e| format!("Verify task failed: {}", e))?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn refused(model: &str) -> bool {
+        matches!(resolve_whisper_model(Some(model), false), Err(AppError::PathNotAllowed { .. }))
+    }
+
+    #[test]
+    fn model_names_with_parent_dirs_are_refused() {
+        assert!(refused("../ggml-base.bin"));
+        assert!(refused("../../models/ggml-base"));
+        assert!(refused("..\\ggml-base.bin"));
+    }
+
+    #[test]
+    fn absolute_model_paths_are_refused() {
+        assert!(refused("/tmp/ggml-base.bin"));
+        assert!(refused("/tmp/base"));
+    }
+
+    #[test]
+    fn plain_model_names_are_looked_up_in_the_models_dir() {
+        let result = resolve_whisper_model(Some("ggml-not-installed.bin"), false);
+        assert!(matches!(result, Err(AppError::ModelNotFound { .. })));
+    }
+}
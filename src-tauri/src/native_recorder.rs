@@ -0,0 +1,350 @@
+use cpal::traits::{DeviceTrait, HostTrait, StreamTrait};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc;
+use std::sync::{Arc, Mutex};
+use std::thread::JoinHandle;
+
+use crate::recorder::RecordingProfile;
+
+/// Most recent callback samples kept for the level meter (about a second at 48 kHz)
+const METER_BUFFER_SAMPLES: usize = 48_000;
+
+/// Where captured audio goes: one file, or numbered chunk-NNNN.wav segments rotated every few seconds
+pub enum NativeTarget {
+    File(PathBuf),
+    Segments {
+        dir: PathBuf,
+        segment_secs: u64,
+        start_index: usize,
+    },
+}
+
+/// An in-process cpal capture; the stream lives on its own thread because cpal streams aren't Send
+pub struct NativeRecording {
+    stop_tx: mpsc::Sender<()>,
+    paused: Arc<AtomicBool>,
+    meter: Arc<Mutex<Vec<f32>>>,
+    stream_thread: Option<JoinHandle<()>>,
+    writer_thread: Option<JoinHandle<Result<Vec<PathBuf>, String>>>,
+}
+
+fn find_input_device(name: Option<&str>) -> Result<cpal::Device, String> {
+    let host = cpal::default_host();
+    match name {
+        None => host
+            .default_input_device()
+            .ok_or_else(|| "No default audio input device found".to_string()),
+        Some(name) => host
+            .input_devices()
+            .map_err(|e| format!("Failed to list input devices: {}", e))?
+            .find(|d| d.name().map(|n| n == name).unwrap_or(false))
+            .ok_or_else(|| format!("Audio input device '{}' not found", name)),
+    }
+}
+
+/// Names of the input devices cpal can open, for device validation and listing
+pub fn input_device_names() -> Vec<String> {
+    cpal::default_host()
+        .input_devices()
+        .map(|devices| devices.filter_map(|d| d.name().ok()).collect())
+        .unwrap_or_default()
+}
+
+/// Whether cpal has a default input device to record from
+pub fn has_default_input() -> bool {
+    find_input_device(None).is_ok()
+}
+
+/// Check a named device exists before starting a recording on it
+pub fn probe_device(name: &str) -> Result<(), String> {
+    find_input_device(Some(name)).map(|_| ())
+}
+
+/// Linear-interpolating channel mapping + resample of interleaved f32 frames to the profile's format
+struct Resampler {
+    in_channels: usize,
+    out_channels: usize,
+    step: f64,
+    pos: f64,
+    last: Vec<f32>,
+}
+
+impl Resampler {
+    fn new(channels: usize, input_rate: u32, profile: &RecordingProfile) -> Self {
+        let out_channels = profile.channels.max(1) as usize;
+        Resampler {
+            in_channels: channels.max(1),
+            out_channels,
+            step: input_rate as f64 / profile.sample_rate as f64,
+            pos: 0.0,
+            last: vec![0.0; out_channels],
+        }
+    }
+
+    /// Average down to mono, or take (repeating the last) input channels for stereo
+    fn map_frame(&self, frame: &[f32], out: &mut Vec<f32>) {
+        if self.out_channels == 1 {
+            out.push(frame.iter().sum::<f32>() / frame.len() as f32);
+        } else {
+            out.extend((0..self.out_channels).map(|c| frame[c.min(frame.len() - 1)]));
+        }
+    }
+
+    /// Append interleaved output samples in [-1.0, 1.0]
+    fn process(&mut self, interleaved: &[f32], out: &mut Vec<f32>) {
+        let mut mapped = Vec::with_capacity(interleaved.len());
+        for frame in interleaved.chunks(self.in_channels) {
+            self.map_frame(frame, &mut mapped);
+        }
+        let frames = mapped.len() / self.out_channels;
+        if frames == 0 {
+            return;
+        }
+        // `pos` is measured from the last frame of the previous buffer (index -1 here)
+        while self.pos < frames as f64 {
+            let idx = self.pos.floor();
+            let frac = (self.pos - idx) as f32;
+            for c in 0..self.out_channels {
+                let a = if idx < 1.0 { self.last[c] } else { mapped[(idx as usize - 1) * self.out_channels + c] };
+                let b = mapped[idx as usize * self.out_channels + c];
+                out.push((a + (b - a) * frac).clamp(-1.0, 1.0));
+            }
+            self.pos += self.step;
+        }
+        self.pos -= frames as f64;
+        self.last.copy_from_slice(&mapped[(frames - 1) * self.out_channels..]);
+    }
+}
+
+fn wav_spec(profile: &RecordingProfile) -> hound::WavSpec {
+    hound::WavSpec {
+        channels: profile.channels,
+        sample_rate: profile.sample_rate,
+        bits_per_sample: profile.bit_depth,
+        sample_format: hound::SampleFormat::Int,
+    }
+}
+
+fn write_sample(
+    writer: &mut hound::WavWriter<std::io::BufWriter<fs::File>>,
+    sample: f32,
+    bit_depth: u16,
+) -> Result<(), String> {
+    let written = if bit_depth == 24 {
+        writer.write_sample((sample * 8_388_607.0) as i32)
+    } else {
+        writer.write_sample((sample * i16::MAX as f32) as i16)
+    };
+    written.map_err(|e| format!("Failed to write audio: {}", e))
+}
+
+/// Segments are written as .part and renamed when complete so watchers never see a half-written WAV
+fn open_segment(
+    dir: &Path,
+    index: usize,
+    profile: &RecordingProfile,
+) -> Result<(hound::WavWriter<std::io::BufWriter<fs::File>>, PathBuf), String> {
+    let final_path = dir.join(format!("chunk-{:04}.wav", index));
+    let part_path = dir.join(format!("chunk-{:04}.wav.part", index));
+    let writer = hound::WavWriter::create(&part_path, wav_spec(profile))
+        .map_err(|e| format!("Failed to create {}: {}", part_path.display(), e))?;
+    Ok((writer, final_path))
+}
+
+fn finish_segment(
+    writer: hound::WavWriter<std::io::BufWriter<fs::File>>,
+    final_path: &Path,
+) -> Result<(), String> {
+    writer.finalize().map_err(|e| format!("Failed to finalize WAV: {}", e))?;
+    let part_path = final_path.with_extension("wav.part");
+    fs::rename(&part_path, final_path)
+        .map_err(|e| format!("Failed to finalize {}: {}", final_path.display(), e))
+}
+
+fn run_writer(
+    rx: mpsc::Receiver<Vec<f32>>,
+    target: NativeTarget,
+    channels: usize,
+    input_rate: u32,
+    profile: RecordingProfile,
+) -> Result<Vec<PathBuf>, String> {
+    let mut resampler = Resampler::new(channels, input_rate, &profile);
+    let mut samples = Vec::new();
+    let mut written = Vec::new();
+
+    match target {
+        NativeTarget::File(path) => {
+            let mut writer = hound::WavWriter::create(&path, wav_spec(&profile))
+                .map_err(|e| format!("Failed to create {}: {}", path.display(), e))?;
+            for buffer in rx {
+                samples.clear();
+                resampler.process(&buffer, &mut samples);
+                for s in &samples {
+                    write_sample(&mut writer, *s, profile.bit_depth)?;
+                }
+            }
+            writer.finalize().map_err(|e| format!("Failed to finalize WAV: {}", e))?;
+            written.push(path);
+        }
+        NativeTarget::Segments { dir, segment_secs, start_index } => {
+            let per_segment = segment_secs.max(1) * profile.sample_rate as u64;
+            let mut index = start_index;
+            let (mut writer, mut final_path) = open_segment(&dir, index, &profile)?;
+            let mut in_segment = 0u64;
+            for buffer in rx {
+                samples.clear();
+                resampler.process(&buffer, &mut samples);
+                for frame in samples.chunks(profile.channels.max(1) as usize) {
+                    // Rotate on an exact frame boundary so consecutive chunks have no gap or overlap
+                    if in_segment == per_segment {
+                        finish_segment(writer, &final_path)?;
+                        written.push(final_path);
+                        index += 1;
+                        (writer, final_path) = open_segment(&dir, index, &profile)?;
+                        in_segment = 0;
+                    }
+                    for s in frame {
+                        write_sample(&mut writer, *s, profile.bit_depth)?;
+                    }
+                    in_segment += 1;
+                }
+            }
+            finish_segment(writer, &final_path)?;
+            written.push(final_path);
+        }
+    }
+    Ok(written)
+}
+
+fn build_stream(
+    device: &cpal::Device,
+    tx: mpsc::Sender<Vec<f32>>,
+    paused: Arc<AtomicBool>,
+    meter: Arc<Mutex<Vec<f32>>>,
+) -> Result<(cpal::Stream, usize, u32), String> {
+    let supported = device
+        .default_input_config()
+        .map_err(|e| format!("Failed to query input config: {}", e))?;
+    let format = supported.sample_format();
+    let config: cpal::StreamConfig = supported.into();
+    let (channels, rate) = (config.channels as usize, config.sample_rate.0);
+    let on_error = |e| log::error!("native recorder stream error: {}", e);
+
+    macro_rules! stream_of {
+        ($t:ty, $convert:expr) => {
+            device.build_input_stream(
+                &config,
+                move |data: &[$t], _: &cpal::InputCallbackInfo| {
+                    if paused.load(Ordering::Relaxed) {
+                        return;
+                    }
+                    let samples: Vec<f32> = data.iter().map($convert).collect();
+                    if let Ok(mut meter) = meter.try_lock() {
+                        if meter.len() + samples.len() > METER_BUFFER_SAMPLES {
+                            meter.clear();
+                        }
+                        meter.extend_from_slice(&samples);
+                    }
+                    let _ = tx.send(samples);
+                },
+                on_error,
+                None,
+            )
+        };
+    }
+
+    let stream = match format {
+        cpal::SampleFormat::F32 => stream_of!(f32, |s: &f32| *s),
+        cpal::SampleFormat::I16 => stream_of!(i16, |s: &i16| *s as f32 / i16::MAX as f32),
+        cpal::SampleFormat::U16 => stream_of!(u16, |s: &u16| (*s as f32 - 32768.0) / 32768.0),
+        other => return Err(format!("Unsupported input sample format {:?}", other)),
+    }
+    .map_err(|e| format!("Failed to open input stream: {}", e))?;
+    Ok((stream, channels, rate))
+}
+
+impl NativeRecording {
+    /// Open the input device and start capturing 16 kHz mono; fails immediately if the device can't be opened
+    pub fn start(device_name: Option<&str>, target: NativeTarget) -> Result<NativeRecording, String> {
+        NativeRecording::start_with_profile(device_name, target, RecordingProfile::default())
+    }
+
+    /// Like `start`, writing WAVs in `profile`'s format instead
+    pub fn start_with_profile(
+        device_name: Option<&str>,
+        target: NativeTarget,
+        profile: RecordingProfile,
+    ) -> Result<NativeRecording, String> {
+        let (stop_tx, stop_rx) = mpsc::channel::<()>();
+        let (ready_tx, ready_rx) = mpsc::channel::<Result<(usize, u32), String>>();
+        let (sample_tx, sample_rx) = mpsc::channel::<Vec<f32>>();
+        let paused = Arc::new(AtomicBool::new(false));
+        let meter = Arc::new(Mutex::new(Vec::new()));
+
+        let device_name = device_name.map(str::to_string);
+        let paused_for_stream = paused.clone();
+        let meter_for_stream = meter.clone();
+        let stream_thread = std::thread::spawn(move || {
+            let started = find_input_device(device_name.as_deref()).and_then(|device| {
+                let (stream, channels, rate) = build_stream(&device, sample_tx, paused_for_stream, meter_for_stream)?;
+                stream.play().map_err(|e| format!("Failed to start input stream: {}", e))?;
+                Ok((stream, channels, rate))
+            });
+            match started {
+                Ok((stream, channels, rate)) => {
+                    let _ = ready_tx.send(Ok((channels, rate)));
+                    // Hold the stream open until asked to stop (or the handle is dropped)
+                    let _ = stop_rx.recv();
+                    drop(stream);
+                }
+                Err(e) => {
+                    let _ = ready_tx.send(Err(e));
+                }
+            }
+        });
+
+        let (channels, rate) = ready_rx
+            .recv()
+            .map_err(|_| "Native recorder thread exited unexpectedly".to_string())??;
+        let writer_thread = std::thread::spawn(move || run_writer(sample_rx, target, channels, rate, profile));
+
+        Ok(NativeRecording {
+            stop_tx,
+            paused,
+            meter,
+            stream_thread: Some(stream_thread),
+            writer_thread: Some(writer_thread),
+        })
+    }
+
+    /// Shared buffer of recent samples for levels::LevelSource::Native
+    pub fn meter_buffer(&self) -> Arc<Mutex<Vec<f32>>> {
+        self.meter.clone()
+    }
+
+    /// Drop incoming audio while paused; segment numbering carries on seamlessly after resume
+    pub fn set_paused(&self, paused: bool) {
+        self.paused.store(paused, Ordering::Relaxed);
+    }
+
+    /// Stop capturing, flush the final WAV, and return every file written
+    pub fn stop(mut self) -> Result<Vec<PathBuf>, String> {
+        let _ = self.stop_tx.send(());
+        if let Some(thread) = self.stream_thread.take() {
+            let _ = thread.join();
+        }
+        // The stream (and its sample sender) is gone, so the writer drains and exits
+        match self.writer_thread.take() {
+            Some(thread) => thread.join().map_err(|_| "Native recorder writer panicked".to_string())?,
+            None => Ok(Vec::new()),
+        }
+    }
+}
+
+impl Drop for NativeRecording {
+    fn drop(&mut self) {
+        let _ = self.stop_tx.send(());
+    }
+}
@@ -0,0 +1,222 @@
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use crate::error::AppError;
+use crate::{logging, note_templates, notes, paths, transcript};
+
+/// Longest folder name built from a note title, leaving room for the date and a collision suffix
+const MAX_TITLE_CHARS: usize = 80;
+
+/// Names Windows won't create as files or folders, whatever the extension
+const RESERVED_NAMES: &[&str] = &[
+    "CON", "PRN", "AUX", "NUL", "COM1", "COM2", "COM3", "COM4", "COM5", "COM6", "COM7", "COM8", "COM9", "LPT1",
+    "LPT2", "LPT3", "LPT4", "LPT5", "LPT6", "LPT7", "LPT8", "LPT9",
+];
+
+#[derive(Deserialize, Default)]
+pub struct ExportOptions {
+    /// "txt", "srt" or "vtt"; defaults to srt when the note has segments and txt otherwise
+    pub transcript_format: Option<String>,
+    /// Copy the recording into the bundle (default true)
+    pub include_audio: Option<bool>,
+    /// "original" (default), "mp3" or "ogg"; anything but original is transcoded with ffmpeg
+    pub audio_format: Option<String>,
+    /// Also write metadata.json describing the note
+    #[serde(default)]
+    pub include_metadata: bool,
+}
+
+#[derive(Serialize)]
+pub struct ExportedNote {
+    pub dir: String,
+    pub files: Vec<String>,
+    /// Parts of the note that couldn't be exported, e.g. a recording that no longer exists
+    pub warnings: Vec<String>,
+}
+
+/// "YYYY-MM-DD" (UTC) for Unix seconds
+pub fn format_date(unix_secs: u64) -> String {
+    let (year, month, day) = logging::civil_from_days((unix_secs / 86_400) as i64);
+    format!("{:04}-{:02}-{:02}", year, month, day)
+}
+
+/// Make a note title safe as a file or folder name on every platform
+pub fn sanitize_file_name(title: &str) -> String {
+    let cleaned: String = title
+        .chars()
+        .map(|c| if c.is_control() || r#"<>:"/\|?*"#.contains(c) { '_' } else { c })
+        .take(MAX_TITLE_CHARS)
+        .collect();
+    // Windows drops trailing dots and spaces, and a leading dot hides the file elsewhere
+    let cleaned = cleaned.trim().trim_matches('.').trim().to_string();
+    if cleaned.is_empty() {
+        return "note".to_string();
+    }
+    let stem = cleaned.split('.').next().unwrap_or_default().to_uppercase();
+    if RESERVED_NAMES.contains(&stem.as_str()) {
+        format!("_{}", cleaned)
+    } else {
+        cleaned
+    }
+}
+
+/// `path`, or the first of "name (2)", "name (3)", … that doesn't exist yet
+pub fn unique_path(path: PathBuf) -> PathBuf {
+    if !path.exists() {
+        return path;
+    }
+    let stem = path.file_stem().unwrap_or_default().to_string_lossy().to_string();
+    let extension = path.extension().map(|e| format!(".{}", e.to_string_lossy())).unwrap_or_default();
+    (2..)
+        .map(|n| path.with_file_name(format!("{} ({}){}", stem, n, extension)))
+        .find(|candidate| !candidate.exists())
+        .unwrap_or(path)
+}
+
+fn transcode_audio(input: &Path, output: &Path, format: &str) -> Result<(), String> {
+    let codec = match format {
+        "mp3" => "libmp3lame",
+        "ogg" => "libvorbis",
+        other => return Err(format!("Unsupported audio format '{}' (expected original, mp3, or ogg)", other)),
+    };
+    let result = Command::new("ffmpeg")
+        .arg("-hide_banner")
+        .arg("-loglevel").arg("error")
+        .arg("-i").arg(input)
+        .arg("-vn")
+        .arg("-c:a").arg(codec)
+        // VBR around 128-160 kbps, plenty for speech
+        .arg("-q:a").arg("4")
+        .arg(output)
+        .output()
+        .map_err(|e| format!("Failed to start ffmpeg: {}", e))?;
+    if !result.status.success() {
+        let _ = fs::remove_file(output);
+        return Err(format!("ffmpeg transcoding failed: {}", String::from_utf8_lossy(&result.stderr).trim()));
+    }
+    Ok(())
+}
+
+fn write_file(path: PathBuf, contents: String, files: &mut Vec<String>) -> Result<(), String> {
+    fs::write(&path, contents).map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+    files.push(path.to_string_lossy().to_string());
+    Ok(())
+}
+
+fn export_bundle(note: &notes::Note, dest_dir: &Path, options: &ExportOptions) -> Result<ExportedNote, String> {
+    if !dest_dir.is_dir() {
+        return Err(format!("Export folder not found: {}", dest_dir.display()));
+    }
+    let format = options
+        .transcript_format
+        .clone()
+        .unwrap_or_else(|| if note.segments.is_empty() { "txt" } else { "srt" }.to_string())
+        .to_lowercase();
+    let transcript = match format.as_str() {
+        "txt" => format!("{}\n", note.transcript.trim_end()),
+        _ if note.segments.is_empty() => {
+            return Err(format!("This note has no timestamps to export as {}; use txt", format));
+        }
+        _ => transcript::render(&note.segments, &format)?,
+    };
+    let audio_format = options.audio_format.as_deref().unwrap_or("original").to_lowercase();
+    if !["original", "mp3", "ogg"].contains(&audio_format.as_str()) {
+        return Err(format!("Unsupported audio format '{}' (expected original, mp3, or ogg)", audio_format));
+    }
+
+    let name = format!("{} {}", format_date(note.created_at), sanitize_file_name(&note.title));
+    let dir = unique_path(dest_dir.join(name));
+    fs::create_dir_all(&dir).map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+
+    let mut files = Vec::new();
+    let mut warnings = Vec::new();
+    write_file(dir.join(format!("transcript.{}", format)), transcript, &mut files)?;
+    if let Some(summary) = &note.summary {
+        write_file(dir.join("summary.md"), format!("{}\n", summary.trim_end()), &mut files)?;
+    }
+
+    let mut audio_file = None;
+    match note.audio_path.as_deref().map(Path::new) {
+        Some(audio) if options.include_audio.unwrap_or(true) && audio.is_file() => {
+            let target = if audio_format == "original" {
+                let target = dir.join("audio").with_extension(audio.extension().unwrap_or_default());
+                fs::copy(audio, &target).map_err(|e| format!("Failed to copy {}: {}", audio.display(), e))?;
+                target
+            } else {
+                let target = dir.join("audio").with_extension(&audio_format);
+                transcode_audio(audio, &target, &audio_format)?;
+                target
+            };
+            audio_file = target.file_name().map(|n| n.to_string_lossy().to_string());
+            files.push(target.to_string_lossy().to_string());
+        }
+        Some(audio) if options.include_audio.unwrap_or(true) => {
+            warnings.push(format!("Recording {} no longer exists", audio.display()));
+        }
+        _ => {}
+    }
+
+    if options.include_metadata {
+        let metadata = serde_json::json!({
+            "id": note.id,
+            "title": note.title,
+            "created_at": note.created_at,
+            "date": format_date(note.created_at),
+            "duration_secs": note.duration_secs,
+            "tags": note.tags,
+            "audio": audio_file,
+            "source_audio_path": note.audio_path,
+        });
+        let json = serde_json::to_string_pretty(&metadata).map_err(|e| format!("Failed to serialize metadata: {}", e))?;
+        write_file(dir.join("metadata.json"), json, &mut files)?;
+    }
+
+    log::info!("Exported note {} to {}", note.id, dir.display());
+    Ok(ExportedNote { dir: dir.to_string_lossy().to_string(), files, warnings })
+}
+
+/// Export a note as a folder under `dest_dir` holding its transcript, summary, recording and,
+/// optionally, metadata.json; an existing folder of the same name gets a numbered sibling
+#[tauri::command]
+pub async fn export_note(
+    app: tauri::AppHandle,
+    note_id: u64,
+    dest_dir: String,
+    options: Option<ExportOptions>,
+) -> Result<ExportedNote, AppError> {
+    let dest_dir = paths::existing(&app, &dest_dir)?;
+    let note = notes::get(&app, note_id)?;
+    let options = options.unwrap_or_default();
+    let exported = tauri::async_runtime::spawn_blocking(move || export_bundle(&note, &dest_dir, &options))
+        .await
+        .map_err(|e| format!("Export task failed: {}", e))??;
+    Ok(exported)
+}
+
+/// Render a note through a Markdown template and write it to `dest_path`; when that's a folder the
+/// file is named after the note's date and title. Existing files are only replaced with `overwrite`.
+#[tauri::command]
+pub async fn export_note_markdown(
+    app: tauri::AppHandle,
+    note_id: u64,
+    template_name: Option<String>,
+    dest_path: String,
+    overwrite: Option<bool>,
+) -> Result<String, AppError> {
+    let note = notes::get(&app, note_id)?;
+    let template = note_templates::resolve(template_name.as_deref())?;
+    let markdown = template.render(&note_templates::TemplateValues::from_note(&note));
+
+    let mut path = paths::writable(&app, &dest_path)?;
+    if path.is_dir() {
+        let name = format!("{} {}.md", format_date(note.created_at), sanitize_file_name(&note.title));
+        path = unique_path(path.join(name));
+    } else if path.exists() && !overwrite.unwrap_or(false) {
+        return Err(format!("File already exists: {}", path.display()).into());
+    }
+    fs::write(&path, markdown).map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+    log::info!("Exported note {} as Markdown to {}", note_id, path.display());
+    Ok(path.to_string_lossy().to_string())
+}
@@ -0,0 +1,394 @@
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::fs;
+
+use crate::{get_config_dir, note_export, notes};
+
+const TEMPLATES_FILE: &str = "note-templates.json";
+
+/// Template export_note_markdown uses when none is named
+pub const DEFAULT_TEMPLATE: &str = "default";
+
+const DEFAULT_MARKDOWN: &str = "{{frontmatter}}
+# {{title}}
+
+{{#if summary}}
+## Summary
+
+{{summary}}
+
+{{/if}}
+## Transcript
+
+<details>
+<summary>Full transcript</summary>
+
+{{#if segments}}
+{{#each segments}}
+**[{{start}}]** {{text}}
+
+{{/each}}
+{{else}}
+{{transcript}}
+{{/if}}
+
+</details>
+";
+
+/// Markdown templates that always exist; saving one of these names overrides it
+const BUILTIN_TEMPLATES: &[(&str, &str)] = &[(DEFAULT_TEMPLATE, DEFAULT_MARKDOWN)];
+
+/// Placeholders available everywhere in a template
+const FIELDS: &[&str] = &["frontmatter", "title", "date", "duration", "duration_secs", "audio_file", "summary", "transcript"];
+
+/// Lists {{#each}} can walk, with the placeholders available inside
+const LISTS: &[(&str, &[&str])] = &[("segments", &["start", "end", "text"]), ("tags", &["this"])];
+
+#[derive(Serialize, Clone, Debug)]
+pub struct NoteTemplate {
+    pub name: String,
+    pub template: String,
+    pub builtin: bool,
+    /// A built-in whose text has been replaced by a saved template
+    pub overridden: bool,
+}
+
+#[derive(Debug)]
+enum Node {
+    Text(String),
+    Field(String),
+    Each(String, Vec<Node>),
+    If(String, Vec<Node>, Vec<Node>),
+}
+
+/// A template parsed and checked against the placeholders a note provides
+pub struct Template {
+    nodes: Vec<Node>,
+}
+
+/// 1-based line and column of a byte offset
+fn line_col(source: &str, offset: usize) -> (usize, usize) {
+    let before = &source[..offset];
+    let line = before.matches('\n').count() + 1;
+    let column = before.rsplit('\n').next().unwrap_or_default().chars().count() + 1;
+    (line, column)
+}
+
+/// A block tag alone on its line takes the whole line with it, like Handlebars' standalone tags,
+/// so templates can put {{#if}} and {{/each}} on lines of their own without leaving blank lines
+fn strip_standalone(source: &str, tag_start: usize, tag_end: usize, text: &mut String) -> usize {
+    let line_start = source[..tag_start].rfind('\n').map(|i| i + 1).unwrap_or(0);
+    let rest = &source[tag_end..];
+    let line_end = rest.find('\n').map(|i| i + 1).unwrap_or(rest.len());
+    let alone = source[line_start..tag_start].trim().is_empty() && rest[..line_end].trim().is_empty();
+    if !alone {
+        return tag_end;
+    }
+    text.truncate(text.trim_end_matches([' ', '\t']).len());
+    tag_end + line_end
+}
+
+struct Parser<'a> {
+    source: &'a str,
+    pos: usize,
+}
+
+/// What ended a run of nodes
+enum Close {
+    Eof,
+    End(&'static str),
+    Else,
+}
+
+impl<'a> Parser<'a> {
+    fn error(&self, offset: usize, message: String) -> String {
+        let (line, column) = line_col(self.source, offset);
+        format!("Line {}, column {}: {}", line, column, message)
+    }
+
+    /// Parse until end of input or a closing tag, checking placeholders against `scope` (the
+    /// fields of the enclosing {{#each}}, if any)
+    fn parse_nodes(&mut self, scope: &[&str]) -> Result<(Vec<Node>, Close, usize), String> {
+        let mut nodes = Vec::new();
+        let mut text = String::new();
+        loop {
+            let Some(open) = self.source[self.pos..].find("{{").map(|i| self.pos + i) else {
+                text.push_str(&self.source[self.pos..]);
+                self.pos = self.source.len();
+                if !text.is_empty() {
+                    nodes.push(Node::Text(text));
+                }
+                return Ok((nodes, Close::Eof, self.source.len()));
+            };
+            text.push_str(&self.source[self.pos..open]);
+            let close = self.source[open..]
+                .find("}}")
+                .map(|i| open + i)
+                .ok_or_else(|| self.error(open, "Unclosed {{".to_string()))?;
+            let tag = self.source[open + 2..close].trim();
+            let tag_end = close + 2;
+
+            let is_block = tag.starts_with('#') || tag.starts_with('/') || tag == "else";
+            self.pos = if is_block { strip_standalone(self.source, open, tag_end, &mut text) } else { tag_end };
+            if is_block && !text.is_empty() {
+                nodes.push(Node::Text(std::mem::take(&mut text)));
+            }
+
+            if let Some(block) = tag.strip_prefix('#') {
+                let (kind, name) = block.split_once(char::is_whitespace).unwrap_or((block, ""));
+                let name = name.trim();
+                match kind {
+                    "each" => {
+                        let fields = LISTS
+                            .iter()
+                            .find(|(list, _)| *list == name)
+                            .map(|(_, fields)| *fields)
+                            .ok_or_else(|| self.error(open, format!("Can't loop over '{}' (expected segments or tags)", name)))?;
+                        let (body, end, at) = self.parse_nodes(fields)?;
+                        self.expect_end(end, at, open, "each")?;
+                        nodes.push(Node::Each(name.to_string(), body));
+                    }
+                    "if" => {
+                        if !self.known(name, scope) && !LISTS.iter().any(|(list, _)| *list == name) {
+                            return Err(self.error(open, format!("Unknown placeholder '{}'", name)));
+                        }
+                        let (then, end, at) = self.parse_nodes(scope)?;
+                        let otherwise = if matches!(end, Close::Else) {
+                            let (otherwise, end, at) = self.parse_nodes(scope)?;
+                            self.expect_end(end, at, open, "if")?;
+                            otherwise
+                        } else {
+                            self.expect_end(end, at, open, "if")?;
+                            Vec::new()
+                        };
+                        nodes.push(Node::If(name.to_string(), then, otherwise));
+                    }
+                    other => return Err(self.error(open, format!("Unknown block '#{}' (expected #each or #if)", other))),
+                }
+            } else if let Some(block) = tag.strip_prefix('/') {
+                let end = match block.trim() {
+                    "each" => "each",
+                    "if" => "if",
+                    other => return Err(self.error(open, format!("Unknown closing tag '/{}'", other))),
+                };
+                return Ok((nodes, Close::End(end), open));
+            } else if tag == "else" {
+                return Ok((nodes, Close::Else, open));
+            } else if self.known(tag, scope) {
+                if !text.is_empty() {
+                    nodes.push(Node::Text(std::mem::take(&mut text)));
+                }
+                nodes.push(Node::Field(tag.to_string()));
+            } else {
+                return Err(self.error(open, format!("Unknown placeholder '{}'", tag)));
+            }
+        }
+    }
+
+    fn known(&self, name: &str, scope: &[&str]) -> bool {
+        FIELDS.contains(&name) || scope.contains(&name)
+    }
+
+    fn expect_end(&self, close: Close, at: usize, open: usize, block: &str) -> Result<(), String> {
+        match close {
+            Close::End(end) if end == block => Ok(()),
+            Close::End(end) => Err(self.error(at, format!("{{{{/{}}}}} doesn't match the open {{{{#{}}}}}", end, block))),
+            Close::Else => Err(self.error(at, "{{else}} is only allowed inside {{#if}}".to_string())),
+            Close::Eof => Err(self.error(open, format!("{{{{#{}}}}} is never closed", block))),
+        }
+    }
+}
+
+impl Template {
+    pub fn parse(source: &str) -> Result<Self, String> {
+        let mut parser = Parser { source, pos: 0 };
+        let (nodes, close, at) = parser.parse_nodes(&[])?;
+        match close {
+            Close::Eof => Ok(Template { nodes }),
+            Close::End(end) => Err(parser.error(at, format!("{{{{/{}}}}} without a matching {{{{#{}}}}}", end, end))),
+            Close::Else => Err(parser.error(at, "{{else}} is only allowed inside {{#if}}".to_string())),
+        }
+    }
+
+    pub fn render(&self, values: &TemplateValues) -> String {
+        let mut out = String::new();
+        render_nodes(&self.nodes, values, None, &mut out);
+        out
+    }
+}
+
+/// What a note contributes to a template
+pub struct TemplateValues {
+    fields: BTreeMap<&'static str, String>,
+    lists: BTreeMap<&'static str, Vec<BTreeMap<&'static str, String>>>,
+}
+
+fn render_nodes(nodes: &[Node], values: &TemplateValues, item: Option<&BTreeMap<&'static str, String>>, out: &mut String) {
+    let lookup = |name: &str| item.and_then(|i| i.get(name)).or_else(|| values.fields.get(name));
+    for node in nodes {
+        match node {
+            Node::Text(text) => out.push_str(text),
+            Node::Field(name) => out.push_str(lookup(name).map(String::as_str).unwrap_or_default()),
+            Node::Each(list, body) => {
+                for entry in values.lists.get(list.as_str()).into_iter().flatten() {
+                    render_nodes(body, values, Some(entry), out);
+                }
+            }
+            Node::If(name, then, otherwise) => {
+                let truthy = match values.lists.get(name.as_str()) {
+                    Some(list) => !list.is_empty(),
+                    None => lookup(name).is_some_and(|v| !v.trim().is_empty()),
+                };
+                render_nodes(if truthy { then } else { otherwise }, values, item, out);
+            }
+        }
+    }
+}
+
+/// "H:MM:SS" for durations of an hour or more, "M:SS" otherwise
+fn format_clock(ms: u64) -> String {
+    let secs = ms / 1000;
+    if secs >= 3600 {
+        format!("{}:{:02}:{:02}", secs / 3600, secs / 60 % 60, secs % 60)
+    } else {
+        format!("{}:{:02}", secs / 60, secs % 60)
+    }
+}
+
+/// JSON strings double as quoted YAML scalars, which keeps titles with colons or quotes valid
+fn yaml_string(value: &str) -> String {
+    serde_json::to_string(value).unwrap_or_default()
+}
+
+impl TemplateValues {
+    pub fn from_note(note: &notes::Note) -> Self {
+        let date = note_export::format_date(note.created_at);
+        let duration = note.duration_secs.map(|d| format_clock((d * 1000.0) as u64));
+        let audio_file = note
+            .audio_path
+            .as_deref()
+            .and_then(|p| std::path::Path::new(p).file_name())
+            .map(|n| n.to_string_lossy().to_string());
+
+        let mut frontmatter = format!("---\ntitle: {}\ndate: {}\n", yaml_string(&note.title), date);
+        if let Some(duration) = &duration {
+            frontmatter.push_str(&format!("duration: {}\n", yaml_string(duration)));
+        }
+        frontmatter.push_str(&format!("tags: {}\n", serde_json::to_string(&note.tags).unwrap_or_default()));
+        if let Some(audio) = &audio_file {
+            frontmatter.push_str(&format!("audio: {}\n", yaml_string(audio)));
+        }
+        frontmatter.push_str("---");
+
+        let fields = BTreeMap::from([
+            ("frontmatter", frontmatter),
+            ("title", note.title.clone()),
+            ("date", date),
+            ("duration", duration.unwrap_or_default()),
+            ("duration_secs", note.duration_secs.map(|d| format!("{:.0}", d)).unwrap_or_default()),
+            ("audio_file", audio_file.unwrap_or_default()),
+            ("summary", note.summary.clone().unwrap_or_default().trim().to_string()),
+            ("transcript", note.transcript.trim().to_string()),
+        ]);
+        let segments = note
+            .segments
+            .iter()
+            .map(|s| {
+                BTreeMap::from([
+                    ("start", format_clock(s.start_ms)),
+                    ("end", format_clock(s.end_ms)),
+                    ("text", s.text.trim().to_string()),
+                ])
+            })
+            .collect();
+        let tags = note.tags.iter().map(|t| BTreeMap::from([("this", t.clone())])).collect();
+        TemplateValues { fields, lists: BTreeMap::from([("segments", segments), ("tags", tags)]) }
+    }
+}
+
+fn builtin(name: &str) -> Option<&'static str> {
+    BUILTIN_TEMPLATES.iter().find(|(n, _)| *n == name).map(|(_, t)| *t)
+}
+
+/// Saved templates, including overrides of built-ins
+fn load_saved() -> BTreeMap<String, String> {
+    get_config_dir()
+        .ok()
+        .and_then(|dir| fs::read_to_string(dir.join(TEMPLATES_FILE)).ok())
+        .and_then(|raw| serde_json::from_str(&raw).ok())
+        .unwrap_or_default()
+}
+
+fn save_all(templates: &BTreeMap<String, String>) -> Result<(), String> {
+    let json = serde_json::to_string_pretty(templates)
+        .map_err(|e| format!("Failed to serialize note templates: {}", e))?;
+    fs::write(get_config_dir()?.join(TEMPLATES_FILE), json)
+        .map_err(|e| format!("Failed to save note templates: {}", e))
+}
+
+/// The parsed template for `name` (or the default), preferring a saved one over a built-in
+pub fn resolve(name: Option<&str>) -> Result<Template, String> {
+    let name = name.unwrap_or(DEFAULT_TEMPLATE);
+    let source = match load_saved().remove(name) {
+        Some(template) => template,
+        None => builtin(name)
+            .map(str::to_string)
+            .ok_or_else(|| format!("Note template '{}' not found", name))?,
+    };
+    Template::parse(&source).map_err(|e| format!("Note template '{}' is invalid: {}", name, e))
+}
+
+#[tauri::command]
+pub async fn list_note_templates() -> Result<Vec<NoteTemplate>, String> {
+    let mut saved = load_saved();
+    let mut templates: Vec<NoteTemplate> = BUILTIN_TEMPLATES
+        .iter()
+        .map(|(name, template)| {
+            let saved = saved.remove(*name);
+            NoteTemplate {
+                name: name.to_string(),
+                overridden: saved.is_some(),
+                template: saved.unwrap_or_else(|| template.to_string()),
+                builtin: true,
+            }
+        })
+        .collect();
+    templates.extend(saved.into_iter().map(|(name, template)| NoteTemplate {
+        name,
+        template,
+        builtin: false,
+        overridden: false,
+    }));
+    Ok(templates)
+}
+
+/// Create or replace a Markdown note template, rejecting it with the line and column of the first
+/// bad placeholder or block
+#[tauri::command]
+pub async fn save_note_template(name: String, template: String) -> Result<NoteTemplate, String> {
+    let name = name.trim().to_string();
+    if name.is_empty() {
+        return Err("Template name can't be empty".to_string());
+    }
+    Template::parse(&template)?;
+
+    let mut saved = load_saved();
+    saved.insert(name.clone(), template.clone());
+    save_all(&saved)?;
+    let builtin = builtin(&name).is_some();
+    Ok(NoteTemplate { name, template, builtin, overridden: builtin })
+}
+
+/// Delete a saved template; for a built-in this removes the override and restores the original
+#[tauri::command]
+pub async fn delete_note_template(name: String) -> Result<(), String> {
+    let mut saved = load_saved();
+    if saved.remove(&name).is_none() {
+        return Err(if builtin(&name).is_some() {
+            format!("'{}' is a built-in template and can't be deleted", name)
+        } else {
+            format!("Note template '{}' not found", name)
+        });
+    }
+    save_all(&saved)
+}
@@ -0,0 +1,1006 @@
+use rusqlite::{params, Connection, OptionalExtension, Transaction};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+use tauri::Manager;
+
+use crate::{get_data_dir, summarize, transcript};
+
+const DB_FILE: &str = "notes.db";
+
+/// The JSON store notes.db replaced; imported once when the database is created, then renamed aside
+const LEGACY_NOTES_FILE: &str = "notes.json";
+
+/// Schema version of notes.db; bump it and append a migration whenever the schema changes
+const SCHEMA_VERSION: usize = 3;
+
+/// MIGRATIONS[n - 1] takes the database from version n - 1 to n, inside one transaction
+const MIGRATIONS: &[&str] = &[
+    // segments and tags are JSON arrays
+    "CREATE TABLE notes (
+        id INTEGER PRIMARY KEY AUTOINCREMENT,
+        title TEXT NOT NULL,
+        created_at INTEGER NOT NULL,
+        audio_path TEXT,
+        transcript TEXT NOT NULL,
+        segments TEXT NOT NULL DEFAULT '[]',
+        summary TEXT,
+        duration_secs REAL,
+        tags TEXT NOT NULL DEFAULT '[]',
+        transcript_draft TEXT
+    );
+    CREATE INDEX notes_created_at ON notes (created_at);",
+    // Full-text index over transcript and summary, kept in step with notes by triggers
+    "CREATE VIRTUAL TABLE notes_fts USING fts5(transcript, summary, content = 'notes', content_rowid = 'id');
+    CREATE TRIGGER notes_fts_insert AFTER INSERT ON notes BEGIN
+        INSERT INTO notes_fts (rowid, transcript, summary) VALUES (new.id, new.transcript, new.summary);
+    END;
+    CREATE TRIGGER notes_fts_delete AFTER DELETE ON notes BEGIN
+        INSERT INTO notes_fts (notes_fts, rowid, transcript, summary) VALUES ('delete', old.id, old.transcript, old.summary);
+    END;
+    CREATE TRIGGER notes_fts_update AFTER UPDATE OF transcript, summary ON notes BEGIN
+        INSERT INTO notes_fts (notes_fts, rowid, transcript, summary) VALUES ('delete', old.id, old.transcript, old.summary);
+        INSERT INTO notes_fts (rowid, transcript, summary) VALUES (new.id, new.transcript, new.summary);
+    END;
+    INSERT INTO notes_fts (notes_fts) VALUES ('rebuild');",
+    // Tags move out of the JSON column into their own table, so a rename is one row
+    "CREATE TABLE tags (id INTEGER PRIMARY KEY, name TEXT NOT NULL UNIQUE);
+    CREATE TABLE note_tags (
+        note_id INTEGER NOT NULL REFERENCES notes (id) ON DELETE CASCADE,
+        tag_id INTEGER NOT NULL REFERENCES tags (id) ON DELETE CASCADE,
+        position INTEGER NOT NULL,
+        PRIMARY KEY (note_id, tag_id)
+    );
+    CREATE INDEX note_tags_tag ON note_tags (tag_id);
+    INSERT OR IGNORE INTO tags (name) SELECT DISTINCT value FROM notes, json_each(notes.tags);
+    INSERT OR IGNORE INTO note_tags (note_id, tag_id, position)
+        SELECT notes.id, tags.id, json_each.key FROM notes, json_each(notes.tags) JOIN tags ON tags.name = json_each.value;
+    ALTER TABLE notes DROP COLUMN tags;",
+];
+
+/// Foreign keys are off by default in SQLite and have to be enabled on every connection
+const CONNECTION_PRAGMAS: &str = "PRAGMA foreign_keys = ON;";
+
+/// Columns note_from_row reads, in order; tags come back as a JSON array in the order they were given
+const NOTE_COLUMNS: &str = "id, title, created_at, audio_path, transcript, segments, summary, duration_secs,
+    (SELECT json_group_array(tags.name ORDER BY note_tags.position) FROM note_tags JOIN tags ON tags.id = note_tags.tag_id
+     WHERE note_tags.note_id = notes.id),
+    transcript_draft";
+
+/// `?n IS NULL OR` this keeps list_notes and search_notes to notes tagged ?n
+const TAG_FILTER: &str = "EXISTS (SELECT 1 FROM note_tags JOIN tags ON tags.id = note_tags.tag_id
+    WHERE note_tags.note_id = notes.id AND tags.name = ?{n})";
+
+const DEFAULT_LIST_LIMIT: usize = 50;
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct Note {
+    pub id: u64,
+    pub title: String,
+    /// Unix seconds
+    pub created_at: u64,
+    pub audio_path: Option<String>,
+    pub transcript: String,
+    /// Timestamped segments, when the transcript came from a detailed transcription
+    #[serde(default)]
+    pub segments: Vec<transcript::TranscriptSegment>,
+    pub summary: Option<String>,
+    pub duration_secs: Option<f64>,
+    /// Normalized: trimmed, lowercase and without duplicates
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// The transcript as it was before retranscribe_session replaced it
+    #[serde(default)]
+    pub transcript_draft: Option<String>,
+}
+
+/// A note as the frontend submits it to save_note
+#[derive(Deserialize, Clone, Debug, Default)]
+pub struct NewNote {
+    pub title: String,
+    pub audio_path: Option<String>,
+    pub transcript: String,
+    #[serde(default)]
+    pub segments: Vec<transcript::TranscriptSegment>,
+    pub summary: Option<String>,
+    pub duration_secs: Option<f64>,
+    #[serde(default)]
+    pub tags: Vec<String>,
+}
+
+/// Fields to change in update_note; omitted fields are left as they are
+#[derive(Deserialize, Clone, Debug, Default)]
+pub struct NotePatch {
+    pub title: Option<String>,
+    pub audio_path: Option<String>,
+    pub transcript: Option<String>,
+    pub segments: Option<Vec<transcript::TranscriptSegment>>,
+    pub summary: Option<String>,
+    pub duration_secs: Option<f64>,
+    pub tags: Option<Vec<String>>,
+    pub transcript_draft: Option<String>,
+}
+
+/// notes.json as the JSON store left it; `next_id` is kept so deleted ids aren't handed out again
+#[derive(Deserialize)]
+struct LegacyFile {
+    #[serde(default)]
+    next_id: u64,
+    #[serde(default)]
+    notes: Vec<Note>,
+}
+
+struct NotesStore {
+    conn: Connection,
+    /// notes.db was migrated by a newer version of the app; changing it could break that version's schema
+    read_only: bool,
+}
+
+impl NotesStore {
+    fn writable(&mut self) -> Result<&mut Connection, String> {
+        if self.read_only {
+            return Err("Notes were saved by a newer version of the app; update it to make changes".to_string());
+        }
+        Ok(&mut self.conn)
+    }
+
+    fn note(&self, id: u64) -> Result<Note, String> {
+        self.conn
+            .query_row(&format!("SELECT {} FROM notes WHERE id = ?1", NOTE_COLUMNS), [id], note_from_row)
+            .optional()
+            .map_err(db_error)?
+            .ok_or_else(|| format!("Note {} not found", id))
+    }
+
+    /// Every note, newest first
+    fn all_notes(&self) -> Result<Vec<Note>, String> {
+        let mut stmt = self
+            .conn
+            .prepare(&format!("SELECT {} FROM notes ORDER BY id DESC", NOTE_COLUMNS))
+            .map_err(db_error)?;
+        let notes = stmt.query_map([], note_from_row).map_err(db_error)?;
+        notes.collect::<Result<_, _>>().map_err(db_error)
+    }
+}
+
+// The notes database, opened (and migrated) at startup
+pub struct NotesState {
+    store: Mutex<NotesStore>,
+}
+
+fn db_error(e: rusqlite::Error) -> String {
+    format!("Notes database error: {}", e)
+}
+
+fn db_path() -> Result<PathBuf, String> {
+    Ok(get_data_dir()?.join(DB_FILE))
+}
+
+fn note_from_row(row: &rusqlite::Row) -> rusqlite::Result<Note> {
+    let json = |i: usize| row.get::<_, String>(i);
+    Ok(Note {
+        id: row.get(0)?,
+        title: row.get(1)?,
+        created_at: row.get(2)?,
+        audio_path: row.get(3)?,
+        transcript: row.get(4)?,
+        segments: serde_json::from_str(&json(5)?).unwrap_or_default(),
+        summary: row.get(6)?,
+        duration_secs: row.get(7)?,
+        tags: serde_json::from_str(&json(8)?).unwrap_or_default(),
+        transcript_draft: row.get(9)?,
+    })
+}
+
+/// Replace a note's tags with `tags` (already normalized), dropping tags no note uses any more
+fn set_tags(conn: &Connection, note_id: u64, tags: &[String]) -> rusqlite::Result<()> {
+    conn.execute("DELETE FROM note_tags WHERE note_id = ?1", [note_id])?;
+    for (position, tag) in tags.iter().enumerate() {
+        conn.execute("INSERT OR IGNORE INTO tags (name) VALUES (?1)", [tag])?;
+        conn.execute(
+            "INSERT INTO note_tags (note_id, tag_id, position) SELECT ?1, id, ?2 FROM tags WHERE name = ?3",
+            params![note_id, position, tag],
+        )?;
+    }
+    prune_tags(conn)
+}
+
+fn prune_tags(conn: &Connection) -> rusqlite::Result<()> {
+    conn.execute("DELETE FROM tags WHERE id NOT IN (SELECT tag_id FROM note_tags)", [])?;
+    Ok(())
+}
+
+/// Insert `note`, with its own id when it has one (imports) or the next free id when it's 0.
+/// Run it in a transaction, since the tags are written separately.
+fn insert_row(conn: &Connection, note: &Note) -> rusqlite::Result<u64> {
+    conn.execute(
+        "INSERT INTO notes (id, title, created_at, audio_path, transcript, segments, summary, duration_secs, transcript_draft)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+        params![
+            (note.id != 0).then_some(note.id),
+            note.title,
+            note.created_at,
+            note.audio_path,
+            note.transcript,
+            serde_json::to_string(&note.segments).unwrap_or_else(|_| "[]".to_string()),
+            note.summary,
+            note.duration_secs,
+            note.transcript_draft,
+        ],
+    )?;
+    let id = conn.last_insert_rowid() as u64;
+    set_tags(conn, id, &note.tags)?;
+    Ok(id)
+}
+
+/// Write every field of an existing note back; like insert_row, run it in a transaction
+fn update_row(conn: &Connection, note: &Note) -> rusqlite::Result<()> {
+    conn.execute(
+        "UPDATE notes SET title = ?2, audio_path = ?3, transcript = ?4, segments = ?5, summary = ?6, duration_secs = ?7,
+         transcript_draft = ?8 WHERE id = ?1",
+        params![
+            note.id,
+            note.title,
+            note.audio_path,
+            note.transcript,
+            serde_json::to_string(&note.segments).unwrap_or_else(|_| "[]".to_string()),
+            note.summary,
+            note.duration_secs,
+            note.transcript_draft,
+        ],
+    )?;
+    set_tags(conn, note.id, &note.tags)
+}
+
+/// Move an unusable file aside so it can be recovered by hand, rather than overwriting it
+fn set_aside(path: &Path, suffix: &str) {
+    let mut backup = path.as_os_str().to_owned();
+    backup.push(format!(".{}", suffix));
+    match fs::rename(path, &backup) {
+        Ok(()) => log::warn!("Moved {} to {}", path.display(), Path::new(&backup).display()),
+        Err(e) => log::error!("Failed to move {} aside: {}", path.display(), e),
+    }
+}
+
+/// Copy the notes out of notes.json into a new database, keeping their ids
+fn import_legacy(tx: &Transaction, legacy: &Path) -> Result<usize, String> {
+    let raw = match fs::read_to_string(legacy) {
+        Ok(raw) => raw,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(0),
+        Err(e) => return Err(format!("Failed to read {}: {}", legacy.display(), e)),
+    };
+    let file: LegacyFile = match serde_json::from_str(&raw) {
+        Ok(file) => file,
+        Err(e) => {
+            log::error!("Not importing {}: {}", legacy.display(), e);
+            set_aside(legacy, "corrupt");
+            return Ok(0);
+        }
+    };
+    for note in &file.notes {
+        let note = Note { tags: normalize_tags(note.tags.clone()), ..note.clone() };
+        insert_row(tx, &note).map_err(db_error)?;
+    }
+    // AUTOINCREMENT continues after its sequence, which has to cover ids the JSON store had already used
+    let max_id = file.notes.iter().map(|n| n.id).max().unwrap_or(0);
+    let used = max_id.max(file.next_id.saturating_sub(1));
+    tx.execute("DELETE FROM sqlite_sequence WHERE name = 'notes'", []).map_err(db_error)?;
+    tx.execute("INSERT INTO sqlite_sequence (name, seq) VALUES ('notes', ?1)", [used]).map_err(db_error)?;
+    Ok(file.notes.len())
+}
+
+/// Bring the schema from `from` up to SCHEMA_VERSION in one transaction; a new database also imports notes.json
+fn migrate(conn: &mut Connection, from: usize, legacy: Option<&Path>) -> Result<usize, String> {
+    let tx = conn.transaction().map_err(db_error)?;
+    for (version, migration) in MIGRATIONS.iter().enumerate().skip(from) {
+        log::info!("Migrating the notes database to schema version {}", version + 1);
+        tx.execute_batch(migration).map_err(db_error)?;
+    }
+    tx.execute("DELETE FROM schema_version", []).map_err(db_error)?;
+    tx.execute("INSERT INTO schema_version (version) VALUES (?1)", [SCHEMA_VERSION]).map_err(db_error)?;
+    let imported = match legacy.filter(|_| from == 0) {
+        Some(legacy) => import_legacy(&tx, legacy)?,
+        None => 0,
+    };
+    tx.commit().map_err(db_error)?;
+    Ok(imported)
+}
+
+/// Open notes.db, creating it on first run (importing notes.json) and migrating older schemas
+fn open_store(path: &Path) -> Result<NotesStore, String> {
+    let mut conn = Connection::open(path).map_err(|e| format!("Failed to open {}: {}", path.display(), e))?;
+    let version = match conn
+        .execute_batch(CONNECTION_PRAGMAS)
+        .and_then(|()| conn.execute_batch("CREATE TABLE IF NOT EXISTS schema_version (version INTEGER NOT NULL)"))
+        .and_then(|()| conn.query_row("SELECT MAX(version) FROM schema_version", [], |r| r.get::<_, Option<usize>>(0)))
+    {
+        Ok(version) => version.unwrap_or(0),
+        Err(e) if e.sqlite_error_code() == Some(rusqlite::ErrorCode::NotADatabase) => {
+            drop(conn);
+            set_aside(path, "corrupt");
+            return open_store(path);
+        }
+        Err(e) => return Err(db_error(e)),
+    };
+
+    if version > SCHEMA_VERSION {
+        log::warn!("notes.db has schema version {} (this build knows {}); opening read-only", version, SCHEMA_VERSION);
+        return Ok(NotesStore { conn, read_only: true });
+    }
+    if version < SCHEMA_VERSION {
+        if version > 0 {
+            // Keep the pre-migration database; a bad migration shouldn't cost anyone their notes
+            let _ = fs::copy(path, path.with_extension(format!("db.v{}", version)));
+        }
+        let legacy = path.with_file_name(LEGACY_NOTES_FILE);
+        let imported = migrate(&mut conn, version, Some(&legacy))?;
+        if imported > 0 {
+            log::info!("Imported {} notes from {}", imported, legacy.display());
+        }
+        if version == 0 && legacy.exists() {
+            set_aside(&legacy, "imported");
+        }
+    }
+    Ok(NotesStore { conn, read_only: false })
+}
+
+/// An empty in-memory database, for when notes.db can't be opened
+fn empty_store() -> Result<NotesStore, String> {
+    let mut conn = Connection::open_in_memory().map_err(db_error)?;
+    conn.execute_batch(CONNECTION_PRAGMAS).map_err(db_error)?;
+    conn.execute_batch("CREATE TABLE schema_version (version INTEGER NOT NULL)").map_err(db_error)?;
+    migrate(&mut conn, 0, None)?;
+    Ok(NotesStore { conn, read_only: true })
+}
+
+impl NotesState {
+    /// Open the notes database in the data dir; problems are logged and leave an empty, read-only store
+    pub fn load() -> Self {
+        let store = db_path().and_then(|path| open_store(&path)).unwrap_or_else(|e| {
+            log::error!("{}", e);
+            empty_store().expect("an in-memory SQLite database always opens")
+        });
+        let count: u64 = store.conn.query_row("SELECT COUNT(*) FROM notes", [], |r| r.get(0)).unwrap_or(0);
+        log::info!("Loaded {} notes", count);
+        NotesState { store: Mutex::new(store) }
+    }
+}
+
+/// Id of the most recently created note
+pub fn latest_id(app: &tauri::AppHandle) -> Option<u64> {
+    let state = app.state::<NotesState>();
+    let store = state.store.lock().unwrap();
+    store
+        .conn
+        .query_row("SELECT id FROM notes ORDER BY created_at DESC, id DESC LIMIT 1", [], |r| r.get(0))
+        .optional()
+        .ok()
+        .flatten()
+}
+
+/// Notes that have audio, newest first
+fn notes_with_audio(store: &NotesStore) -> Vec<Note> {
+    store
+        .all_notes()
+        .unwrap_or_default()
+        .into_iter()
+        .filter(|n| n.audio_path.is_some())
+        .collect()
+}
+
+/// The newest note whose audio is `path`
+pub fn with_audio(app: &tauri::AppHandle, path: &Path) -> Option<Note> {
+    let resolved = path.canonicalize().ok();
+    let matches = |audio: &Path| audio == path || (resolved.is_some() && audio.canonicalize().ok() == resolved);
+    let state = app.state::<NotesState>();
+    let store = state.store.lock().unwrap();
+    let mut notes = notes_with_audio(&store);
+    notes.sort_by_key(|n| std::cmp::Reverse((n.created_at, n.id)));
+    notes.into_iter().find(|n| n.audio_path.as_deref().map(Path::new).is_some_and(matches))
+}
+
+/// Set the duration of every note whose audio is `path`, after the file was cut; returns how many changed
+pub fn audio_edited(app: &tauri::AppHandle, path: &Path, duration_secs: f64) -> Result<usize, String> {
+    let path = path.canonicalize().map_err(|e| format!("Failed to resolve {}: {}", path.display(), e))?;
+    let state = app.state::<NotesState>();
+    let mut store = state.store.lock().unwrap();
+    let ids: Vec<u64> = notes_with_audio(&store)
+        .into_iter()
+        .filter(|n| n.audio_path.as_deref().and_then(|a| Path::new(a).canonicalize().ok()).as_ref() == Some(&path))
+        .map(|n| n.id)
+        .collect();
+    if ids.is_empty() {
+        return Ok(0);
+    }
+    let tx = store.writable()?.transaction().map_err(db_error)?;
+    for id in &ids {
+        tx.execute("UPDATE notes SET duration_secs = ?2 WHERE id = ?1", params![id, duration_secs])
+            .map_err(db_error)?;
+    }
+    tx.commit().map_err(db_error)?;
+    Ok(ids.len())
+}
+
+/// Add a note to the store and return it with its id
+pub fn insert(app: &tauri::AppHandle, note: NewNote) -> Result<Note, String> {
+    let state = app.state::<NotesState>();
+    let mut store = state.store.lock().unwrap();
+    let mut note = Note {
+        id: 0,
+        title: note.title,
+        created_at: SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0),
+        audio_path: note.audio_path,
+        transcript: note.transcript,
+        segments: note.segments,
+        summary: note.summary,
+        duration_secs: note.duration_secs,
+        tags: normalize_tags(note.tags),
+        transcript_draft: None,
+    };
+    let tx = store.writable()?.transaction().map_err(db_error)?;
+    note.id = insert_row(&tx, &note).map_err(db_error)?;
+    tx.commit().map_err(db_error)?;
+    log::info!("Saved note {} ({})", note.id, note.title);
+    Ok(note)
+}
+
+/// Fill in a generated title when the note has none, falling back to the transcript's opening
+/// when titling fails so the note is still saved
+pub async fn with_title(app: &tauri::AppHandle, mut note: NewNote) -> NewNote {
+    if note.title.trim().is_empty() {
+        note.title = match summarize::generate_title(app, &note.transcript).await {
+            Ok(generated) => generated.title,
+            Err(e) => {
+                log::warn!("Titling failed, using the transcript's opening instead: {}", e);
+                summarize::fallback_title(&note.transcript)
+            }
+        };
+    }
+    note
+}
+
+/// Save a note, generating a title for it if `title` is empty
+#[tauri::command]
+pub async fn save_note(app: tauri::AppHandle, note: NewNote) -> Result<Note, String> {
+    let note = with_title(&app, note).await;
+    insert(&app, note)
+}
+
+/// A copy of one note
+pub fn get(app: &tauri::AppHandle, id: u64) -> Result<Note, String> {
+    let state = app.state::<NotesState>();
+    let store = state.store.lock().unwrap();
+    store.note(id)
+}
+
+#[tauri::command]
+pub async fn get_note(app: tauri::AppHandle, id: u64) -> Result<Note, String> {
+    get(&app, id)
+}
+
+/// Notes newest first, `limit` (default 50) at a time, only those tagged `tag` if given
+#[tauri::command]
+pub async fn list_notes(
+    state: tauri::State<'_, NotesState>,
+    offset: Option<usize>,
+    limit: Option<usize>,
+    tag: Option<String>,
+) -> Result<Vec<Note>, String> {
+    let tag = tag.map(|t| normalize_tag(&t));
+    let store = state.store.lock().unwrap();
+    let mut stmt = store
+        .conn
+        .prepare(&format!(
+            "SELECT {} FROM notes
+             WHERE ?1 IS NULL OR {}
+             ORDER BY id DESC LIMIT ?2 OFFSET ?3",
+            NOTE_COLUMNS,
+            TAG_FILTER.replace("{n}", "1")
+        ))
+        .map_err(db_error)?;
+    let notes = stmt
+        .query_map(params![tag, limit.unwrap_or(DEFAULT_LIST_LIMIT), offset.unwrap_or(0)], note_from_row)
+        .map_err(db_error)?;
+    notes.collect::<Result<_, _>>().map_err(db_error)
+}
+
+/// Apply `patch` to a note and save, returning the updated note
+pub fn update(app: &tauri::AppHandle, id: u64, patch: NotePatch) -> Result<Note, String> {
+    let state = app.state::<NotesState>();
+    let mut store = state.store.lock().unwrap();
+    let mut note = store.note(id)?;
+    if let Some(title) = patch.title {
+        note.title = title;
+    }
+    if let Some(audio_path) = patch.audio_path {
+        note.audio_path = Some(audio_path);
+    }
+    if let Some(transcript) = patch.transcript {
+        note.transcript = transcript;
+    }
+    if let Some(segments) = patch.segments {
+        note.segments = segments;
+    }
+    if let Some(summary) = patch.summary {
+        note.summary = Some(summary);
+    }
+    if let Some(duration) = patch.duration_secs {
+        note.duration_secs = Some(duration);
+    }
+    if let Some(tags) = patch.tags {
+        note.tags = normalize_tags(tags);
+    }
+    if let Some(draft) = patch.transcript_draft {
+        note.transcript_draft = Some(draft);
+    }
+    let tx = store.writable()?.transaction().map_err(db_error)?;
+    update_row(&tx, &note).map_err(db_error)?;
+    tx.commit().map_err(db_error)?;
+    Ok(note)
+}
+
+#[tauri::command]
+pub async fn update_note(app: tauri::AppHandle, id: u64, patch: NotePatch) -> Result<Note, String> {
+    update(&app, id, patch)
+}
+
+/// Delete a note, and its recording too when `delete_audio` is set
+#[tauri::command]
+pub async fn delete_note(state: tauri::State<'_, NotesState>, id: u64, delete_audio: bool) -> Result<(), String> {
+    let mut store = state.store.lock().unwrap();
+    let note = store.note(id)?;
+    let tx = store.writable()?.transaction().map_err(db_error)?;
+    tx.execute("DELETE FROM notes WHERE id = ?1", [id]).map_err(db_error)?;
+    prune_tags(&tx).map_err(db_error)?;
+    tx.commit().map_err(db_error)?;
+    if delete_audio {
+        if let Some(audio) = note.audio_path.as_deref().map(Path::new).filter(|p| p.is_file()) {
+            fs::remove_file(audio).map_err(|e| format!("Note deleted, but removing {} failed: {}", audio.display(), e))?;
+        }
+    }
+    log::info!("Deleted note {}", id);
+    Ok(())
+}
+
+const DEFAULT_SEARCH_LIMIT: usize = 20;
+
+/// Tokens of context FTS5's snippet() keeps around the best match
+const SNIPPET_TOKENS: u32 = 17;
+
+const HIGHLIGHT_START: &str = "<mark>";
+const HIGHLIGHT_END: &str = "</mark>";
+
+/// What snippet() wraps matches in; control characters that never survive into a transcript, so the text
+/// around them can be HTML-escaped before they become <mark></mark>
+const RAW_HIGHLIGHT_START: char = '\u{1}';
+const RAW_HIGHLIGHT_END: char = '\u{2}';
+
+/// Where a query term matched, in characters (not bytes) into the field's text
+#[derive(Serialize, Clone, Debug)]
+pub struct SearchMatch {
+    /// "transcript" or "summary"
+    pub field: &'static str,
+    pub start: usize,
+    pub end: usize,
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct SearchResult {
+    pub note_id: u64,
+    pub title: String,
+    pub created_at: u64,
+    /// HTML-escaped text around the best match, with matches wrapped in <mark></mark>
+    pub snippet: String,
+    pub matches: Vec<SearchMatch>,
+}
+
+/// A query word; a trailing * matches any word starting with it
+struct Term {
+    text: String,
+    prefix: bool,
+}
+
+/// FTS5 query operators; they're dropped rather than searched for
+const QUERY_OPERATORS: &[&str] = &["AND", "OR", "NOT", "NEAR"];
+
+/// Split a query into plain words, so quotes, operators and other punctuation can never make it invalid
+fn parse_query(query: &str) -> Vec<Term> {
+    query
+        .split_whitespace()
+        .filter(|word| !QUERY_OPERATORS.contains(&word.trim_matches(|c: char| !c.is_alphanumeric())))
+        .filter_map(|word| {
+            let prefix = word.trim_end_matches(|c: char| !c.is_alphanumeric() && c != '*').ends_with('*');
+            let text: String = word.chars().filter(|c| c.is_alphanumeric()).flat_map(char::to_lowercase).collect();
+            (!text.is_empty()).then_some(Term { text, prefix })
+        })
+        .collect()
+}
+
+/// The FTS5 MATCH expression for `terms`: each one quoted (they're alphanumeric, so quoting is all the escaping
+/// needed), prefix terms starred, all of them required
+fn fts_query(terms: &[Term]) -> String {
+    terms
+        .iter()
+        .map(|term| format!("\"{}\"{}", term.text, if term.prefix { "*" } else { "" }))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn escape_html(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// snippet()'s output escaped for display, its raw markers turned into <mark></mark>
+fn highlight_snippet(raw: &str) -> String {
+    escape_html(raw).replace(RAW_HIGHLIGHT_START, HIGHLIGHT_START).replace(RAW_HIGHLIGHT_END, HIGHLIGHT_END)
+}
+
+/// Words of `text` with their char ranges, lowercased for matching
+fn words(chars: &[char]) -> Vec<(String, usize, usize)> {
+    let mut words = Vec::new();
+    let mut start = None;
+    for (i, c) in chars.iter().chain(std::iter::once(&' ')).enumerate() {
+        match (c.is_alphanumeric(), start) {
+            (true, None) => start = Some(i),
+            (false, Some(s)) => {
+                words.push((chars[s..i].iter().flat_map(|c| c.to_lowercase()).collect(), s, i));
+                start = None;
+            }
+            _ => {}
+        }
+    }
+    words
+}
+
+fn term_matches(term: &Term, word: &str) -> bool {
+    if term.prefix {
+        word.starts_with(&term.text)
+    } else {
+        word == term.text
+    }
+}
+
+/// Char ranges of every word in `text` that one of `terms` matches, so the UI can jump to them
+fn field_matches(field: &'static str, text: &str, terms: &[Term]) -> Vec<SearchMatch> {
+    let chars: Vec<char> = text.chars().collect();
+    words(&chars)
+        .into_iter()
+        .filter(|(word, _, _)| terms.iter().any(|term| term_matches(term, word)))
+        .map(|(_, start, end)| SearchMatch { field, start, end })
+        .collect()
+}
+
+/// Notes whose transcript or summary contain every word of `query`, best match first (FTS5's bm25 rank),
+/// optionally only those tagged `tag`. The query is taken as plain words; a trailing * makes a word a prefix.
+#[tauri::command]
+pub async fn search_notes(
+    state: tauri::State<'_, NotesState>,
+    query: String,
+    limit: Option<usize>,
+    tag: Option<String>,
+) -> Result<Vec<SearchResult>, String> {
+    search(&state.store.lock().unwrap(), &query, limit, tag.as_deref())
+}
+
+fn search(store: &NotesStore, query: &str, limit: Option<usize>, tag: Option<&str>) -> Result<Vec<SearchResult>, String> {
+    let terms = parse_query(query);
+    if terms.is_empty() {
+        return Ok(Vec::new());
+    }
+    let tag = tag.map(normalize_tag);
+    let mut stmt = store
+        .conn
+        .prepare(&format!(
+            "SELECT notes.id, notes.title, notes.created_at, notes.transcript, notes.summary,
+                    snippet(notes_fts, -1, ?2, ?3, '…', ?4)
+             FROM notes_fts JOIN notes ON notes.id = notes_fts.rowid
+             WHERE notes_fts MATCH ?1 AND (?5 IS NULL OR {})
+             ORDER BY rank LIMIT ?6",
+            TAG_FILTER.replace("{n}", "5")
+        ))
+        .map_err(db_error)?;
+    let rows = stmt
+        .query_map(
+            params![
+                fts_query(&terms),
+                RAW_HIGHLIGHT_START.to_string(),
+                RAW_HIGHLIGHT_END.to_string(),
+                SNIPPET_TOKENS,
+                tag,
+                limit.unwrap_or(DEFAULT_SEARCH_LIMIT),
+            ],
+            |row| {
+                let transcript: String = row.get(3)?;
+                let summary: Option<String> = row.get(4)?;
+                let mut matches = field_matches("transcript", &transcript, &terms);
+                if let Some(summary) = &summary {
+                    matches.extend(field_matches("summary", summary, &terms));
+                }
+                Ok(SearchResult {
+                    note_id: row.get(0)?,
+                    title: row.get(1)?,
+                    created_at: row.get(2)?,
+                    snippet: highlight_snippet(&row.get::<_, String>(5)?),
+                    matches,
+                })
+            },
+        )
+        .map_err(db_error)?;
+    rows.collect::<Result<_, _>>().map_err(db_error)
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct TagCount {
+    pub name: String,
+    pub notes: usize,
+}
+
+fn normalize_tag(tag: &str) -> String {
+    tag.trim().to_lowercase()
+}
+
+/// Trim and lowercase tags, dropping empty ones and repeats while keeping their order
+pub fn normalize_tags(tags: Vec<String>) -> Vec<String> {
+    let mut normalized: Vec<String> = Vec::with_capacity(tags.len());
+    for tag in tags.iter().map(|t| normalize_tag(t)) {
+        if !tag.is_empty() && !normalized.contains(&tag) {
+            normalized.push(tag);
+        }
+    }
+    normalized
+}
+
+/// Replace a note's tags
+#[tauri::command]
+pub async fn set_note_tags(app: tauri::AppHandle, note_id: u64, tags: Vec<String>) -> Result<Note, String> {
+    update(&app, note_id, NotePatch { tags: Some(tags), ..Default::default() })
+}
+
+/// Every tag in use with how many notes carry it, alphabetically
+#[tauri::command]
+pub async fn list_tags(state: tauri::State<'_, NotesState>) -> Result<Vec<TagCount>, String> {
+    tag_counts(&state.store.lock().unwrap().conn).map_err(db_error)
+}
+
+fn tag_counts(conn: &Connection) -> rusqlite::Result<Vec<TagCount>> {
+    let mut stmt = conn.prepare(
+        "SELECT tags.name, COUNT(*) FROM tags JOIN note_tags ON note_tags.tag_id = tags.id GROUP BY tags.id ORDER BY tags.name",
+    )?;
+    let counts = stmt.query_map([], |row| Ok(TagCount { name: row.get(0)?, notes: row.get(1)? }))?;
+    counts.collect()
+}
+
+/// Rename a tag on every note in one transaction, merging it into `new` where a note already has both;
+/// returns how many notes changed
+#[tauri::command]
+pub async fn rename_tag(state: tauri::State<'_, NotesState>, old: String, new: String) -> Result<usize, String> {
+    rename(&mut state.store.lock().unwrap(), &old, &new)
+}
+
+fn rename(store: &mut NotesStore, old: &str, new: &str) -> Result<usize, String> {
+    let (old, new) = (normalize_tag(old), normalize_tag(new));
+    if new.is_empty() {
+        return Err("Tag name can't be empty".to_string());
+    }
+    let tx = store.writable()?.transaction().map_err(db_error)?;
+    let tag_id = |name: &str| {
+        tx.query_row("SELECT id FROM tags WHERE name = ?1", [name], |r| r.get::<_, u64>(0)).optional().map_err(db_error)
+    };
+    let Some(old_id) = tag_id(&old)? else {
+        return Err(format!("No notes are tagged '{}'", old));
+    };
+    let tagged: usize =
+        tx.query_row("SELECT COUNT(*) FROM note_tags WHERE tag_id = ?1", [old_id], |r| r.get(0)).map_err(db_error)?;
+    if old == new {
+        return Ok(0);
+    }
+    match tag_id(&new)? {
+        None => {
+            tx.execute("UPDATE tags SET name = ?2 WHERE id = ?1", params![old_id, new]).map_err(db_error)?;
+        }
+        // Merge: notes with only `old` move over; those that already have `new` just lose `old`
+        Some(new_id) => {
+            tx.execute("UPDATE OR IGNORE note_tags SET tag_id = ?2 WHERE tag_id = ?1", params![old_id, new_id])
+                .map_err(db_error)?;
+            tx.execute("DELETE FROM tags WHERE id = ?1", [old_id]).map_err(db_error)?;
+        }
+    }
+    tx.commit().map_err(db_error)?;
+    log::info!("Renamed tag '{}' to '{}' on {} notes", old, new, tagged);
+    Ok(tagged)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn scratch() -> PathBuf {
+        let dir = std::env::temp_dir().join(crate::audio::unique_name("notes-test"));
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn imports_notes_json_once_keeping_ids() {
+        let dir = scratch();
+        let legacy = r#"{"schema_version": 2, "next_id": 8, "notes": [
+            {"id": 3, "title": "Standup", "created_at": 10, "audio_path": null, "transcript": "pricing change",
+             "summary": null, "duration_secs": 4.5, "tags": [" Work ", "work"]},
+            {"id": 5, "title": "Lecture", "created_at": 20, "audio_path": "/tmp/lecture.wav", "transcript": "",
+             "summary": "notes", "duration_secs": null}
+        ]}"#;
+        fs::write(dir.join(LEGACY_NOTES_FILE), legacy).unwrap();
+
+        let mut store = open_store(&dir.join(DB_FILE)).unwrap();
+        let notes = store.all_notes().unwrap();
+        assert_eq!(notes.iter().map(|n| n.id).collect::<Vec<_>>(), vec![5, 3]);
+        assert_eq!(notes[1].tags, vec!["work"]);
+        assert_eq!(notes[0].audio_path.as_deref(), Some("/tmp/lecture.wav"));
+        assert!(!dir.join(LEGACY_NOTES_FILE).exists());
+        assert!(dir.join("notes.json.imported").exists());
+
+        // Ids the JSON store had handed out aren't reused
+        let note = Note { id: 0, title: "New".into(), created_at: 30, transcript: "x".into(), ..notes[1].clone() };
+        assert_eq!(insert_row(store.writable().unwrap(), &note).unwrap(), 8);
+        drop(store);
+
+        // A notes.json appearing later is left alone
+        fs::write(dir.join(LEGACY_NOTES_FILE), legacy).unwrap();
+        let store = open_store(&dir.join(DB_FILE)).unwrap();
+        assert_eq!(store.all_notes().unwrap().len(), 3);
+        assert!(dir.join(LEGACY_NOTES_FILE).exists());
+    }
+
+    fn note(title: &str, transcript: &str, summary: Option<&str>) -> Note {
+        Note {
+            id: 0,
+            title: title.to_string(),
+            created_at: 0,
+            audio_path: None,
+            transcript: transcript.to_string(),
+            segments: Vec::new(),
+            summary: summary.map(str::to_string),
+            duration_secs: None,
+            tags: Vec::new(),
+            transcript_draft: None,
+        }
+    }
+
+    #[test]
+    fn search_uses_the_index_and_escapes_snippets() {
+        let dir = scratch();
+        let mut store = open_store(&dir.join(DB_FILE)).unwrap();
+        let conn = store.writable().unwrap();
+        let pricing = insert_row(conn, &note("Pricing", "We discussed the <b>pricing</b> change & its timing", None)).unwrap();
+        insert_row(conn, &note("Other", "Nothing relevant here", Some("prices stay"))).unwrap();
+
+        let results = search(&store, "pricing", None, None).unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].note_id, pricing);
+        assert_eq!(results[0].snippet, "We discussed the &lt;b&gt;<mark>pricing</mark>&lt;/b&gt; change &amp; its timing");
+        assert_eq!(results[0].matches.len(), 1);
+        assert_eq!((results[0].matches[0].start, results[0].matches[0].end), (20, 27));
+
+        // Prefix terms reach the summary too; FTS syntax in a query is taken as plain words
+        assert_eq!(search(&store, "pric*", None, None).unwrap().len(), 2);
+        assert_eq!(search(&store, "\"pricing OR (NEAR", None, None).unwrap().len(), 1);
+        assert!(search(&store, "\"*:", None, None).unwrap().is_empty());
+    }
+
+    #[test]
+    fn search_index_follows_updates_and_deletes() {
+        let dir = scratch();
+        let mut store = open_store(&dir.join(DB_FILE)).unwrap();
+        let conn = store.writable().unwrap();
+        let id = insert_row(conn, &note("Draft", "first wording", None)).unwrap();
+        update_row(conn, &Note { id, ..note("Draft", "second wording", None) }).unwrap();
+        assert!(search(&store, "first", None, None).unwrap().is_empty());
+        assert_eq!(search(&store, "second", None, None).unwrap().len(), 1);
+
+        store.writable().unwrap().execute("DELETE FROM notes WHERE id = ?1", [id]).unwrap();
+        assert!(search(&store, "second", None, None).unwrap().is_empty());
+    }
+
+    fn tagged(title: &str, tags: &[&str]) -> Note {
+        Note { tags: tags.iter().map(|t| t.to_string()).collect(), ..note(title, "", None) }
+    }
+
+    fn counts(store: &NotesStore) -> Vec<(String, usize)> {
+        tag_counts(&store.conn).unwrap().into_iter().map(|t| (t.name, t.notes)).collect()
+    }
+
+    #[test]
+    fn renaming_a_tag_merges_it_in_one_transaction() {
+        let dir = scratch();
+        let mut store = open_store(&dir.join(DB_FILE)).unwrap();
+        let conn = store.writable().unwrap();
+        let both = insert_row(conn, &tagged("Both", &["lectures", "lecture"])).unwrap();
+        insert_row(conn, &tagged("Old", &["lecture", "personal"])).unwrap();
+        insert_row(conn, &tagged("New", &["lectures"])).unwrap();
+
+        assert_eq!(rename(&mut store, "Lecture", "lectures").unwrap(), 2);
+        assert_eq!(counts(&store), vec![("lectures".to_string(), 3), ("personal".to_string(), 1)]);
+        assert_eq!(store.note(both).unwrap().tags, vec!["lectures"]);
+
+        assert_eq!(rename(&mut store, "personal", "1:1s").unwrap(), 1);
+        assert_eq!(counts(&store), vec![("1:1s".to_string(), 1), ("lectures".to_string(), 3)]);
+        assert!(rename(&mut store, "personal", "other").is_err());
+        assert!(rename(&mut store, "lectures", " ").is_err());
+    }
+
+    #[test]
+    fn tags_filter_searches_and_follow_deleted_notes() {
+        let dir = scratch();
+        let mut store = open_store(&dir.join(DB_FILE)).unwrap();
+        let conn = store.writable().unwrap();
+        let work = insert_row(conn, &Note { transcript: "pricing".into(), ..tagged("Work", &["work"]) }).unwrap();
+        insert_row(conn, &Note { transcript: "pricing".into(), ..tagged("Home", &["personal"]) }).unwrap();
+
+        let results = search(&store, "pricing", None, Some("WORK")).unwrap();
+        assert_eq!(results.iter().map(|r| r.note_id).collect::<Vec<_>>(), vec![work]);
+
+        store.writable().unwrap().execute("DELETE FROM notes WHERE id = ?1", [work]).unwrap();
+        prune_tags(&store.conn).unwrap();
+        assert_eq!(counts(&store), vec![("personal".to_string(), 1)]);
+    }
+
+    #[test]
+    fn migrates_json_tags_into_the_tag_tables() {
+        let dir = scratch();
+        let path = dir.join(DB_FILE);
+        let conn = Connection::open(&path).unwrap();
+        conn.execute_batch("CREATE TABLE schema_version (version INTEGER NOT NULL); INSERT INTO schema_version VALUES (2);")
+            .unwrap();
+        for migration in &MIGRATIONS[..2] {
+            conn.execute_batch(migration).unwrap();
+        }
+        conn.execute(
+            "INSERT INTO notes (id, title, created_at, transcript, tags) VALUES (4, 'Old', 0, '', '[\"work\",\"1:1s\"]')",
+            [],
+        )
+        .unwrap();
+        drop(conn);
+
+        let store = open_store(&path).unwrap();
+        assert_eq!(store.note(4).unwrap().tags, vec!["work", "1:1s"]);
+        assert_eq!(counts(&store), vec![("1:1s".to_string(), 1), ("work".to_string(), 1)]);
+        assert!(dir.join("notes.db.v2").exists());
+    }
+
+    #[test]
+    fn newer_schema_opens_read_only() {
+        let dir = scratch();
+        let path = dir.join(DB_FILE);
+        drop(open_store(&path).unwrap());
+        let conn = Connection::open(&path).unwrap();
+        conn.execute("UPDATE schema_version SET version = ?1", [SCHEMA_VERSION + 1]).unwrap();
+        drop(conn);
+
+        let mut store = open_store(&path).unwrap();
+        assert!(store.read_only);
+        assert!(store.writable().is_err());
+    }
+
+    #[test]
+    fn corrupt_database_is_set_aside() {
+        let dir = scratch();
+        let path = dir.join(DB_FILE);
+        fs::write(&path, b"this is not a database").unwrap();
+        let store = open_store(&path).unwrap();
+        assert!(store.all_notes().unwrap().is_empty());
+        assert!(dir.join("notes.db.corrupt").exists());
+    }
+}
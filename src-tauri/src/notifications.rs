@@ -0,0 +1,144 @@
+use serde::Serialize;
+use std::path::Path;
+use std::sync::Mutex;
+use tauri::{Emitter, Manager};
+
+use crate::recorder_status;
+use crate::{settings, tray};
+
+/// Longest excerpt put in a notification body
+const MAX_BODY_CHARS: usize = 160;
+
+#[derive(Serialize, Clone, Copy, PartialEq, Eq, Debug)]
+#[serde(rename_all = "kebab-case")]
+pub enum NotificationKind {
+    Transcription,
+    Summary,
+    Batch,
+    AutoStop,
+}
+
+impl NotificationKind {
+    fn enabled(self, toggles: &settings::NotificationToggles) -> bool {
+        match self {
+            NotificationKind::Transcription => toggles.transcription,
+            NotificationKind::Summary => toggles.summary,
+            NotificationKind::Batch => toggles.batch,
+            NotificationKind::AutoStop => toggles.auto_stop,
+        }
+    }
+}
+
+/// Sent to the frontend when a notification is clicked
+#[derive(Serialize, Clone)]
+struct NotificationClicked {
+    kind: NotificationKind,
+    /// The file, folder or recording the notification was about
+    target: Option<String>,
+}
+
+struct Pending {
+    kind: NotificationKind,
+    title: String,
+    body: String,
+    target: Option<String>,
+}
+
+// Notifications held back while recording, shown once it stops
+pub struct NotificationState {
+    held: Mutex<Vec<Pending>>,
+}
+
+impl NotificationState {
+    pub fn new() -> Self {
+        NotificationState { held: Mutex::new(Vec::new()) }
+    }
+}
+
+/// A file's name for a notification, falling back to the whole path
+pub fn display_name(path: &str) -> String {
+    Path::new(path)
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_else(|| path.to_string())
+}
+
+/// The first non-blank line of an error or summary, shortened to fit a notification
+pub fn first_line(text: &str) -> String {
+    let line = text.lines().find(|l| !l.trim().is_empty()).unwrap_or("").trim();
+    if line.chars().count() <= MAX_BODY_CHARS {
+        return line.to_string();
+    }
+    let mut short: String = line.chars().take(MAX_BODY_CHARS - 1).collect();
+    short.push('…');
+    short
+}
+
+fn recording(app: &tauri::AppHandle) -> bool {
+    recorder_status::current(app).recording()
+}
+
+/// Show the notification and wait, on its own thread, for it to be clicked or dismissed
+fn show(app: &tauri::AppHandle, pending: Pending) {
+    let app = app.clone();
+    // Waiting lasts as long as the notification stays up, so it doesn't tie up the blocking pool
+    std::thread::spawn(move || {
+        let mut notification = notify_rust::Notification::new();
+        notification.appname("last-gen-notes").summary(&pending.title).body(&pending.body);
+        #[cfg(all(unix, not(target_os = "macos")))]
+        notification.action("default", "Open");
+        match notification.show() {
+            Ok(handle) => handle.wait_for_action(|action| {
+                if action != "__closed" {
+                    tray::show_main_window(&app);
+                    let _ = app.emit("notification-clicked", NotificationClicked {
+                        kind: pending.kind,
+                        target: pending.target.clone(),
+                    });
+                }
+            }),
+            Err(e) => log::warn!("Failed to show notification '{}': {}", pending.title, e),
+        }
+    });
+}
+
+/// Notify about a finished job, if notifications of its kind are on. While recording they're held
+/// until it stops when quiet_while_recording is set.
+pub fn notify(app: &tauri::AppHandle, kind: NotificationKind, title: &str, body: &str, target: Option<String>) {
+    let settings = settings::current(app);
+    if !settings.notifications_enabled || !kind.enabled(&settings.notify_on) {
+        return;
+    }
+    let pending = Pending { kind, title: title.to_string(), body: body.to_string(), target };
+    if settings.quiet_while_recording && recording(app) {
+        app.state::<NotificationState>().held.lock().unwrap().push(pending);
+        return;
+    }
+    show(app, pending);
+}
+
+/// Notify that transcribing `path` finished, or failed with `error`
+pub fn transcription_finished(app: &tauri::AppHandle, path: &str, error: Option<String>) {
+    let name = display_name(path);
+    match error {
+        None => notify(app, NotificationKind::Transcription, "Transcription finished", &name, Some(path.to_string())),
+        Some(e) => notify(
+            app,
+            NotificationKind::Transcription,
+            "Transcription failed",
+            &format!("{}: {}", name, first_line(&e)),
+            Some(path.to_string()),
+        ),
+    }
+}
+
+/// Show whatever was held back once nothing is recording any more
+pub fn recording_ended(app: &tauri::AppHandle) {
+    if recording(app) {
+        return;
+    }
+    let held = std::mem::take(&mut *app.state::<NotificationState>().held.lock().unwrap());
+    for pending in held {
+        show(app, pending);
+    }
+}
@@ -0,0 +1,183 @@
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+use tauri_plugin_fs::FsExt;
+
+use crate::error::AppError;
+use crate::{get_cache_dir, get_data_dir};
+
+fn not_allowed(path: &str, message: String) -> AppError {
+    AppError::PathNotAllowed { path: path.to_string(), message }
+}
+
+/// A bare file name ending in one of `extensions`, safe to join onto a directory
+pub fn file_name<'a>(filename: &'a str, extensions: &[&str]) -> Result<&'a Path, AppError> {
+    let name = Path::new(filename);
+    let mut components = name.components();
+    let single = matches!((components.next(), components.next()), (Some(Component::Normal(_)), None));
+    // Backslashes are separators on Windows only, but a name with one is never legitimate anywhere
+    if !single || filename.contains(['/', '\\']) {
+        return Err(not_allowed(filename, format!("'{}' must be a plain file name", filename)));
+    }
+    let lower = filename.to_lowercase();
+    if !extensions.iter().any(|ext| lower.ends_with(&format!(".{}", ext))) {
+        return Err(not_allowed(
+            filename,
+            format!("'{}' must end in .{}", filename, extensions.join(", .")),
+        ));
+    }
+    Ok(name)
+}
+
+/// `filename` in the cache dir, refusing one that's a symlink out of it or to nothing
+pub fn in_cache_dir(filename: &str, extensions: &[&str]) -> Result<PathBuf, AppError> {
+    in_dir(&get_cache_dir()?, filename, extensions)
+}
+
+fn in_dir(dir: &Path, filename: &str, extensions: &[&str]) -> Result<PathBuf, AppError> {
+    let name = file_name(filename, extensions)?;
+    let dir = dir.canonicalize().map_err(|e| AppError::io("Failed to resolve cache directory", e))?;
+    let path = dir.join(name);
+    match path.canonicalize() {
+        Ok(resolved) if !resolved.starts_with(&dir) => {
+            Err(not_allowed(filename, format!("'{}' is outside the recordings directory", filename)))
+        }
+        // A dangling link would have writes land wherever it points
+        Err(_) if fs::symlink_metadata(&path).is_ok() => {
+            Err(not_allowed(filename, format!("'{}' is a link to a missing file", filename)))
+        }
+        _ => Ok(path),
+    }
+}
+
+/// Folders commands may use without the user having picked them: recordings and live sessions
+/// live in the cache dir, models and notes in the data dir
+fn app_roots() -> Vec<PathBuf> {
+    [get_cache_dir(), get_data_dir()]
+        .into_iter()
+        .flatten()
+        .filter_map(|dir| dir.canonicalize().ok())
+        .collect()
+}
+
+/// Whether the user picked `path`, or a folder holding it, in a file dialog; the dialog plugin
+/// adds whatever it returns to the fs scope
+fn granted(app: &tauri::AppHandle, path: &Path) -> bool {
+    let Some(scope) = app.try_fs_scope() else { return false };
+    path.ancestors().any(|p| scope.is_allowed(p))
+}
+
+/// Only the resolved path is checked: the path as given can sit lexically inside a picked folder
+/// and still lead out of it through `..` or a symlink
+fn check(app: &tauri::AppHandle, original: &str, resolved: PathBuf) -> Result<PathBuf, AppError> {
+    let allowed = app_roots().iter().any(|root| resolved.starts_with(root)) || granted(app, &resolved);
+    if !allowed {
+        return Err(not_allowed(
+            original,
+            format!("'{}' isn't in the app's folders or one picked in a file dialog", original),
+        ));
+    }
+    Ok(resolved)
+}
+
+/// An existing file or folder from the frontend, with `..` and symlinks resolved before it's
+/// checked against the app's folders and the ones the user picked
+pub fn existing(app: &tauri::AppHandle, path: &str) -> Result<PathBuf, AppError> {
+    check(app, path, resolve_existing(path)?)
+}
+
+fn resolve_existing(path: &str) -> Result<PathBuf, AppError> {
+    if !Path::new(path).is_absolute() {
+        return Err(not_allowed(path, format!("'{}' must be an absolute path", path)));
+    }
+    Path::new(path)
+        .canonicalize()
+        .map_err(|e| AppError::io(format!("Failed to open {}", path), e))
+}
+
+/// A file about to be written, which may not exist yet, nor its folders. The part that exists is
+/// resolved and checked, so neither a symlink nor `..` can redirect the write elsewhere.
+pub fn writable(app: &tauri::AppHandle, path: &str) -> Result<PathBuf, AppError> {
+    check(app, path, resolve_writable(path)?)
+}
+
+fn resolve_writable(path: &str) -> Result<PathBuf, AppError> {
+    let target = Path::new(path);
+    if !target.is_absolute() {
+        return Err(not_allowed(path, format!("'{}' must be an absolute path", path)));
+    }
+    let mut existing = target;
+    let mut missing = Vec::new();
+    let resolved = loop {
+        if let Ok(resolved) = existing.canonicalize() {
+            break resolved;
+        }
+        if fs::symlink_metadata(existing).is_ok() {
+            return Err(not_allowed(path, format!("'{}' is a link to a missing file", existing.display())));
+        }
+        let (Some(parent), Some(Component::Normal(name))) = (existing.parent(), existing.components().next_back()) else {
+            return Err(not_allowed(path, format!("'{}' isn't a plain file path", path)));
+        };
+        missing.push(name);
+        existing = parent;
+    };
+    Ok(missing.into_iter().rev().fold(resolved, |dir, name| dir.join(name)))
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use std::os::unix::fs::symlink;
+
+    /// A picked folder and an outside one beside it, both canonical
+    fn scratch() -> (PathBuf, PathBuf) {
+        let root = std::env::temp_dir().join(crate::audio::unique_name("paths-test"));
+        fs::create_dir_all(&root).unwrap();
+        let root = root.canonicalize().unwrap();
+        let (picked, outside) = (root.join("picked"), root.join("outside"));
+        fs::create_dir_all(&picked).unwrap();
+        fs::create_dir_all(&outside).unwrap();
+        fs::write(outside.join("secret.wav"), b"x").unwrap();
+        (picked, outside)
+    }
+
+    #[test]
+    fn parent_dirs_resolve_out_of_the_folder_they_start_in() {
+        let (picked, outside) = scratch();
+        let sneaky = format!("{}/../outside/secret.wav", picked.display());
+        assert_eq!(resolve_existing(&sneaky).unwrap(), outside.join("secret.wav"));
+        let write = format!("{}/../outside/new.txt", picked.display());
+        assert_eq!(resolve_writable(&write).unwrap(), outside.join("new.txt"));
+    }
+
+    #[test]
+    fn symlinks_resolve_to_their_targets() {
+        let (picked, outside) = scratch();
+        symlink(&outside, picked.join("link")).unwrap();
+        let through = picked.join("link/secret.wav");
+        assert_eq!(resolve_existing(&through.to_string_lossy()).unwrap(), outside.join("secret.wav"));
+        let write = picked.join("link/sub/new.txt");
+        assert_eq!(resolve_writable(&write.to_string_lossy()).unwrap(), outside.join("sub/new.txt"));
+    }
+
+    #[test]
+    fn writable_refuses_a_dangling_link_and_parent_dirs_past_it() {
+        let (picked, outside) = scratch();
+        symlink(outside.join("missing.txt"), picked.join("dangling.txt")).unwrap();
+        assert!(resolve_writable(&picked.join("dangling.txt").to_string_lossy()).is_err());
+        assert!(resolve_writable(&format!("{}/nope/../x.txt", picked.display())).is_err());
+        assert!(resolve_writable("relative/x.txt").is_err());
+    }
+
+    #[test]
+    fn in_dir_refuses_links_out_and_dangling_links() {
+        let (picked, outside) = scratch();
+        symlink(outside.join("secret.wav"), picked.join("out.wav")).unwrap();
+        symlink(outside.join("missing.wav"), picked.join("dangling.wav")).unwrap();
+        fs::write(picked.join("ok.wav"), b"x").unwrap();
+        assert!(in_dir(&picked, "out.wav", &["wav"]).is_err());
+        assert!(in_dir(&picked, "dangling.wav", &["wav"]).is_err());
+        assert!(in_dir(&picked, "../outside/secret.wav", &["wav"]).is_err());
+        assert_eq!(in_dir(&picked, "ok.wav", &["wav"]).unwrap(), picked.join("ok.wav"));
+        assert_eq!(in_dir(&picked, "new.wav", &["wav"]).unwrap(), picked.join("new.wav"));
+    }
+}
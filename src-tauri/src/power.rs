@@ -0,0 +1,196 @@
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+
+const POWER_SUPPLY_DIR: &str = "/sys/class/power_supply";
+
+/// Win32_Battery reports this for EstimatedRunTime while charging or when it can't estimate
+const WMI_UNKNOWN_RUNTIME: u64 = 71_582_788;
+
+#[derive(Serialize, Deserialize, Default)]
+pub struct PowerStatus {
+    /// Battery draw; 0 when the platform doesn't report one
+    pub power_now_mw: f64,
+    pub power_now_w: f64,
+    pub battery_percent: Option<f64>,
+    pub charging: Option<bool>,
+    /// Estimated minutes until empty while discharging
+    pub time_remaining_minutes: Option<u64>,
+}
+
+impl PowerStatus {
+    fn with_power_mw(mut self, power_mw: f64) -> Self {
+        self.power_now_mw = power_mw;
+        self.power_now_w = power_mw / 1000.0;
+        self
+    }
+}
+
+fn read_sysfs(dir: &Path, name: &str) -> Option<f64> {
+    fs::read_to_string(dir.join(name)).ok()?.trim().parse().ok()
+}
+
+/// One battery from /sys/class/power_supply; sysfs values are in µW, µWh, µA, µAh and µV
+struct LinuxBattery {
+    power_uw: Option<f64>,
+    /// Energy left in µWh, derived from charge_now × voltage when only charge is reported
+    energy_uwh: Option<f64>,
+    energy_full_uwh: Option<f64>,
+    capacity: Option<f64>,
+    status: String,
+}
+
+fn read_linux_battery(dir: &Path) -> LinuxBattery {
+    let voltage = read_sysfs(dir, "voltage_now");
+    let from_charge = |charge: Option<f64>| Some(charge? * voltage? / 1_000_000.0);
+    // ThinkPads and some others expose current_now/charge_now instead of power_now/energy_now
+    let power_uw = read_sysfs(dir, "power_now").or_else(|| from_charge(read_sysfs(dir, "current_now")));
+    let energy_uwh = read_sysfs(dir, "energy_now").or_else(|| from_charge(read_sysfs(dir, "charge_now")));
+    let energy_full_uwh = read_sysfs(dir, "energy_full").or_else(|| from_charge(read_sysfs(dir, "charge_full")));
+    LinuxBattery {
+        power_uw,
+        energy_uwh,
+        energy_full_uwh,
+        capacity: read_sysfs(dir, "capacity"),
+        status: fs::read_to_string(dir.join("status")).unwrap_or_default().trim().to_string(),
+    }
+}
+
+/// Combine every BAT* supply, so dual-battery laptops report their total draw and charge
+fn linux_power_status() -> Result<PowerStatus, String> {
+    let entries = fs::read_dir(POWER_SUPPLY_DIR).map_err(|e| format!("Failed to read power status: {}", e))?;
+    let mut dirs: Vec<_> = entries
+        .filter_map(Result::ok)
+        .filter(|e| e.file_name().to_string_lossy().starts_with("BAT"))
+        .map(|e| e.path())
+        .collect();
+    dirs.sort();
+    if dirs.is_empty() {
+        return Err("No battery found".to_string());
+    }
+
+    let batteries: Vec<LinuxBattery> = dirs.iter().map(|dir| read_linux_battery(dir)).collect();
+    let power_uw: f64 = batteries.iter().filter_map(|b| b.power_uw).sum();
+    let charging = batteries.iter().any(|b| b.status == "Charging");
+    let discharging = batteries.iter().any(|b| b.status == "Discharging");
+    let energy: Option<f64> = batteries.iter().map(|b| b.energy_uwh).sum();
+    let energy_full: Option<f64> = batteries.iter().map(|b| b.energy_full_uwh).sum();
+
+    let battery_percent = match (energy, energy_full) {
+        (Some(now), Some(full)) if full > 0.0 => Some((now / full * 100.0).min(100.0)),
+        _ => batteries.iter().filter_map(|b| b.capacity).reduce(f64::max),
+    };
+    let time_remaining_minutes = match energy {
+        Some(now) if discharging && power_uw > 0.0 => Some((now / power_uw * 60.0) as u64),
+        _ => None,
+    };
+
+    Ok(PowerStatus {
+        battery_percent,
+        charging: Some(charging),
+        time_remaining_minutes,
+        ..Default::default()
+    }
+    .with_power_mw(power_uw / 1000.0))
+}
+
+/// Parse `pmset -g batt`: " -InternalBattery-0 (id=1234)<tab>85%; discharging; 4:32 remaining present: true"
+fn parse_pmset(output: &str) -> Option<(f64, bool, Option<u64>)> {
+    let line = output.lines().find(|l| l.contains("InternalBattery"))?;
+    let (_, fields) = line.split_once('\t')?;
+    let mut parts = fields.split(';').map(str::trim);
+    let percent = parts.next()?.trim_end_matches('%').parse().ok()?;
+    let state = parts.next().unwrap_or_default();
+    let charging = state == "charging" || state == "finishing charge";
+    let remaining = parts.next().and_then(|r| {
+        let (hours, minutes) = r.split_whitespace().next()?.split_once(':')?;
+        Some(hours.parse::<u64>().ok()? * 60 + minutes.parse::<u64>().ok()?)
+    });
+    Some((percent, charging, remaining.filter(|_| !charging)))
+}
+
+/// Battery draw in mW from the AppleSmartBattery registry entry's voltage (mV) and amperage (mA)
+fn macos_power_mw() -> Option<f64> {
+    let output = Command::new("ioreg").args(["-rn", "AppleSmartBattery"]).output().ok()?;
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let value = |key: &str| -> Option<i64> {
+        stdout.lines().find_map(|line| {
+            let (name, value) = line.trim().split_once(" = ")?;
+            if name.trim_matches('"') != key {
+                return None;
+            }
+            // Negative amperage is printed as an unsigned 64-bit value
+            value.trim().parse::<i128>().ok().map(|v| v as u64 as i64)
+        })
+    };
+    let voltage = value("Voltage")?;
+    let amperage = value("InstantAmperage").or_else(|| value("Amperage"))?;
+    Some((voltage * amperage).unsigned_abs() as f64 / 1000.0)
+}
+
+fn macos_power_status() -> Result<PowerStatus, String> {
+    let output = Command::new("pmset")
+        .args(["-g", "batt"])
+        .output()
+        .map_err(|e| format!("Failed to run pmset: {}", e))?;
+    let (percent, charging, remaining) =
+        parse_pmset(&String::from_utf8_lossy(&output.stdout)).ok_or("No battery found")?;
+    Ok(PowerStatus {
+        battery_percent: Some(percent),
+        charging: Some(charging),
+        time_remaining_minutes: remaining,
+        ..Default::default()
+    }
+    .with_power_mw(macos_power_mw().unwrap_or(0.0)))
+}
+
+// Shape of the JSON our PowerShell query prints
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct WindowsBattery {
+    estimated_charge_remaining: Option<f64>,
+    estimated_run_time: Option<u64>,
+    /// mW, from root\wmi BatteryStatus
+    discharge_rate: Option<f64>,
+    charging: Option<bool>,
+}
+
+/// Query Win32_Battery for charge and runtime, and root\wmi BatteryStatus for the discharge rate
+fn windows_power_status() -> Result<PowerStatus, String> {
+    let script = "$b = Get-CimInstance Win32_Battery | Select-Object -First 1; \
+        $s = Get-CimInstance -Namespace root\\wmi -ClassName BatteryStatus -ErrorAction SilentlyContinue | Select-Object -First 1; \
+        if ($b) { @{ EstimatedChargeRemaining = $b.EstimatedChargeRemaining; EstimatedRunTime = $b.EstimatedRunTime; \
+        DischargeRate = $s.DischargeRate; Charging = $s.Charging } | ConvertTo-Json }";
+    let output = Command::new("powershell")
+        .args(["-NoProfile", "-Command", script])
+        .output()
+        .map_err(|e| format!("Failed to query battery status: {}", e))?;
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    if stdout.trim().is_empty() {
+        return Err("No battery found".to_string());
+    }
+    let battery: WindowsBattery =
+        serde_json::from_str(&stdout).map_err(|e| format!("Failed to parse battery status: {}", e))?;
+    Ok(PowerStatus {
+        battery_percent: battery.estimated_charge_remaining,
+        charging: battery.charging,
+        time_remaining_minutes: battery.estimated_run_time.filter(|&r| r != WMI_UNKNOWN_RUNTIME),
+        ..Default::default()
+    }
+    .with_power_mw(battery.discharge_rate.unwrap_or(0.0)))
+}
+
+/// Monitor battery power consumption, charge and estimated runtime
+#[tauri::command]
+pub async fn get_power_status() -> Result<PowerStatus, String> {
+    if cfg!(target_os = "linux") {
+        linux_power_status()
+    } else if cfg!(target_os = "macos") {
+        macos_power_status()
+    } else if cfg!(target_os = "windows") {
+        windows_power_status()
+    } else {
+        Err("Power monitoring isn't supported on this platform".to_string())
+    }
+}
@@ -0,0 +1,157 @@
+use std::collections::HashSet;
+use std::io;
+use std::process::{Child, Command, ExitStatus, Output, Stdio};
+use std::sync::Mutex;
+use tauri::Manager;
+
+use crate::jobs;
+
+// Recorder processes this app spawned; cleanup signals only these, never the user's own ffmpeg
+pub struct ProcessRegistry {
+    pids: Mutex<HashSet<u32>>,
+}
+
+impl ProcessRegistry {
+    pub fn new() -> Self {
+        ProcessRegistry { pids: Mutex::new(HashSet::new()) }
+    }
+
+    pub fn register(&self, pid: u32) {
+        self.pids.lock().unwrap().insert(pid);
+    }
+
+    /// Forget a child once its owner has reaped it
+    pub fn unregister(&self, pid: u32) {
+        self.pids.lock().unwrap().remove(&pid);
+    }
+
+    /// Terminate every registered child; owners unregister them as they reap them. Returns how many were signalled.
+    pub fn terminate_all(&self) -> usize {
+        let pids: Vec<u32> = self.pids.lock().unwrap().iter().copied().collect();
+        for pid in &pids {
+            jobs::terminate_pid(*pid);
+        }
+        pids.len()
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub enum Signal {
+    /// Ask the process to exit
+    Term,
+    /// Ctrl-C; recorders finish their WAV header on it
+    Int,
+    /// Freeze the process in place
+    Stop,
+    /// Continue a stopped process
+    Cont,
+}
+
+/// Send `signal` to `pid` directly rather than shelling out to `kill`. Windows has no signals, so
+/// there only Term is supported, through taskkill.
+pub fn signal(pid: u32, signal: Signal) -> io::Result<()> {
+    #[cfg(unix)]
+    {
+        let signo = match signal {
+            Signal::Term => libc::SIGTERM,
+            Signal::Int => libc::SIGINT,
+            Signal::Stop => libc::SIGSTOP,
+            Signal::Cont => libc::SIGCONT,
+        };
+        let pid = libc::pid_t::try_from(pid).map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "PID out of range"))?;
+        // SAFETY: kill has no memory-safety preconditions; a stale PID just fails with ESRCH
+        if unsafe { libc::kill(pid, signo) } == -1 {
+            return Err(io::Error::last_os_error());
+        }
+        Ok(())
+    }
+    #[cfg(not(unix))]
+    {
+        match signal {
+            Signal::Term => {
+                let output = Command::new("taskkill").arg("/PID").arg(pid.to_string()).arg("/T").arg("/F").output()?;
+                if output.status.success() {
+                    Ok(())
+                } else {
+                    Err(io::Error::other(String::from_utf8_lossy(&output.stderr).trim().to_string()))
+                }
+            }
+            other => Err(io::Error::new(io::ErrorKind::Unsupported, format!("{:?} isn't supported on Windows", other))),
+        }
+    }
+}
+
+/// Spawn a recorder and register it until `wait`/`unregister` is called for it
+pub fn spawn(app: &tauri::AppHandle, cmd: &mut Command) -> io::Result<Child> {
+    let child = cmd.spawn()?;
+    app.state::<ProcessRegistry>().register(child.id());
+    Ok(child)
+}
+
+/// Wait for a registered child to exit, then forget it
+pub fn wait(app: &tauri::AppHandle, child: &mut Child) -> io::Result<ExitStatus> {
+    let status = child.wait();
+    app.state::<ProcessRegistry>().unregister(child.id());
+    status
+}
+
+/// Like Command::output, with the child registered while it runs
+pub fn output(app: &tauri::AppHandle, cmd: &mut Command) -> io::Result<Output> {
+    let child = spawn(app, cmd.stdout(Stdio::piped()).stderr(Stdio::piped()))?;
+    let pid = child.id();
+    let output = child.wait_with_output();
+    app.state::<ProcessRegistry>().unregister(pid);
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unregistered_pids_are_forgotten() {
+        let registry = ProcessRegistry::new();
+        registry.register(u32::MAX - 1);
+        registry.register(u32::MAX - 2);
+        registry.register(u32::MAX - 1);
+        assert_eq!(registry.pids.lock().unwrap().len(), 2);
+        registry.unregister(u32::MAX - 1);
+        registry.unregister(u32::MAX - 1);
+        assert_eq!(registry.pids.lock().unwrap().iter().copied().collect::<Vec<_>>(), vec![u32::MAX - 2]);
+    }
+
+    #[test]
+    fn terminate_all_with_nothing_registered_signals_nothing() {
+        assert_eq!(ProcessRegistry::new().terminate_all(), 0);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn terminate_all_signals_only_registered_children() {
+        use std::os::unix::process::ExitStatusExt;
+
+        let registry = ProcessRegistry::new();
+        let mut registered = Command::new("sleep").arg("30").spawn().unwrap();
+        let mut unrelated = Command::new("sleep").arg("30").spawn().unwrap();
+        registry.register(registered.id());
+
+        assert_eq!(registry.terminate_all(), 1);
+        assert_eq!(registered.wait().unwrap().signal(), Some(libc::SIGTERM));
+        // Reaping is the owner's job, so the PID stays until it unregisters it
+        registry.unregister(registered.id());
+        assert_eq!(registry.terminate_all(), 0);
+
+        assert!(unrelated.try_wait().unwrap().is_none());
+        unrelated.kill().unwrap();
+        unrelated.wait().unwrap();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn signal_reports_a_missing_process() {
+        let mut child = Command::new("true").spawn().unwrap();
+        let pid = child.id();
+        child.wait().unwrap();
+        assert!(signal(pid, Signal::Term).is_err());
+    }
+}
@@ -0,0 +1,134 @@
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::fs;
+
+use crate::get_config_dir;
+
+const TEMPLATES_FILE: &str = "prompt-templates.json";
+
+/// Where the transcript goes in a template
+pub const PLACEHOLDER: &str = "{{transcript}}";
+
+/// Template summarize_text_llama uses when none is named
+pub const DEFAULT_TEMPLATE: &str = "default";
+
+/// Templates that always exist; saving one of these names overrides it, and deleting the override restores it
+const BUILTIN_TEMPLATES: &[(&str, &str)] = &[
+    (
+        DEFAULT_TEMPLATE,
+        "You are a concise note-taking assistant. Summarize the following transcript into clear bullet points with timestamps if present, avoiding speculation.\n\nTranscript:\n{{transcript}}\n\nSummary:",
+    ),
+    (
+        "lecture",
+        "You are a study assistant. Turn the following lecture transcript into study notes: the main topics as headings, key definitions and examples under each, and a short list of review questions at the end.\n\nTranscript:\n{{transcript}}\n\nStudy notes:",
+    ),
+    (
+        "standup",
+        "You are a meeting assistant. From the following standup transcript, list each person who spoke with what they did, what they will do next, and any blockers. Only include what was actually said.\n\nTranscript:\n{{transcript}}\n\nStandup notes:",
+    ),
+    (
+        "interview",
+        "You are an interview assistant. Rewrite the following interview transcript as a list of questions and answers, one Q: line and one A: line each, condensing the answers without changing their meaning.\n\nTranscript:\n{{transcript}}\n\nQ&A:",
+    ),
+];
+
+#[derive(Serialize, Clone, Debug)]
+pub struct PromptTemplate {
+    pub name: String,
+    pub template: String,
+    pub builtin: bool,
+    /// A built-in whose text has been replaced by a saved template
+    pub overridden: bool,
+}
+
+fn builtin(name: &str) -> Option<&'static str> {
+    BUILTIN_TEMPLATES.iter().find(|(n, _)| *n == name).map(|(_, t)| *t)
+}
+
+/// Saved templates, including overrides of built-ins
+fn load_saved() -> BTreeMap<String, String> {
+    get_config_dir()
+        .ok()
+        .and_then(|dir| fs::read_to_string(dir.join(TEMPLATES_FILE)).ok())
+        .and_then(|raw| serde_json::from_str(&raw).ok())
+        .unwrap_or_default()
+}
+
+fn save_all(templates: &BTreeMap<String, String>) -> Result<(), String> {
+    let json = serde_json::to_string_pretty(templates)
+        .map_err(|e| format!("Failed to serialize prompt templates: {}", e))?;
+    fs::write(get_config_dir()?.join(TEMPLATES_FILE), json)
+        .map_err(|e| format!("Failed to save prompt templates: {}", e))
+}
+
+/// Template text for `name` (or the default), preferring a saved one over a built-in
+pub fn resolve(name: Option<&str>) -> Result<String, String> {
+    let name = name.unwrap_or(DEFAULT_TEMPLATE);
+    if let Some(template) = load_saved().remove(name) {
+        return Ok(template);
+    }
+    builtin(name)
+        .map(str::to_string)
+        .ok_or_else(|| format!("Prompt template '{}' not found", name))
+}
+
+/// Substitute the transcript into a template
+pub fn render(template: &str, transcript: &str) -> String {
+    template.replace(PLACEHOLDER, transcript)
+}
+
+#[tauri::command]
+pub async fn list_prompt_templates() -> Result<Vec<PromptTemplate>, String> {
+    let mut saved = load_saved();
+    let mut templates: Vec<PromptTemplate> = BUILTIN_TEMPLATES
+        .iter()
+        .map(|(name, template)| {
+            let saved = saved.remove(*name);
+            PromptTemplate {
+                name: name.to_string(),
+                overridden: saved.is_some(),
+                template: saved.unwrap_or_else(|| template.to_string()),
+                builtin: true,
+            }
+        })
+        .collect();
+    templates.extend(saved.into_iter().map(|(name, template)| PromptTemplate {
+        name,
+        template,
+        builtin: false,
+        overridden: false,
+    }));
+    Ok(templates)
+}
+
+/// Create or replace a template; the template must contain {{transcript}}
+#[tauri::command]
+pub async fn save_prompt_template(name: String, template: String) -> Result<PromptTemplate, String> {
+    let name = name.trim().to_string();
+    if name.is_empty() {
+        return Err("Template name can't be empty".to_string());
+    }
+    if !template.contains(PLACEHOLDER) {
+        return Err(format!("Template must contain {}", PLACEHOLDER));
+    }
+
+    let mut saved = load_saved();
+    saved.insert(name.clone(), template.clone());
+    save_all(&saved)?;
+    let builtin = builtin(&name).is_some();
+    Ok(PromptTemplate { name, template, builtin, overridden: builtin })
+}
+
+/// Delete a saved template; for a built-in this removes the override and restores the original
+#[tauri::command]
+pub async fn delete_prompt_template(name: String) -> Result<(), String> {
+    let mut saved = load_saved();
+    if saved.remove(&name).is_none() {
+        return Err(if builtin(&name).is_some() {
+            format!("'{}' is a built-in template and can't be deleted", name)
+        } else {
+            format!("Prompt template '{}' not found", name)
+        });
+    }
+    save_all(&saved)
+}
@@ -0,0 +1,1115 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::io::{BufRead, BufReader, Write};
+use std::path::Path;
+use std::process::{Child, Command};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tauri::Manager;
+
+use crate::audio::WHISPER_SAMPLE_RATE;
+use crate::error::AppError;
+use crate::{events, has_ffmpeg, logging, native_recorder, processes};
+
+/// How long ffmpeg gets to flush and exit after "q" before it's killed
+const FFMPEG_QUIT_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// How much of a long-running recorder's stderr is kept for error messages
+const STDERR_TAIL_BYTES: usize = 4096;
+
+/// How long to wait for the last of an exited recorder's stderr to be read
+const STDERR_SETTLE: Duration = Duration::from_millis(250);
+
+const SUPPORTED_SAMPLE_RATES: &[u32] = &[16000, 44100, 48000];
+
+const SUPPORTED_BIT_DEPTHS: &[u16] = &[16, 24];
+
+/// Which audio stack captures the microphone
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug)]
+#[serde(rename_all = "lowercase")]
+pub enum RecorderBackend {
+    Alsa,
+    Pulse,
+    PipeWire,
+    /// In-process capture through cpal; needs no external tools
+    Native,
+    /// ffmpeg's DirectShow input, the capture path on Windows
+    #[serde(rename = "dshow")]
+    DirectShow,
+    /// ffmpeg's AVFoundation input, the capture path on macOS
+    AvFoundation,
+}
+
+impl RecorderBackend {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            RecorderBackend::Alsa => "alsa",
+            RecorderBackend::Pulse => "pulse",
+            RecorderBackend::PipeWire => "pipewire",
+            RecorderBackend::Native => "native",
+            RecorderBackend::DirectShow => "dshow",
+            RecorderBackend::AvFoundation => "avfoundation",
+        }
+    }
+
+    /// Recorders driven through ffmpeg's own platform input rather than a Linux sound server
+    fn is_platform_ffmpeg(self) -> bool {
+        matches!(self, RecorderBackend::DirectShow | RecorderBackend::AvFoundation)
+    }
+}
+
+pub fn is_process_running(name: &str) -> bool {
+    Command::new("pgrep").arg(name).output().map(|o| o.status.success()).unwrap_or(false)
+}
+
+pub fn has_tool(name: &str) -> bool {
+    let finder = if cfg!(target_os = "windows") { "where" } else { "which" };
+    Command::new(finder).arg(name).output().map(|o| o.status.success()).unwrap_or(false)
+}
+
+fn backend_available(backend: RecorderBackend) -> bool {
+    match backend {
+        RecorderBackend::Alsa => has_tool("arecord"),
+        RecorderBackend::Pulse => has_ffmpeg() || has_tool("parecord"),
+        RecorderBackend::PipeWire => has_tool("pw-record"),
+        RecorderBackend::Native => true,
+        RecorderBackend::DirectShow => cfg!(target_os = "windows") && has_ffmpeg(),
+        RecorderBackend::AvFoundation => cfg!(target_os = "macos") && has_ffmpeg(),
+    }
+}
+
+/// Prefer the sound server the desktop is actually running; raw ALSA grabs the device exclusively
+pub fn detect_backend() -> RecorderBackend {
+    if cfg!(target_os = "windows") {
+        return if backend_available(RecorderBackend::DirectShow) {
+            RecorderBackend::DirectShow
+        } else {
+            RecorderBackend::Native
+        };
+    }
+    if cfg!(target_os = "macos") {
+        return if backend_available(RecorderBackend::AvFoundation) {
+            RecorderBackend::AvFoundation
+        } else {
+            RecorderBackend::Native
+        };
+    }
+    if is_process_running("pipewire") && backend_available(RecorderBackend::PipeWire) {
+        RecorderBackend::PipeWire
+    } else if (is_process_running("pipewire-pulse") || is_process_running("pulseaudio"))
+        && backend_available(RecorderBackend::Pulse)
+    {
+        RecorderBackend::Pulse
+    } else if backend_available(RecorderBackend::Alsa) {
+        RecorderBackend::Alsa
+    } else {
+        // No capture tools installed (e.g. Fedora without alsa-utils)
+        RecorderBackend::Native
+    }
+}
+
+/// Resolve "alsa" / "pulse" / "pipewire" / "dshow" / "avfoundation" / "native" / "auto" (or None) to an installed backend
+pub fn resolve_backend(requested: Option<&str>) -> Result<RecorderBackend, String> {
+    let backend = match requested.map(|b| b.trim().to_lowercase()).as_deref() {
+        None | Some("") | Some("auto") => return Ok(detect_backend()),
+        Some("alsa") => RecorderBackend::Alsa,
+        Some("pulse") | Some("pulseaudio") => RecorderBackend::Pulse,
+        Some("pipewire") => RecorderBackend::PipeWire,
+        Some("native") => RecorderBackend::Native,
+        Some("dshow") | Some("directshow") => RecorderBackend::DirectShow,
+        Some("avfoundation") => RecorderBackend::AvFoundation,
+        Some(other) => {
+            return Err(format!(
+                "Unknown recorder backend '{}' (expected alsa, pulse, pipewire, dshow, avfoundation, native, or auto)",
+                other
+            ));
+        }
+    };
+    if !backend_available(backend) {
+        let tool = match backend {
+            RecorderBackend::Alsa => "arecord",
+            RecorderBackend::Pulse => "ffmpeg or parecord",
+            RecorderBackend::PipeWire => "pw-record",
+            RecorderBackend::DirectShow if !cfg!(target_os = "windows") => {
+                return Err("The dshow backend is only available on Windows".to_string());
+            }
+            RecorderBackend::AvFoundation if !cfg!(target_os = "macos") => {
+                return Err("The avfoundation backend is only available on macOS".to_string());
+            }
+            RecorderBackend::DirectShow | RecorderBackend::AvFoundation => "ffmpeg",
+            RecorderBackend::Native => "an input device",
+        };
+        return Err(format!("The {} backend needs {}, which isn't installed", backend.as_str(), tool));
+    }
+    Ok(backend)
+}
+
+/// Build a command that records a `profile` WAV to `output`, for `duration_secs` or until signalled.
+/// The native backend has none; it records in-process through native_recorder.
+pub fn capture_command(
+    backend: RecorderBackend,
+    device: Option<&str>,
+    output: &Path,
+    duration_secs: Option<u64>,
+    profile: &RecordingProfile,
+) -> Result<Command, String> {
+    // parecord and pw-record have no duration flag; SIGINT makes them finalize the WAV header
+    let timed = |program: &str| {
+        let mut cmd = match duration_secs {
+            Some(secs) => {
+                let mut cmd = Command::new("timeout");
+                cmd.arg("--preserve-status").arg("--signal=INT").arg(secs.to_string()).arg(program);
+                cmd
+            }
+            None => Command::new(program),
+        };
+        cmd.stdin(std::process::Stdio::null());
+        cmd
+    };
+
+    match backend {
+        RecorderBackend::Alsa => {
+            let mut cmd = Command::new("arecord");
+            if let Some(device) = device {
+                cmd.arg("-D").arg(device);
+            }
+            cmd.arg("-f").arg(if profile.bit_depth == 24 { "S24_3LE" } else { "S16_LE" })
+                .arg("-r").arg(profile.sample_rate.to_string())
+                .arg("-c").arg(profile.channels.to_string());
+            if let Some(secs) = duration_secs {
+                cmd.arg("-d").arg(secs.to_string());
+            }
+            cmd.arg(output);
+            Ok(cmd)
+        }
+        RecorderBackend::Native => Err("The native backend records in-process, not with a command".to_string()),
+        RecorderBackend::DirectShow | RecorderBackend::AvFoundation => {
+            let mut cmd = Command::new("ffmpeg");
+            cmd.arg("-hide_banner")
+                .arg("-loglevel").arg("error")
+                .arg("-y")
+                .arg("-f").arg(ffmpeg_input_format(backend))
+                .arg("-i").arg(ffmpeg_device_input(backend, device.unwrap_or("default")));
+            if let Some(secs) = duration_secs {
+                cmd.arg("-t").arg(secs.to_string());
+            }
+            profile.ffmpeg_output_args(&mut cmd);
+            cmd.arg(output)
+                // stop_process asks ffmpeg to quit over stdin instead of signalling it
+                .stdin(std::process::Stdio::piped());
+            Ok(cmd)
+        }
+        RecorderBackend::Pulse if has_ffmpeg() => {
+            let mut cmd = Command::new("ffmpeg");
+            cmd.arg("-hide_banner")
+                .arg("-loglevel").arg("error")
+                .arg("-y")
+                .arg("-f").arg("pulse")
+                .arg("-i").arg(device.unwrap_or("default"));
+            if let Some(secs) = duration_secs {
+                cmd.arg("-t").arg(secs.to_string());
+            }
+            profile.ffmpeg_output_args(&mut cmd);
+            cmd.arg(output)
+                .stdin(std::process::Stdio::null());
+            Ok(cmd)
+        }
+        RecorderBackend::Pulse => {
+            let mut cmd = timed("parecord");
+            if let Some(device) = device {
+                cmd.arg(format!("--device={}", device));
+            }
+            cmd.arg("--file-format=wav")
+                .arg(format!("--format=s{}le", profile.bit_depth))
+                .arg(format!("--rate={}", profile.sample_rate))
+                .arg(format!("--channels={}", profile.channels))
+                .arg(output);
+            Ok(cmd)
+        }
+        RecorderBackend::PipeWire => {
+            let mut cmd = timed("pw-record");
+            if let Some(device) = device {
+                cmd.arg("--target").arg(device);
+            }
+            cmd.arg("--rate").arg(profile.sample_rate.to_string())
+                .arg("--channels").arg(profile.channels.to_string())
+                .arg("--format").arg(format!("s{}", profile.bit_depth))
+                .arg(output);
+            Ok(cmd)
+        }
+    }
+}
+
+/// ffmpeg input format for the segmenting live recorder; ffmpeg has no native PipeWire input,
+/// so that backend goes through PipeWire's PulseAudio compatibility layer
+pub fn ffmpeg_input_format(backend: RecorderBackend) -> &'static str {
+    match backend {
+        RecorderBackend::Alsa | RecorderBackend::Native => "alsa",
+        RecorderBackend::Pulse | RecorderBackend::PipeWire => "pulse",
+        RecorderBackend::DirectShow => "dshow",
+        RecorderBackend::AvFoundation => "avfoundation",
+    }
+}
+
+/// The `-i` value for a device: dshow wants `audio=<friendly name>`, avfoundation `:<audio index>`
+fn ffmpeg_device_input(backend: RecorderBackend, device: &str) -> String {
+    match backend {
+        RecorderBackend::DirectShow => format!("audio={}", device),
+        RecorderBackend::AvFoundation => format!(":{}", device),
+        _ => device.to_string(),
+    }
+}
+
+/// Send ffmpeg "q" on stdin (its own clean shutdown) and wait for it to write out the
+/// WAV header; killing it outright would leave the header sizes unset. Killed if it wedges.
+pub fn quit_ffmpeg(child: &mut Child) {
+    if let Some(mut stdin) = child.stdin.take() {
+        let _ = stdin.write_all(b"q");
+    }
+    let deadline = Instant::now() + FFMPEG_QUIT_TIMEOUT;
+    while Instant::now() < deadline {
+        match child.try_wait() {
+            Ok(None) => std::thread::sleep(Duration::from_millis(50)),
+            _ => return,
+        }
+    }
+    let _ = child.kill();
+    let _ = child.wait();
+}
+
+/// The end of a running recorder's stderr, drained on a background thread so the pipe never fills
+#[derive(Clone, Default)]
+pub struct StderrTail {
+    text: Arc<Mutex<String>>,
+    // Set once the pipe closes, when the recorder (and anything it started) has exited
+    closed: Arc<AtomicBool>,
+}
+
+impl StderrTail {
+    /// Start reading `child`'s stderr, which must have been spawned piped
+    pub fn capture(child: &mut Child) -> Self {
+        Self::read(child, None)
+    }
+
+    /// Like capture, also logging each line and emitting recorder-warning for lines mentioning an
+    /// error, so a long recording's problems show up while it's still running
+    pub fn stream(app: &tauri::AppHandle, child: &mut Child, recorder: &str) -> Self {
+        Self::read(child, Some((app.clone(), recorder.to_string())))
+    }
+
+    fn read(child: &mut Child, watch: Option<(tauri::AppHandle, String)>) -> Self {
+        let tail = StderrTail::default();
+        let Some(stderr) = child.stderr.take() else {
+            tail.closed.store(true, Ordering::Relaxed);
+            return tail;
+        };
+        let (buffer, closed) = (tail.text.clone(), tail.closed.clone());
+        std::thread::spawn(move || {
+            for line in BufReader::new(stderr).lines().map_while(Result::ok) {
+                if let Some((app, recorder)) = &watch {
+                    if line.to_lowercase().contains("error") {
+                        log::warn!("{}: {}", recorder, line);
+                        events::emit(app, "recorder-warning", serde_json::json!({
+                            "recorder": recorder,
+                            "message": line,
+                        }));
+                    } else if !line.trim().is_empty() {
+                        log::info!("{}: {}", recorder, line);
+                    }
+                }
+                let mut buffer = buffer.lock().unwrap();
+                buffer.push_str(&line);
+                buffer.push('\n');
+                if buffer.len() > STDERR_TAIL_BYTES {
+                    let cut = (buffer.len() - STDERR_TAIL_BYTES..).find(|&i| buffer.is_char_boundary(i)).unwrap_or(0);
+                    buffer.drain(..cut);
+                }
+            }
+            closed.store(true, Ordering::Relaxed);
+        });
+        tail
+    }
+
+    /// The last lines read so far
+    pub fn text(&self) -> String {
+        logging::stderr_tail(self.text.lock().unwrap().as_bytes())
+    }
+
+    /// The last lines once the recorder has exited, giving the reader a moment to catch up with
+    /// what it printed on the way out
+    pub fn final_text(&self) -> String {
+        let deadline = Instant::now() + STDERR_SETTLE;
+        while !self.closed.load(Ordering::Relaxed) && Instant::now() < deadline {
+            std::thread::sleep(Duration::from_millis(10));
+        }
+        self.text()
+    }
+
+    /// `message` for a recorder that has failed, ending with its last stderr line and carrying the
+    /// rest in the error's details
+    pub fn failure(&self, backend: RecorderBackend, message: String) -> AppError {
+        let stderr = self.final_text();
+        let message = match stderr.lines().last() {
+            Some(last) => format!("{}: {}", message, last),
+            None => message,
+        };
+        AppError::ProcessFailed { cmd: backend.as_str().to_string(), stderr, message }
+    }
+}
+
+/// Ask a recorder process to finish its WAV and wait for it to exit
+pub fn stop_process(child: &mut Child, backend: RecorderBackend) {
+    if backend.is_platform_ffmpeg() {
+        return quit_ffmpeg(child);
+    }
+    // ffmpeg, parecord and pw-record finalize the WAV header most reliably on SIGINT
+    let signal = if backend == RecorderBackend::Alsa { processes::Signal::Term } else { processes::Signal::Int };
+    if let Err(e) = processes::signal(child.id(), signal) {
+        log::warn!("Couldn't signal the {} recorder, killing it: {}", backend.as_str(), e);
+        let _ = child.kill();
+    }
+    let _ = child.wait();
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct AudioDevice {
+    pub id: String,
+    pub description: String,
+}
+
+// Input device chosen by the last recording command; None records from the backend's default source
+pub struct AudioDeviceState {
+    pub device: Mutex<Option<String>>,
+}
+
+/// PCM format long system recordings are written in; the default is what whisper reads directly
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub struct RecordingProfile {
+    pub sample_rate: u32,
+    pub channels: u16,
+    pub bit_depth: u16,
+}
+
+impl Default for RecordingProfile {
+    fn default() -> Self {
+        RecordingProfile { sample_rate: WHISPER_SAMPLE_RATE, channels: 1, bit_depth: 16 }
+    }
+}
+
+impl RecordingProfile {
+    pub fn validate(&self) -> Result<(), String> {
+        if !SUPPORTED_SAMPLE_RATES.contains(&self.sample_rate) {
+            return Err(format!(
+                "Unsupported sample rate {} Hz (expected 16000, 44100, or 48000)",
+                self.sample_rate
+            ));
+        }
+        if !(1..=2).contains(&self.channels) {
+            return Err(format!("Unsupported channel count {} (expected 1 or 2)", self.channels));
+        }
+        if !SUPPORTED_BIT_DEPTHS.contains(&self.bit_depth) {
+            return Err(format!("Unsupported bit depth {} (expected 16 or 24)", self.bit_depth));
+        }
+        Ok(())
+    }
+
+    fn ffmpeg_codec(&self) -> &'static str {
+        if self.bit_depth == 24 { "pcm_s24le" } else { "pcm_s16le" }
+    }
+
+    /// Output options shared by every ffmpeg recorder command
+    fn ffmpeg_output_args(&self, cmd: &mut Command) {
+        cmd.arg("-ac").arg(self.channels.to_string())
+            .arg("-ar").arg(self.sample_rate.to_string())
+            .arg("-c:a").arg(self.ffmpeg_codec());
+    }
+}
+
+// Profile applied by start_system_recording
+pub struct RecordingProfileState {
+    pub profile: Mutex<RecordingProfile>,
+}
+
+/// Compressed archival copy written next to a system recording's WAV, sharing its file stem
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug)]
+#[serde(rename_all = "lowercase")]
+pub enum ArchiveFormat {
+    Flac,
+    Opus,
+}
+
+impl ArchiveFormat {
+    /// Parse "flac" / "opus"; None, "" and "none" mean no archival copy
+    pub fn parse(requested: Option<&str>) -> Result<Option<ArchiveFormat>, String> {
+        match requested.map(|f| f.trim().to_lowercase()).as_deref() {
+            None | Some("") | Some("none") => Ok(None),
+            Some("flac") => Ok(Some(ArchiveFormat::Flac)),
+            Some("opus") => Ok(Some(ArchiveFormat::Opus)),
+            Some(other) => Err(format!("Unknown archive format '{}' (expected flac, opus, or none)", other)),
+        }
+    }
+
+    pub fn extension(self) -> &'static str {
+        match self {
+            ArchiveFormat::Flac => "flac",
+            ArchiveFormat::Opus => "opus",
+        }
+    }
+
+    pub fn archive_path(self, wav: &Path) -> std::path::PathBuf {
+        wav.with_extension(self.extension())
+    }
+
+    /// Standalone encoder used when ffmpeg isn't installed
+    pub fn encoder_tool(self) -> &'static str {
+        match self {
+            ArchiveFormat::Flac => "flac",
+            ArchiveFormat::Opus => "opusenc",
+        }
+    }
+
+    pub fn can_encode(self) -> bool {
+        has_ffmpeg() || has_tool(self.encoder_tool())
+    }
+
+    /// ffmpeg output options for the archive; libopus only takes 48 kHz among the supported rates
+    fn ffmpeg_output_args(self, profile: &RecordingProfile, cmd: &mut Command) {
+        cmd.arg("-ac").arg(profile.channels.to_string());
+        match self {
+            ArchiveFormat::Flac => {
+                cmd.arg("-ar").arg(profile.sample_rate.to_string()).arg("-c:a").arg("flac");
+            }
+            ArchiveFormat::Opus => {
+                cmd.arg("-ar").arg("48000").arg("-c:a").arg("libopus").arg("-b:a").arg("96k");
+            }
+        }
+    }
+
+    /// Command that encodes a finished WAV into the archive, for recorders that can't write it directly
+    pub fn encode_command(self, wav: &Path, archive: &Path) -> Result<Command, String> {
+        if has_ffmpeg() {
+            let mut cmd = Command::new("ffmpeg");
+            cmd.arg("-hide_banner").arg("-loglevel").arg("error").arg("-y").arg("-i").arg(wav);
+            let profile = match crate::audio::read_wav_info(wav) {
+                Ok(info) => RecordingProfile { sample_rate: info.sample_rate, channels: info.channels, bit_depth: info.bits_per_sample },
+                Err(_) => RecordingProfile::default(),
+            };
+            self.ffmpeg_output_args(&profile, &mut cmd);
+            cmd.arg(archive);
+            return Ok(cmd);
+        }
+        if !has_tool(self.encoder_tool()) {
+            return Err(format!(
+                "Writing a {} archive needs ffmpeg or {}, neither of which is installed",
+                self.extension(),
+                self.encoder_tool()
+            ));
+        }
+        let mut cmd = Command::new(self.encoder_tool());
+        match self {
+            ArchiveFormat::Flac => cmd.arg("--silent").arg("-f").arg("-o").arg(archive).arg(wav),
+            ArchiveFormat::Opus => cmd.arg("--quiet").arg(wav).arg(archive),
+        };
+        Ok(cmd)
+    }
+}
+
+/// Parse `arecord -L`: device names start at column 0, their descriptions are indented below
+fn parse_arecord_list(output: &str) -> Vec<AudioDevice> {
+    let mut devices: Vec<AudioDevice> = Vec::new();
+    for line in output.lines() {
+        if line.trim().is_empty() {
+            continue;
+        }
+        if line.starts_with(char::is_whitespace) {
+            if let Some(device) = devices.last_mut() {
+                if !device.description.is_empty() {
+                    device.description.push_str(", ");
+                }
+                device.description.push_str(line.trim());
+            }
+        } else {
+            devices.push(AudioDevice { id: line.trim().to_string(), description: String::new() });
+        }
+    }
+    devices
+}
+
+/// Parse `ffmpeg -sources alsa`: lines look like "* default [Default ALSA Output]"
+fn parse_ffmpeg_sources(output: &str) -> Vec<AudioDevice> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let line = line.trim().trim_start_matches('*').trim();
+            let (id, rest) = line.split_once(" [")?;
+            Some(AudioDevice {
+                id: id.trim().to_string(),
+                description: rest.trim_end_matches(']').to_string(),
+            })
+        })
+        .collect()
+}
+
+/// Parse `ffmpeg -list_devices true -f dshow -i dummy` (stderr). Newer ffmpeg tags each device
+/// with "(audio)"/"(video)"; older builds group them under "DirectShow audio devices" headings.
+fn parse_dshow_devices(output: &str) -> Vec<AudioDevice> {
+    let mut devices = Vec::new();
+    let mut in_audio_section = false;
+    for line in output.lines() {
+        let Some((prefix, line)) = line.split_once(']') else { continue };
+        if !prefix.starts_with("[dshow") {
+            continue;
+        }
+        let line = line.trim();
+        if line.starts_with("DirectShow") {
+            in_audio_section = line.starts_with("DirectShow audio devices");
+            continue;
+        }
+        let Some((name, kind)) = line.strip_prefix('"').and_then(|rest| rest.split_once('"')) else {
+            continue;
+        };
+        let kind = kind.trim();
+        let is_audio = if kind.is_empty() { in_audio_section } else { kind.contains("audio") };
+        if is_audio {
+            devices.push(AudioDevice { id: name.to_string(), description: format!("{} (dshow)", name) });
+        }
+    }
+    devices
+}
+
+/// DirectShow audio capture devices, in the order ffmpeg lists them
+fn dshow_devices() -> Vec<AudioDevice> {
+    Command::new("ffmpeg")
+        .arg("-hide_banner")
+        .arg("-list_devices").arg("true")
+        .arg("-f").arg("dshow")
+        .arg("-i").arg("dummy")
+        .output()
+        // The listing always exits non-zero (there's no "dummy" input), so parse regardless
+        .map(|output| parse_dshow_devices(&String::from_utf8_lossy(&output.stderr)))
+        .unwrap_or_default()
+}
+
+/// dshow has no "default" device name, so fall back to the first capture device listed
+fn default_dshow_device() -> Result<String, String> {
+    dshow_devices()
+        .into_iter()
+        .next()
+        .map(|d| d.id)
+        .ok_or_else(|| "No DirectShow audio capture devices found".to_string())
+}
+
+fn probe_dshow_device(device: &str) -> Result<(), String> {
+    let devices = dshow_devices();
+    if devices.is_empty() || devices.iter().any(|d| d.id == device) {
+        Ok(())
+    } else {
+        Err(format!(
+            "Audio device '{}' not found; see `ffmpeg -list_devices true -f dshow -i dummy`",
+            device
+        ))
+    }
+}
+
+/// Parse `ffmpeg -f avfoundation -list_devices true -i ""` (stderr): "[0] MacBook Pro Microphone"
+/// lines under the "AVFoundation audio devices:" heading. The index is the device id ffmpeg takes.
+fn parse_avfoundation_devices(output: &str) -> Vec<AudioDevice> {
+    let mut devices = Vec::new();
+    let mut in_audio_section = false;
+    for line in output.lines() {
+        let Some((prefix, line)) = line.split_once(']') else { continue };
+        if !prefix.starts_with("[AVFoundation") {
+            continue;
+        }
+        let line = line.trim();
+        if line.starts_with("AVFoundation") {
+            in_audio_section = line.starts_with("AVFoundation audio devices");
+            continue;
+        }
+        let Some((index, name)) = line.strip_prefix('[').and_then(|rest| rest.split_once(']')) else {
+            continue;
+        };
+        if in_audio_section && index.parse::<usize>().is_ok() {
+            devices.push(AudioDevice { id: index.to_string(), description: name.trim().to_string() });
+        }
+    }
+    devices
+}
+
+fn avfoundation_devices() -> Vec<AudioDevice> {
+    Command::new("ffmpeg")
+        .arg("-hide_banner")
+        .arg("-f").arg("avfoundation")
+        .arg("-list_devices").arg("true")
+        .arg("-i").arg("")
+        .output()
+        .map(|output| parse_avfoundation_devices(&String::from_utf8_lossy(&output.stderr)))
+        .unwrap_or_default()
+}
+
+/// avfoundation accepts either the audio index or the device name
+fn probe_avfoundation_device(device: &str) -> Result<(), String> {
+    let devices = avfoundation_devices();
+    if devices.is_empty() || devices.iter().any(|d| d.id == device || d.description == device) {
+        Ok(())
+    } else {
+        Err(format!(
+            "Audio device '{}' not found; see `ffmpeg -f avfoundation -list_devices true -i \"\"`",
+            device
+        ))
+    }
+}
+
+// What macOS ffmpeg builds print when the app hasn't been granted microphone access
+const PERMISSION_ERRORS: &[&str] = &["not authorized", "cannot use", "permission"];
+
+/// Capture a fraction of a second from the default input to see whether macOS allows mic access.
+/// None when it couldn't be determined (no ffmpeg, or it failed for some other reason).
+pub fn probe_mic_permission() -> Option<bool> {
+    let output = Command::new("ffmpeg")
+        .arg("-hide_banner")
+        .arg("-loglevel").arg("error")
+        .arg("-f").arg("avfoundation")
+        .arg("-i").arg(":default")
+        .arg("-t").arg("0.2")
+        .arg("-f").arg("null")
+        .arg("-")
+        .stdin(std::process::Stdio::null())
+        .output()
+        .ok()?;
+    if output.status.success() {
+        return Some(true);
+    }
+    let stderr = String::from_utf8_lossy(&output.stderr).to_lowercase();
+    PERMISSION_ERRORS.iter().any(|e| stderr.contains(e)).then_some(false)
+}
+
+/// Check a device can actually be opened for 16 kHz mono capture by reading a single sample
+fn probe_device(device: &str, backend: RecorderBackend) -> Result<(), String> {
+    match backend {
+        RecorderBackend::Alsa => {}
+        RecorderBackend::Native => return native_recorder::probe_device(device),
+        RecorderBackend::Pulse | RecorderBackend::PipeWire => return probe_pulse_source(device),
+        RecorderBackend::DirectShow => return probe_dshow_device(device),
+        RecorderBackend::AvFoundation => return probe_avfoundation_device(device),
+    }
+    let output = match Command::new("arecord")
+        .arg("-D").arg(device)
+        .arg("-f").arg("S16_LE")
+        .arg("-r").arg("16000")
+        .arg("-c").arg("1")
+        .arg("-s").arg("1")
+        .arg("-q")
+        .arg("/dev/null")
+        .output()
+    {
+        Ok(output) => output,
+        // Without arecord there's nothing to probe with; let the recorder report errors itself
+        Err(_) => return Ok(()),
+    };
+    if output.status.success() {
+        Ok(())
+    } else {
+        Err(format!(
+            "Audio device '{}' can't be opened: {}",
+            device,
+            String::from_utf8_lossy(&output.stderr).trim()
+        ))
+    }
+}
+
+/// Source names from `pactl list short sources`, or None when pactl isn't usable
+fn pulse_sources() -> Option<Vec<String>> {
+    let output = Command::new("pactl").arg("list").arg("short").arg("sources").output().ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    Some(
+        String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .filter_map(|line| line.split('\t').nth(1).map(str::to_string))
+            .collect(),
+    )
+}
+
+/// How long the capture probe may take before a hung sound server counts as a failure
+const CAPTURE_PROBE_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// The sound server's default input, with its state from `pactl list short sources`
+/// ("RUNNING", "IDLE" or "SUSPENDED")
+pub struct DefaultSource {
+    pub name: String,
+    pub state: Option<String>,
+}
+
+/// Default source from `pactl info`, or `wpctl inspect` where pipewire-pulse/pactl are missing
+pub fn default_source() -> Option<DefaultSource> {
+    let from_pactl = Command::new("pactl").arg("info").output().ok().filter(|o| o.status.success()).and_then(|o| {
+        String::from_utf8_lossy(&o.stdout)
+            .lines()
+            .find_map(|line| line.strip_prefix("Default Source:").map(|name| name.trim().to_string()))
+    });
+    let name = from_pactl.or_else(|| {
+        let output = Command::new("wpctl").arg("inspect").arg("@DEFAULT_AUDIO_SOURCE@").output().ok()?;
+        if !output.status.success() {
+            return None;
+        }
+        String::from_utf8_lossy(&output.stdout).lines().find_map(|line| {
+            let value = line.trim().trim_start_matches('*').trim().strip_prefix("node.name = ")?;
+            Some(value.trim_matches('"').to_string())
+        })
+    })?;
+    if name.is_empty() || name == "@DEFAULT_SOURCE@" {
+        return None;
+    }
+
+    let state = Command::new("pactl").arg("list").arg("short").arg("sources").output().ok().and_then(|o| {
+        String::from_utf8_lossy(&o.stdout).lines().find_map(|line| {
+            let fields: Vec<&str> = line.split('\t').collect();
+            (fields.get(1) == Some(&name.as_str())).then(|| fields.get(4).map(|s| s.trim().to_string())).flatten()
+        })
+    });
+    Some(DefaultSource { name, state })
+}
+
+/// Peak sample magnitude at or below which a probe capture counts as silence;
+/// muted and suspended sources deliver exact zeros, a live mic's noise floor sits above this
+const CAPTURE_SILENCE_PEAK: i32 = 1;
+
+/// Whether raw s16le audio has any signal above the silence threshold
+fn has_signal(pcm: &[u8]) -> bool {
+    pcm.chunks_exact(2)
+        .map(|s| (i16::from_le_bytes([s[0], s[1]]) as i32).abs())
+        .any(|peak| peak > CAPTURE_SILENCE_PEAK)
+}
+
+/// Record 200ms from the default Pulse/PipeWire source with ffmpeg and check it carries signal,
+/// not just silence from a muted or suspended source; None when ffmpeg isn't installed
+pub fn probe_default_capture() -> Option<bool> {
+    if !has_ffmpeg() {
+        return None;
+    }
+    let mut child = Command::new("ffmpeg")
+        .arg("-hide_banner")
+        .arg("-loglevel").arg("error")
+        .arg("-f").arg("pulse")
+        .arg("-i").arg("default")
+        .arg("-t").arg("0.2")
+        .arg("-ac").arg("1")
+        .arg("-ar").arg(WHISPER_SAMPLE_RATE.to_string())
+        .arg("-f").arg("s16le")
+        .arg("-")
+        .stdin(std::process::Stdio::null())
+        .stdout(std::process::Stdio::piped())
+        .stderr(std::process::Stdio::null())
+        .spawn()
+        .ok()?;
+    let deadline = Instant::now() + CAPTURE_PROBE_TIMEOUT;
+    while child.try_wait().ok()?.is_none() {
+        if Instant::now() >= deadline {
+            let _ = child.kill();
+            let _ = child.wait();
+            return Some(false);
+        }
+        std::thread::sleep(Duration::from_millis(50));
+    }
+    let output = child.wait_with_output().ok()?;
+    Some(output.status.success() && has_signal(&output.stdout))
+}
+
+/// The first backend with a default input it can open right now: the sound server's default source,
+/// ALSA's default PCM, or a cpal input device
+pub fn working_capture_backend() -> Option<RecorderBackend> {
+    let sound_server = [RecorderBackend::PipeWire, RecorderBackend::Pulse];
+    if sound_server.iter().any(|b| backend_available(*b)) && default_source().is_some() {
+        return sound_server.into_iter().find(|b| backend_available(*b));
+    }
+    if backend_available(RecorderBackend::Alsa) && probe_device("default", RecorderBackend::Alsa).is_ok() {
+        return Some(RecorderBackend::Alsa);
+    }
+    native_recorder::has_default_input().then_some(RecorderBackend::Native)
+}
+
+/// Pulse/PipeWire sources are checked by name against `pactl list short sources`
+fn probe_pulse_source(device: &str) -> Result<(), String> {
+    let Some(sources) = pulse_sources() else {
+        return Ok(());
+    };
+    if sources.iter().any(|name| name == device) {
+        Ok(())
+    } else {
+        Err(format!("Audio source '{}' not found; see `pactl list short sources`", device))
+    }
+}
+
+/// Validate and remember the device for this recording, falling back to the stored choice when None
+pub fn select_device(
+    app: &tauri::AppHandle,
+    device: Option<String>,
+    backend: RecorderBackend,
+) -> Result<Option<String>, String> {
+    let state = app.state::<AudioDeviceState>();
+    let device = match device {
+        None => state.device.lock().unwrap().clone(),
+        Some(device) => {
+            let device = device.trim().to_string();
+            let device = if device.is_empty() || device == "default" { None } else { Some(device) };
+            if let Some(device) = &device {
+                probe_device(device, backend)?;
+            }
+            *state.device.lock().unwrap() = device.clone();
+            device
+        }
+    };
+    match (device, backend) {
+        (None, RecorderBackend::DirectShow) => default_dshow_device().map(Some),
+        (device, _) => Ok(device),
+    }
+}
+
+/// What to record: the microphone, whatever is playing, or both mixed together
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug)]
+#[serde(rename_all = "lowercase")]
+pub enum CaptureSource {
+    Mic,
+    System,
+    Both,
+}
+
+impl CaptureSource {
+    pub fn parse(source: Option<&str>) -> Result<CaptureSource, String> {
+        match source.map(|s| s.trim().to_lowercase()).as_deref() {
+            None | Some("") | Some("mic") => Ok(CaptureSource::Mic),
+            Some("system") => Ok(CaptureSource::System),
+            Some("both") => Ok(CaptureSource::Both),
+            Some(other) => Err(format!("Unknown capture source '{}' (expected mic, system, or both)", other)),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct MonitorSource {
+    pub id: String,
+    pub sink: String,
+    pub is_default: bool,
+}
+
+fn default_sink() -> Option<String> {
+    let output = Command::new("pactl").arg("get-default-sink").output().ok()?;
+    let sink = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    (output.status.success() && !sink.is_empty()).then_some(sink)
+}
+
+fn monitor_sources() -> Vec<MonitorSource> {
+    let default_monitor = default_sink().map(|sink| format!("{}.monitor", sink));
+    pulse_sources()
+        .unwrap_or_default()
+        .into_iter()
+        .filter_map(|id| {
+            let sink = id.strip_suffix(".monitor")?.to_string();
+            Some(MonitorSource { is_default: Some(&id) == default_monitor.as_ref(), id, sink })
+        })
+        .collect()
+}
+
+const NO_MONITOR_SOURCE: &str = "No monitor source found for system audio. Recording what's playing needs PulseAudio or PipeWire (pipewire-pulse) with pactl installed.";
+
+/// The monitor of the default sink, i.e. what's currently coming out of the speakers
+fn default_monitor_source() -> Result<String, String> {
+    let monitors = monitor_sources();
+    monitors
+        .iter()
+        .find(|m| m.is_default)
+        .or(monitors.first())
+        .map(|m| m.id.clone())
+        .ok_or_else(|| NO_MONITOR_SOURCE.to_string())
+}
+
+/// Everything needed to spawn the recorder for one capture configuration, resolved up front
+#[derive(Clone, Debug)]
+pub struct CapturePlan {
+    pub backend: RecorderBackend,
+    pub source: CaptureSource,
+    pub mic: Option<String>,
+    pub monitor: Option<String>,
+    pub profile: RecordingProfile,
+    pub archive: Option<ArchiveFormat>,
+}
+
+impl CapturePlan {
+    pub fn new(backend: RecorderBackend, source: CaptureSource, mic: Option<String>) -> Result<CapturePlan, String> {
+        if source != CaptureSource::Mic
+            && (matches!(backend, RecorderBackend::Alsa | RecorderBackend::Native) || backend.is_platform_ffmpeg())
+        {
+            return Err(format!(
+                "System audio capture needs the pulse or pipewire backend; {} has no loopback source",
+                backend.as_str()
+            ));
+        }
+        if source == CaptureSource::Both && !has_ffmpeg() {
+            return Err("Recording mic and system audio together needs ffmpeg to mix them".to_string());
+        }
+        let monitor = match source {
+            CaptureSource::Mic => None,
+            CaptureSource::System | CaptureSource::Both => Some(default_monitor_source()?),
+        };
+        Ok(CapturePlan { backend, source, mic, monitor, profile: RecordingProfile::default(), archive: None })
+    }
+
+    pub fn with_profile(mut self, profile: RecordingProfile) -> CapturePlan {
+        self.profile = profile;
+        self
+    }
+
+    pub fn with_archive(mut self, archive: Option<ArchiveFormat>) -> CapturePlan {
+        self.archive = archive;
+        self
+    }
+
+    /// The recorder is ffmpeg itself, so it can write the archive as a second output while recording
+    pub fn writes_archive_directly(&self) -> bool {
+        self.archive.is_some()
+            && has_ffmpeg()
+            && match self.source {
+                CaptureSource::Both | CaptureSource::System => true,
+                CaptureSource::Mic => self.backend == RecorderBackend::Pulse || self.backend.is_platform_ffmpeg(),
+            }
+    }
+
+    /// ffmpeg input arguments (plus the amix filter when mixing) for this plan
+    pub fn ffmpeg_input_args(&self) -> Vec<String> {
+        self.ffmpeg_inputs(false)
+    }
+
+    /// With `split`, the mix is fed to two labelled outputs, [wav] and [archive]
+    fn ffmpeg_inputs(&self, split: bool) -> Vec<String> {
+        let format = ffmpeg_input_format(self.backend).to_string();
+        let mic = self.mic.clone().unwrap_or_else(|| "default".to_string());
+        let input = |device: String| {
+            vec!["-f".to_string(), format.clone(), "-i".to_string(), ffmpeg_device_input(self.backend, &device)]
+        };
+        match (self.source, self.monitor.clone()) {
+            (CaptureSource::System, Some(monitor)) => input(monitor),
+            (CaptureSource::Both, Some(monitor)) => {
+                let mut args = input(mic);
+                args.extend(input(monitor));
+                args.push("-filter_complex".to_string());
+                args.push(if split {
+                    "amix=inputs=2:duration=longest,asplit=2[wav][archive]".to_string()
+                } else {
+                    "amix=inputs=2:duration=longest".to_string()
+                });
+                args
+            }
+            _ => input(mic),
+        }
+    }
+
+    /// Recorder command writing a WAV in the plan's profile to `output`; when it also writes the
+    /// archive, the WAV is whisper's 16 kHz mono and the archive gets the profile instead
+    pub fn command(&self, output: &Path, duration_secs: Option<u64>) -> Result<Command, String> {
+        let direct_archive = self.archive.filter(|_| self.writes_archive_directly());
+        let wav_profile = if direct_archive.is_some() { RecordingProfile::default() } else { self.profile };
+        let mut cmd = match self.source {
+            CaptureSource::Mic => capture_command(self.backend, self.mic.as_deref(), output, duration_secs, &wav_profile)?,
+            // pw-record can't target a sink monitor by source name, so use the Pulse path for loopback
+            CaptureSource::System => {
+                capture_command(RecorderBackend::Pulse, self.monitor.as_deref(), output, duration_secs, &wav_profile)?
+            }
+            CaptureSource::Both => {
+                let mut cmd = Command::new("ffmpeg");
+                cmd.arg("-hide_banner")
+                    .arg("-loglevel").arg("error")
+                    .arg("-y")
+                    .args(self.ffmpeg_inputs(direct_archive.is_some()));
+                if let Some(secs) = duration_secs {
+                    cmd.arg("-t").arg(secs.to_string());
+                }
+                if direct_archive.is_some() {
+                    cmd.arg("-map").arg("[wav]");
+                }
+                wav_profile.ffmpeg_output_args(&mut cmd);
+                cmd.arg(output)
+                    .stdin(std::process::Stdio::null());
+                if direct_archive.is_some() {
+                    cmd.arg("-map").arg("[archive]");
+                }
+                cmd
+            }
+        };
+        if let Some(format) = direct_archive {
+            if let Some(secs) = duration_secs {
+                cmd.arg("-t").arg(secs.to_string());
+            }
+            format.ffmpeg_output_args(&self.profile, &mut cmd);
+            cmd.arg(format.archive_path(output));
+        }
+        Ok(cmd)
+    }
+}
+
+/// Set the sample rate, channel count, and bit depth used by start_system_recording
+#[tauri::command]
+pub async fn set_recording_profile(
+    state: tauri::State<'_, RecordingProfileState>,
+    sample_rate: u32,
+    channels: u16,
+    bit_depth: u16,
+) -> Result<RecordingProfile, String> {
+    let profile = RecordingProfile { sample_rate, channels, bit_depth };
+    profile.validate()?;
+    *state.profile.lock().unwrap() = profile;
+    Ok(profile)
+}
+
+/// List PulseAudio/PipeWire monitor sources, flagging the one for the default sink
+#[tauri::command]
+pub async fn list_monitor_sources() -> Result<Vec<MonitorSource>, String> {
+    let monitors = monitor_sources();
+    if monitors.is_empty() {
+        return Err(NO_MONITOR_SOURCE.to_string());
+    }
+    Ok(monitors)
+}
+
+/// List capture devices from arecord, ffmpeg's source probe (dshow/avfoundation off Linux), and cpal
+#[tauri::command]
+pub async fn list_audio_devices() -> Result<Vec<AudioDevice>, String> {
+    let mut devices = Vec::new();
+
+    if cfg!(target_os = "windows") {
+        if has_ffmpeg() {
+            devices.extend(dshow_devices());
+        }
+    } else if cfg!(target_os = "macos") {
+        if has_ffmpeg() {
+            devices.extend(avfoundation_devices());
+        }
+    } else {
+        if let Ok(output) = Command::new("arecord").arg("-L").output() {
+            if output.status.success() {
+                devices.extend(parse_arecord_list(&String::from_utf8_lossy(&output.stdout)));
+            }
+        }
+        if has_ffmpeg() {
+            if let Ok(output) = Command::new("ffmpeg").arg("-hide_banner").arg("-sources").arg("alsa").output() {
+                // ffmpeg exits non-zero after listing on some builds, so parse whatever it printed
+                devices.extend(parse_ffmpeg_sources(&String::from_utf8_lossy(&output.stdout)));
+            }
+        }
+    }
+
+    devices.extend(native_recorder::input_device_names().into_iter().map(|name| AudioDevice {
+        description: format!("{} (native)", name),
+        id: name,
+    }));
+
+    let mut seen = HashSet::new();
+    devices.retain(|d| seen.insert(d.id.clone()));
+
+    if devices.is_empty() {
+        return Err("No audio capture devices found (is alsa-utils installed?)".to_string());
+    }
+    Ok(devices)
+}
@@ -0,0 +1,140 @@
+use std::fs;
+use std::io::{ErrorKind, Write};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+use sysinfo::{Pid, ProcessRefreshKind, ProcessesToUpdate, System};
+
+use crate::error::AppError;
+use crate::get_cache_dir;
+
+/// Holds the PID of the instance that owns the recorders, in the cache dir they record into
+const LOCK_FILE: &str = "recorders.lock";
+
+/// Set once this instance holds the lock. It's kept until exit, so later recording commands don't
+/// read the lock again; another instance only gets it through force_takeover_recorders.
+static OWNED: AtomicBool = AtomicBool::new(false);
+
+fn lock_path() -> Result<PathBuf, String> {
+    Ok(get_cache_dir()?.join(LOCK_FILE))
+}
+
+fn read_owner() -> Option<u32> {
+    fs::read_to_string(lock_path().ok()?).ok()?.trim().parse().ok()
+}
+
+/// Whether `pid` is a running copy of this app; a PID reused by some other program doesn't count
+fn is_instance_alive(pid: u32) -> bool {
+    let (theirs, ours) = (Pid::from_u32(pid), Pid::from_u32(std::process::id()));
+    let mut sys = System::new();
+    sys.refresh_processes_specifics(ProcessesToUpdate::Some(&[theirs, ours]), true, ProcessRefreshKind::new());
+    match (sys.process(theirs), sys.process(ours)) {
+        (Some(theirs), Some(ours)) => theirs.name() == ours.name(),
+        (Some(_), None) => true,
+        (None, _) => false,
+    }
+}
+
+/// Write our PID, failing if another instance got there first
+fn write_lock(replace: bool) -> Result<bool, String> {
+    let path = lock_path()?;
+    if replace {
+        let _ = fs::remove_file(&path);
+    }
+    match fs::OpenOptions::new().write(true).create_new(true).open(&path) {
+        Ok(mut file) => {
+            write!(file, "{}", std::process::id()).map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+            Ok(true)
+        }
+        Err(e) if e.kind() == ErrorKind::AlreadyExists => Ok(false),
+        Err(e) => Err(format!("Failed to create {}: {}", path.display(), e)),
+    }
+}
+
+/// Make sure this instance owns the recorders, taking over a lock left by one that crashed.
+/// Recording commands call this first so two instances never drive (or clean up) the same recorders.
+pub fn ensure_owner() -> Result<(), AppError> {
+    if OWNED.load(Ordering::SeqCst) {
+        return Ok(());
+    }
+    claim_lock()?;
+    OWNED.store(true, Ordering::SeqCst);
+    Ok(())
+}
+
+/// ensure_owner for async commands. Until the lock is held, claiming it can check on another
+/// process and wait for it, so that runs on the blocking pool.
+pub async fn ensure_owner_async() -> Result<(), AppError> {
+    if OWNED.load(Ordering::SeqCst) {
+        return Ok(());
+    }
+    tauri::async_runtime::spawn_blocking(ensure_owner)
+        .await
+        .map_err(|e| AppError::Other(format!("Checking the recorder lock failed: {}", e)))?
+}
+
+fn claim_lock() -> Result<(), AppError> {
+    let ours = std::process::id();
+    let mut unreadable = 0;
+    loop {
+        match read_owner() {
+            Some(pid) if pid == ours => return Ok(()),
+            Some(pid) if is_instance_alive(pid) => {
+                return Err(AppError::RecorderBusy(format!(
+                    "Another last-gen-notes window (PID {}) owns the recorders; close it, or call force_takeover_recorders",
+                    pid
+                )));
+            }
+            Some(pid) => {
+                log::warn!("Taking over the recorder lock from PID {}, which is no longer running", pid);
+                write_lock(true)?;
+                return Ok(());
+            }
+            // If another instance creates it first, go round and check on that one
+            None if !lock_path()?.exists() => {
+                if write_lock(false)? {
+                    return Ok(());
+                }
+            }
+            // Just created by another instance that hasn't written its PID yet, or garbled
+            None if unreadable < 3 => {
+                unreadable += 1;
+                std::thread::sleep(Duration::from_millis(50));
+            }
+            None => {
+                write_lock(true)?;
+                return Ok(());
+            }
+        }
+    }
+}
+
+/// Claim the recorders at startup if nobody else holds them; recording commands retry later
+pub fn claim() {
+    if let Err(e) = ensure_owner() {
+        log::warn!("{}", e);
+    }
+}
+
+/// Take the recorder lock whatever holds it, returning the PID it was taken from. For when the
+/// running-instance check gets it wrong, e.g. a lock copied over with a synced cache dir.
+#[tauri::command]
+pub async fn force_takeover_recorders() -> Result<Option<u32>, String> {
+    let previous = read_owner().filter(|&pid| pid != std::process::id());
+    write_lock(true)?;
+    OWNED.store(true, Ordering::SeqCst);
+    if let Some(pid) = previous {
+        log::warn!("Took the recorder lock from PID {}", pid);
+    }
+    Ok(previous)
+}
+
+/// Remove the lock on exit, if it's still ours
+pub fn release() {
+    OWNED.store(false, Ordering::SeqCst);
+    if read_owner() == Some(std::process::id()) {
+        if let Ok(path) = lock_path() {
+            let _ = fs::remove_file(path);
+        }
+    }
+}
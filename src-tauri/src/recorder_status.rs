@@ -0,0 +1,128 @@
+use serde::Serialize;
+use std::sync::Mutex;
+use tauri::Manager;
+
+use crate::{events, finish_live_recording, notifications, sleep_inhibit, start_live_recording, tray, ChunkedRecorderState};
+
+/// Where the live session is in its lifecycle
+#[derive(Serialize, Clone, Copy, PartialEq, Eq, Debug, Default)]
+#[serde(rename_all = "kebab-case")]
+pub enum LivePhase {
+    #[default]
+    Idle,
+    Recording,
+    Paused,
+    /// Stopped, with the last chunks still being transcribed
+    Transcribing,
+}
+
+/// Everything that moves the recorder from one state to another
+#[derive(Clone, Copy, Debug)]
+pub enum Transition {
+    LiveStarted,
+    LivePaused,
+    LiveResumed,
+    LiveStopping,
+    LiveFinished,
+    SystemStarted,
+    SystemStopped,
+    OneShotStarted,
+    OneShotStopped,
+    /// Live chunks now waiting for a transcription slot
+    Backlog(usize),
+}
+
+/// What the tray and the frontend show about recording
+#[derive(Serialize, Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub struct RecorderStatus {
+    pub live: LivePhase,
+    pub system_recording: bool,
+    /// A fixed-length record_system_audio capture is running
+    pub one_shot_recording: bool,
+    pub backlog: usize,
+}
+
+impl RecorderStatus {
+    /// Whether anything is capturing audio or still transcribing a live session
+    pub fn recording(&self) -> bool {
+        self.live != LivePhase::Idle || self.system_recording || self.one_shot_recording
+    }
+
+    /// The status after `transition`, or None if it can't happen from this one
+    fn apply(self, transition: Transition) -> Option<RecorderStatus> {
+        use LivePhase::*;
+        use Transition::*;
+        let mut next = self;
+        match (self.live, transition) {
+            (Idle, LiveStarted) => {
+                next.live = Recording;
+                next.backlog = 0;
+            }
+            (Recording, LivePaused) => next.live = Paused,
+            (Paused, LiveResumed) => next.live = Recording,
+            (Recording | Paused, LiveStopping) => next.live = Transcribing,
+            // Also ends sessions that stopped without going through LiveStopping, e.g. a failed start
+            (_, LiveFinished) => {
+                next.live = Idle;
+                next.backlog = 0;
+            }
+            (_, SystemStarted) => next.system_recording = true,
+            (_, SystemStopped) => next.system_recording = false,
+            (_, OneShotStarted) => next.one_shot_recording = true,
+            (_, OneShotStopped) => next.one_shot_recording = false,
+            (Idle, Backlog(_)) => {}
+            (_, Backlog(waiting)) => next.backlog = waiting,
+            _ => return None,
+        }
+        Some(next)
+    }
+}
+
+// Single source of truth for recorder state; every start, pause and stop goes through transition
+pub struct RecorderStatusState {
+    status: Mutex<RecorderStatus>,
+}
+
+impl RecorderStatusState {
+    pub fn new() -> Self {
+        RecorderStatusState { status: Mutex::new(RecorderStatus::default()) }
+    }
+}
+
+pub fn current(app: &tauri::AppHandle) -> RecorderStatus {
+    *app.state::<RecorderStatusState>().status.lock().unwrap()
+}
+
+/// Move the recorder to its next state, updating the tray and telling the frontend. Transitions
+/// that don't apply (pausing while idle, say) are logged and ignored.
+pub fn transition(app: &tauri::AppHandle, transition: Transition) {
+    let next = {
+        let state = app.state::<RecorderStatusState>();
+        let mut status = state.status.lock().unwrap();
+        let Some(next) = status.apply(transition) else {
+            log::warn!("Ignoring recorder transition {:?} while live recording is {:?}", transition, status.live);
+            return;
+        };
+        if next == *status {
+            return;
+        }
+        *status = next;
+        next
+    };
+    tray::refresh(app);
+    sleep_inhibit::sync_in_background(app);
+    notifications::recording_ended(app);
+    events::emit(app, "recorder-status", next);
+}
+
+/// Start a live recording with the saved settings, or stop the one in progress
+pub async fn toggle_live(app: &tauri::AppHandle) -> Result<(), String> {
+    let state = app.state::<ChunkedRecorderState>();
+    if *state.active.lock() {
+        finish_live_recording(app).await.map(|_| ())
+    } else {
+        start_live_recording(state, app.clone(), None, None, None, None, None, None, None, None, None, None, None, None, None)
+            .map(|_| ())
+            .map_err(|e| e.to_string())
+    }
+}
@@ -0,0 +1,241 @@
+use serde::Serialize;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+use tauri::Manager;
+
+use crate::{audio, get_cache_dir, paths, retranscribe, session, ChunkedRecorderState, RecorderState};
+
+/// Extensions listed as recordings
+pub const RECORDING_EXTENSIONS: &[&str] = &["wav", "mp3", "m4a", "ogg", "opus", "flac", "webm"];
+
+/// Transcript files that sit next to a recording with the same stem
+const TRANSCRIPT_EXTENSIONS: &[&str] = &["txt", "srt", "vtt", "json", "summary.md"];
+
+/// Archival copies start_system_recording writes next to the WAV; the pair is listed and deleted as one
+const ARCHIVE_EXTENSIONS: &[&str] = &["flac", "opus"];
+
+#[derive(Serialize, Clone, Debug)]
+pub struct RecordingEntry {
+    pub filename: String,
+    pub path: String,
+    pub size: u64,
+    pub created_at: u64,
+    pub duration_secs: Option<f64>,
+    pub has_transcript: bool,
+    pub archive_path: Option<String>,
+}
+
+/// What a cleanup command actually did
+#[derive(Serialize, Clone, Default, Debug)]
+pub struct CleanupSummary {
+    pub processes_signalled: usize,
+    pub recordings_stopped: usize,
+    pub files_removed: Vec<String>,
+    pub bytes_freed: u64,
+}
+
+fn has_extension(path: &Path, extensions: &[&str]) -> bool {
+    path.extension()
+        .and_then(|e| e.to_str())
+        .map(|e| extensions.iter().any(|x| e.eq_ignore_ascii_case(x)))
+        .unwrap_or(false)
+}
+
+fn transcript_siblings(path: &Path) -> Vec<PathBuf> {
+    TRANSCRIPT_EXTENSIONS
+        .iter()
+        .map(|ext| path.with_extension(ext))
+        .filter(|p| p.exists())
+        .collect()
+}
+
+fn archive_sibling(path: &Path) -> Option<PathBuf> {
+    if !has_extension(path, &["wav"]) {
+        return None;
+    }
+    ARCHIVE_EXTENSIONS.iter().map(|ext| path.with_extension(ext)).find(|p| p.is_file())
+}
+
+/// An archive whose WAV is still around is part of that recording, not a recording of its own
+fn is_paired_archive(path: &Path) -> bool {
+    has_extension(path, ARCHIVE_EXTENSIONS) && path.with_extension("wav").is_file()
+}
+
+/// Delete a recording together with its archival copy, if it has one
+pub fn remove_recording(path: &Path) -> std::io::Result<()> {
+    if let Some(archive) = archive_sibling(path) {
+        fs::remove_file(archive)?;
+    }
+    fs::remove_file(path)
+}
+
+fn millis_since_epoch(time: SystemTime) -> u64 {
+    time.duration_since(SystemTime::UNIX_EPOCH).map(|d| d.as_millis() as u64).unwrap_or(0)
+}
+
+pub fn is_recording_in_progress(app: &tauri::AppHandle, path: &Path) -> bool {
+    let state = app.state::<RecorderState>();
+    let current = state.current.lock();
+    current
+        .as_ref()
+        .map(|p| {
+            let path = path.canonicalize().ok();
+            p.parts.iter().any(|part| part.canonicalize().ok() == path)
+        })
+        .unwrap_or(false)
+}
+
+/// Recordings in the cache dir, newest first
+pub fn scan_recordings() -> Result<Vec<RecordingEntry>, String> {
+    let cache_dir = get_cache_dir()?;
+    let entries = fs::read_dir(&cache_dir).map_err(|e| format!("Failed to read {}: {}", cache_dir.display(), e))?;
+
+    let mut recordings: Vec<RecordingEntry> = entries
+        .flatten()
+        .filter_map(|entry| {
+            let path = entry.path();
+            let filename = entry.file_name().to_string_lossy().to_string();
+            // convert-*.wav are transcription temp files, not recordings
+            if !has_extension(&path, RECORDING_EXTENSIONS) || filename.starts_with("convert-") || is_paired_archive(&path) {
+                return None;
+            }
+            let meta = entry.metadata().ok().filter(|m| m.is_file())?;
+            let created = meta.created().or_else(|_| meta.modified()).map(millis_since_epoch).unwrap_or(0);
+            let duration_secs = has_extension(&path, &["wav"])
+                .then(|| audio::read_wav_info(&path).ok())
+                .flatten()
+                .map(|info| info.duration_secs());
+            let archive = archive_sibling(&path);
+            let archive_size = archive.as_ref().and_then(|a| fs::metadata(a).ok()).map(|m| m.len()).unwrap_or(0);
+            Some(RecordingEntry {
+                filename,
+                path: path.to_string_lossy().to_string(),
+                size: meta.len() + archive_size,
+                created_at: created,
+                duration_secs,
+                has_transcript: !transcript_siblings(&path).is_empty(),
+                archive_path: archive.map(|a| a.to_string_lossy().to_string()),
+            })
+        })
+        .collect();
+
+    recordings.sort_by_key(|r| std::cmp::Reverse(r.created_at));
+    Ok(recordings)
+}
+
+#[tauri::command]
+pub async fn list_recordings() -> Result<Vec<RecordingEntry>, String> {
+    scan_recordings()
+}
+
+/// Delete a recording and its archival copy from the cache dir (its transcripts are kept)
+#[tauri::command]
+pub async fn delete_recording(app: tauri::AppHandle, filename: String) -> Result<(), String> {
+    let path = paths::in_cache_dir(&filename, RECORDING_EXTENSIONS)?;
+    if !path.is_file() {
+        return Err(format!("Recording '{}' not found", filename));
+    }
+    if is_recording_in_progress(&app, &path) {
+        return Err("Can't delete a recording that is still in progress".into());
+    }
+    remove_recording(&path).map_err(|e| format!("Failed to delete {}: {}", filename, e))
+}
+
+/// Rename a recording, carrying along its archive and any same-named transcripts; returns the new path
+#[tauri::command]
+pub async fn rename_recording(app: tauri::AppHandle, old: String, new: String) -> Result<String, String> {
+    let from = paths::in_cache_dir(&old, RECORDING_EXTENSIONS)?;
+    let to = paths::in_cache_dir(&new, RECORDING_EXTENSIONS)?;
+    if !from.is_file() {
+        return Err(format!("Recording '{}' not found", old));
+    }
+    if to.exists() {
+        return Err(format!("A file named '{}' already exists", new));
+    }
+    if is_recording_in_progress(&app, &from) {
+        return Err("Can't rename a recording that is still in progress".into());
+    }
+
+    let mut siblings = transcript_siblings(&from);
+    // Pairing is by WAV stem, so a non-WAV name leaves the archive listed on its own
+    if has_extension(&to, &["wav"]) {
+        siblings.extend(archive_sibling(&from));
+    }
+    fs::rename(&from, &to).map_err(|e| format!("Failed to rename {}: {}", old, e))?;
+    for sibling in siblings {
+        if let Some(ext) = sibling.extension() {
+            let _ = fs::rename(&sibling, to.with_extension(ext));
+        }
+    }
+    Ok(to.to_string_lossy().to_string())
+}
+
+/// Delete recordings older than `older_than_secs` (all when None), except excluded names and the one in progress
+#[tauri::command]
+pub async fn clear_recordings(
+    app: tauri::AppHandle,
+    older_than_secs: Option<u64>,
+    exclude: Vec<String>,
+) -> Result<CleanupSummary, String> {
+    let now = millis_since_epoch(SystemTime::now());
+    let mut summary = CleanupSummary::default();
+    for recording in scan_recordings()? {
+        let old_enough = older_than_secs
+            .map(|secs| now.saturating_sub(recording.created_at) >= secs * 1000)
+            .unwrap_or(true);
+        let excluded = exclude.iter().any(|e| *e == recording.filename || *e == recording.path);
+        let path = Path::new(&recording.path);
+        if !old_enough || excluded || is_recording_in_progress(&app, path) {
+            continue;
+        }
+        if remove_recording(path).is_ok() {
+            summary.bytes_freed += recording.size;
+            summary.files_removed.push(recording.filename);
+        }
+    }
+    Ok(summary)
+}
+
+/// What cleanup_recorders_and_cache always did: delete the cache dir's WAVs and nothing else,
+/// leaving other formats and archival copies alone
+pub fn clear_wav_recordings(app: &tauri::AppHandle) -> Result<CleanupSummary, String> {
+    let mut summary = CleanupSummary::default();
+    for recording in scan_recordings()? {
+        let path = Path::new(&recording.path);
+        if !has_extension(path, &["wav"]) || is_recording_in_progress(app, path) {
+            continue;
+        }
+        // recording.size counts the archive too, which stays
+        let size = fs::metadata(path).map(|m| m.len()).unwrap_or(0);
+        if fs::remove_file(path).is_ok() {
+            summary.bytes_freed += size;
+            summary.files_removed.push(recording.filename);
+        }
+    }
+    Ok(summary)
+}
+
+/// Empty the live-session chunk directory; refused while a live recording is using it
+#[tauri::command]
+pub async fn clear_live_session_cache(app: tauri::AppHandle) -> Result<CleanupSummary, String> {
+    if *app.state::<ChunkedRecorderState>().active.lock() {
+        return Err("Stop the live recording before clearing its cache".into());
+    }
+    let dir = session::live_session_dir()?;
+    if retranscribe::is_pinned(&app, &dir) {
+        return Err("The live session is still being retranscribed".into());
+    }
+    let mut summary = CleanupSummary::default();
+    let Ok(entries) = fs::read_dir(&dir) else {
+        return Ok(summary);
+    };
+    for entry in entries.flatten() {
+        let size = entry.metadata().map(|m| m.len()).unwrap_or(0);
+        if fs::remove_file(entry.path()).is_ok() {
+            summary.bytes_freed += size;
+            summary.files_removed.push(entry.file_name().to_string_lossy().to_string());
+        }
+    }
+    Ok(summary)
+}
@@ -0,0 +1,108 @@
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+use tauri::Emitter;
+
+use crate::{get_config_dir, notes, recordings, retranscribe};
+
+const POLICY_FILE: &str = "retention.json";
+
+const SECS_PER_DAY: u64 = 24 * 60 * 60;
+
+/// Limits on how much recorded audio is kept in the cache dir; all unset means keep everything
+#[derive(Serialize, Deserialize, Clone, Default, Debug)]
+pub struct RetentionPolicy {
+    pub max_total_bytes: Option<u64>,
+    pub max_age_days: Option<u32>,
+    /// Never delete recordings that have a transcript saved next to them
+    pub keep_transcribed: bool,
+}
+
+/// Payload of `retention-cleanup`
+#[derive(Serialize, Clone, Default, Debug)]
+pub struct RetentionReport {
+    pub removed: Vec<String>,
+    pub bytes_freed: u64,
+}
+
+pub fn load_policy() -> RetentionPolicy {
+    get_config_dir()
+        .ok()
+        .and_then(|dir| fs::read_to_string(dir.join(POLICY_FILE)).ok())
+        .and_then(|raw| serde_json::from_str(&raw).ok())
+        .unwrap_or_default()
+}
+
+fn save_policy(policy: &RetentionPolicy) -> Result<(), String> {
+    let json = serde_json::to_string_pretty(policy)
+        .map_err(|e| format!("Failed to serialize retention policy: {}", e))?;
+    fs::write(get_config_dir()?.join(POLICY_FILE), json)
+        .map_err(|e| format!("Failed to save retention policy: {}", e))
+}
+
+/// Delete the oldest recordings that exceed the policy's age or total-size limits
+pub fn enforce(app: &tauri::AppHandle, policy: &RetentionPolicy) -> Result<RetentionReport, String> {
+    let mut report = RetentionReport::default();
+    if policy.max_total_bytes.is_none() && policy.max_age_days.is_none() {
+        return Ok(report);
+    }
+
+    let mut recordings = recordings::scan_recordings()?;
+    // Oldest first, so the size limit trims from the back of history
+    recordings.reverse();
+    let mut total: u64 = recordings.iter().map(|r| r.size).sum();
+    let now_ms = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map_err(|e| format!("time error: {}", e))?
+        .as_millis() as u64;
+    let max_age_ms = policy.max_age_days.map(|days| days as u64 * SECS_PER_DAY * 1000);
+
+    for recording in recordings {
+        let too_old = max_age_ms.map(|max| now_ms.saturating_sub(recording.created_at) > max).unwrap_or(false);
+        let over_budget = policy.max_total_bytes.map(|max| total > max).unwrap_or(false);
+        if !too_old && !over_budget {
+            continue;
+        }
+        let path = Path::new(&recording.path);
+        if (policy.keep_transcribed && recording.has_transcript)
+            || recordings::is_recording_in_progress(app, path)
+            || retranscribe::is_pinned(app, path)
+            // A note's audio (a session recording, say) goes with the note, not with the cache
+            || notes::with_audio(app, path).is_some()
+        {
+            continue;
+        }
+        if recordings::remove_recording(path).is_ok() {
+            total = total.saturating_sub(recording.size);
+            report.bytes_freed += recording.size;
+            report.removed.push(recording.filename);
+        }
+    }
+
+    if !report.removed.is_empty() {
+        let _ = app.emit("retention-cleanup", report.clone());
+    }
+    Ok(report)
+}
+
+/// Apply the saved policy off the async runtime; used at startup and after each recording
+pub fn enforce_in_background(app: tauri::AppHandle) {
+    tauri::async_runtime::spawn_blocking(move || {
+        if let Err(e) = enforce(&app, &load_policy()) {
+            log::warn!("retention cleanup failed: {}", e);
+        }
+    });
+}
+
+/// Save the retention policy and apply it right away
+#[tauri::command]
+pub async fn set_retention_policy(
+    app: tauri::AppHandle,
+    max_total_bytes: Option<u64>,
+    max_age_days: Option<u32>,
+    keep_transcribed: bool,
+) -> Result<RetentionReport, String> {
+    let policy = RetentionPolicy { max_total_bytes, max_age_days, keep_transcribed };
+    save_policy(&policy)?;
+    enforce(&app, &policy)
+}
@@ -0,0 +1,171 @@
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use tauri::Manager;
+
+use crate::error::AppError;
+use crate::{audio, jobs, notes, paths, session, transcribe_detailed_internal, transcript, whisper, ChunkedRecorderState};
+
+// Session dirs and recordings a queued or running retranscription still reads; retention and the
+// live-session cleanup leave them alone until it's done
+pub struct RetranscribeState {
+    pinned: Mutex<Vec<PathBuf>>,
+}
+
+impl RetranscribeState {
+    pub fn new() -> Self {
+        RetranscribeState { pinned: Mutex::new(Vec::new()) }
+    }
+}
+
+/// Whether `path` is, or is inside, something a retranscription is using
+pub fn is_pinned(app: &tauri::AppHandle, path: &Path) -> bool {
+    let resolved = path.canonicalize().ok();
+    let state = app.state::<RetranscribeState>();
+    let pinned = state.pinned.lock().unwrap();
+    pinned.iter().any(|p| path.starts_with(p) || resolved.as_ref().is_some_and(|r| r.starts_with(p)))
+}
+
+/// Paths pinned for one retranscription, released when it's dropped
+struct Pin {
+    app: tauri::AppHandle,
+    paths: Vec<PathBuf>,
+}
+
+impl Pin {
+    fn new(app: &tauri::AppHandle) -> Self {
+        Pin { app: app.clone(), paths: Vec::new() }
+    }
+
+    /// Pinned as given and resolved, so a lookup by either form matches
+    fn add(&mut self, path: &Path) {
+        let mut added = vec![path.to_path_buf()];
+        added.extend(path.canonicalize().ok().filter(|p| p != path));
+        self.app.state::<RetranscribeState>().pinned.lock().unwrap().extend(added.iter().cloned());
+        self.paths.extend(added);
+    }
+}
+
+impl Drop for Pin {
+    fn drop(&mut self) {
+        let state = self.app.state::<RetranscribeState>();
+        let mut pinned = state.pinned.lock().unwrap();
+        for path in &self.paths {
+            if let Some(index) = pinned.iter().position(|p| p == path) {
+                pinned.remove(index);
+            }
+        }
+    }
+}
+
+enum Source {
+    /// A live session's chunk directory
+    Session(PathBuf),
+    Recording(PathBuf),
+}
+
+fn source_for(path: PathBuf) -> Source {
+    if path.is_dir() {
+        Source::Session(path)
+    } else {
+        Source::Recording(path)
+    }
+}
+
+/// What to transcribe and the note to update: a note's audio (or the live session it was saved
+/// from), or a session dir or recording along with the note that points at it
+fn resolve(app: &tauri::AppHandle, target: &str) -> Result<(Source, Option<u64>), AppError> {
+    if let Ok(id) = target.parse::<u64>() {
+        let note = notes::get(app, id)?;
+        if let Some(audio) = note.audio_path.as_deref() {
+            return Ok((source_for(paths::existing(app, audio)?), Some(id)));
+        }
+        let dir = session::live_session_dir()?;
+        if session::read_manifest(&dir).and_then(|m| m.note_id) != Some(id) {
+            return Err(format!("Note {} has no audio left to retranscribe", id).into());
+        }
+        return Ok((Source::Session(dir), Some(id)));
+    }
+    let source = source_for(paths::existing(app, target)?);
+    let note_id = match &source {
+        Source::Session(dir) => session::read_manifest(dir).and_then(|m| m.note_id),
+        Source::Recording(path) => notes::with_audio(app, path).map(|n| n.id),
+    };
+    Ok((source, note_id))
+}
+
+#[derive(Serialize)]
+pub struct Retranscription {
+    /// What was transcribed; a session's chunks are joined into one recording first
+    pub audio_path: String,
+    /// The note whose transcript was replaced, if the audio belongs to one
+    pub note: Option<notes::Note>,
+    pub result: transcript::TranscriptResult,
+}
+
+/// Transcribe a live session (joining its chunks into one recording) or an existing recording
+/// again in a single detailed pass, e.g. with a bigger model than the live one. Its note gets the
+/// new transcript, keeping the one it replaced as `transcript_draft`.
+#[tauri::command]
+pub async fn retranscribe_session(
+    app: tauri::AppHandle,
+    session_dir_or_note_id: String,
+    model: Option<String>,
+    options: Option<whisper::TranscriptionOptions>,
+) -> Result<Retranscription, AppError> {
+    let (source, note_id) = resolve(&app, &session_dir_or_note_id)?;
+    let mut pin = Pin::new(&app);
+    let (audio_path, manifest, joined) = match source {
+        Source::Session(dir) => {
+            let live_dir = session::live_session_dir()?;
+            if *app.state::<ChunkedRecorderState>().active.lock() && live_dir.canonicalize().ok().as_ref() == Some(&dir) {
+                return Err(AppError::RecorderBusy("Stop the live recording before retranscribing it".into()));
+            }
+            pin.add(&dir);
+            let output = session::joined_recording_path(&dir)?;
+            pin.add(&output);
+            // Already joined when the session stopped, if its chunks are gone
+            if !session::chunk_wavs(&dir).is_empty() || !output.is_file() {
+                let (app, dir) = (app.clone(), dir.clone());
+                tauri::async_runtime::spawn_blocking(move || session::join_chunks(&app, &dir))
+                    .await
+                    .map_err(|e| format!("Joining the session's chunks failed: {}", e))??;
+            }
+            (output, session::read_manifest(&dir), true)
+        }
+        Source::Recording(path) => {
+            pin.add(&path);
+            (path, None, false)
+        }
+    };
+
+    let params = whisper::WhisperParams {
+        model,
+        language: manifest.as_ref().and_then(|m| m.language.clone()),
+        translate: manifest.as_ref().is_some_and(|m| m.translate),
+        options,
+        ..Default::default()
+    }
+    .with_settings(&app);
+    let audio = audio_path.to_string_lossy().to_string();
+    let job_id = jobs::queue(&app, &audio)?;
+    let (result, _) = transcribe_detailed_internal(&app, job_id, &audio, &params).await?;
+
+    let note = match note_id {
+        Some(id) => {
+            let previous = notes::get(&app, id)?;
+            Some(notes::update(&app, id, notes::NotePatch {
+                transcript: Some(result.text.clone()),
+                segments: Some(result.segments.clone()),
+                // Retranscribing again keeps the first draft, not the previous retranscription
+                transcript_draft: Some(previous.transcript_draft.unwrap_or(previous.transcript)),
+                // The chunks are cleared by the next live session, so point the note at the joined recording
+                audio_path: joined.then(|| audio.clone()),
+                duration_secs: audio::read_wav_info(&audio_path).ok().map(|info| info.duration_secs()),
+                ..Default::default()
+            })?)
+        }
+        None => None,
+    };
+    Ok(Retranscription { audio_path: audio, note, result })
+}
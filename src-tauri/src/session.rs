@@ -0,0 +1,270 @@
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use crate::{audio, events, get_cache_dir, transcribe_audio_internal, transcript, whisper, ChunkedRecorderState};
+
+const MANIFEST_FILE: &str = "session.json";
+
+/// Sessions with more chunks than this report session-join-progress while they're joined
+const JOIN_PROGRESS_MIN_CHUNKS: usize = 20;
+
+// Chunk transcriptions can finish concurrently in arecord mode; serialize manifest read-modify-write
+static MANIFEST_LOCK: Mutex<()> = Mutex::new(());
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct SessionChunk {
+    pub index: usize,
+    pub wav: String,
+    pub transcript: Option<String>,
+}
+
+/// On-disk description of a live session, enough to re-transcribe it after a crash
+#[derive(Serialize, Deserialize, Clone)]
+pub struct SessionManifest {
+    pub started_at: u64,
+    /// Length of the first chunk; adaptive sessions change it as they go
+    pub segment_seconds: u64,
+    pub recorder: String,
+    pub model: Option<String>,
+    pub language: Option<String>,
+    pub translate: bool,
+    pub chunks: Vec<SessionChunk>,
+    /// The note stop_live_recording saved the session as
+    #[serde(default)]
+    pub note_id: Option<u64>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct RecoveredSession {
+    pub session_dir: String,
+    pub started_at: Option<u64>,
+    pub chunk_count: usize,
+    pub retranscribed: usize,
+    pub failed: Vec<String>,
+    pub transcript: String,
+}
+
+/// Directory live recording writes its chunks into
+pub fn live_session_dir() -> Result<PathBuf, String> {
+    Ok(dirs::cache_dir()
+        .ok_or("Could not find cache directory")?
+        .join("last-gen-notes")
+        .join("live-session"))
+}
+
+pub fn chunk_wav_name(index: usize) -> String {
+    format!("chunk-{:04}.wav", index)
+}
+
+fn chunk_txt_name(index: usize) -> String {
+    format!("chunk-{:04}.txt", index)
+}
+
+/// Write to a temp file and rename over the target so a crash never leaves a half-written file
+fn write_atomic(path: &Path, contents: &[u8]) -> Result<(), String> {
+    let tmp = path.with_extension("json.tmp");
+    fs::write(&tmp, contents).map_err(|e| format!("Failed to write {}: {}", tmp.display(), e))?;
+    fs::rename(&tmp, path).map_err(|e| format!("Failed to replace {}: {}", path.display(), e))
+}
+
+pub fn read_manifest(dir: &Path) -> Option<SessionManifest> {
+    let raw = fs::read_to_string(dir.join(MANIFEST_FILE)).ok()?;
+    serde_json::from_str(&raw).ok()
+}
+
+fn write_manifest(dir: &Path, manifest: &SessionManifest) -> Result<(), String> {
+    let json = serde_json::to_vec_pretty(manifest)
+        .map_err(|e| format!("Failed to serialize session manifest: {}", e))?;
+    write_atomic(&dir.join(MANIFEST_FILE), &json)
+}
+
+/// Create the manifest for a freshly started live session
+pub fn start_session(dir: &Path, segment_seconds: u64, recorder: &str, params: &whisper::WhisperParams) -> Result<(), String> {
+    let started_at = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map_err(|e| format!("time error: {}", e))?
+        .as_millis() as u64;
+    let manifest = SessionManifest {
+        started_at,
+        segment_seconds,
+        recorder: recorder.to_string(),
+        model: params.model.clone(),
+        language: params.language.clone(),
+        translate: params.translate,
+        chunks: Vec::new(),
+        note_id: None,
+    };
+    let _guard = MANIFEST_LOCK.lock().unwrap();
+    write_manifest(dir, &manifest)
+}
+
+/// Persist one chunk's raw transcript next to its WAV and list it in the manifest
+pub fn record_chunk(dir: &Path, index: usize, text: &str) -> Result<(), String> {
+    let txt_name = chunk_txt_name(index);
+    fs::write(dir.join(&txt_name), text)
+        .map_err(|e| format!("Failed to write chunk transcript: {}", e))?;
+
+    let _guard = MANIFEST_LOCK.lock().unwrap();
+    let Some(mut manifest) = read_manifest(dir) else {
+        return Ok(());
+    };
+    let entry = SessionChunk { index, wav: chunk_wav_name(index), transcript: Some(txt_name) };
+    match manifest.chunks.iter_mut().find(|c| c.index == index) {
+        Some(existing) => *existing = entry,
+        None => {
+            manifest.chunks.push(entry);
+            manifest.chunks.sort_by_key(|c| c.index);
+        }
+    }
+    write_manifest(dir, &manifest)
+}
+
+/// Remember which note the session was saved as, so it can be retranscribed from the note
+pub fn set_note(dir: &Path, note_id: u64) -> Result<(), String> {
+    let _guard = MANIFEST_LOCK.lock().unwrap();
+    let Some(mut manifest) = read_manifest(dir) else {
+        return Ok(());
+    };
+    manifest.note_id = Some(note_id);
+    write_manifest(dir, &manifest)
+}
+
+/// Chunk indices present on disk, from WAV file names
+fn chunk_indices_on_disk(dir: &Path) -> Vec<usize> {
+    let mut indices: Vec<usize> = fs::read_dir(dir)
+        .map(|entries| {
+            entries
+                .flatten()
+                .filter_map(|e| {
+                    let name = e.file_name().to_string_lossy().to_string();
+                    name.strip_prefix("chunk-")?.strip_suffix(".wav")?.parse().ok()
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+    indices.sort_unstable();
+    indices
+}
+
+/// The session's chunk WAVs in recording order
+pub fn chunk_wavs(dir: &Path) -> Vec<PathBuf> {
+    chunk_indices_on_disk(dir).into_iter().map(|index| dir.join(chunk_wav_name(index))).collect()
+}
+
+/// Where a session's chunks are joined into one recording: session-<start>.wav in the recordings dir
+pub fn joined_recording_path(dir: &Path) -> Result<PathBuf, String> {
+    let started_at = read_manifest(dir).map(|m| m.started_at).unwrap_or_else(|| {
+        std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_millis() as u64)
+            .unwrap_or(0)
+    });
+    Ok(get_cache_dir()?.join(format!("session-{}.wav", started_at)))
+}
+
+/// Join the session's chunk WAVs into its recording and check the result. Blocking.
+/// arecord chunks run 3s past their segment length, but each is recorded after the last one
+/// exits, so no audio repeats between chunks and every sample is kept.
+pub fn join_chunks(app: &tauri::AppHandle, dir: &Path) -> Result<PathBuf, String> {
+    let chunks = chunk_wavs(dir);
+    if chunks.is_empty() {
+        return Err(format!("{} has no chunk recordings", dir.display()));
+    }
+    let output = joined_recording_path(dir)?;
+    let total = chunks.len();
+    let info = audio::concat_wavs(&chunks, &output, |joined| {
+        if total > JOIN_PROGRESS_MIN_CHUNKS {
+            events::emit(app, "session-join-progress", serde_json::json!({ "joined": joined, "total": total }));
+        }
+    })?;
+
+    let written = audio::read_wav_info(&output)?;
+    let file_len = fs::metadata(&output).map_err(|e| format!("Failed to read {}: {}", output.display(), e))?.len();
+    if written.data_len != info.data_len || file_len < 44 + info.data_len {
+        return Err(format!("{} didn't come out as written; the chunks were kept", output.display()));
+    }
+    log::info!("Joined {} chunks into {} ({:.0}s)", total, output.display(), info.duration_secs());
+    Ok(output)
+}
+
+/// Delete the chunk WAVs once they've been joined, except the `keep` chunks still waiting on a
+/// transcription; their transcripts and the manifest stay
+pub fn remove_chunk_wavs(dir: &Path, keep: &[usize]) {
+    for index in chunk_indices_on_disk(dir).into_iter().filter(|index| !keep.contains(index)) {
+        let wav = dir.join(chunk_wav_name(index));
+        if let Err(e) = fs::remove_file(&wav) {
+            log::warn!("Couldn't remove {}: {}", wav.display(), e);
+        }
+    }
+}
+
+/// Total audio across the session's chunk WAVs
+pub fn recorded_secs(dir: &Path) -> f64 {
+    chunk_indices_on_disk(dir)
+        .into_iter()
+        .filter_map(|index| audio::read_wav_info(&dir.join(chunk_wav_name(index))).ok())
+        .map(|info| info.duration_secs())
+        .sum()
+}
+
+/// Recover transcripts from a live session left behind by a crash, re-transcribing chunks with no .txt
+#[tauri::command]
+pub async fn recover_live_session(
+    app: tauri::AppHandle,
+    state: tauri::State<'_, ChunkedRecorderState>,
+) -> Result<Option<RecoveredSession>, String> {
+    if *state.active.lock() {
+        return Err("Can't recover while a live recording is in progress".into());
+    }
+
+    let dir = live_session_dir()?;
+    let indices = chunk_indices_on_disk(&dir);
+    if indices.is_empty() {
+        return Ok(None);
+    }
+
+    let manifest = read_manifest(&dir);
+    let params = whisper::WhisperParams {
+        model: manifest.as_ref().and_then(|m| m.model.clone()),
+        language: manifest.as_ref().and_then(|m| m.language.clone()),
+        translate: manifest.as_ref().map(|m| m.translate).unwrap_or(false),
+        ..Default::default()
+    };
+
+    let mut stitched: Vec<String> = Vec::new();
+    let mut retranscribed = 0;
+    let mut failed = Vec::new();
+    for index in &indices {
+        let txt_path = dir.join(chunk_txt_name(*index));
+        let text = match fs::read_to_string(&txt_path) {
+            Ok(text) => text,
+            Err(_) => {
+                let wav = dir.join(chunk_wav_name(*index));
+                match transcribe_audio_internal(&app, &wav.to_string_lossy(), &params).await {
+                    Ok(text) => {
+                        retranscribed += 1;
+                        let _ = record_chunk(&dir, *index, &text);
+                        text
+                    }
+                    Err(e) => {
+                        failed.push(format!("{}: {}", chunk_wav_name(*index), e));
+                        continue;
+                    }
+                }
+            }
+        };
+        let next = transcript::stitch_chunk(&stitched, text.trim());
+        stitched.push(next);
+    }
+
+    Ok(Some(RecoveredSession {
+        session_dir: dir.to_string_lossy().to_string(),
+        started_at: manifest.map(|m| m.started_at),
+        chunk_count: indices.len(),
+        retranscribed,
+        failed,
+        transcript: stitched.iter().filter(|t| !t.is_empty()).cloned().collect::<Vec<_>>().join(" "),
+    }))
+}
@@ -0,0 +1,292 @@
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::sync::Mutex;
+use tauri::Manager;
+
+use crate::denoise::NoiseReduction;
+use crate::{get_config_dir, recorder, sleep_inhibit};
+
+const SETTINGS_FILE: &str = "settings.json";
+
+/// Live segment length bounds, matching what start_live_recording accepts
+const SEGMENT_SECONDS_RANGE: (u64, u64) = (5, 60);
+
+const MAX_LLAMA_TOKENS: u32 = 8192;
+
+/// Each concurrent live transcription is its own whisper process with its own copy of the model
+const MAX_LIVE_CONCURRENCY: usize = 4;
+
+const LLAMA_CTX_RANGE: (u32, u32) = (512, 131_072);
+
+/// Stands in for the remote API key in settings sent to the frontend
+pub const REDACTED_KEY: &str = "********";
+
+/// Where summaries are generated
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug, Default)]
+#[serde(rename_all = "kebab-case")]
+pub enum SummarizationBackend {
+    /// Spawn llama-cli for every summary
+    LocalCli,
+    /// Use llama-server when it's running with the model loaded, llama-cli otherwise
+    #[default]
+    LocalServer,
+    /// Send transcripts to remote_base_url; nothing leaves the machine unless this is selected
+    Remote,
+}
+
+/// Compute backend whisper-cli runs on
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum TranscriptionBackend {
+    /// Force CPU with --no-gpu
+    Cpu,
+    Vulkan,
+    Cuda,
+    /// Whatever detect_gpu recommends, if the installed whisper-cli build supports it
+    #[default]
+    Auto,
+}
+
+/// Which finished jobs raise a desktop notification, when notifications are enabled
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(default, deny_unknown_fields)]
+pub struct NotificationToggles {
+    pub transcription: bool,
+    pub summary: bool,
+    pub batch: bool,
+    pub auto_stop: bool,
+}
+
+impl Default for NotificationToggles {
+    fn default() -> Self {
+        NotificationToggles { transcription: true, summary: true, batch: true, auto_stop: true }
+    }
+}
+
+/// User preferences that commands fall back to when a parameter is omitted
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(default, deny_unknown_fields)]
+pub struct AppSettings {
+    /// Whisper model name, e.g. "base.en"
+    pub model: Option<String>,
+    pub language: Option<String>,
+    pub device: Option<String>,
+    pub backend: Option<String>,
+    pub segment_seconds: u64,
+    /// Let live sessions lengthen or shorten chunks within min/max_segment_seconds to keep
+    /// transcripts arriving within 1.5x a chunk's length
+    pub adaptive_segments: bool,
+    pub min_segment_seconds: u64,
+    pub max_segment_seconds: u64,
+    /// Switch live sessions to the tiny model once chunks take longer to transcribe than to record
+    pub adaptive_model: bool,
+    /// whisper-cli thread count when the transcription options don't set one
+    pub threads: Option<usize>,
+    /// Live chunks transcribed at once; the rest queue behind them
+    pub live_concurrency: usize,
+    /// Noise filter applied before every transcription, live chunks included, unless a call's options pick one
+    pub noise_reduction: NoiseReduction,
+    pub transcription_backend: TranscriptionBackend,
+    pub llama_model_path: Option<String>,
+    pub llama_max_tokens: u32,
+    pub llama_temperature: f32,
+    /// Context window llama-cli is run with; long transcripts are summarized in windows that fit it
+    pub llama_ctx_size: u32,
+    pub summarization_backend: SummarizationBackend,
+    /// OpenAI-compatible API root for the remote backend, e.g. "https://api.openai.com/v1"
+    pub remote_base_url: Option<String>,
+    pub remote_api_key: Option<String>,
+    pub remote_model: Option<String>,
+    /// Accelerator that toggles live recording, e.g. "CommandOrControl+Shift+R"; set through
+    /// register_recording_hotkey so it's only saved once the OS has accepted it
+    pub recording_hotkey: Option<String>,
+    /// Start a new part file when a system recording's recorder dies or stalls
+    pub restart_failed_recorder: bool,
+    /// Keep the machine from sleeping while recording or finishing a live transcript
+    pub prevent_sleep: bool,
+    pub notifications_enabled: bool,
+    pub notify_on: NotificationToggles,
+    /// Hold notifications back until recording stops, so their sound isn't recorded
+    pub quiet_while_recording: bool,
+}
+
+impl Default for AppSettings {
+    fn default() -> Self {
+        AppSettings {
+            model: None,
+            language: None,
+            device: None,
+            backend: None,
+            segment_seconds: 10,
+            adaptive_segments: false,
+            min_segment_seconds: 5,
+            max_segment_seconds: 30,
+            adaptive_model: false,
+            threads: None,
+            live_concurrency: 1,
+            noise_reduction: NoiseReduction::Off,
+            transcription_backend: TranscriptionBackend::default(),
+            llama_model_path: None,
+            llama_max_tokens: 256,
+            llama_temperature: 0.7,
+            llama_ctx_size: 4096,
+            summarization_backend: SummarizationBackend::default(),
+            remote_base_url: None,
+            remote_api_key: None,
+            remote_model: None,
+            recording_hotkey: None,
+            restart_failed_recorder: true,
+            prevent_sleep: true,
+            notifications_enabled: true,
+            notify_on: NotificationToggles::default(),
+            quiet_while_recording: true,
+        }
+    }
+}
+
+impl AppSettings {
+    fn validate(&self) -> Result<(), String> {
+        let (min, max) = SEGMENT_SECONDS_RANGE;
+        if !(min..=max).contains(&self.segment_seconds) {
+            return Err(format!("segment_seconds must be between {} and {}", min, max));
+        }
+        if !(min..=max).contains(&self.min_segment_seconds) || !(min..=max).contains(&self.max_segment_seconds) {
+            return Err(format!("min_segment_seconds and max_segment_seconds must be between {} and {}", min, max));
+        }
+        if self.min_segment_seconds > self.max_segment_seconds {
+            return Err("min_segment_seconds can't be more than max_segment_seconds".to_string());
+        }
+        if self.threads == Some(0) {
+            return Err("threads must be at least 1".to_string());
+        }
+        if !(1..=MAX_LIVE_CONCURRENCY).contains(&self.live_concurrency) {
+            return Err(format!("live_concurrency must be between 1 and {}", MAX_LIVE_CONCURRENCY));
+        }
+        if !(1..=MAX_LLAMA_TOKENS).contains(&self.llama_max_tokens) {
+            return Err(format!("llama_max_tokens must be between 1 and {}", MAX_LLAMA_TOKENS));
+        }
+        if !(0.0..=2.0).contains(&self.llama_temperature) {
+            return Err("llama_temperature must be between 0 and 2".to_string());
+        }
+        let (min_ctx, max_ctx) = LLAMA_CTX_RANGE;
+        if !(min_ctx..=max_ctx).contains(&self.llama_ctx_size) {
+            return Err(format!("llama_ctx_size must be between {} and {}", min_ctx, max_ctx));
+        }
+        if self.llama_max_tokens >= self.llama_ctx_size {
+            return Err("llama_max_tokens must be smaller than llama_ctx_size".to_string());
+        }
+        if let Some(backend) = &self.backend {
+            recorder::resolve_backend(Some(backend))?;
+        }
+        if let Some(url) = &self.remote_base_url {
+            if !url.starts_with("http://") && !url.starts_with("https://") {
+                return Err("remote_base_url must start with http:// or https://".to_string());
+            }
+        }
+        if self.summarization_backend == SummarizationBackend::Remote && self.remote_base_url.is_none() {
+            return Err("The remote summarization backend needs remote_base_url".to_string());
+        }
+        Ok(())
+    }
+
+    /// Copy safe to hand to the frontend, with the API key masked
+    fn redacted(&self) -> AppSettings {
+        let mut settings = self.clone();
+        if settings.remote_api_key.is_some() {
+            settings.remote_api_key = Some(REDACTED_KEY.to_string());
+        }
+        settings
+    }
+}
+
+// Settings loaded from the config dir at startup and kept in sync with it
+pub struct SettingsState {
+    pub settings: Mutex<AppSettings>,
+}
+
+impl SettingsState {
+    /// Load settings.json, falling back to defaults if it's missing or unreadable
+    pub fn load() -> Self {
+        let settings = get_config_dir()
+            .ok()
+            .and_then(|dir| fs::read_to_string(dir.join(SETTINGS_FILE)).ok())
+            .and_then(|raw| serde_json::from_str(&raw).ok())
+            .unwrap_or_default();
+        SettingsState { settings: Mutex::new(settings) }
+    }
+}
+
+/// Snapshot of the current settings
+pub fn current(app: &tauri::AppHandle) -> AppSettings {
+    app.state::<SettingsState>().settings.lock().unwrap().clone()
+}
+
+/// Write settings.json via a temp file and rename, so a crash never leaves it half written
+fn save(settings: &AppSettings) -> Result<(), String> {
+    let dir = get_config_dir()?;
+    let json = serde_json::to_string_pretty(settings)
+        .map_err(|e| format!("Failed to serialize settings: {}", e))?;
+    let tmp = dir.join(format!("{}.tmp", SETTINGS_FILE));
+    fs::write(&tmp, json).map_err(|e| format!("Failed to save settings: {}", e))?;
+    fs::rename(&tmp, dir.join(SETTINGS_FILE)).map_err(|e| format!("Failed to save settings: {}", e))
+}
+
+/// Save a new recording hotkey, or clear it with None
+pub fn set_recording_hotkey(app: &tauri::AppHandle, hotkey: Option<String>) -> Result<(), String> {
+    let state = app.state::<SettingsState>();
+    let mut settings = state.settings.lock().unwrap();
+    let mut updated = settings.clone();
+    updated.recording_hotkey = hotkey;
+    save(&updated)?;
+    *settings = updated;
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn get_settings(state: tauri::State<'_, SettingsState>) -> Result<AppSettings, String> {
+    Ok(state.settings.lock().unwrap().redacted())
+}
+
+/// Merge a partial settings object into the current settings; a null field resets it to its default.
+/// A remote_api_key still equal to the redacted placeholder keeps the saved key.
+#[tauri::command]
+pub async fn update_settings(app: tauri::AppHandle, patch: serde_json::Value) -> Result<AppSettings, String> {
+    let serde_json::Value::Object(patch) = patch else {
+        return Err("Settings patch must be an object".to_string());
+    };
+
+    let state = app.state::<SettingsState>();
+    let mut settings = state.settings.lock().unwrap();
+    let mut merged = match serde_json::to_value(&*settings) {
+        Ok(serde_json::Value::Object(map)) => map,
+        _ => return Err("Failed to serialize settings".to_string()),
+    };
+    for (key, value) in patch {
+        if key == "remote_api_key" && value.as_str() == Some(REDACTED_KEY) {
+            continue;
+        }
+        if value.is_null() {
+            merged.remove(&key);
+        } else {
+            merged.insert(key, value);
+        }
+    }
+    let updated: AppSettings = serde_json::from_value(serde_json::Value::Object(merged))
+        .map_err(|e| format!("Invalid settings: {}", e))?;
+    updated.validate()?;
+    if updated.recording_hotkey != settings.recording_hotkey {
+        return Err("Change recording_hotkey with register_recording_hotkey".to_string());
+    }
+    save(&updated)?;
+
+    if updated.device != settings.device {
+        *app.state::<recorder::AudioDeviceState>().device.lock().unwrap() = updated.device.clone();
+    }
+    let prevent_sleep_changed = updated.prevent_sleep != settings.prevent_sleep;
+    let redacted = updated.redacted();
+    *settings = updated;
+    if prevent_sleep_changed {
+        sleep_inhibit::sync_in_background(&app);
+    }
+    Ok(redacted)
+}
@@ -0,0 +1,172 @@
+use serde::Serialize;
+
+use crate::error::AppError;
+use crate::{binaries, check_mic_portal, download_whisper, llama, models, recorder, resolve_whisper_binary, whisper_build};
+
+/// Whisper model run_setup_step("model") installs; the one transcription prefers by default
+const DEFAULT_MODEL: &str = "tiny.en";
+
+/// Recorders any one of which is enough to capture audio
+const RECORDER_TOOLS: &[&str] = &["arecord", "ffmpeg", "pw-record", "parecord"];
+
+/// One thing the app needs, with what to do about it when it's missing
+#[derive(Serialize, Clone, Debug)]
+pub struct SetupItem {
+    pub id: &'static str,
+    pub label: &'static str,
+    pub ready: bool,
+    pub detail: Option<String>,
+    /// What the user should do, when not ready
+    pub action: Option<String>,
+    /// Step run_setup_step can perform for this item, if it can be automated
+    pub step: Option<&'static str>,
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct SetupStatus {
+    /// True once every required item is ready; llama-cli only matters for summaries
+    pub ready: bool,
+    pub items: Vec<SetupItem>,
+}
+
+fn whisper_item(app: &tauri::AppHandle) -> SetupItem {
+    let (ready, detail) = match resolve_whisper_binary(app) {
+        Ok(path) => {
+            let version = binaries::binary_version(app, &path);
+            (true, Some(match version {
+                Some(v) => format!("{} ({})", path.display(), v),
+                None => path.display().to_string(),
+            }))
+        }
+        Err(e) => (false, Some(e.to_string())),
+    };
+    let (action, step) = if cfg!(target_os = "linux") {
+        ("Build whisper-cli from source, or configure a prebuilt download", "whisper-source")
+    } else {
+        ("Download whisper-cli", "whisper")
+    };
+    SetupItem {
+        id: "whisper",
+        label: "whisper-cli",
+        ready,
+        detail,
+        action: (!ready).then(|| action.to_string()),
+        step: (!ready).then_some(step),
+    }
+}
+
+async fn model_item() -> SetupItem {
+    let whisper_models: Vec<String> = models::list_models()
+        .await
+        .unwrap_or_default()
+        .into_iter()
+        .filter(|m| m.kind == models::ModelKind::Whisper)
+        .map(|m| m.name)
+        .collect();
+    let ready = !whisper_models.is_empty();
+    SetupItem {
+        id: "model",
+        label: "Whisper model",
+        ready,
+        detail: ready.then(|| whisper_models.join(", ")),
+        action: (!ready).then(|| format!("Download the {} model", DEFAULT_MODEL)),
+        step: (!ready).then_some("model"),
+    }
+}
+
+fn recorder_item() -> SetupItem {
+    let found: Vec<&str> = RECORDER_TOOLS.iter().copied().filter(|tool| recorder::has_tool(tool)).collect();
+    let ready = !found.is_empty();
+    let action = if cfg!(target_os = "linux") {
+        "Install ffmpeg or alsa-utils (e.g. `sudo apt install ffmpeg alsa-utils`)"
+    } else {
+        "Install ffmpeg and make sure it's on PATH"
+    };
+    SetupItem {
+        id: "recorder",
+        label: "Audio recorder",
+        ready,
+        detail: ready.then(|| found.join(", ")),
+        action: (!ready).then(|| action.to_string()),
+        step: None,
+    }
+}
+
+async fn mic_item() -> SetupItem {
+    let status = check_mic_portal(None).await;
+    let (mut ready, mut detail) = match &status {
+        Ok(s) if cfg!(target_os = "macos") => (s.mic_permission != Some(false), s.message.clone()),
+        Ok(s) if cfg!(target_os = "linux") => {
+            (s.portal_running && s.pipewire_running && s.default_source.is_some(), s.message.clone())
+        }
+        Ok(s) => (true, s.message.clone()),
+        Err(e) => (false, e.clone()),
+    };
+    // Any backend that can capture will do; the portal and pipewire are just the usual route on Linux
+    if !ready && cfg!(target_os = "linux") {
+        if let Ok(Some(backend)) = tauri::async_runtime::spawn_blocking(recorder::working_capture_backend).await {
+            ready = true;
+            detail = format!("Capturing through the {} backend ({})", backend.as_str(), detail);
+        }
+    }
+    let action = if cfg!(target_os = "macos") {
+        "Allow microphone access in System Settings > Privacy & Security > Microphone"
+    } else {
+        "Start pipewire and xdg-desktop-portal (they normally run with your desktop session)"
+    };
+    SetupItem {
+        id: "microphone",
+        label: "Microphone access",
+        ready,
+        detail: Some(detail),
+        action: (!ready).then(|| action.to_string()),
+        step: None,
+    }
+}
+
+fn llama_item(app: &tauri::AppHandle) -> SetupItem {
+    let resolved = llama::resolve_binary();
+    let ready = resolved.is_ok();
+    let detail = match resolved {
+        Ok(path) => Some(match binaries::binary_version(app, &path) {
+            Some(v) => format!("{} ({})", path.display(), v),
+            None => path.display().to_string(),
+        }),
+        Err(e) => Some(e.to_string()),
+    };
+    SetupItem {
+        id: "llama",
+        label: "llama-cli (summaries)",
+        ready,
+        detail,
+        action: (!ready).then(|| "Download llama-cli".to_string()),
+        step: (!ready).then_some("llama"),
+    }
+}
+
+/// Everything a first run needs, checked in one go for the setup wizard
+#[tauri::command]
+pub async fn get_setup_status(app: tauri::AppHandle) -> Result<SetupStatus, String> {
+    // Version probes run the binaries, so keep them off the async runtime
+    let probe_app = app.clone();
+    let (whisper, llama) = tauri::async_runtime::spawn_blocking(move || (whisper_item(&probe_app), llama_item(&probe_app)))
+        .await
+        .map_err(|e| format!("Setup check failed: {}", e))?;
+
+    let items = vec![whisper, model_item().await, recorder_item(), mic_item().await, llama];
+    let ready = items.iter().filter(|item| item.id != "llama").all(|item| item.ready);
+    Ok(SetupStatus { ready, items })
+}
+
+/// Run one automatable setup step; progress arrives through the usual download/build events
+#[tauri::command]
+pub async fn run_setup_step(window: tauri::Window, step: String) -> Result<String, AppError> {
+    match step.as_str() {
+        "whisper" => download_whisper(window).await,
+        "whisper-source" => Ok(whisper_build::build_whisper_from_source(window).await?),
+        "model" => models::download_model(window, DEFAULT_MODEL.to_string()).await,
+        "llama" => llama::download_llama(window).await,
+        "recorder" | "microphone" | "llama-model" => Err(format!("Setup step '{}' has to be done outside the app", step).into()),
+        _ => Err(format!("Unknown setup step '{}'", step).into()),
+    }
+}
@@ -0,0 +1,175 @@
+use std::process::{Child, Command, Stdio};
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::Manager;
+
+use crate::recorder_status;
+use crate::settings;
+
+const WHY: &str = "Recording in progress";
+
+/// How long a helper process gets to fail (missing permission, no logind) before it counts as holding
+const HOLDER_STARTUP: Duration = Duration::from_millis(200);
+
+/// Held for as long as sleep should be blocked; releasing it lets the machine sleep again
+enum Inhibitor {
+    /// logind inhibitor lock, released when the fd is closed
+    #[cfg(target_os = "linux")]
+    Logind(#[allow(dead_code)] zbus::zvariant::OwnedFd),
+    /// systemd-inhibit or caffeinate, holding the lock until killed
+    Process(Child),
+    /// Thread keeping SetThreadExecutionState set until the sender is dropped
+    #[cfg(target_os = "windows")]
+    ExecutionState(#[allow(dead_code)] std::sync::mpsc::Sender<()>),
+}
+
+impl Inhibitor {
+    fn release(self) {
+        if let Inhibitor::Process(mut child) = self {
+            let _ = child.kill();
+            let _ = child.wait();
+        }
+    }
+}
+
+/// Run a helper that holds the inhibition while it lives. It's tied to our pid so it also goes away
+/// if the app is killed.
+fn spawn_holder(program: &str, args: &[&str]) -> Result<Inhibitor, String> {
+    let mut child = Command::new(program)
+        .args(args)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+        .map_err(|e| format!("Failed to run {}: {}", program, e))?;
+    std::thread::sleep(HOLDER_STARTUP);
+    match child.try_wait() {
+        Ok(None) => Ok(Inhibitor::Process(child)),
+        Ok(Some(status)) => Err(format!("{} exited with {}", program, status)),
+        Err(e) => Err(format!("Failed to check on {}: {}", program, e)),
+    }
+}
+
+/// Ask logind for a blocking inhibitor lock over D-Bus
+#[cfg(target_os = "linux")]
+fn logind_inhibit(what: &str) -> Result<zbus::zvariant::OwnedFd, String> {
+    let connection = zbus::blocking::Connection::system().map_err(|e| format!("No system bus: {}", e))?;
+    let reply = connection
+        .call_method(
+            Some("org.freedesktop.login1"),
+            "/org/freedesktop/login1",
+            Some("org.freedesktop.login1.Manager"),
+            "Inhibit",
+            &(what, "last-gen-notes", WHY, "block"),
+        )
+        .map_err(|e| format!("logind Inhibit failed: {}", e))?;
+    reply.body().deserialize().map_err(|e| format!("Unexpected logind reply: {}", e))
+}
+
+#[cfg(target_os = "linux")]
+fn acquire() -> Result<Inhibitor, String> {
+    // Closing the lid suspends through logind's lid handling, which only a handle-lid-switch lock holds
+    // off; some polkit setups refuse that one, so plain sleep:idle is tried after it
+    for what in ["sleep:idle:handle-lid-switch", "sleep:idle"] {
+        match logind_inhibit(what) {
+            Ok(fd) => return Ok(Inhibitor::Logind(fd)),
+            Err(e) => log::warn!("Couldn't take a {} inhibitor: {}", what, e),
+        }
+    }
+    let pid = std::process::id().to_string();
+    let why = format!("--why={}", WHY);
+    spawn_holder(
+        "systemd-inhibit",
+        &["--what=sleep:idle", "--who=last-gen-notes", &why, "--mode=block", "tail", "--pid", &pid, "-f", "/dev/null"],
+    )
+}
+
+#[cfg(target_os = "macos")]
+fn acquire() -> Result<Inhibitor, String> {
+    let pid = std::process::id().to_string();
+    spawn_holder("caffeinate", &["-i", "-s", "-w", &pid])
+}
+
+/// Keeps the system awake through idle timeouts; the lid-close action in Windows power settings
+/// still applies
+#[cfg(target_os = "windows")]
+fn acquire() -> Result<Inhibitor, String> {
+    #[link(name = "kernel32")]
+    extern "system" {
+        fn SetThreadExecutionState(flags: u32) -> u32;
+    }
+    const ES_CONTINUOUS: u32 = 0x8000_0000;
+    const ES_SYSTEM_REQUIRED: u32 = 0x0000_0001;
+
+    // The execution state belongs to the thread that set it, so one thread holds it until released
+    let (release_tx, release_rx) = std::sync::mpsc::channel::<()>();
+    let (ready_tx, ready_rx) = std::sync::mpsc::channel();
+    std::thread::spawn(move || {
+        let held = unsafe { SetThreadExecutionState(ES_CONTINUOUS | ES_SYSTEM_REQUIRED) } != 0;
+        let _ = ready_tx.send(held);
+        if held {
+            let _ = release_rx.recv();
+            unsafe { SetThreadExecutionState(ES_CONTINUOUS) };
+        }
+    });
+    match ready_rx.recv() {
+        Ok(true) => Ok(Inhibitor::ExecutionState(release_tx)),
+        _ => Err("SetThreadExecutionState failed".to_string()),
+    }
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos", target_os = "windows")))]
+fn acquire() -> Result<Inhibitor, String> {
+    Err("Preventing sleep isn't supported on this platform".to_string())
+}
+
+// Sleep inhibitor held while anything is recording or still transcribing a live session
+pub struct SleepInhibitState {
+    inhibitor: Mutex<Option<Inhibitor>>,
+}
+
+impl SleepInhibitState {
+    pub fn new() -> Self {
+        SleepInhibitState { inhibitor: Mutex::new(None) }
+    }
+
+    pub fn is_held(&self) -> bool {
+        self.inhibitor.lock().unwrap().is_some()
+    }
+}
+
+/// Take or release the inhibitor to match the current recorder status and prevent_sleep setting.
+/// Blocking; it reads the status itself, so calls that arrive out of order still settle correctly.
+pub fn sync(app: &tauri::AppHandle) {
+    let status = recorder_status::current(app);
+    let wanted = settings::current(app).prevent_sleep && status.recording();
+    let state = app.state::<SleepInhibitState>();
+    let mut inhibitor = state.inhibitor.lock().unwrap();
+    if wanted && inhibitor.is_none() {
+        match acquire() {
+            Ok(held) => {
+                log::info!("Preventing sleep while recording");
+                *inhibitor = Some(held);
+            }
+            Err(e) => log::warn!("Couldn't prevent sleep while recording: {}", e),
+        }
+    } else if !wanted {
+        if let Some(held) = inhibitor.take() {
+            held.release();
+            log::info!("Allowing sleep again");
+        }
+    }
+}
+
+/// sync on the blocking pool, for callers on the main thread or holding locks
+pub fn sync_in_background(app: &tauri::AppHandle) {
+    let app = app.clone();
+    tauri::async_runtime::spawn_blocking(move || sync(&app));
+}
+
+/// Drop the inhibitor on exit
+pub fn release(app: &tauri::AppHandle) {
+    if let Some(held) = app.state::<SleepInhibitState>().inhibitor.lock().unwrap().take() {
+        held.release();
+    }
+}
@@ -0,0 +1,119 @@
+use std::path::Path;
+use tauri::{Emitter, Manager};
+
+use crate::error::AppError;
+use crate::transcript::{TranscriptResult, TranscriptSegment};
+use crate::{audio, hallucination, jobs, notifications, paths, transcribe_audio_detailed, whisper};
+
+/// Labels for the left and right channel, in that order
+const SPEAKER_LABELS: [&str; 2] = ["Speaker A", "Speaker B"];
+
+/// Interleave both channels' segments by start time into one conversation, with a labelled line
+/// per speaker turn
+fn merge_channels(left: Vec<TranscriptSegment>, right: Vec<TranscriptSegment>) -> TranscriptResult {
+    let mut segments: Vec<TranscriptSegment> = left
+        .into_iter()
+        .map(|s| (0, s))
+        .chain(right.into_iter().map(|s| (1, s)))
+        .map(|(speaker, s)| TranscriptSegment { speaker: Some(speaker), speaker_turn: false, ..s })
+        .collect();
+    segments.sort_by_key(|s| (s.start_ms, s.speaker));
+    for i in 1..segments.len() {
+        segments[i - 1].speaker_turn = segments[i - 1].speaker != segments[i].speaker;
+    }
+
+    let mut lines: Vec<String> = Vec::new();
+    let mut previous = None;
+    for segment in &segments {
+        match lines.last_mut() {
+            Some(line) if segment.speaker == previous => {
+                line.push(' ');
+                line.push_str(&segment.text);
+            }
+            _ => {
+                let label = SPEAKER_LABELS[segment.speaker.unwrap_or(0) as usize];
+                lines.push(format!("{}: {}", label, segment.text));
+            }
+        }
+        previous = segment.speaker;
+    }
+
+    let mut result = TranscriptResult::from_segments(segments);
+    result.text = lines.join("\n");
+    result
+}
+
+/// Timestamped segments for one channel's WAV, hallucination-filtered when that's enabled
+async fn transcribe_channel(
+    app: &tauri::AppHandle,
+    path: &Path,
+    params: &whisper::WhisperParams,
+    job_id: &str,
+) -> Result<(Vec<TranscriptSegment>, Option<String>), AppError> {
+    let path_str = path.to_string_lossy().to_string();
+    let output = whisper::run_whisper(app, &path_str, params, &whisper::OutputMode::Timestamped, Some(job_id)).await?;
+    let mut segments = crate::transcript::parse_whisper_segments(&output.stdout);
+    if whisper::effective_options(app, params).filter_hallucinations.unwrap_or(false) {
+        let (phrases, low_energy) = hallucination::filter_context(app, path);
+        segments = hallucination::filter_segments(segments, &phrases, low_energy);
+    }
+    Ok((segments, output.detected_language))
+}
+
+/// Transcribe a call recording with one party per channel (left = Speaker A, right = Speaker B),
+/// returning a single conversation ordered by time. Mono recordings are transcribed normally,
+/// with a warning in the result.
+#[tauri::command]
+pub async fn transcribe_stereo_split(
+    window: tauri::Window,
+    audio_path: String,
+    model: Option<String>,
+    language: Option<String>,
+) -> Result<TranscriptResult, AppError> {
+    let audio_path = paths::existing(window.app_handle(), &audio_path)?.to_string_lossy().to_string();
+    let channels = audio::read_metadata(Path::new(&audio_path))?.channels;
+    if channels == Some(1) {
+        log::warn!("{} is mono; transcribing it without a channel split", audio_path);
+        let mut result = transcribe_audio_detailed(window, audio_path, model, language, None, None, None, None).await?;
+        result.warning = Some("The recording is mono, so speakers couldn't be separated by channel".to_string());
+        return Ok(result);
+    }
+
+    let params = whisper::WhisperParams {
+        model,
+        language: whisper::normalize_language(language),
+        ..Default::default()
+    }
+    .with_settings(window.app_handle());
+
+    let job_id = jobs::queue(window.app_handle(), &audio_path)?;
+    let size = std::fs::metadata(&audio_path).map(|m| m.len()).unwrap_or(0);
+    let backend = whisper::backend_for_run(window.app_handle(), &audio_path).await;
+    let _ = window.emit("transcribe-start", serde_json::json!({
+        "path": audio_path.clone(),
+        "job_id": job_id,
+        "size": size,
+        "translate": false,
+        "backend": backend,
+    }));
+
+    let app = window.app_handle();
+    let (path_ref, params_ref) = (audio_path.as_str(), &params);
+    let result = jobs::run_job(app, job_id, |job_id| async move {
+        let (left, right) = audio::split_stereo_channels(path_ref).await?;
+        let (left_segments, language) = transcribe_channel(app, &left.path, params_ref, &job_id).await?;
+        let (right_segments, _) = transcribe_channel(app, &right.path, params_ref, &job_id).await?;
+        let mut result = merge_channels(left_segments, right_segments);
+        result.language = language.or(params_ref.language.clone());
+        Ok(result)
+    })
+    .await;
+
+    notifications::transcription_finished(app, &audio_path, result.as_ref().err().map(|e| e.to_string()));
+    let _ = window.emit("transcribe-complete", serde_json::json!({
+        "path": audio_path,
+        "ok": result.is_ok(),
+        "error": result.as_ref().err().map(|e| e.to_string()),
+    }));
+    result
+}
@@ -0,0 +1,813 @@
+use futures_util::future::{self, Either};
+use regex::Regex;
+use serde::Serialize;
+use std::future::Future;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Arc, LazyLock, Mutex};
+use std::time::{Duration, Instant};
+use tauri::{Emitter, Manager};
+
+use crate::error::AppError;
+use crate::notifications::{self, NotificationKind};
+use crate::settings::SummarizationBackend;
+use crate::telemetry::{self, ProcessMonitor, RunStats};
+use crate::{chat_api, jobs, llama, llama_server, logging, models, processes, prompts, settings, whisper};
+
+/// How often summarize-progress fires while llama-cli runs, whether or not it has produced output yet
+pub const HEARTBEAT_INTERVAL: Duration = Duration::from_millis(500);
+
+/// How often an HTTP summary request checks whether its session was cancelled
+const CANCEL_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Last line of the window prompts, which generation continues from
+const PROMPT_TAIL: &str = "Summary:";
+
+const TITLE_INSTRUCTION: &str = "Write a title of 5 to 8 words for the following transcript. Reply with only the title: no quotes, no trailing punctuation.";
+
+/// Bracketed whisper annotations such as [BLANK_AUDIO] or (music), which never make a good title
+static MARKER_RE: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"\[[^\]]*\]|\([^)]*\)").unwrap());
+
+/// Transcript characters given to the title prompt; the opening is usually enough to name it
+const TITLE_INPUT_CHARS: usize = 4000;
+
+/// Room for an 8-word title with some slack, so a rambling model is cut off quickly
+const TITLE_MAX_TOKENS: u32 = 32;
+
+const TITLE_MAX_WORDS: usize = 8;
+
+/// Longest title taken from the transcript itself when llama can't write one
+const FALLBACK_TITLE_CHARS: usize = 60;
+
+/// Instruction for one window of a long transcript
+const WINDOW_INSTRUCTION: &str = "You are a concise note-taking assistant. Summarize this excerpt of a longer transcript into clear bullet points with timestamps if present, avoiding speculation.";
+
+/// Instruction for merging window summaries into the final one
+const COMBINE_INSTRUCTION: &str = "You are a concise note-taking assistant. Combine these partial summaries of one transcript into a single set of clear bullet points, merging duplicates and keeping timestamps if present.";
+
+/// Tokens of prompt around the input, plus slack for the chars/4 estimate running low
+const PROMPT_OVERHEAD_TOKENS: usize = 128;
+
+/// Smallest window we'll split into, however small the context is configured
+const MIN_WINDOW_CHARS: usize = 1024;
+
+/// Share of each window repeated from the previous one, so a point split across windows isn't lost
+const WINDOW_OVERLAP: f32 = 0.1;
+
+/// Passes over the window summaries before the final pass takes whatever fits
+const MAX_REDUCE_ROUNDS: usize = 3;
+
+/// What llama-cli prints once generation stops
+const END_MARKER: &str = "[end of text]";
+
+/// Line prefixes of llama-cli's startup output when it lands on stdout
+const BANNER_PREFIXES: &[&str] = &["main:", "build:", "llama_", "llm_load", "load", "print_info", "common_", "system_info", "sampler", "sampling", "generate:", "== Running"];
+
+// The summary being generated, if any; summaries run one at a time
+pub struct SummarizationState {
+    /// Cancel flag of the Session holding the slot
+    session: Mutex<Option<Arc<AtomicBool>>>,
+    /// The session's running llama-cli, if it's using one
+    pid: Mutex<Option<u32>>,
+    /// Cost of the last finished run, attached to the next summary-complete
+    last_stats: Mutex<Option<RunStats>>,
+}
+
+impl SummarizationState {
+    pub fn new() -> Self {
+        SummarizationState {
+            session: Mutex::new(None),
+            pid: Mutex::new(None),
+            last_stats: Mutex::new(None),
+        }
+    }
+}
+
+/// The summary slot, held for one whole summary however many llama runs it takes (every window of a long one),
+/// so nothing else starts between its runs and a cancel stops all of them. Released on drop.
+pub struct Session {
+    app: tauri::AppHandle,
+    cancelled: Arc<AtomicBool>,
+}
+
+/// Cancels one session and never whichever other summary holds the slot; made before the session begins, so it
+/// can be cancelled before it has started
+#[derive(Clone, Default)]
+pub struct CancelHandle {
+    cancelled: Arc<AtomicBool>,
+}
+
+impl CancelHandle {
+    pub fn new() -> Self {
+        CancelHandle::default()
+    }
+
+    /// Stop the session before its next run, killing its llama-cli or dropping its request if it's running one
+    pub fn cancel(&self, state: &SummarizationState) {
+        self.cancelled.store(true, Ordering::Relaxed);
+        let session = state.session.lock().unwrap();
+        if session.as_ref().is_some_and(|current| Arc::ptr_eq(current, &self.cancelled)) {
+            if let Some(pid) = *state.pid.lock().unwrap() {
+                jobs::terminate_pid(pid);
+            }
+        }
+    }
+}
+
+#[derive(Serialize, Clone)]
+pub struct SummarizeProgress {
+    /// Rough count of generated tokens: words of summary so far
+    pub tokens: usize,
+    pub elapsed_secs: f32,
+}
+
+#[derive(Serialize, Clone)]
+pub struct SummaryToken {
+    /// Text generated since the previous summary-token event
+    pub delta: String,
+}
+
+#[derive(Serialize, Clone)]
+struct WindowProgress {
+    /// "window" while summarizing the transcript, "reduce" while folding summaries, then "final"
+    stage: &'static str,
+    window: usize,
+    windows: usize,
+}
+
+#[derive(Serialize, Clone)]
+struct SummaryComplete {
+    summary: String,
+    stats: Option<RunStats>,
+}
+
+/// The prompt's instruction line; llama-cli's echo of the prompt starts here, after any banner lines
+fn prompt_head(prompt: &str) -> &str {
+    prompt.lines().next().unwrap_or(prompt)
+}
+
+enum Echo {
+    Pending,
+    /// Byte offset just past the echoed prompt
+    Ends(usize),
+}
+
+/// Find the end of llama-cli's echo of `prompt`, comparing only non-whitespace since detokenizing can reflow spacing
+fn echo_end(text: &str, prompt: &str) -> Echo {
+    let Some(start) = text.find(prompt_head(prompt)) else {
+        return Echo::Pending;
+    };
+    let mut expected = prompt.chars().filter(|c| !c.is_whitespace()).peekable();
+    for (i, c) in text[start..].char_indices() {
+        if c.is_whitespace() {
+            continue;
+        }
+        match expected.next() {
+            Some(e) if e == c => {
+                if expected.peek().is_none() {
+                    return Echo::Ends(start + i + c.len_utf8());
+                }
+            }
+            // Some characters came back differently; settle for the prompt's last line
+            _ => {
+                let from = start + i;
+                let tail = prompt.trim_end().lines().last().unwrap_or_default().trim();
+                return match text[from..].find(tail).filter(|_| !tail.is_empty()) {
+                    Some(at) => Echo::Ends(from + at + tail.len()),
+                    None => Echo::Pending,
+                };
+            }
+        }
+    }
+    Echo::Pending
+}
+
+/// Generated text without the whitespace and `>` interactive marker llama-cli puts before it
+fn strip_leading(text: &str) -> &str {
+    let text = text.trim_start();
+    text.strip_prefix('>').map(str::trim_start).unwrap_or(text)
+}
+
+/// Length of `text` without trailing whitespace, `>` and end-of-text markers, or a marker still being printed
+fn markers_start(text: &str) -> usize {
+    let mut text = text.trim_end();
+    loop {
+        if let Some(rest) = text.strip_suffix(END_MARKER) {
+            text = rest.trim_end();
+        } else if let Some(rest) = text.strip_suffix('>').filter(|rest| rest.is_empty() || rest.ends_with('\n')) {
+            text = rest.trim_end();
+        } else if let Some(at) = text.rfind('[').filter(|&at| END_MARKER.starts_with(&text[at..])) {
+            text = text[..at].trim_end();
+        } else {
+            return text.len();
+        }
+    }
+}
+
+/// llama-cli's stdout as it arrives, split into the echoed prompt and the summary after it
+struct SummaryStream {
+    prompt: String,
+    /// Bytes of a multi-byte character split across reads
+    pending: Vec<u8>,
+    text: String,
+    generation_start: Option<usize>,
+    /// How much of the cleaned summary has gone out as summary-token events
+    emitted: usize,
+}
+
+impl SummaryStream {
+    fn new(prompt: String) -> Self {
+        SummaryStream { prompt, pending: Vec::new(), text: String::new(), generation_start: None, emitted: 0 }
+    }
+
+    fn summary(&self) -> &str {
+        match self.generation_start {
+            Some(start) => strip_leading(&self.text[start..]),
+            None => "",
+        }
+    }
+
+    fn tokens(&self) -> usize {
+        self.summary().split_whitespace().count()
+    }
+
+    /// Add a chunk of stdout, returning newly generated text
+    fn push(&mut self, bytes: &[u8]) -> String {
+        self.pending.extend_from_slice(bytes);
+        self.text.push_str(&whisper::drain_utf8(&mut self.pending));
+        if self.generation_start.is_none() {
+            if let Echo::Ends(end) = echo_end(&self.text, &self.prompt) {
+                self.generation_start = Some(end);
+            }
+        }
+        let summary = self.summary();
+        // Hold back anything that may turn out to be a marker
+        let ready = markers_start(summary);
+        let delta = summary.get(self.emitted..ready).unwrap_or_default().to_string();
+        self.emitted = self.emitted.max(ready);
+        delta
+    }
+
+    /// Flush what's left once stdout closes, returning the last delta and the cleaned summary
+    fn finish(mut self) -> (String, String) {
+        if !self.pending.is_empty() {
+            self.text.push_str(&String::from_utf8_lossy(&self.pending));
+        }
+        let summary = match self.generation_start {
+            Some(_) => {
+                let summary = self.summary();
+                summary[..markers_start(summary)].to_string()
+            }
+            // Never saw the prompt echoed, so whatever isn't banner is the summary
+            None if !self.text.contains(prompt_head(&self.prompt)) => {
+                let lines: Vec<&str> = self
+                    .text
+                    .lines()
+                    .filter(|line| !BANNER_PREFIXES.iter().any(|prefix| line.trim_start().starts_with(prefix)))
+                    .collect();
+                let joined = lines.join("\n");
+                let summary = strip_leading(&joined);
+                summary[..markers_start(summary)].to_string()
+            }
+            None => String::new(),
+        };
+        let delta = summary.get(self.emitted..).unwrap_or_default().to_string();
+        (delta, summary)
+    }
+}
+
+/// Where a prompt is sent
+#[derive(Clone)]
+pub enum Engine {
+    /// llama-cli with `model`, or llama-server instead when `use_server` is set and it has that model loaded.
+    /// `binary` is only None in server mode, where llama-cli is just the fallback.
+    Local { binary: Option<PathBuf>, model: PathBuf, use_server: bool },
+    /// The user's OpenAI-compatible API; only ever chosen by the "remote" backend setting
+    Remote(chat_api::Endpoint),
+}
+
+/// Backend and sampling settings for one summary run
+#[derive(Clone)]
+pub struct LlamaRun {
+    pub engine: Engine,
+    pub max_tokens: u32,
+    pub temperature: f32,
+    pub ctx_size: u32,
+    /// Spawn llama-cli even if the model looks too big for available memory
+    pub force: bool,
+}
+
+impl LlamaRun {
+    /// Resolve the configured backend (llama-cli and the model, unless it's remote), with settings filling in
+    /// whatever wasn't passed
+    pub fn resolve(
+        app: &tauri::AppHandle,
+        model_path: Option<&str>,
+        max_tokens: Option<u32>,
+        temperature: Option<f32>,
+    ) -> Result<LlamaRun, AppError> {
+        let settings = settings::current(app);
+        let engine = match settings.summarization_backend {
+            SummarizationBackend::Remote => Engine::Remote(chat_api::Endpoint::remote(&settings)?),
+            backend => {
+                let use_server = backend == SummarizationBackend::LocalServer;
+                let binary = match llama::resolve_binary() {
+                    Ok(binary) => Some(binary),
+                    Err(_) if use_server => None,
+                    Err(e) => return Err(e),
+                };
+                let (model, _) = models::resolve_llama_model(model_path, settings.llama_model_path.as_deref())?;
+                Engine::Local { binary, model, use_server }
+            }
+        };
+        Ok(LlamaRun {
+            engine,
+            max_tokens: max_tokens.unwrap_or(settings.llama_max_tokens),
+            temperature: temperature.unwrap_or(settings.llama_temperature),
+            ctx_size: settings.llama_ctx_size,
+            force: false,
+        })
+    }
+}
+
+/// Instruction, labelled input, then the PROMPT_TAIL line generation continues from
+fn build_prompt(instruction: &str, label: &str, input: &str) -> String {
+    format!("{}\n\n{}:\n{}\n\n{}", instruction, label, input, PROMPT_TAIL)
+}
+
+/// Rough token count for sizing windows; English averages about four characters per token
+fn estimate_tokens(text: &str) -> usize {
+    text.len().div_ceil(4)
+}
+
+/// Input characters that fit one llama-cli context alongside the prompt and the generated summary
+fn window_chars(ctx_size: u32, max_tokens: u32) -> usize {
+    let input_tokens = (ctx_size as usize).saturating_sub(max_tokens as usize + PROMPT_OVERHEAD_TOKENS);
+    (input_tokens * 4).max(MIN_WINDOW_CHARS)
+}
+
+fn floor_char_boundary(text: &str, mut index: usize) -> usize {
+    while !text.is_char_boundary(index) {
+        index -= 1;
+    }
+    index
+}
+
+/// Split `text` into windows of at most `size` bytes, each repeating the last `overlap` bytes of the one before.
+/// Windows break at whitespace where there is some in their second half.
+fn split_windows(text: &str, size: usize, overlap: usize) -> Vec<&str> {
+    let mut windows = Vec::new();
+    let mut start = 0;
+    loop {
+        let mut end = floor_char_boundary(text, (start + size).min(text.len()));
+        if end < text.len() {
+            if let Some(space) = text[start..end].rfind(char::is_whitespace).filter(|&at| at > size / 2) {
+                end = start + space;
+            }
+        }
+        windows.push(text[start..end].trim());
+        if end >= text.len() {
+            return windows;
+        }
+        let mut next = floor_char_boundary(text, end - overlap.min(size / 4));
+        if let Some(space) = text[next..end].find(char::is_whitespace) {
+            next += space;
+        }
+        start = next;
+    }
+}
+
+/// Persist a finished run's stats and keep them for the summary-complete event
+fn finish_run(state: &SummarizationState, stats: RunStats) {
+    telemetry::record(&stats);
+    *state.last_stats.lock().unwrap() = Some(stats);
+}
+
+fn cancelled_error() -> AppError {
+    log::info!("Summarization cancelled");
+    AppError::Cancelled("Summarization cancelled".to_string())
+}
+
+/// Run `prompt` on the resolved backend in a session of its own; see Session::run
+pub async fn run_llama(app: &tauri::AppHandle, run: &LlamaRun, prompt: String, stream: bool) -> Result<String, AppError> {
+    Session::begin(app)?.run(run, prompt, stream).await
+}
+
+impl Session {
+    /// Claim the summary slot, failing if another summary holds it
+    pub fn begin(app: &tauri::AppHandle) -> Result<Session, AppError> {
+        Session::begin_with(app, &CancelHandle::new())
+    }
+
+    /// Claim the summary slot for a session `handle` cancels
+    pub fn begin_with(app: &tauri::AppHandle, handle: &CancelHandle) -> Result<Session, AppError> {
+        let state = app.state::<SummarizationState>();
+        let mut session = state.session.lock().unwrap();
+        if session.is_some() {
+            return Err(AppError::RecorderBusy("A summary is already being generated".to_string()));
+        }
+        *session = Some(handle.cancelled.clone());
+        Ok(Session { app: app.clone(), cancelled: handle.cancelled.clone() })
+    }
+
+    fn check_cancelled(&self) -> Result<(), AppError> {
+        if self.cancelled.load(Ordering::Relaxed) {
+            return Err(cancelled_error());
+        }
+        Ok(())
+    }
+
+    /// Await a request to llama-server or the remote API, dropping it as soon as the session is cancelled;
+    /// that closes the connection, which makes the server stop generating
+    async fn request(&self, request: impl Future<Output = Result<String, AppError>>) -> Result<String, AppError> {
+        let cancelled = async {
+            while !self.cancelled.load(Ordering::Relaxed) {
+                tokio::time::sleep(CANCEL_POLL_INTERVAL).await;
+            }
+        };
+        match future::select(std::pin::pin!(request), std::pin::pin!(cancelled)).await {
+            Either::Left((result, _)) => {
+                self.check_cancelled()?;
+                result
+            }
+            Either::Right(((), _)) => Err(cancelled_error()),
+        }
+    }
+
+    /// Run `prompt` on the resolved backend and return the cleaned generation, emitting summary-token events when
+    /// `stream` is set
+    pub async fn run(&self, run: &LlamaRun, prompt: String, stream: bool) -> Result<String, AppError> {
+        let app = &self.app;
+        let state = app.state::<SummarizationState>().inner();
+        self.check_cancelled()?;
+        let (binary, model) = match &run.engine {
+            Engine::Remote(endpoint) => {
+                log::info!("Sending {} chars to {} ({} tokens max)", prompt.len(), endpoint.base_url, run.max_tokens);
+                let client = chat_api::remote_client()?;
+                let request = chat_api::stream_chat(app, &client, endpoint, run, &prompt, stream, &self.cancelled);
+                let monitor = ProcessMonitor::wall_clock();
+                let summary = self.request(request).await?;
+                finish_run(state, monitor.finish("summarization", Path::new(endpoint.model.as_deref().unwrap_or(endpoint.name)), None));
+                return Ok(summary);
+            }
+            Engine::Local { binary, model, use_server } => {
+                if let Some(url) = use_server.then(|| llama_server::url_for(app, model)).flatten() {
+                    let monitor = ProcessMonitor::wall_clock();
+                    let summary = self.request(llama_server::chat(app, &url, run, &prompt, stream, &self.cancelled)).await?;
+                    finish_run(state, monitor.finish("summarization", model, None));
+                    return Ok(summary);
+                }
+                let binary = match binary {
+                    Some(binary) => binary.clone(),
+                    // Server mode without llama-cli installed, and the server isn't running this model
+                    None => llama::resolve_binary()?,
+                };
+                (binary, model)
+            }
+        };
+        self.run_cli(run, &binary, model, prompt, stream).await
+    }
+
+    /// Run llama-cli itself, reading the generation off its stdout
+    async fn run_cli(&self, run: &LlamaRun, binary: &Path, model: &Path, prompt: String, stream: bool) -> Result<String, AppError> {
+        let app = &self.app;
+        let state = app.state::<SummarizationState>().inner();
+
+        // Use logical CPUs if available via env or fallback to 4
+        let threads = std::thread::available_parallelism()
+            .map(|n| n.get())
+            .unwrap_or(4)
+            .to_string();
+
+        let mut cmd = Command::new(binary);
+        cmd.arg("-m").arg(model)
+            .arg("-p").arg(&prompt)
+            .arg("-n").arg(run.max_tokens.to_string())
+            .arg("-c").arg(run.ctx_size.to_string())
+            .arg("--temp").arg(format!("{:.2}", run.temperature))
+            .arg("-t").arg(&threads)
+            // No stdin, so an interactive-mode llama-cli exits instead of waiting for input
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped());
+
+        models::check_memory(model, models::ModelKind::Llama, run.force)?;
+
+        let mut child = {
+            // cancel() takes this lock before killing, so a cancel can't slip in between the check and the spawn
+            let mut pid = state.pid.lock().unwrap();
+            self.check_cancelled()?;
+            log::info!("Running llama-cli on {} chars with {} ({} tokens max)", prompt.len(), model.display(), run.max_tokens);
+            let child = processes::spawn(app, &mut cmd).map_err(|e| AppError::io("Failed to run llama-cli", e))?;
+            *pid = Some(child.id());
+            child
+        };
+
+        let monitor = ProcessMonitor::start(child.id());
+        let model_lease = models::ModelLease::acquire(app, model);
+        let stats_model = model.to_path_buf();
+        let run_app = app.clone();
+        let result = tauri::async_runtime::spawn_blocking(move || {
+            let _model_lease = model_lease;
+            let mut stdout = child.stdout.take().expect("llama-cli stdout is piped");
+            let mut stderr = child.stderr.take().expect("llama-cli stderr is piped");
+            // Drain stderr alongside stdout so a chatty llama-cli can't fill the pipe and stall
+            let stderr_reader = std::thread::spawn(move || {
+                let mut buf = Vec::new();
+                let _ = stderr.read_to_end(&mut buf);
+                buf
+            });
+
+            let tokens = Arc::new(AtomicUsize::new(0));
+            let done = Arc::new(AtomicBool::new(false));
+            let heartbeat = {
+                let (app, tokens, done) = (run_app.clone(), tokens.clone(), done.clone());
+                std::thread::spawn(move || {
+                    let started = Instant::now();
+                    while !done.load(Ordering::Relaxed) {
+                        let _ = app.emit("summarize-progress", SummarizeProgress {
+                            tokens: tokens.load(Ordering::Relaxed),
+                            elapsed_secs: started.elapsed().as_secs_f32(),
+                        });
+                        std::thread::sleep(HEARTBEAT_INTERVAL);
+                    }
+                })
+            };
+
+            let emit_delta = |delta: String| {
+                if stream && !delta.is_empty() {
+                    let _ = run_app.emit("summary-token", SummaryToken { delta });
+                }
+            };
+            let mut stream = SummaryStream::new(prompt);
+            let mut chunk = [0u8; 1024];
+            loop {
+                match stdout.read(&mut chunk) {
+                    Ok(0) | Err(_) => break,
+                    Ok(n) => {
+                        emit_delta(stream.push(&chunk[..n]));
+                        tokens.store(stream.tokens(), Ordering::Relaxed);
+                    }
+                }
+            }
+
+            let status = processes::wait(&run_app, &mut child);
+            let stats = monitor.finish("summarization", &stats_model, None);
+            done.store(true, Ordering::Relaxed);
+            let _ = heartbeat.join();
+            let stderr = stderr_reader.join().unwrap_or_default();
+            let stdout_text = stream.text.clone();
+            let (delta, summary) = stream.finish();
+            if matches!(&status, Ok(s) if s.success()) {
+                emit_delta(delta);
+            }
+            (status, stdout_text, summary, stderr, stats)
+        })
+        .await;
+
+        *state.pid.lock().unwrap() = None;
+        let (status, stdout, summary, stderr, stats) = result.map_err(|e| format!("Summarization task failed: {}", e))?;
+        self.check_cancelled()?;
+        let status = status.map_err(|e| AppError::io("Failed to wait for llama-cli", e))?;
+
+        if !status.success() {
+            let stderr_text = String::from_utf8_lossy(&stderr);
+            let msg = if !stderr_text.is_empty() { stderr_text.to_string() } else { stdout };
+            log::error!("llama-cli failed ({}): {}", status, logging::stderr_tail(&stderr));
+            return Err(AppError::ProcessFailed {
+                cmd: binary.display().to_string(),
+                stderr: stderr_text.to_string(),
+                message: format!("llama-cli failed: {}", msg),
+            });
+        }
+
+        log::info!("llama-cli finished ({} chars)", summary.len());
+        finish_run(state, stats);
+        Ok(summary)
+    }
+}
+
+impl Drop for Session {
+    fn drop(&mut self) {
+        self.app.state::<SummarizationState>().session.lock().unwrap().take();
+    }
+}
+
+/// Announce a finished summary, or notify that it failed, and hand the result back
+fn complete(app: &tauri::AppHandle, result: Result<String, AppError>) -> Result<String, AppError> {
+    match &result {
+        Ok(summary) => {
+            let stats = app.state::<SummarizationState>().last_stats.lock().unwrap().take();
+            let _ = app.emit("summary-complete", SummaryComplete { summary: summary.clone(), stats });
+            let preview = summary.lines().find(|l| !l.trim().is_empty()).unwrap_or("");
+            notifications::notify(app, NotificationKind::Summary, "Summary ready", &notifications::first_line(preview), None);
+        }
+        Err(AppError::Cancelled(_)) => {}
+        Err(e) => notifications::notify(
+            app,
+            NotificationKind::Summary,
+            "Summary failed",
+            &notifications::first_line(&e.to_string()),
+            None,
+        ),
+    }
+    result
+}
+
+/// Summarize text using a local llama.cpp CLI binary and a provided or default model path.
+/// `template_name` picks a prompt template, defaulting to the built-in bullet-point one;
+/// `force` skips the free-memory check.
+#[tauri::command]
+pub async fn summarize_text_llama(
+    app: tauri::AppHandle,
+    text: String,
+    model_path: Option<String>,
+    max_tokens: Option<u32>,
+    temperature: Option<f32>,
+    template_name: Option<String>,
+    force: Option<bool>,
+) -> Result<String, AppError> {
+    let template = prompts::resolve(template_name.as_deref())?;
+    let run = LlamaRun {
+        force: force.unwrap_or(false),
+        ..LlamaRun::resolve(&app, model_path.as_deref(), max_tokens, temperature)?
+    };
+    let result = run_llama(&app, &run, prompts::render(&template, &text), true).await;
+    complete(&app, result)
+}
+
+/// Summarize each window of `inputs`, emitting summarize-window-progress as each starts when `stream` is set
+#[allow(clippy::too_many_arguments)]
+async fn summarize_windows(
+    app: &tauri::AppHandle,
+    session: &Session,
+    run: &LlamaRun,
+    inputs: &[&str],
+    stage: &'static str,
+    instruction: &str,
+    label: &str,
+    stream: bool,
+) -> Result<Vec<String>, AppError> {
+    let mut summaries = Vec::with_capacity(inputs.len());
+    for (i, input) in inputs.iter().enumerate() {
+        if stream {
+            let _ = app.emit("summarize-window-progress", WindowProgress { stage, window: i + 1, windows: inputs.len() });
+        }
+        summaries.push(session.run(run, build_prompt(instruction, label, input), false).await?);
+    }
+    Ok(summaries)
+}
+
+/// Summarize a transcript too long for one llama context: summarize overlapping windows of it,
+/// then summarize those summaries. `target_length` caps the final summary in tokens.
+#[tauri::command]
+pub async fn summarize_long_text(app: tauri::AppHandle, text: String, target_length: Option<u32>) -> Result<String, AppError> {
+    let result = async {
+        // One claim on the slot for every window, so a cancel anywhere stops the whole summary
+        let session = Session::begin(&app)?;
+        summarize_long(&app, &session, &text, target_length, true).await
+    }
+    .await;
+    complete(&app, result)
+}
+
+/// summarize_long_text's windowed summary within `session`; `stream` emits the window progress and the final
+/// summary's tokens
+pub async fn summarize_long(
+    app: &tauri::AppHandle,
+    session: &Session,
+    text: &str,
+    target_length: Option<u32>,
+    stream: bool,
+) -> Result<String, AppError> {
+    let run = LlamaRun::resolve(app, None, None, None)?;
+    let final_run = LlamaRun { max_tokens: target_length.unwrap_or(run.max_tokens), ..run.clone() };
+    let window = window_chars(run.ctx_size, run.max_tokens.max(final_run.max_tokens));
+
+    if text.len() <= window {
+        let template = prompts::resolve(None)?;
+        return session.run(&final_run, prompts::render(&template, text), stream).await;
+    }
+
+    let overlap = (window as f32 * WINDOW_OVERLAP) as usize;
+    let windows = split_windows(text, window, overlap);
+    log::info!("Summarizing ~{} tokens in {} windows", estimate_tokens(text), windows.len());
+    let partials = summarize_windows(app, session, &run, &windows, "window", WINDOW_INSTRUCTION, "Transcript excerpt", stream).await?;
+    let mut combined = partials.join("\n\n");
+
+    // Summaries of a very long transcript can overflow a window themselves; fold them again
+    for _ in 0..MAX_REDUCE_ROUNDS {
+        if combined.len() <= window {
+            break;
+        }
+        let groups = split_windows(&combined, window, 0);
+        let reduced = summarize_windows(app, session, &run, &groups, "reduce", COMBINE_INSTRUCTION, "Partial summaries", stream).await?;
+        combined = reduced.join("\n\n");
+    }
+    combined.truncate(floor_char_boundary(&combined, window.min(combined.len())));
+
+    if stream {
+        let _ = app.emit("summarize-window-progress", WindowProgress { stage: "final", window: 1, windows: 1 });
+    }
+    session.run(&final_run, build_prompt(COMBINE_INSTRUCTION, "Partial summaries", &combined), stream).await
+}
+
+#[derive(Serialize, Clone)]
+pub struct GeneratedTitle {
+    pub title: String,
+    /// "llama" when the model wrote it, "transcript" when it's the transcript's first sentence
+    pub source: &'static str,
+}
+
+/// The model's reply reduced to a bare title, or None if it didn't give a usable one
+fn clean_title(reply: &str) -> Option<String> {
+    let line = reply.lines().map(str::trim).find(|l| !l.is_empty())?;
+    let line = line.strip_prefix("Title:").unwrap_or(line).trim_start_matches(['#', '*', ' ']);
+    let line = line.trim_matches(|c: char| c == '"' || c == '\'' || c == '*' || c.is_whitespace());
+    let words: Vec<&str> = line.split_whitespace().take(TITLE_MAX_WORDS).collect();
+    let title = words.join(" ").trim_end_matches(['.', ',', ';', ':', '!']).to_string();
+    (words.len() >= 2).then_some(title)
+}
+
+/// The transcript's first sentence of three or more words, ignoring [BLANK_AUDIO]-style markers,
+/// cut at a word boundary to FALLBACK_TITLE_CHARS
+pub fn fallback_title(transcript: &str) -> String {
+    let text = MARKER_RE.replace_all(transcript, " ");
+    let Some(sentence) = text
+        .split(['.', '!', '?', '\n'])
+        .map(str::trim)
+        .find(|s| s.split_whitespace().count() >= 3)
+        .or_else(|| text.split(['.', '!', '?', '\n']).map(str::trim).find(|s| !s.is_empty()))
+    else {
+        return "Untitled note".to_string();
+    };
+    let mut title = String::new();
+    for word in sentence.split_whitespace() {
+        if title.chars().count() + word.chars().count() + 1 > FALLBACK_TITLE_CHARS {
+            break;
+        }
+        if !title.is_empty() {
+            title.push(' ');
+        }
+        title.push_str(word);
+    }
+    if title.is_empty() {
+        // One very long word
+        title = sentence.chars().take(FALLBACK_TITLE_CHARS).collect();
+    }
+    title
+}
+
+/// Ask the summarization backend for a short title, falling back to the transcript's first sentence
+/// when it isn't set up, is busy or replies with nothing usable. Cancel it with cancel_summarization.
+pub async fn generate_title(app: &tauri::AppHandle, transcript: &str) -> Result<GeneratedTitle, AppError> {
+    let fallback = || GeneratedTitle { title: fallback_title(transcript), source: "transcript" };
+    let run = match LlamaRun::resolve(app, None, Some(TITLE_MAX_TOKENS), Some(0.2)) {
+        Ok(run) => run,
+        Err(e) => {
+            log::info!("Titling from the transcript; no summarization backend: {}", e);
+            return Ok(fallback());
+        }
+    };
+    let input = &transcript[..floor_char_boundary(transcript, TITLE_INPUT_CHARS.min(transcript.len()))];
+    let prompt = format!("{}\n\nTranscript:\n{}\n\nTitle:", TITLE_INSTRUCTION, input.trim());
+    let reply = run_llama(app, &run, prompt, false).await;
+    // Title runs shouldn't show up as the stats of the next summary
+    app.state::<SummarizationState>().last_stats.lock().unwrap().take();
+    match reply {
+        Ok(reply) => Ok(clean_title(&reply).map(|title| GeneratedTitle { title, source: "llama" }).unwrap_or_else(fallback)),
+        Err(e @ AppError::Cancelled(_)) => Err(e),
+        Err(e) => {
+            log::warn!("Title generation failed, using the transcript: {}", e);
+            Ok(fallback())
+        }
+    }
+}
+
+/// Suggest a title for a transcript without saving anything, so it can be edited first
+#[tauri::command]
+pub async fn generate_note_title(app: tauri::AppHandle, transcript: String) -> Result<GeneratedTitle, AppError> {
+    generate_title(&app, &transcript).await
+}
+
+/// Cancel the session holding the slot: kill its running llama-cli, or abandon its llama-server or remote request,
+/// and stop it before its next run. Returns false if no summary was being generated.
+pub fn cancel(state: &SummarizationState) -> bool {
+    let Some(cancelled) = state.session.lock().unwrap().clone() else {
+        return false;
+    };
+    CancelHandle { cancelled }.cancel(state);
+    true
+}
+
+/// Stop the summary being generated; the summary command then returns a cancelled error
+#[tauri::command]
+pub async fn cancel_summarization(state: tauri::State<'_, SummarizationState>) -> Result<(), String> {
+    if cancel(&state) {
+        Ok(())
+    } else {
+        Err("No summary in progress".into())
+    }
+}
@@ -0,0 +1,151 @@
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use sysinfo::{CpuRefreshKind, Pid, ProcessRefreshKind, ProcessesToUpdate, RefreshKind, System};
+
+use crate::get_data_dir;
+
+const SAMPLE_INTERVAL: Duration = Duration::from_millis(500);
+
+/// How often the sampler checks whether the run finished, so finish() doesn't wait out a whole interval
+const STOP_POLL: Duration = Duration::from_millis(50);
+
+const HISTORY_FILE: &str = "performance-history.json";
+
+/// Runs kept on disk; live sessions add one per chunk, so this covers an hour or two of recording
+const MAX_HISTORY: usize = 500;
+
+const DEFAULT_HISTORY_LIMIT: usize = 50;
+
+/// Resource cost of one whisper-cli or llama run
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct RunStats {
+    /// "transcription" or "summarization"
+    pub kind: String,
+    pub model: String,
+    pub wall_time_ms: u64,
+    /// Sampled, so it undercounts the first second or so and the last interval.
+    /// None when another process did the work (llama-server or a remote API).
+    pub cpu_time_ms: Option<u64>,
+    pub peak_rss_mb: Option<f64>,
+    pub audio_duration_secs: Option<f64>,
+    /// Wall time over audio duration; below 1 is faster than realtime
+    pub realtime_factor: Option<f64>,
+    /// Unix seconds
+    pub finished_at: u64,
+}
+
+#[derive(Default)]
+struct Usage {
+    cpu_time_ms: f64,
+    peak_rss_bytes: u64,
+}
+
+/// Samples a child process's CPU and resident memory on a background thread while it runs
+pub struct ProcessMonitor {
+    started: Instant,
+    done: Arc<AtomicBool>,
+    sampler: Option<JoinHandle<Usage>>,
+}
+
+impl ProcessMonitor {
+    pub fn start(pid: u32) -> Self {
+        let done = Arc::new(AtomicBool::new(false));
+        let stop = done.clone();
+        let sampler = std::thread::spawn(move || {
+            let pid = Pid::from_u32(pid);
+            let refresh = ProcessRefreshKind::new().with_cpu().with_memory();
+            // Process CPU% is relative to total CPU time, so the CPU list has to be loaded too
+            let mut sys = System::new_with_specifics(RefreshKind::new().with_cpu(CpuRefreshKind::new().with_cpu_usage()));
+            let mut usage = Usage::default();
+            let mut last_sample = Instant::now();
+            loop {
+                // sysinfo only computes CPU% on a full refresh; refreshing just `pid` leaves it at 0 on Linux
+                sys.refresh_processes_specifics(ProcessesToUpdate::All, true, refresh);
+                let Some(process) = sys.process(pid) else {
+                    break;
+                };
+                // cpu_usage is the percentage of one core since the previous refresh
+                usage.cpu_time_ms += process.cpu_usage() as f64 / 100.0 * last_sample.elapsed().as_millis() as f64;
+                usage.peak_rss_bytes = usage.peak_rss_bytes.max(process.memory());
+                last_sample = Instant::now();
+
+                while last_sample.elapsed() < SAMPLE_INTERVAL && !stop.load(Ordering::Relaxed) {
+                    std::thread::sleep(STOP_POLL);
+                }
+                if stop.load(Ordering::Relaxed) {
+                    break;
+                }
+            }
+            usage
+        });
+        ProcessMonitor { started: Instant::now(), done, sampler: Some(sampler) }
+    }
+
+    /// Wall time only, for runs served by a process we don't own
+    pub fn wall_clock() -> Self {
+        ProcessMonitor { started: Instant::now(), done: Arc::new(AtomicBool::new(true)), sampler: None }
+    }
+
+    /// Stop sampling and summarize the run
+    pub fn finish(self, kind: &str, model: &Path, audio_duration_secs: Option<f64>) -> RunStats {
+        let wall_time_ms = self.started.elapsed().as_millis() as u64;
+        self.done.store(true, Ordering::Relaxed);
+        let usage = self.sampler.map(|sampler| sampler.join().unwrap_or_default());
+        let model = model.file_name().unwrap_or(model.as_os_str()).to_string_lossy().to_string();
+        RunStats {
+            kind: kind.to_string(),
+            model,
+            wall_time_ms,
+            cpu_time_ms: usage.as_ref().map(|u| u.cpu_time_ms as u64),
+            peak_rss_mb: usage.as_ref().map(|u| u.peak_rss_bytes as f64 / (1024.0 * 1024.0)),
+            audio_duration_secs,
+            realtime_factor: audio_duration_secs.filter(|&d| d > 0.0).map(|d| wall_time_ms as f64 / 1000.0 / d),
+            finished_at: SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0),
+        }
+    }
+}
+
+// Serializes writes to the history file; transcriptions and summaries can finish at the same time
+static HISTORY_LOCK: Mutex<()> = Mutex::new(());
+
+fn history_file() -> Result<PathBuf, String> {
+    Ok(get_data_dir()?.join(HISTORY_FILE))
+}
+
+fn load_history(path: &Path) -> Vec<RunStats> {
+    fs::read_to_string(path)
+        .ok()
+        .and_then(|raw| serde_json::from_str(&raw).ok())
+        .unwrap_or_default()
+}
+
+/// Append a run to the persisted history, dropping the oldest past MAX_HISTORY
+pub fn record(stats: &RunStats) {
+    let _guard = HISTORY_LOCK.lock().unwrap();
+    let result = history_file().and_then(|path| {
+        let mut history = load_history(&path);
+        history.push(stats.clone());
+        let excess = history.len().saturating_sub(MAX_HISTORY);
+        history.drain(..excess);
+        let json = serde_json::to_string(&history).map_err(|e| e.to_string())?;
+        fs::write(&path, json).map_err(|e| e.to_string())
+    });
+    if let Err(e) = result {
+        log::warn!("Failed to save performance history: {}", e);
+    }
+}
+
+/// The last `limit` runs (default 50), oldest first
+#[tauri::command]
+pub async fn get_performance_history(limit: Option<usize>) -> Result<Vec<RunStats>, String> {
+    let _guard = HISTORY_LOCK.lock().unwrap();
+    let mut history = load_history(&history_file()?);
+    let skip = history.len().saturating_sub(limit.unwrap_or(DEFAULT_HISTORY_LIMIT));
+    history.drain(..skip);
+    Ok(history)
+}
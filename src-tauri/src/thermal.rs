@@ -0,0 +1,243 @@
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::Manager;
+
+use crate::{events, models, settings, whisper};
+
+const THERMAL_DIR: &str = "/sys/class/thermal";
+const HWMON_DIR: &str = "/sys/class/hwmon";
+const CPU_DIR: &str = "/sys/devices/system/cpu";
+
+/// Below this share of its maximum clock, a CPU under load is most likely being throttled
+const THROTTLE_FREQ_RATIO: f64 = 0.7;
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ThermalZone {
+    pub name: String,
+    /// The zone's type, e.g. "x86_pkg_temp" or "acpitz"
+    pub kind: String,
+    pub temp_c: f64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct HwmonSensor {
+    /// Driver name, e.g. "coretemp" or "k10temp"
+    pub chip: String,
+    pub label: String,
+    pub temp_c: f64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct ThermalStatus {
+    pub zones: Vec<ThermalZone>,
+    pub sensors: Vec<HwmonSensor>,
+    /// Hottest reading across zones and sensors
+    pub max_temp_c: Option<f64>,
+    /// Average current clock across cores
+    pub cpu_freq_mhz: Option<f64>,
+    pub cpu_max_freq_mhz: Option<f64>,
+    /// Cores are running well below their maximum clock
+    pub throttled: bool,
+}
+
+fn read_trimmed(path: &Path) -> Option<String> {
+    fs::read_to_string(path).ok().map(|s| s.trim().to_string())
+}
+
+/// sysfs temperatures are in millidegrees Celsius
+fn read_millidegrees(path: &Path) -> Option<f64> {
+    read_trimmed(path)?.parse::<f64>().ok().map(|t| t / 1000.0)
+}
+
+fn sorted_entries(dir: &str, prefix: &str) -> Vec<std::path::PathBuf> {
+    let mut paths: Vec<_> = fs::read_dir(dir)
+        .map(|entries| {
+            entries
+                .filter_map(Result::ok)
+                .filter(|e| e.file_name().to_string_lossy().starts_with(prefix))
+                .map(|e| e.path())
+                .collect()
+        })
+        .unwrap_or_default();
+    paths.sort();
+    paths
+}
+
+fn thermal_zones() -> Vec<ThermalZone> {
+    sorted_entries(THERMAL_DIR, "thermal_zone")
+        .into_iter()
+        .filter_map(|dir| {
+            Some(ThermalZone {
+                temp_c: read_millidegrees(&dir.join("temp"))?,
+                kind: read_trimmed(&dir.join("type")).unwrap_or_default(),
+                name: dir.file_name()?.to_string_lossy().to_string(),
+            })
+        })
+        .collect()
+}
+
+fn hwmon_sensors() -> Vec<HwmonSensor> {
+    let mut sensors = Vec::new();
+    for dir in sorted_entries(HWMON_DIR, "hwmon") {
+        let chip = read_trimmed(&dir.join("name")).unwrap_or_default();
+        for input in sorted_entries(&dir.to_string_lossy(), "temp") {
+            let file = input.file_name().unwrap_or_default().to_string_lossy().to_string();
+            let Some(sensor) = file.strip_suffix("_input") else {
+                continue;
+            };
+            let Some(temp_c) = read_millidegrees(&input) else {
+                continue;
+            };
+            let label = read_trimmed(&dir.join(format!("{}_label", sensor))).unwrap_or_else(|| sensor.to_string());
+            sensors.push(HwmonSensor { chip: chip.clone(), label, temp_c });
+        }
+    }
+    sensors
+}
+
+/// Average current and maximum clock in MHz from cpufreq (reported in kHz)
+fn cpu_frequencies() -> (Option<f64>, Option<f64>) {
+    let cores = sorted_entries(CPU_DIR, "cpu");
+    let read_khz = |name: &str| -> Vec<f64> {
+        cores
+            .iter()
+            .filter_map(|core| read_trimmed(&core.join("cpufreq").join(name))?.parse::<f64>().ok())
+            .collect()
+    };
+    let average = |values: Vec<f64>| (!values.is_empty()).then(|| values.iter().sum::<f64>() / values.len() as f64 / 1000.0);
+    (average(read_khz("scaling_cur_freq")), average(read_khz("cpuinfo_max_freq")))
+}
+
+fn read_thermal_status() -> ThermalStatus {
+    if !cfg!(target_os = "linux") {
+        return ThermalStatus::default();
+    }
+    let zones = thermal_zones();
+    let sensors = hwmon_sensors();
+    let max_temp_c = zones
+        .iter()
+        .map(|z| z.temp_c)
+        .chain(sensors.iter().map(|s| s.temp_c))
+        .reduce(f64::max);
+    let (cpu_freq_mhz, cpu_max_freq_mhz) = cpu_frequencies();
+    let throttled = matches!((cpu_freq_mhz, cpu_max_freq_mhz), (Some(cur), Some(max)) if cur < max * THROTTLE_FREQ_RATIO);
+    ThermalStatus { zones, sensors, max_temp_c, cpu_freq_mhz, cpu_max_freq_mhz, throttled }
+}
+
+/// Temperatures from thermal zones and hwmon sensors plus CPU clocks (Linux only; empty elsewhere)
+#[tauri::command]
+pub async fn get_thermal_status() -> Result<ThermalStatus, String> {
+    tauri::async_runtime::spawn_blocking(read_thermal_status)
+        .await
+        .map_err(|e| format!("Failed to read thermal status: {}", e))
+}
+
+// The smaller model the live session switched to after falling behind, when adaptive_model is on
+pub struct LivePaceState {
+    downshifted: Mutex<Option<String>>,
+}
+
+impl LivePaceState {
+    pub fn new() -> Self {
+        LivePaceState { downshifted: Mutex::new(None) }
+    }
+}
+
+#[derive(Serialize, Clone)]
+struct PerformanceWarning {
+    chunk: usize,
+    transcribe_secs: f32,
+    segment_secs: u64,
+    temperature_c: Option<f64>,
+    throttled: bool,
+    suggestions: Vec<String>,
+    /// Set when adaptive_model switched later chunks to this model
+    switched_to: Option<String>,
+}
+
+/// Forget a previous session's downshift
+pub fn start_session(app: &tauri::AppHandle) {
+    *app.state::<LivePaceState>().downshifted.lock().unwrap() = None;
+}
+
+/// Drop the downshift of a session that failed to start
+pub fn end_session(app: &tauri::AppHandle) {
+    *app.state::<LivePaceState>().downshifted.lock().unwrap() = None;
+}
+
+/// Params for the next live chunk, with the model swapped if the session has downshifted
+pub fn chunk_params(app: &tauri::AppHandle, mut params: whisper::WhisperParams) -> whisper::WhisperParams {
+    if let Some(model) = app.state::<LivePaceState>().downshifted.lock().unwrap().clone() {
+        params.model = Some(model);
+    }
+    params
+}
+
+fn is_tiny(model: Option<&str>) -> bool {
+    model.is_some_and(|m| m.starts_with("tiny"))
+}
+
+/// The installed tiny model that can handle `params`' language, if any
+fn tiny_model(params: &whisper::WhisperParams) -> Option<String> {
+    let name = if params.needs_multilingual() { "tiny" } else { "tiny.en" };
+    let model = models::find_model(name)?;
+    models::get_models_dir().ok()?.join(model.file_name).exists().then(|| name.to_string())
+}
+
+/// Called after each live chunk is transcribed; warns when transcription took longer than the
+/// chunk's audio, and downshifts to the tiny model if adaptive_model is enabled
+pub fn chunk_transcribed(
+    app: &tauri::AppHandle,
+    chunk: usize,
+    elapsed: Duration,
+    segment_secs: u64,
+    params: &whisper::WhisperParams,
+) {
+    if elapsed.as_secs_f32() <= segment_secs as f32 {
+        return;
+    }
+    let thermal = read_thermal_status();
+    let threads = whisper::effective_options(app, params).threads.unwrap_or(0);
+    let mut suggestions = Vec::new();
+    if !is_tiny(params.model.as_deref()) {
+        suggestions.push("Switch to the tiny model".to_string());
+    }
+    if threads > 2 {
+        suggestions.push(format!("Reduce threads below {} so the CPU runs cooler", threads));
+    }
+    if thermal.throttled {
+        suggestions.push("The CPU is throttling; let it cool down or plug in".to_string());
+    }
+
+    let mut switched_to = None;
+    if settings::current(app).adaptive_model && !is_tiny(params.model.as_deref()) {
+        let state = app.state::<LivePaceState>();
+        let mut downshifted = state.downshifted.lock().unwrap();
+        if downshifted.is_none() {
+            *downshifted = tiny_model(params);
+            switched_to = downshifted.clone();
+        }
+    }
+
+    log::warn!(
+        "Live chunk {} took {:.1}s for {}s of audio (max temp {:?}, throttled {}){}",
+        chunk,
+        elapsed.as_secs_f32(),
+        segment_secs,
+        thermal.max_temp_c,
+        thermal.throttled,
+        switched_to.as_ref().map(|m| format!(", switching to {}", m)).unwrap_or_default()
+    );
+    events::emit(app, "performance-warning", PerformanceWarning {
+        chunk,
+        transcribe_secs: elapsed.as_secs_f32(),
+        segment_secs,
+        temperature_c: thermal.max_temp_c,
+        throttled: thermal.throttled,
+        suggestions,
+        switched_to,
+    });
+}
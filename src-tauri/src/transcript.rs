@@ -0,0 +1,531 @@
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+use crate::error::AppError;
+use crate::paths;
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct TranscriptSegment {
+    pub start_ms: u64,
+    pub end_ms: u64,
+    pub text: String,
+    /// A different speaker takes over after this segment (tinydiarize models only)
+    #[serde(default)]
+    pub speaker_turn: bool,
+    /// 0 or 1, alternating at each speaker turn; None unless the transcription was diarized
+    #[serde(default)]
+    pub speaker: Option<u32>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct TranscriptResult {
+    pub segments: Vec<TranscriptSegment>,
+    pub text: String,
+    pub language: Option<String>,
+    /// Unfiltered whisper text, present when the hallucination filter changed the output
+    pub raw_text: Option<String>,
+    /// Why the result isn't quite what was asked for, e.g. mono audio given to a stereo split
+    #[serde(default)]
+    pub warning: Option<String>,
+}
+
+impl TranscriptResult {
+    pub fn from_segments(segments: Vec<TranscriptSegment>) -> Self {
+        let text = segments
+            .iter()
+            .map(|s| s.text.as_str())
+            .collect::<Vec<_>>()
+            .join(" ");
+        TranscriptResult { segments, text, language: None, raw_text: None, warning: None }
+    }
+}
+
+/// Longest run of words checked when stitching overlapping live chunks
+const MAX_OVERLAP_WORDS: usize = 20;
+
+fn overlap_key(word: &str) -> String {
+    word.chars().filter(|c| c.is_alphanumeric()).flat_map(char::to_lowercase).collect()
+}
+
+/// Drop the leading words of `next` that repeat the end of `previous`, ignoring case and punctuation.
+/// Overlaps shorter than two words are kept, since a single shared word is usually a coincidence.
+pub fn trim_overlap(previous: &str, next: &str) -> String {
+    let prev_keys: Vec<String> = previous.split_whitespace().map(overlap_key).collect();
+    let next_words: Vec<&str> = next.split_whitespace().collect();
+    let next_keys: Vec<String> = next_words.iter().map(|w| overlap_key(w)).collect();
+
+    let max = MAX_OVERLAP_WORDS.min(prev_keys.len()).min(next_keys.len());
+    let overlap = (2..=max)
+        .rev()
+        .find(|&k| prev_keys[prev_keys.len() - k..] == next_keys[..k])
+        .unwrap_or(0);
+
+    next_words[overlap..].join(" ")
+}
+
+/// Trim `text` against the tail of the chunks stitched so far.
+/// A heavily trimmed chunk can be shorter than the overlap, so compare against the last few joined.
+pub fn stitch_chunk(stitched: &[String], text: &str) -> String {
+    let start = stitched.len().saturating_sub(3);
+    trim_overlap(&stitched[start..].join(" "), text)
+}
+
+/// Parse "HH:MM:SS.mmm" (or with a comma before the millis) into milliseconds
+fn parse_timestamp(ts: &str) -> Option<u64> {
+    let ts = ts.trim().replace(',', ".");
+    let (hms, millis) = ts.split_once('.')?;
+    let mut parts = hms.split(':').map(|p| p.parse::<u64>().ok());
+    let h = parts.next()??;
+    let m = parts.next()??;
+    let s = parts.next()??;
+    if parts.next().is_some() {
+        return None;
+    }
+    let ms: u64 = millis.parse().ok()?;
+    Some(((h * 60 + m) * 60 + s) * 1000 + ms)
+}
+
+/// Split a "[00:00:00.000 --> 00:00:05.120]  text" line into its times and remaining text
+fn parse_segment_header(line: &str) -> Option<(u64, u64, &str)> {
+    let rest = line.trim_start().strip_prefix('[')?;
+    let (range, text) = rest.split_once(']')?;
+    let (start, end) = range.split_once("-->")?;
+    Some((parse_timestamp(start)?, parse_timestamp(end)?, text.trim()))
+}
+
+/// What whisper-cli appends to a segment's text when a tdrz model predicts a speaker change after it
+const SPEAKER_TURN_MARKER: &str = "[SPEAKER_TURN]";
+
+/// Lines whisper/ggml log to stderr that occasionally end up interleaved with stdout
+fn is_log_noise(line: &str) -> bool {
+    const PREFIXES: &[&str] = &[
+        "whisper_", "ggml_", "main:", "system_info:", "output_", "log_mel", "load_backend",
+    ];
+    let trimmed = line.trim_start();
+    PREFIXES.iter().any(|p| trimmed.starts_with(p))
+}
+
+/// Parse timestamped whisper-cli output into segments, joining continuation lines
+pub fn parse_whisper_segments(output: &str) -> Vec<TranscriptSegment> {
+    let mut segments: Vec<TranscriptSegment> = Vec::new();
+
+    for line in output.lines() {
+        let trimmed = line.trim();
+        if trimmed.is_empty() || is_log_noise(trimmed) {
+            continue;
+        }
+
+        if let Some((start_ms, end_ms, text)) = parse_segment_header(trimmed) {
+            segments.push(TranscriptSegment {
+                start_ms,
+                end_ms,
+                text: text.to_string(),
+                ..Default::default()
+            });
+        } else if let Some(last) = segments.last_mut() {
+            // Text wrapped onto the next line belongs to the previous segment
+            if !last.text.is_empty() {
+                last.text.push(' ');
+            }
+            last.text.push_str(trimmed);
+        }
+    }
+
+    for segment in &mut segments {
+        if let Some(text) = segment.text.strip_suffix(SPEAKER_TURN_MARKER) {
+            segment.text = text.trim_end().to_string();
+            segment.speaker_turn = true;
+        }
+    }
+    // A turn marker on an otherwise empty segment still belongs to the speech before it
+    let mut kept: Vec<TranscriptSegment> = Vec::with_capacity(segments.len());
+    for segment in segments {
+        match kept.last_mut() {
+            Some(last) if segment.text.is_empty() => last.speaker_turn |= segment.speaker_turn,
+            _ if segment.text.is_empty() => {}
+            _ => kept.push(segment),
+        }
+    }
+    kept
+}
+
+/// Label segments with alternating speakers, flipping after every speaker turn. Returns the speaker
+/// the next segment would have, so live chunks can carry it across chunk boundaries.
+pub fn assign_speakers(segments: &mut [TranscriptSegment], first_speaker: u32) -> u32 {
+    let mut speaker = first_speaker;
+    for segment in segments {
+        segment.speaker = Some(speaker);
+        if segment.speaker_turn {
+            speaker = 1 - speaker;
+        }
+    }
+    speaker
+}
+
+/// Speaker state carried between live chunks, so labels keep alternating across chunk boundaries
+#[derive(Default)]
+pub struct SpeakerTracker {
+    next: u32,
+    last_labelled: Option<u32>,
+}
+
+impl SpeakerTracker {
+    /// Assign speakers to a chunk's segments and join their text, starting a "[Speaker N]" line
+    /// wherever the speaker changes
+    pub fn label_chunk(&mut self, segments: &mut [TranscriptSegment]) -> String {
+        self.next = assign_speakers(segments, self.next);
+        let mut text = String::new();
+        for segment in segments.iter() {
+            if segment.speaker != self.last_labelled {
+                if !text.is_empty() {
+                    text.push('\n');
+                }
+                text.push_str(&format!("[Speaker {}] ", segment.speaker.unwrap_or(0) + 1));
+                self.last_labelled = segment.speaker;
+            } else if !text.is_empty() {
+                text.push(' ');
+            }
+            text.push_str(&segment.text);
+        }
+        text
+    }
+}
+
+// Shape of whisper-cli's --output-json-full file (only the fields we use)
+#[derive(Deserialize)]
+struct WhisperJson {
+    #[serde(default)]
+    result: Option<WhisperJsonResult>,
+    #[serde(default)]
+    transcription: Vec<WhisperJsonSegment>,
+}
+
+#[derive(Deserialize)]
+struct WhisperJsonResult {
+    language: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct WhisperJsonOffsets {
+    from: u64,
+    to: u64,
+}
+
+#[derive(Deserialize)]
+struct WhisperJsonSegment {
+    offsets: WhisperJsonOffsets,
+    text: String,
+    #[serde(default)]
+    tokens: Vec<WhisperJsonToken>,
+}
+
+#[derive(Deserialize)]
+struct WhisperJsonToken {
+    text: String,
+    offsets: Option<WhisperJsonOffsets>,
+    #[serde(default)]
+    p: f32,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct TranscriptToken {
+    pub text: String,
+    pub start_ms: u64,
+    pub end_ms: u64,
+    pub probability: f32,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct TranscriptWord {
+    pub word: String,
+    pub start_ms: u64,
+    pub end_ms: u64,
+    /// Lowest token probability in the word, so one shaky sub-word flags the whole word
+    pub confidence: f32,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct DetailedSegment {
+    pub start_ms: u64,
+    pub end_ms: u64,
+    pub text: String,
+    pub tokens: Vec<TranscriptToken>,
+    pub words: Vec<TranscriptWord>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct DetailedTranscript {
+    pub segments: Vec<DetailedSegment>,
+    pub text: String,
+    pub language: Option<String>,
+}
+
+/// Group sub-word tokens into words; a leading space marks the start of a new word
+fn tokens_to_words(tokens: &[TranscriptToken]) -> Vec<TranscriptWord> {
+    let mut words: Vec<TranscriptWord> = Vec::new();
+    for token in tokens {
+        let starts_word = token.text.starts_with(' ') || words.is_empty();
+        let piece = token.text.trim();
+        if piece.is_empty() {
+            continue;
+        }
+        match words.last_mut() {
+            Some(word) if !starts_word => {
+                word.word.push_str(piece);
+                word.end_ms = token.end_ms;
+                word.confidence = word.confidence.min(token.probability);
+            }
+            _ => words.push(TranscriptWord {
+                word: piece.to_string(),
+                start_ms: token.start_ms,
+                end_ms: token.end_ms,
+                confidence: token.probability,
+            }),
+        }
+    }
+    words
+}
+
+fn parse_whisper_json(raw: &str) -> Result<DetailedTranscript, String> {
+    let parsed: WhisperJson = serde_json::from_str(raw)
+        .map_err(|e| format!("Failed to parse whisper JSON: {}", e))?;
+
+    let segments: Vec<DetailedSegment> = parsed
+        .transcription
+        .into_iter()
+        .map(|seg| {
+            let tokens: Vec<TranscriptToken> = seg
+                .tokens
+                .into_iter()
+                // Special tokens like [_BEG_] and [_TT_150] aren't speech
+                .filter(|t| !t.text.starts_with("[_"))
+                .map(|t| {
+                    let (start_ms, end_ms) = t
+                        .offsets
+                        .map(|o| (o.from, o.to))
+                        .unwrap_or((seg.offsets.from, seg.offsets.to));
+                    TranscriptToken { text: t.text, start_ms, end_ms, probability: t.p }
+                })
+                .collect();
+            DetailedSegment {
+                start_ms: seg.offsets.from,
+                end_ms: seg.offsets.to,
+                text: seg.text.trim().to_string(),
+                words: tokens_to_words(&tokens),
+                tokens,
+            }
+        })
+        .collect();
+
+    let text = segments.iter().map(|s| s.text.as_str()).collect::<Vec<_>>().join(" ");
+    Ok(DetailedTranscript {
+        segments,
+        text,
+        language: parsed.result.and_then(|r| r.language),
+    })
+}
+
+/// Find the JSON whisper wrote (requested base, next to the input, or the cwd), parse it, and delete it
+pub fn take_whisper_json(output_base: &Path, input_path: &str) -> Result<DetailedTranscript, String> {
+    let input = Path::new(input_path);
+    let mut candidates: Vec<PathBuf> = vec![PathBuf::from(format!("{}.json", output_base.display()))];
+    candidates.push(PathBuf::from(format!("{}.json", input.display())));
+    if let Some(stem) = input.file_name() {
+        candidates.push(PathBuf::from(format!("{}.json", stem.to_string_lossy())));
+    }
+
+    let json_path = candidates
+        .into_iter()
+        .find(|p| p.is_file())
+        .ok_or("whisper did not produce a JSON output file")?;
+
+    let raw = std::fs::read_to_string(&json_path)
+        .map_err(|e| format!("Failed to read whisper JSON: {}", e))?;
+    let _ = std::fs::remove_file(&json_path);
+
+    parse_whisper_json(&raw)
+}
+
+/// Subtitle line width we wrap segment text to
+const MAX_LINE_CHARS: usize = 42;
+
+fn format_timestamp(ms: u64, millis_sep: char) -> String {
+    let h = ms / 3_600_000;
+    let m = (ms / 60_000) % 60;
+    let s = (ms / 1000) % 60;
+    format!("{:02}:{:02}:{:02}{}{:03}", h, m, s, millis_sep, ms % 1000)
+}
+
+/// Wrap text at word boundaries so no line exceeds MAX_LINE_CHARS (single long words stay whole)
+fn wrap_subtitle_text(text: &str) -> Vec<String> {
+    let mut lines: Vec<String> = Vec::new();
+    let mut current = String::new();
+    for word in text.split_whitespace() {
+        if !current.is_empty() && current.chars().count() + 1 + word.chars().count() > MAX_LINE_CHARS {
+            lines.push(std::mem::take(&mut current));
+        }
+        if !current.is_empty() {
+            current.push(' ');
+        }
+        current.push_str(word);
+    }
+    if !current.is_empty() {
+        lines.push(current);
+    }
+    lines
+}
+
+/// Split segments whose text would wrap past two subtitle lines into time-proportional cues
+fn split_long_segments(segments: &[TranscriptSegment]) -> Vec<TranscriptSegment> {
+    let mut cues = Vec::new();
+    for seg in segments {
+        let lines = wrap_subtitle_text(&seg.text);
+        if lines.len() <= 2 {
+            cues.push(seg.clone());
+            continue;
+        }
+
+        let total_chars: usize = lines.iter().map(|l| l.chars().count()).sum::<usize>().max(1);
+        let duration = seg.end_ms.saturating_sub(seg.start_ms);
+        let mut start = seg.start_ms;
+        let mut consumed = 0usize;
+        for pair in lines.chunks(2) {
+            consumed += pair.iter().map(|l| l.chars().count()).sum::<usize>();
+            let end = seg.start_ms + duration * consumed as u64 / total_chars as u64;
+            cues.push(TranscriptSegment {
+                start_ms: start,
+                end_ms: end,
+                text: pair.join(" "),
+                // The turn comes after the last cue of the segment
+                speaker_turn: seg.speaker_turn && consumed == total_chars,
+                speaker: seg.speaker,
+            });
+            start = end;
+        }
+    }
+    cues
+}
+
+pub fn to_srt(segments: &[TranscriptSegment]) -> String {
+    let mut out = String::new();
+    for (i, cue) in split_long_segments(segments).iter().enumerate() {
+        out.push_str(&format!(
+            "{}\n{} --> {}\n{}\n\n",
+            i + 1,
+            format_timestamp(cue.start_ms, ','),
+            format_timestamp(cue.end_ms, ','),
+            wrap_subtitle_text(&cue.text).join("\n")
+        ));
+    }
+    out
+}
+
+pub fn to_vtt(segments: &[TranscriptSegment]) -> String {
+    let mut out = String::from("WEBVTT\n\n");
+    for cue in split_long_segments(segments) {
+        out.push_str(&format!(
+            "{} --> {}\n{}\n\n",
+            format_timestamp(cue.start_ms, '.'),
+            format_timestamp(cue.end_ms, '.'),
+            wrap_subtitle_text(&cue.text).join("\n")
+        ));
+    }
+    out
+}
+
+pub fn to_txt(segments: &[TranscriptSegment]) -> String {
+    let mut out = segments
+        .iter()
+        .map(|s| s.text.as_str())
+        .collect::<Vec<_>>()
+        .join("\n");
+    out.push('\n');
+    out
+}
+
+/// One "[HH:MM:SS] text" line per segment
+pub fn to_timestamped_txt(segments: &[TranscriptSegment]) -> String {
+    segments
+        .iter()
+        .map(|s| format!("[{}] {}", &format_timestamp(s.start_ms, '.')[..8], s.text.trim()))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Render segments in "srt", "vtt", or "txt" format
+pub fn render(segments: &[TranscriptSegment], format: &str) -> Result<String, String> {
+    match format.to_lowercase().as_str() {
+        "srt" => Ok(to_srt(segments)),
+        "vtt" => Ok(to_vtt(segments)),
+        "txt" => Ok(to_txt(segments)),
+        other => Err(format!("Unsupported export format '{}' (expected srt, vtt, or txt)", other)),
+    }
+}
+
+/// Export transcript segments as subtitles or plain text, returning the written path
+#[tauri::command]
+pub async fn export_transcript(
+    app: tauri::AppHandle,
+    segments: Vec<TranscriptSegment>,
+    format: String,
+    out_path: String,
+    overwrite: Option<bool>,
+) -> Result<String, AppError> {
+    let contents = render(&segments, &format)?;
+    let path = paths::writable(&app, &out_path)?;
+
+    if path.exists() && !overwrite.unwrap_or(false) {
+        return Err(format!("File already exists: {}", path.display()).into());
+    }
+    if let Some(parent) = path.parent() {
+        if !parent.as_os_str().is_empty() {
+            std::fs::create_dir_all(parent)
+                .map_err(|e| format!("Failed to create export directory: {}", e))?;
+        }
+    }
+
+    std::fs::write(&path, contents)
+        .map_err(|e| format!("Failed to write transcript: {}", e))?;
+
+    Ok(path.to_string_lossy().to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn trims_an_exact_overlap() {
+        assert_eq!(
+            trim_overlap("we should ship the release on friday", "ship the release on friday after the review"),
+            "after the review"
+        );
+    }
+
+    #[test]
+    fn trims_an_overlap_differing_in_case_and_punctuation() {
+        assert_eq!(trim_overlap("Let's meet at noon, okay?", "okay... let's meet at noon then"), "okay... let's meet at noon then");
+        assert_eq!(trim_overlap("Let's meet at noon, okay?", "AT NOON okay. See you"), "See you");
+    }
+
+    #[test]
+    fn keeps_text_without_an_overlap() {
+        assert_eq!(trim_overlap("the first chunk ends here", "a second chunk starts"), "a second chunk starts");
+        assert_eq!(trim_overlap("", "nothing before this"), "nothing before this");
+        assert_eq!(trim_overlap("something before", ""), "");
+    }
+
+    #[test]
+    fn keeps_a_single_shared_word() {
+        assert_eq!(trim_overlap("see you there", "there is more"), "there is more");
+    }
+
+    #[test]
+    fn stitches_against_several_short_chunks() {
+        let stitched = vec!["one two".to_string(), "three".to_string(), "four five".to_string()];
+        assert_eq!(stitch_chunk(&stitched, "three four five six"), "six");
+        // Only the last three chunks are compared
+        let stitched = vec!["alpha beta".to_string(), "x".to_string(), "y".to_string(), "z".to_string()];
+        assert_eq!(stitch_chunk(&stitched, "alpha beta gamma"), "alpha beta gamma");
+    }
+}
@@ -0,0 +1,165 @@
+use tauri::image::Image;
+use tauri::menu::{Menu, MenuItem, PredefinedMenuItem};
+use tauri::tray::{TrayIcon, TrayIconBuilder};
+use tauri::{Emitter, Manager};
+
+use crate::recorder_status::{self, LivePhase, RecorderStatus};
+use crate::{notes, pause_live_recording, resume_live_recording, ChunkedRecorderState};
+
+const TOGGLE_ID: &str = "toggle-recording";
+const PAUSE_ID: &str = "pause-recording";
+const OPEN_NOTE_ID: &str = "open-last-note";
+
+/// Badge colours drawn over the app icon's corner
+const RECORDING_BADGE: [u8; 3] = [0xe5, 0x39, 0x35];
+const PAUSED_BADGE: [u8; 3] = [0xf9, 0xa8, 0x25];
+const TRANSCRIBING_BADGE: [u8; 3] = [0x1e, 0x88, 0xe5];
+
+// The tray icon and the menu items whose text follows the recorder state
+struct TrayHandles {
+    icon: TrayIcon,
+    toggle: MenuItem<tauri::Wry>,
+    pause: MenuItem<tauri::Wry>,
+    idle_image: Image<'static>,
+    recording_image: Image<'static>,
+    paused_image: Image<'static>,
+    transcribing_image: Image<'static>,
+}
+
+/// The app icon with a filled circle in its bottom-right quarter
+fn badged(base: &Image<'_>, color: [u8; 3]) -> Image<'static> {
+    let (width, height) = (base.width() as i64, base.height() as i64);
+    let mut rgba = base.rgba().to_vec();
+    let radius = width.min(height) / 4;
+    let (cx, cy) = (width - radius - 1, height - radius - 1);
+    for y in (cy - radius).max(0)..height {
+        for x in (cx - radius).max(0)..width {
+            if (x - cx).pow(2) + (y - cy).pow(2) <= radius * radius {
+                let i = ((y * width + x) * 4) as usize;
+                rgba[i..i + 4].copy_from_slice(&[color[0], color[1], color[2], 0xff]);
+            }
+        }
+    }
+    Image::new_owned(rgba, width as u32, height as u32)
+}
+
+fn tooltip(status: &RecorderStatus) -> String {
+    let mut text = match status.live {
+        LivePhase::Idle if status.system_recording || status.one_shot_recording => "last-gen-notes — recording".to_string(),
+        LivePhase::Idle => "last-gen-notes — idle".to_string(),
+        LivePhase::Recording => "last-gen-notes — live recording".to_string(),
+        LivePhase::Paused => "last-gen-notes — live recording paused".to_string(),
+        LivePhase::Transcribing => "last-gen-notes — transcribing".to_string(),
+    };
+    if status.backlog > 0 {
+        text.push_str(&format!(" ({} chunks waiting)", status.backlog));
+    }
+    text
+}
+
+/// Bring the main window forward, restoring it if it was minimized or hidden
+pub fn show_main_window(app: &tauri::AppHandle) {
+    if let Some(window) = app.get_webview_window("main") {
+        let _ = window.unminimize();
+        let _ = window.show();
+        let _ = window.set_focus();
+    }
+}
+
+/// Bring the main window forward and ask it to open the newest note
+fn open_last_note(app: &tauri::AppHandle) {
+    let Some(id) = notes::latest_id(app) else {
+        log::info!("No notes to open from the tray");
+        return;
+    };
+    show_main_window(app);
+    let _ = app.emit("open-note", serde_json::json!({ "id": id }));
+}
+
+fn on_menu_event(app: &tauri::AppHandle, id: &str) {
+    match id {
+        TOGGLE_ID => {
+            let app = app.clone();
+            tauri::async_runtime::spawn(async move {
+                if let Err(e) = recorder_status::toggle_live(&app).await {
+                    log::error!("Tray couldn't toggle live recording: {}", e);
+                }
+            });
+        }
+        PAUSE_ID => {
+            let state = app.state::<ChunkedRecorderState>();
+            let result = if *state.paused.lock() {
+                resume_live_recording(app.clone(), state)
+            } else {
+                pause_live_recording(app.clone(), state)
+            };
+            if let Err(e) = result {
+                log::error!("Tray couldn't pause or resume live recording: {}", e);
+            }
+        }
+        OPEN_NOTE_ID => open_last_note(app),
+        _ => {}
+    }
+}
+
+/// Add the tray icon; a desktop without a tray (or appindicator on Linux) just goes without
+pub fn init(app: &tauri::AppHandle) {
+    let Some(base) = app.default_window_icon() else {
+        log::warn!("System tray unavailable: the app has no icon");
+        return;
+    };
+    let base = Image::new_owned(base.rgba().to_vec(), base.width(), base.height());
+    let build = || -> tauri::Result<TrayHandles> {
+        let toggle = MenuItem::with_id(app, TOGGLE_ID, "Start live recording", true, None::<&str>)?;
+        let pause = MenuItem::with_id(app, PAUSE_ID, "Pause", false, None::<&str>)?;
+        let open_note = MenuItem::with_id(app, OPEN_NOTE_ID, "Open last note", true, None::<&str>)?;
+        let menu = Menu::with_items(app, &[&toggle, &pause, &PredefinedMenuItem::separator(app)?, &open_note])?;
+        let icon = TrayIconBuilder::with_id("recorder")
+            .icon(base.clone())
+            .tooltip(tooltip(&RecorderStatus::default()))
+            .menu(&menu)
+            .on_menu_event(|app, event| on_menu_event(app, event.id().as_ref()))
+            .build(app)?;
+        Ok(TrayHandles {
+            icon,
+            toggle,
+            pause,
+            recording_image: badged(&base, RECORDING_BADGE),
+            paused_image: badged(&base, PAUSED_BADGE),
+            transcribing_image: badged(&base, TRANSCRIBING_BADGE),
+            idle_image: base.clone(),
+        })
+    };
+    match build() {
+        Ok(handles) => {
+            app.manage(handles);
+            refresh(app);
+        }
+        Err(e) => log::warn!("System tray unavailable: {}", e),
+    }
+}
+
+/// Redraw the tray from the current recorder status. Runs on the main thread, which the tray and
+/// its menu belong to; reading the status there keeps out-of-order refreshes from showing a stale one.
+pub fn refresh(app: &tauri::AppHandle) {
+    let app_for_tray = app.clone();
+    let _ = app.run_on_main_thread(move || {
+        let Some(tray) = app_for_tray.try_state::<TrayHandles>() else { return };
+        let status = recorder_status::current(&app_for_tray);
+        let image = match status.live {
+            LivePhase::Recording => &tray.recording_image,
+            LivePhase::Paused => &tray.paused_image,
+            LivePhase::Transcribing => &tray.transcribing_image,
+            LivePhase::Idle if status.system_recording || status.one_shot_recording => &tray.recording_image,
+            LivePhase::Idle => &tray.idle_image,
+        };
+        let _ = tray.icon.set_icon(Some(image.clone()));
+        let _ = tray.icon.set_tooltip(Some(tooltip(&status)));
+
+        let live_active = matches!(status.live, LivePhase::Recording | LivePhase::Paused);
+        let _ = tray.toggle.set_text(if live_active { "Stop live recording" } else { "Start live recording" });
+        let _ = tray.toggle.set_enabled(status.live != LivePhase::Transcribing);
+        let _ = tray.pause.set_text(if status.live == LivePhase::Paused { "Resume" } else { "Pause" });
+        let _ = tray.pause.set_enabled(live_active);
+    });
+}
@@ -0,0 +1,241 @@
+use std::fs;
+use std::io::{BufRead, BufReader};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use tauri::Manager;
+
+use crate::error::AppError;
+use crate::{audio, events, has_ffmpeg, notes, paths, processes, recordings};
+
+/// Files bigger than this report audio-edit-progress while they're cut
+const PROGRESS_MIN_BYTES: u64 = 100 * 1024 * 1024;
+
+fn is_wav(path: &Path) -> bool {
+    path.extension().is_some_and(|e| e.eq_ignore_ascii_case("wav"))
+}
+
+/// Length of a recording: from the header for PCM WAV, otherwise from ffprobe
+fn duration_ms(path: &Path) -> Result<u64, String> {
+    match audio::pcm_info(path) {
+        Ok(info) => Ok((info.duration_secs() * 1000.0) as u64),
+        Err(_) => audio::probe_duration_secs(path)
+            .map(|secs| (secs * 1000.0) as u64)
+            .ok_or_else(|| format!("Couldn't read the length of {}; is ffmpeg installed?", path.display())),
+    }
+}
+
+/// `<stem><suffix>.<ext>` next to `source`
+pub fn sibling(source: &Path, suffix: &str) -> PathBuf {
+    let stem = source.file_stem().map(|s| s.to_string_lossy().to_string()).unwrap_or_default();
+    let ext = source.extension().map(|e| e.to_string_lossy().to_string()).unwrap_or_else(|| "wav".into());
+    source.with_file_name(format!("{}{}.{}", stem, suffix, ext))
+}
+
+/// Refuse to write over the source, or over any other file, unless `overwrite` is set
+pub fn check_output(source: &Path, output: &Path, overwrite: bool) -> Result<(), AppError> {
+    let display = output.to_string_lossy().to_string();
+    if output == source && !overwrite {
+        return Err(AppError::PathNotAllowed {
+            path: display,
+            message: "Refusing to overwrite the source recording; pass overwrite to replace it".into(),
+        });
+    }
+    if output.exists() && !overwrite {
+        return Err(AppError::PathNotAllowed {
+            message: format!("{} already exists; pass overwrite to replace it", display),
+            path: display,
+        });
+    }
+    Ok(())
+}
+
+/// Cut `start_ms..end_ms` with ffmpeg, for inputs or outputs that aren't PCM WAV. Decoding from the
+/// start (-ss after -i) keeps the cut sample-accurate rather than snapping to a keyframe.
+fn ffmpeg_cut(
+    app: &tauri::AppHandle,
+    source: &Path,
+    start_ms: u64,
+    end_ms: u64,
+    output: &Path,
+    progress: &mut dyn FnMut(f64),
+) -> Result<(), String> {
+    if !has_ffmpeg() {
+        return Err(format!(
+            "ffmpeg is required to cut {}. Install ffmpeg (e.g. `sudo apt install ffmpeg`) and try again.",
+            source.display()
+        ));
+    }
+    let seconds = |ms: u64| format!("{}.{:03}", ms / 1000, ms % 1000);
+    let mut child = processes::spawn(
+        app,
+        Command::new("ffmpeg")
+            .arg("-hide_banner")
+            .arg("-loglevel").arg("error")
+            .arg("-y")
+            .arg("-i").arg(source)
+            .arg("-ss").arg(seconds(start_ms))
+            .arg("-to").arg(seconds(end_ms))
+            .arg("-progress").arg("pipe:1")
+            .arg("-nostats")
+            .arg(output)
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped()),
+    )
+    .map_err(|e| format!("Failed to start ffmpeg: {}", e))?;
+
+    if let Some(stdout) = child.stdout.take() {
+        let span_us = (end_ms - start_ms) as f64 * 1000.0;
+        for line in BufReader::new(stdout).lines().map_while(Result::ok) {
+            if let Some(us) = line.strip_prefix("out_time_us=").and_then(|v| v.trim().parse::<f64>().ok()) {
+                progress((us / span_us).clamp(0.0, 1.0));
+            }
+        }
+    }
+    let pid = child.id();
+    let result = child.wait_with_output();
+    app.state::<processes::ProcessRegistry>().unregister(pid);
+    let result = result.map_err(|e| format!("Failed to wait for ffmpeg: {}", e))?;
+    if !result.status.success() {
+        return Err(format!("ffmpeg couldn't cut {}: {}", source.display(), String::from_utf8_lossy(&result.stderr).trim()));
+    }
+    Ok(())
+}
+
+/// Write `start_ms..end_ms` of `source` to `output` by way of a temp file beside it, so a failed cut
+/// never leaves a half-written file (or clobbers the source). Blocking.
+fn cut(
+    app: &tauri::AppHandle,
+    source: &Path,
+    start_ms: u64,
+    end_ms: u64,
+    output: &Path,
+    progress: &mut dyn FnMut(f64),
+) -> Result<(), String> {
+    let ext = output.extension().map(|e| e.to_string_lossy().to_string()).unwrap_or_default();
+    let tmp = output.with_extension(format!("tmp.{}", ext));
+    let result = if is_wav(output) && audio::pcm_info(source).is_ok() {
+        audio::extract_wav_span(source, start_ms, end_ms, &tmp, |done, total| progress(done as f64 / total.max(1) as f64))
+            .map(|_| ())
+    } else {
+        ffmpeg_cut(app, source, start_ms, end_ms.min(duration_ms(source)?), &tmp, progress)
+    };
+    match result {
+        Ok(()) => fs::rename(&tmp, output).map_err(|e| format!("Failed to replace {}: {}", output.display(), e)),
+        Err(e) => {
+            let _ = fs::remove_file(&tmp);
+            Err(e)
+        }
+    }
+}
+
+/// A non-empty span that starts inside the recording; an end past it is cut to fit
+fn check_span(source: &Path, start_ms: u64, end_ms: u64) -> Result<(), String> {
+    let duration = duration_ms(source)?;
+    if start_ms >= end_ms {
+        return Err(format!("The span {}–{} ms is empty", start_ms, end_ms));
+    }
+    if start_ms >= duration {
+        return Err(format!("{} ms is past the end of the recording ({} ms)", start_ms, duration));
+    }
+    Ok(())
+}
+
+/// Reports audio-edit-progress as whole percents, for sources big enough to take a while
+fn progress_reporter(app: &tauri::AppHandle, source: &Path) -> impl FnMut(f64) {
+    let report = fs::metadata(source).map(|m| m.len() > PROGRESS_MIN_BYTES).unwrap_or(false);
+    let (app, path) = (app.clone(), source.to_string_lossy().to_string());
+    let mut last = 0u32;
+    move |fraction| {
+        let percent = (fraction * 100.0) as u32;
+        if report && percent > last {
+            last = percent;
+            events::emit(&app, "audio-edit-progress", serde_json::json!({ "path": path, "percent": percent }));
+        }
+    }
+}
+
+fn not_recording(app: &tauri::AppHandle, source: &Path) -> Result<(), AppError> {
+    if recordings::is_recording_in_progress(app, source) {
+        return Err(AppError::RecorderBusy(format!("{} is still being recorded", source.display())));
+    }
+    Ok(())
+}
+
+/// Keep only `start_ms..end_ms` of a recording, e.g. to cut off the chatter before a meeting.
+/// Written to `out_path` (default `<name>-trimmed` beside it); the source is only replaced when
+/// `overwrite` is set. Notes pointing at the written file get its new duration.
+#[tauri::command]
+pub async fn trim_audio(
+    app: tauri::AppHandle,
+    path: String,
+    start_ms: u64,
+    end_ms: u64,
+    out_path: Option<String>,
+    overwrite: Option<bool>,
+) -> Result<String, AppError> {
+    let source = paths::existing(&app, &path)?;
+    not_recording(&app, &source)?;
+    let output = match out_path {
+        Some(out) => paths::writable(&app, &out)?,
+        None => sibling(&source, "-trimmed"),
+    };
+    check_output(&source, &output, overwrite.unwrap_or(false))?;
+
+    let (task_app, written) = (app.clone(), output.clone());
+    tauri::async_runtime::spawn_blocking(move || {
+        check_span(&source, start_ms, end_ms)?;
+        cut(&task_app, &source, start_ms, end_ms, &written, &mut progress_reporter(&task_app, &source))
+    })
+    .await
+    .map_err(|e| format!("Trim task failed: {}", e))??;
+
+    if let Ok(ms) = duration_ms(&output) {
+        if let Err(e) = notes::audio_edited(&app, &output, ms as f64 / 1000.0) {
+            log::warn!("Couldn't update the notes using {}: {}", output.display(), e);
+        }
+    }
+    Ok(output.to_string_lossy().to_string())
+}
+
+/// Split a recording at `points_ms` into `<name>-split-1`, `-split-2` and so on beside it,
+/// leaving the source as it is
+#[tauri::command]
+pub async fn split_audio(
+    app: tauri::AppHandle,
+    path: String,
+    points_ms: Vec<u64>,
+    overwrite: Option<bool>,
+) -> Result<Vec<String>, AppError> {
+    let source = paths::existing(&app, &path)?;
+    not_recording(&app, &source)?;
+    let mut points = points_ms;
+    points.sort_unstable();
+    points.dedup();
+    if points.is_empty() {
+        return Err("Give at least one point to split at".into());
+    }
+    let outputs: Vec<PathBuf> = (1..=points.len() + 1).map(|n| sibling(&source, &format!("-split-{}", n))).collect();
+    for output in &outputs {
+        check_output(&source, output, overwrite.unwrap_or(false))?;
+    }
+
+    let (task_app, written) = (app.clone(), outputs.clone());
+    tauri::async_runtime::spawn_blocking(move || {
+        let duration = duration_ms(&source)?;
+        if let Some(bad) = points.iter().find(|&&p| p == 0 || p >= duration) {
+            return Err(format!("{} ms isn't inside the recording (0–{} ms)", bad, duration));
+        }
+        let bounds: Vec<u64> = std::iter::once(0).chain(points).chain(std::iter::once(duration)).collect();
+        let mut report = progress_reporter(&task_app, &source);
+        let parts = written.len() as f64;
+        for (index, (span, output)) in bounds.windows(2).zip(&written).enumerate() {
+            let mut part_progress = |fraction: f64| report((index as f64 + fraction) / parts);
+            cut(&task_app, &source, span[0], span[1], output, &mut part_progress)?;
+        }
+        Ok::<_, String>(())
+    })
+    .await
+    .map_err(|e| format!("Split task failed: {}", e))??;
+
+    Ok(outputs.iter().map(|p| p.to_string_lossy().to_string()).collect())
+}
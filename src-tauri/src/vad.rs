@@ -0,0 +1,113 @@
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use std::sync::Mutex;
+use tauri::Manager;
+
+use crate::error::AppError;
+use crate::{audio, paths};
+
+/// Energy-based voice activity thresholds
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct VadOptions {
+    pub enabled: bool,
+    /// RMS level (0.0 - 1.0 of full scale) a frame must exceed to count as speech
+    pub energy_threshold: f32,
+    /// Percentage of frames that must be active for the chunk to be transcribed
+    pub min_active_percent: f32,
+    pub frame_ms: u32,
+}
+
+impl Default for VadOptions {
+    fn default() -> Self {
+        VadOptions {
+            enabled: true,
+            energy_threshold: 0.01,
+            min_active_percent: 5.0,
+            frame_ms: 100,
+        }
+    }
+}
+
+pub struct VadState {
+    pub options: Mutex<VadOptions>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct SilenceReport {
+    pub silent: bool,
+    pub frames: usize,
+    pub active_frames: usize,
+    pub active_percent: f32,
+    pub mean_rms: f32,
+    pub peak_rms: f32,
+    pub duration_secs: f64,
+}
+
+/// Compute per-frame RMS energy of a 16-bit PCM WAV and decide whether it's effectively silent
+pub fn analyze_silence(path: &Path, options: &VadOptions) -> Result<SilenceReport, String> {
+    let (samples, info) = audio::read_pcm_samples(path)?;
+    let frame_len = ((info.sample_rate as u64 * options.frame_ms.max(1) as u64) / 1000).max(1) as usize;
+
+    let mut frames = 0;
+    let mut active_frames = 0;
+    let mut peak_rms = 0.0f32;
+    let mut energy = 0.0f32;
+    for frame in samples.chunks(frame_len) {
+        let frame_energy = frame.iter().map(|s| s * s).sum::<f32>();
+        let rms = (frame_energy / frame.len() as f32).sqrt();
+        frames += 1;
+        if rms > options.energy_threshold {
+            active_frames += 1;
+        }
+        peak_rms = peak_rms.max(rms);
+        energy += frame_energy;
+    }
+
+    let active_percent = if frames == 0 { 0.0 } else { active_frames as f32 / frames as f32 * 100.0 };
+    Ok(SilenceReport {
+        silent: active_percent < options.min_active_percent,
+        frames,
+        active_frames,
+        active_percent,
+        mean_rms: if samples.is_empty() { 0.0 } else { (energy / samples.len() as f32).sqrt() },
+        peak_rms,
+        duration_secs: samples.len() as f64 / info.sample_rate.max(1) as f64,
+    })
+}
+
+/// True when VAD is enabled and the chunk has too little speech to be worth transcribing.
+/// Unreadable chunks are never treated as silent so whisper still gets a chance at them.
+pub fn should_skip_chunk(app: &tauri::AppHandle, path: &Path) -> bool {
+    let options = app.state::<VadState>().options.lock().unwrap().clone();
+    if !options.enabled {
+        return false;
+    }
+    analyze_silence(path, &options).map(|r| r.silent).unwrap_or(false)
+}
+
+/// Update the thresholds used to skip silent live chunks
+#[tauri::command]
+pub async fn set_vad_options(
+    state: tauri::State<'_, VadState>,
+    options: VadOptions,
+) -> Result<VadOptions, String> {
+    let mut options = options;
+    options.energy_threshold = options.energy_threshold.clamp(0.0, 1.0);
+    options.min_active_percent = options.min_active_percent.clamp(0.0, 100.0);
+    options.frame_ms = options.frame_ms.clamp(10, 1000);
+    *state.options.lock().unwrap() = options.clone();
+    Ok(options)
+}
+
+/// Report how much of an audio file is above the VAD energy threshold
+#[tauri::command]
+pub async fn analyze_audio_silence(
+    app: tauri::AppHandle,
+    state: tauri::State<'_, VadState>,
+    path: String,
+) -> Result<SilenceReport, AppError> {
+    let path = paths::existing(&app, &path)?.to_string_lossy().to_string();
+    let options = state.options.lock().unwrap().clone();
+    let prepared = audio::prepare_audio_for_transcription(&app, &path).await?;
+    Ok(analyze_silence(&prepared.path, &options)?)
+}
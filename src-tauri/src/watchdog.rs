@@ -0,0 +1,164 @@
+use serde::Serialize;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+use tauri::Manager;
+
+use crate::error::AppError;
+use crate::{audio, events, processes, recorder, settings, RecorderHandle, RecorderState};
+
+const POLL_INTERVAL: Duration = Duration::from_secs(3);
+
+/// A recorder whose file hasn't grown for this long has stalled, even if it's still running
+const STALL_AFTER: Duration = Duration::from_secs(10);
+
+/// Restarts per recording before the watchdog gives up on a device that keeps failing
+const MAX_RESTARTS: usize = 3;
+
+/// Sent as `recording-failed` when a system recording's recorder dies or stalls
+#[derive(Serialize, Clone)]
+struct RecordingFailed {
+    /// The recording, as returned by start_system_recording
+    path: String,
+    reason: String,
+    stderr: String,
+    /// The file the recording carries on in, when the recorder was restarted
+    continued_in: Option<String>,
+    restart_error: Option<String>,
+}
+
+/// File a restarted recorder writes: sys-recording-<ts>-part2.wav and so on
+fn part_path(first: &Path, part: usize) -> PathBuf {
+    let stem = first.file_stem().map(|s| s.to_string_lossy().to_string()).unwrap_or_default();
+    first.with_file_name(format!("{}-part{}.wav", stem, part))
+}
+
+enum Health {
+    /// The recording was stopped, so there's nothing left to watch
+    Stopped,
+    Recording,
+    Failed(String),
+}
+
+/// Check on the recorder writing the current part, updating the growth tracking
+fn check(app: &tauri::AppHandle, recording: &Path, last_size: &mut u64, last_growth: &mut Instant) -> Health {
+    let state = app.state::<RecorderState>();
+    let mut current = state.current.lock();
+    let Some(proc) = current.as_mut().filter(|p| p.path == recording) else { return Health::Stopped };
+    let RecorderHandle::Process(child) = &mut proc.handle else { return Health::Stopped };
+    if let Ok(Some(status)) = child.try_wait() {
+        return Health::Failed(format!("The {} recorder exited ({})", proc.backend.as_str(), status));
+    }
+    let size = proc.parts.last().and_then(|part| fs::metadata(part).ok()).map(|m| m.len()).unwrap_or(0);
+    if size > *last_size {
+        *last_size = size;
+        *last_growth = Instant::now();
+    } else if last_growth.elapsed() > STALL_AFTER {
+        return Health::Failed(format!("The recording file hasn't grown for {}s", last_growth.elapsed().as_secs()));
+    }
+    Health::Recording
+}
+
+/// Stop the failed recorder and start a new one on the next part file. Blocking. The old recorder is
+/// taken out of the state and stopped without holding its lock, so a stop arriving meanwhile doesn't
+/// wait on it; finding the recording gone afterwards, the watchdog doesn't start another.
+fn restart(app: &tauri::AppHandle, recording: &Path) -> Result<PathBuf, AppError> {
+    let state = app.state::<RecorderState>();
+    let (mut child, backend, part) = {
+        let mut current = state.current.lock();
+        let proc = current
+            .as_mut()
+            .filter(|p| p.path == recording)
+            .ok_or("The recording was stopped")?;
+        let child = match std::mem::replace(&mut proc.handle, RecorderHandle::Restarting) {
+            RecorderHandle::Process(child) => child,
+            other => {
+                proc.handle = other;
+                return Err("Only external recorders are restarted".into());
+            }
+        };
+        (child, proc.backend, proc.parts.last().cloned())
+    };
+    // Harmless if it already exited; a stalled one gets the usual chance to finish its header
+    recorder::stop_process(&mut child, backend);
+    app.state::<processes::ProcessRegistry>().unregister(child.id());
+    if let Some(part) = part {
+        if let Err(e) = audio::repair_wav_header(&part) {
+            log::warn!("Couldn't repair {} after its recorder failed: {}", part.display(), e);
+        }
+    }
+
+    let mut current = state.current.lock();
+    let proc = current
+        .as_mut()
+        .filter(|p| p.path == recording && matches!(p.handle, RecorderHandle::Restarting))
+        .ok_or("The recording was stopped")?;
+    let next = part_path(&proc.path, proc.parts.len() + 1);
+    let mut replacement = processes::spawn(app, proc.plan.command(&next, None)?.stderr(std::process::Stdio::piped()))
+        .map_err(|e| format!("Failed to restart the {} recorder: {}", proc.backend.as_str(), e))?;
+    proc.stderr = Some(recorder::StderrTail::capture(&mut replacement));
+    proc.handle = RecorderHandle::Process(replacement);
+    proc.parts.push(next.clone());
+    Ok(next)
+}
+
+/// Watch an external system recorder for exiting or its file no longer growing, and report it as
+/// `recording-failed`. With restart_failed_recorder on, recording carries on in a new part file.
+/// Native recordings are written in-process and aren't watched.
+pub fn start(app: &tauri::AppHandle, recording: PathBuf) {
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        let mut last_size = 0u64;
+        let mut last_growth = Instant::now();
+        let mut restarts = 0;
+        loop {
+            tokio::time::sleep(POLL_INTERVAL).await;
+            let reason = match check(&app, &recording, &mut last_size, &mut last_growth) {
+                Health::Stopped => return,
+                Health::Recording => continue,
+                Health::Failed(reason) => reason,
+            };
+            let stderr = app
+                .state::<RecorderState>()
+                .current
+                .lock()
+                .as_ref()
+                .and_then(|p| p.stderr.clone())
+                .map(|s| s.text())
+                .unwrap_or_default();
+            log::error!("System recording {} failed: {}: {}", recording.display(), reason, stderr);
+
+            let restart_wanted = settings::current(&app).restart_failed_recorder && restarts < MAX_RESTARTS;
+            let (continued_in, restart_error) = if restart_wanted {
+                restarts += 1;
+                let (app_for_restart, path) = (app.clone(), recording.clone());
+                let restarted = tauri::async_runtime::spawn_blocking(move || restart(&app_for_restart, &path))
+                    .await
+                    .unwrap_or_else(|e| Err(format!("Restart task failed: {}", e).into()));
+                match restarted {
+                    Ok(next) => {
+                        log::info!("Recording continues in {}", next.display());
+                        (Some(next.to_string_lossy().to_string()), None)
+                    }
+                    Err(e) => (None, Some(e.to_string())),
+                }
+            } else {
+                (None, None)
+            };
+
+            let restarted = continued_in.is_some();
+            events::emit(&app, "recording-failed", RecordingFailed {
+                path: recording.to_string_lossy().to_string(),
+                reason,
+                stderr,
+                continued_in,
+                restart_error,
+            });
+            if !restarted {
+                return;
+            }
+            last_size = 0;
+            last_growth = Instant::now();
+        }
+    });
+}
@@ -0,0 +1,648 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::io::{BufRead, BufReader, Read};
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use std::sync::Mutex;
+use std::time::SystemTime;
+use tauri::{Emitter, Manager};
+
+use crate::denoise::{self, NoiseReduction};
+use crate::error::AppError;
+use crate::settings::TranscriptionBackend;
+use crate::telemetry::{self, ProcessMonitor, RunStats};
+use crate::{audio, binaries, gpu, jobs, live_queue, models, resolve_whisper_binary, settings, transcript};
+
+/// Upper bound whisper.cpp accepts sensibly for beam search / best-of sampling
+const MAX_BEAM_SIZE: u32 = 8;
+
+/// Decoder tuning for whisper-cli; unset fields use whisper's defaults
+#[derive(Serialize, Deserialize, Clone, Default, Debug)]
+pub struct TranscriptionOptions {
+    pub threads: Option<usize>,
+    pub beam_size: Option<u32>,
+    pub best_of: Option<u32>,
+    pub entropy_threshold: Option<f32>,
+    /// Strip non-speech annotations, repeat loops, and known hallucinated phrases from output
+    pub filter_hallucinations: Option<bool>,
+    /// Denoise a temp copy before transcribing; unset uses the noise_reduction setting
+    pub noise_reduction: Option<NoiseReduction>,
+}
+
+impl TranscriptionOptions {
+    /// Clamp out-of-range values, returning a warning for each adjustment made
+    pub fn sanitize(&mut self) -> Vec<String> {
+        let mut warnings = Vec::new();
+        let max_threads = available_threads();
+
+        if let Some(t) = self.threads {
+            let clamped = t.clamp(1, max_threads);
+            if clamped != t {
+                warnings.push(format!("threads {} out of range, using {}", t, clamped));
+                self.threads = Some(clamped);
+            }
+        }
+        if let Some(b) = self.beam_size {
+            let clamped = b.clamp(1, MAX_BEAM_SIZE);
+            if clamped != b {
+                warnings.push(format!("beam_size {} out of range, using {}", b, clamped));
+                self.beam_size = Some(clamped);
+            }
+        }
+        if let Some(b) = self.best_of {
+            let clamped = b.clamp(1, MAX_BEAM_SIZE);
+            if clamped != b {
+                warnings.push(format!("best_of {} out of range, using {}", b, clamped));
+                self.best_of = Some(clamped);
+            }
+        }
+        if let Some(e) = self.entropy_threshold {
+            let clamped = if e.is_finite() { e.clamp(0.0, 10.0) } else { 2.4 };
+            if clamped != e {
+                warnings.push(format!("entropy_threshold {} out of range, using {}", e, clamped));
+                self.entropy_threshold = Some(clamped);
+            }
+        }
+
+        warnings
+    }
+}
+
+/// The per-call options if given, else the managed defaults, with invalid values clamped
+pub fn effective_options(app: &tauri::AppHandle, params: &WhisperParams) -> TranscriptionOptions {
+    let mut options = match &params.options {
+        Some(options) => options.clone(),
+        None => app.state::<TranscriptionOptionsState>().options.lock().unwrap().clone(),
+    };
+    let settings = settings::current(app);
+    if options.threads.is_none() {
+        options.threads = settings.threads;
+    }
+    if options.noise_reduction.is_none() {
+        options.noise_reduction = Some(settings.noise_reduction);
+    }
+    options.sanitize();
+    options
+}
+
+fn available_threads() -> usize {
+    std::thread::available_parallelism().map(|p| p.get()).unwrap_or(2)
+}
+
+// Default decoder options used whenever a call doesn't pass its own (e.g. live chunks)
+pub struct TranscriptionOptionsState {
+    pub options: Mutex<TranscriptionOptions>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct TranscriptionOptionsAck {
+    pub options: TranscriptionOptions,
+    pub warnings: Vec<String>,
+}
+
+/// Store default decoder options for all subsequent transcriptions, clamping invalid values
+#[tauri::command]
+pub async fn set_transcription_options(
+    state: tauri::State<'_, TranscriptionOptionsState>,
+    options: TranscriptionOptions,
+) -> Result<TranscriptionOptionsAck, String> {
+    let mut options = options;
+    let warnings = options.sanitize();
+    *state.options.lock().unwrap() = options.clone();
+    Ok(TranscriptionOptionsAck { options, warnings })
+}
+
+/// Per-call whisper-cli settings shared by batch and live transcription
+#[derive(Clone, Default)]
+pub struct WhisperParams {
+    pub model: Option<String>,
+    pub language: Option<String>,
+    pub translate: bool,
+    /// Vocabulary / context passed to --prompt to bias decoding
+    pub initial_prompt: Option<String>,
+    /// Overrides the managed TranscriptionOptionsState for this call
+    pub options: Option<TranscriptionOptions>,
+    /// Load the model even if it looks too big for available memory
+    pub force: bool,
+    /// Mark speaker turns with a tinydiarize model (--tinydiarize)
+    pub diarize: bool,
+}
+
+/// How many words of the previous live chunk to carry over as prompt context
+const CONTEXT_TAIL_WORDS: usize = 32;
+
+impl WhisperParams {
+    /// Fill an omitted model or language from the saved settings
+    pub fn with_settings(mut self, app: &tauri::AppHandle) -> WhisperParams {
+        let settings = settings::current(app);
+        if self.model.is_none() {
+            self.model = settings.model;
+        }
+        if self.language.is_none() {
+            self.language = normalize_language(settings.language);
+        }
+        self
+    }
+
+    /// Copy of these params whose prompt also carries the tail of the previous chunk's text,
+    /// so sentences split across chunk boundaries decode coherently
+    pub fn with_context(&self, previous: Option<&str>) -> WhisperParams {
+        let mut params = self.clone();
+        let Some(previous) = previous else {
+            return params;
+        };
+        let words: Vec<&str> = previous.split_whitespace().collect();
+        if words.is_empty() {
+            return params;
+        }
+        let tail = words[words.len().saturating_sub(CONTEXT_TAIL_WORDS)..].join(" ");
+        params.initial_prompt = Some(match params.initial_prompt.take() {
+            Some(vocab) => format!("{} {}", vocab, tail),
+            None => tail,
+        });
+        params
+    }
+
+    /// Whether the requested language or translation needs a multilingual (non-.en) model
+    pub fn needs_multilingual(&self) -> bool {
+        self.translate || self.language.as_deref().is_some_and(|l| l != "en")
+    }
+}
+
+/// GPU backends a whisper-cli build was compiled with, from its ggml strings and what it logs while printing --help
+#[derive(Clone, Default)]
+struct GpuSupport {
+    backends: Vec<&'static str>,
+    /// Whether the build accepts --no-gpu
+    no_gpu_flag: bool,
+}
+
+// GpuSupport probed per whisper-cli path, invalidated when the file's mtime changes
+pub struct GpuSupportCache {
+    entries: Mutex<HashMap<PathBuf, (SystemTime, GpuSupport)>>,
+}
+
+impl GpuSupportCache {
+    pub fn new() -> Self {
+        GpuSupportCache { entries: Mutex::new(HashMap::new()) }
+    }
+}
+
+/// Backend name as it appears in ggml's init logs ("ggml_cuda_init: found 1 CUDA devices"), and ours
+const GPU_BACKEND_MARKERS: &[(&str, &str)] = &[("cuda", "cuda"), ("vulkan", "vulkan"), ("metal", "metal")];
+
+/// Strings only a ggml build with that backend contains (its function names, which its log lines start with), and ours
+const GPU_BINARY_MARKERS: &[(&[u8], &str)] = &[(b"ggml_cuda", "cuda"), (b"ggml_vulkan", "vulkan"), (b"ggml_metal", "metal")];
+
+/// Bytes read at a time while scanning a binary for GPU_BINARY_MARKERS
+const SCAN_CHUNK_BYTES: usize = 1 << 20;
+
+/// Which GPU_BINARY_MARKERS backends appear in a file, read in chunks since CUDA builds run to hundreds of MB
+fn scan_for_backends(path: &Path, found: &mut Vec<&'static str>) {
+    let Ok(mut file) = std::fs::File::open(path) else {
+        return;
+    };
+    let keep = GPU_BINARY_MARKERS.iter().map(|(marker, _)| marker.len()).max().unwrap_or(0) - 1;
+    let mut buf = Vec::with_capacity(SCAN_CHUNK_BYTES + keep);
+    let mut chunk = vec![0u8; SCAN_CHUNK_BYTES];
+    loop {
+        let n = match file.read(&mut chunk) {
+            Ok(0) | Err(_) => return,
+            Ok(n) => n,
+        };
+        buf.extend_from_slice(&chunk[..n]);
+        for (marker, backend) in GPU_BINARY_MARKERS {
+            if !found.contains(backend) && buf.windows(marker.len()).any(|w| w == *marker) {
+                found.push(backend);
+            }
+        }
+        // A marker split across reads is found once the rest arrives
+        let tail = buf.len().saturating_sub(keep);
+        buf.drain(..tail);
+    }
+}
+
+/// GPU backends compiled into whisper-cli or the ggml libraries installed beside it. A static build only
+/// initialises its backends once it loads a model, so --help alone can't tell.
+fn compiled_backends(binary: &Path) -> Vec<&'static str> {
+    let mut found = Vec::new();
+    scan_for_backends(binary, &mut found);
+    let siblings = binary.parent().and_then(|dir| std::fs::read_dir(dir).ok());
+    for entry in siblings.into_iter().flatten().flatten() {
+        let name = entry.file_name().to_string_lossy().to_lowercase();
+        if name.starts_with("libggml") || (name.starts_with("ggml") && name.ends_with(".dll")) {
+            scan_for_backends(&entry.path(), &mut found);
+        }
+    }
+    found
+}
+
+/// A backend counts as supported if it's compiled in or ggml mentions it while printing `help`,
+/// and none of the lines mentioning it report a failure
+fn parse_gpu_support(help: &str, compiled: &[&'static str]) -> GpuSupport {
+    let lines: Vec<String> = help.lines().map(str::to_lowercase).collect();
+    let backends = GPU_BACKEND_MARKERS
+        .iter()
+        .filter(|(marker, backend)| {
+            let mentions: Vec<&String> = lines.iter().filter(|l| l.contains(marker)).collect();
+            (compiled.contains(backend) || !mentions.is_empty())
+                && !mentions.iter().any(|l| l.contains("fail") || l.contains("no devices") || l.contains("found 0"))
+        })
+        .map(|(_, backend)| *backend)
+        .collect();
+    GpuSupport { backends, no_gpu_flag: help.contains("--no-gpu") }
+}
+
+fn gpu_support(app: &tauri::AppHandle, binary: &Path) -> GpuSupport {
+    let Ok(mtime) = std::fs::metadata(binary).and_then(|m| m.modified()) else {
+        return GpuSupport::default();
+    };
+    let cache = app.state::<GpuSupportCache>();
+    if let Some((cached_mtime, support)) = cache.entries.lock().unwrap().get(binary) {
+        if *cached_mtime == mtime {
+            return support.clone();
+        }
+    }
+    let help = binaries::run_with_timeout(binary, "--help").unwrap_or_default();
+    let support = parse_gpu_support(&help, &compiled_backends(binary));
+    log::info!("{} supports GPU backends: {:?}", binary.display(), support.backends);
+    cache.entries.lock().unwrap().insert(binary.to_path_buf(), (mtime, support.clone()));
+    support
+}
+
+/// The backend a run will use: "cpu", "cuda", "vulkan" or "metal"
+pub struct BackendChoice {
+    pub backend: &'static str,
+    /// Why the configured backend couldn't be used, when the run ends up on another
+    pub fallback: Option<String>,
+    /// Pass --no-gpu, so a build with a GPU backend we didn't pick still runs on the CPU we report
+    no_gpu: bool,
+}
+
+/// Match the transcription_backend setting against what the whisper-cli build supports
+fn choose_backend(app: &tauri::AppHandle, binary: &Path) -> BackendChoice {
+    let support = gpu_support(app, binary);
+    let setting = settings::current(app).transcription_backend;
+    let requested = match setting {
+        TranscriptionBackend::Cpu => None,
+        TranscriptionBackend::Vulkan => Some("vulkan"),
+        TranscriptionBackend::Cuda => Some("cuda"),
+        TranscriptionBackend::Auto => Some(gpu::recommended()).filter(|b| ["cuda", "vulkan", "metal"].contains(b)),
+    };
+    let fallback = match requested {
+        Some(backend) if support.backends.contains(&backend) => {
+            return BackendChoice { backend, fallback: None, no_gpu: false };
+        }
+        Some(backend) => Some(format!("{} isn't built with {} support; transcribing on CPU", binary.display(), backend)),
+        None => None,
+    };
+    // Without --no-gpu a GPU build picks its own backend, so report that rather than CPU
+    if let (false, Some(&backend)) = (support.no_gpu_flag, support.backends.first()) {
+        let reason = format!("{} has no --no-gpu flag; transcribing on {}", binary.display(), backend);
+        return BackendChoice { backend, fallback: Some(reason), no_gpu: false };
+    }
+    BackendChoice { backend: "cpu", fallback, no_gpu: support.no_gpu_flag }
+}
+
+/// choose_backend off the async runtime, since probing a new whisper-cli runs it and scans its files
+async fn choose_backend_blocking(app: &tauri::AppHandle, binary: &Path) -> BackendChoice {
+    let (app, binary) = (app.clone(), binary.to_path_buf());
+    tauri::async_runtime::spawn_blocking(move || choose_backend(&app, &binary))
+        .await
+        .unwrap_or(BackendChoice { backend: "cpu", fallback: None, no_gpu: false })
+}
+
+/// Resolve the backend for a user-started transcription, warning the frontend if it isn't the configured one
+pub async fn backend_for_run(app: &tauri::AppHandle, audio_path: &str) -> &'static str {
+    let Ok(binary) = resolve_whisper_binary(app) else {
+        return "cpu";
+    };
+    let choice = choose_backend_blocking(app, &binary).await;
+    if let Some(reason) = &choice.fallback {
+        log::warn!("{}", reason);
+        let _ = app.emit("transcription-backend-fallback", serde_json::json!({
+            "path": audio_path,
+            "backend": choice.backend,
+            "reason": reason,
+        }));
+    }
+    choice.backend
+}
+
+/// What whisper-cli should print (and write) for a run
+pub enum OutputMode {
+    /// Plain text only (--no-timestamps)
+    Plain,
+    /// "[start --> end]  text" segment lines on stdout
+    Timestamped,
+    /// Timestamped stdout plus a full JSON file (tokens + probabilities) at `<output_base>.json`
+    JsonFull { output_base: std::path::PathBuf },
+}
+
+/// Raw whisper-cli output plus metadata parsed from its logs
+pub struct WhisperOutput {
+    pub stdout: String,
+    pub detected_language: Option<String>,
+    pub stats: RunStats,
+}
+
+/// Treat empty language strings as unset and normalize case ("Auto" -> "auto")
+pub fn normalize_language(language: Option<String>) -> Option<String> {
+    language
+        .map(|l| l.trim().to_lowercase())
+        .filter(|l| !l.is_empty())
+}
+
+/// Treat empty or whitespace-only prompts as unset
+pub fn normalize_prompt(prompt: Option<String>) -> Option<String> {
+    prompt
+        .map(|p| p.trim().to_string())
+        .filter(|p| !p.is_empty())
+}
+
+/// Pull the language code out of whisper's "auto-detected language: es (p = 0.97)" log line
+fn parse_detected_language(stderr: &str) -> Option<String> {
+    stderr.lines().find_map(|line| {
+        let (_, rest) = line.split_once("auto-detected language:")?;
+        rest.split_whitespace().next().map(|code| code.to_string())
+    })
+}
+
+/// Parse whisper's "whisper_print_progress_callback: progress =  42%" line
+fn parse_progress_percent(line: &str) -> Option<f32> {
+    let (_, rest) = line.split_once("progress =")?;
+    rest.trim().trim_end_matches('%').trim().parse().ok()
+}
+
+/// Decode as much valid UTF-8 as possible, leaving a split multi-byte sequence in the buffer
+pub fn drain_utf8(buf: &mut Vec<u8>) -> String {
+    let valid = match std::str::from_utf8(buf) {
+        Ok(s) => s.len(),
+        Err(e) if e.error_len().is_none() => e.valid_up_to(),
+        // Genuinely invalid bytes: decode lossily rather than stalling forever
+        Err(_) => buf.len(),
+    };
+    let text = String::from_utf8_lossy(&buf[..valid]).to_string();
+    buf.drain(..valid);
+    text
+}
+
+/// What the stdout reader has seen so far, used to emit progress and partial text
+struct ProgressEmitter {
+    app: tauri::AppHandle,
+    audio_path: String,
+    duration_ms: Option<u64>,
+    last_percent: f32,
+}
+
+impl ProgressEmitter {
+    fn progress(&mut self, timestamp_ms: u64) {
+        let percent = self.duration_ms
+            .filter(|total| *total > 0)
+            .map(|total| (timestamp_ms as f32 / total as f32 * 100.0).min(100.0))
+            .unwrap_or(self.last_percent);
+        // Segments can end slightly out of order; don't let the bar move backwards
+        self.last_percent = self.last_percent.max(percent);
+        let _ = self.app.emit("transcribe-progress", serde_json::json!({
+            "path": self.audio_path,
+            "timestamp_ms": timestamp_ms,
+            "duration_ms": self.duration_ms,
+            "percent": self.last_percent,
+        }));
+    }
+
+    fn partial(&self, text: &str) {
+        let _ = self.app.emit("transcribe-partial", serde_json::json!({
+            "path": self.audio_path,
+            "text": text,
+        }));
+    }
+}
+
+/// Run whisper-cli on a file and return its raw stdout (with segment timestamps if requested)
+pub async fn run_whisper(
+    app: &tauri::AppHandle,
+    audio_path: &str,
+    params: &WhisperParams,
+    mode: &OutputMode,
+    job_id: Option<&str>,
+) -> Result<WhisperOutput, AppError> {
+    // Verify file exists and has minimum size
+    let file_path = std::path::PathBuf::from(audio_path);
+    if !file_path.exists() {
+        return Err(format!("Audio file not found: {}", audio_path).into());
+    }
+
+    let file_size = std::fs::metadata(&file_path)
+        .map_err(|e| AppError::io("Failed to stat file", e))?
+        .len();
+
+    if file_size < 100 {
+        return Err(AppError::Other(format!("Audio file too small ({} bytes). Recording may have failed.", file_size)));
+    }
+
+    let whisper_path = resolve_whisper_binary(app)?;
+
+    let model_path = if params.diarize {
+        models::resolve_diarize_model(params.model.as_deref(), params.needs_multilingual())?
+    } else {
+        models::resolve_whisper_model(params.model.as_deref(), params.needs_multilingual())?
+    };
+    models::check_model_header(&model_path)?;
+    models::check_memory(&model_path, models::ModelKind::Whisper, params.force)?;
+    let _model_lease = models::ModelLease::acquire(app, &model_path);
+
+    // whisper-cli only reads 16 kHz mono WAV; anything else goes through ffmpeg into a temp copy
+    let prepared = audio::prepare_audio_for_transcription(app, audio_path).await?;
+    let duration_ms = audio::read_wav_info(&prepared.path)
+        .ok()
+        .map(|info| (info.duration_secs() * 1000.0) as u64);
+
+    let options = effective_options(app, params);
+    // Off, or when ffmpeg can't, leaves whisper reading the prepared copy
+    let denoised = denoise::prepare(app, &prepared.path, options.noise_reduction.unwrap_or_default()).await;
+    let input_path = denoised.as_ref().map_or(&prepared.path, |d| &d.path);
+    let num_threads = options.threads.unwrap_or_else(available_threads);
+
+    let backend = choose_backend_blocking(app, &whisper_path).await;
+
+    log::info!("Transcribing {} with {} on {}", audio_path, model_path.display(), backend.backend);
+    let started = std::time::Instant::now();
+    let mut cmd = binaries::command(&whisper_path);
+    cmd.arg("-m")
+        .arg(&model_path)
+        .arg("-f")
+        .arg(input_path)
+        .arg("-t")
+        .arg(num_threads.to_string())
+        .arg("--print-progress");
+    // GPU builds use the GPU by default, so every run we put on the CPU needs the flag
+    if backend.no_gpu {
+        cmd.arg("--no-gpu");
+    }
+    match mode {
+        OutputMode::Plain => {
+            cmd.arg("--no-timestamps");
+        }
+        OutputMode::Timestamped => {}
+        OutputMode::JsonFull { output_base } => {
+            cmd.arg("--output-json-full").arg("--output-file").arg(output_base);
+        }
+    }
+    let timestamps = !matches!(mode, OutputMode::Plain);
+    if let Some(lang) = &params.language {
+        cmd.arg("-l").arg(lang);
+    }
+    if params.translate {
+        cmd.arg("--translate");
+    }
+    if let Some(prompt) = &params.initial_prompt {
+        cmd.arg("--prompt").arg(prompt);
+    }
+    if params.diarize {
+        cmd.arg("--tinydiarize");
+    }
+    if let Some(beam_size) = options.beam_size {
+        cmd.arg("--beam-size").arg(beam_size.to_string());
+    }
+    if let Some(best_of) = options.best_of {
+        cmd.arg("--best-of").arg(best_of.to_string());
+    }
+    if let Some(threshold) = options.entropy_threshold {
+        cmd.arg("--entropy-thold").arg(threshold.to_string());
+    }
+    let mut child = cmd
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| AppError::io("Failed to run whisper-cli", e))?;
+    let monitor = ProcessMonitor::start(child.id());
+    let pid = child.id();
+    live_queue::register_pid(app, pid);
+
+    // Register the child so cancel_transcription can kill it mid-run
+    if let Some(id) = job_id {
+        if !app.state::<jobs::TranscriptionJobState>().register_pid(id, child.id()) {
+            log::info!("Transcription job {} was cancelled as whisper started", id);
+        }
+    }
+
+    let stdout_pipe = child.stdout.take().ok_or("Failed to capture whisper stdout")?;
+    let stderr_pipe = child.stderr.take().ok_or("Failed to capture whisper stderr")?;
+    let mut emitter = ProgressEmitter {
+        app: app.clone(),
+        audio_path: audio_path.to_string(),
+        duration_ms,
+        last_percent: 0.0,
+    };
+
+    let stats_model = model_path.clone();
+    let (stdout, stderr, status, stats) = tauri::async_runtime::spawn_blocking(move || {
+        // Progress lines arrive on stderr; read them on their own thread so neither pipe fills up
+        let stderr_app = emitter.app.clone();
+        let stderr_path = emitter.audio_path.clone();
+        let total_ms = emitter.duration_ms;
+        let stderr_reader = std::thread::spawn(move || {
+            let mut collected = String::new();
+            for line in BufReader::new(stderr_pipe).lines().map_while(Result::ok) {
+                if let Some(percent) = parse_progress_percent(&line) {
+                    let _ = stderr_app.emit("transcribe-progress", serde_json::json!({
+                        "path": stderr_path,
+                        "timestamp_ms": total_ms.map(|t| (t as f32 * percent / 100.0) as u64),
+                        "duration_ms": total_ms,
+                        "percent": percent,
+                    }));
+                }
+                collected.push_str(&line);
+                collected.push('\n');
+            }
+            collected
+        });
+
+        let mut stdout = String::new();
+        let mut pending = Vec::new();
+        let mut line_buf = String::new();
+        let mut reader = stdout_pipe;
+        let mut chunk = [0u8; 4096];
+        loop {
+            let n = match reader.read(&mut chunk) {
+                Ok(0) | Err(_) => break,
+                Ok(n) => n,
+            };
+            pending.extend_from_slice(&chunk[..n]);
+            let text = drain_utf8(&mut pending);
+            if text.is_empty() {
+                continue;
+            }
+            stdout.push_str(&text);
+
+            if !timestamps {
+                // --no-timestamps prints segment text without newlines, so stream it as-is
+                emitter.partial(&text);
+                continue;
+            }
+
+            line_buf.push_str(&text);
+            while let Some(pos) = line_buf.find('\n') {
+                let line: String = line_buf.drain(..=pos).collect();
+                let segments = transcript::parse_whisper_segments(&line);
+                if let Some(seg) = segments.last() {
+                    emitter.progress(seg.end_ms);
+                    emitter.partial(&seg.text);
+                }
+            }
+        }
+
+        if !pending.is_empty() {
+            stdout.push_str(&String::from_utf8_lossy(&pending));
+        }
+
+        let status = child.wait();
+        let stats = monitor.finish("transcription", &stats_model, total_ms.map(|ms| ms as f64 / 1000.0));
+        let stderr = stderr_reader.join().unwrap_or_default();
+        (stdout, stderr, status, stats)
+    })
+    .await
+    .map_err(|e| format!("Whisper task failed: {}", e))?;
+    live_queue::unregister_pid(app, pid);
+
+    let status = status.map_err(|e| AppError::io("Failed to run whisper-cli", e))?;
+
+    if let Some(id) = job_id {
+        let jobs = app.state::<jobs::TranscriptionJobState>();
+        jobs.unregister_pid(id);
+        if jobs.is_cancelled(id) {
+            return Err(AppError::Cancelled("Transcription cancelled".to_string()));
+        }
+    }
+
+    if !status.success() {
+        // Progress lines are noise in an error message
+        let stderr: String = stderr
+            .lines()
+            .filter(|l| parse_progress_percent(l).is_none())
+            .collect::<Vec<_>>()
+            .join("\n");
+        let msg = if !stderr.is_empty() {
+            stderr.clone()
+        } else if !stdout.is_empty() {
+            stdout
+        } else {
+            format!("Unknown error (exit code: {:?})", status.code())
+        };
+        log::error!("whisper-cli failed on {} ({}): {}", audio_path, status, msg);
+        return Err(AppError::ProcessFailed {
+            cmd: whisper_path.display().to_string(),
+            stderr,
+            message: format!("Whisper failed: {}", msg),
+        });
+    }
+
+    log::info!("Transcribed {} in {:.1}s", audio_path, started.elapsed().as_secs_f32());
+    telemetry::record(&stats);
+    Ok(WhisperOutput {
+        stdout,
+        detected_language: parse_detected_language(&stderr),
+        stats,
+    })
+}
@@ -0,0 +1,290 @@
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io::{BufRead, BufReader};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use tauri::{Emitter, Manager};
+
+use crate::error::AppError;
+use crate::{
+    archive, downloads, emit_progress, get_binaries_dir, get_cache_dir, get_config_dir, processes, recorder,
+    WhisperState,
+};
+
+/// whisper.cpp release built by build_whisper_from_source, matching the prebuilt downloads
+const WHISPER_VERSION: &str = "1.8.2";
+
+const SOURCE_URL: &str = "https://github.com/ggml-org/whisper.cpp/archive/refs/tags";
+
+const SOURCE_CONFIG_FILE: &str = "whisper-download.json";
+
+/// Tools the cmake build needs, with the command that proves each one is installed
+const BUILD_TOOLS: &[(&str, &[&str])] = &[
+    ("cmake", &["cmake"]),
+    ("make", &["make", "ninja"]),
+    ("C compiler (gcc or clang)", &["cc", "gcc", "clang"]),
+    ("C++ compiler (g++ or clang++)", &["c++", "g++", "clang++"]),
+];
+
+/// Where download_whisper fetches a prebuilt Linux whisper-cli from; there are no official Linux builds
+#[derive(Serialize, Deserialize, Clone, Default, Debug)]
+pub struct WhisperSource {
+    pub url: Option<String>,
+    pub sha256: Option<String>,
+}
+
+/// Payload of `whisper-build-progress`
+#[derive(Serialize, Clone)]
+struct BuildProgress {
+    stage: &'static str,
+    line: String,
+}
+
+fn load_source() -> WhisperSource {
+    get_config_dir()
+        .ok()
+        .and_then(|dir| fs::read_to_string(dir.join(SOURCE_CONFIG_FILE)).ok())
+        .and_then(|raw| serde_json::from_str(&raw).ok())
+        .unwrap_or_default()
+}
+
+fn exe_name() -> &'static str {
+    if cfg!(target_os = "windows") { "whisper-cli.exe" } else { "whisper-cli" }
+}
+
+/// Copy a built or extracted whisper-cli to the top of the binaries dir and make it executable
+fn install_binary(app: &tauri::AppHandle, binary: &Path, binaries_dir: &Path) -> Result<PathBuf, String> {
+    let dest = binaries_dir.join(exe_name());
+    if binary != dest {
+        fs::copy(binary, &dest).map_err(|e| format!("Failed to install whisper-cli: {}", e))?;
+    }
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let mut perms = fs::metadata(&dest)
+            .map_err(|e| format!("Failed to get permissions: {}", e))?
+            .permissions();
+        perms.set_mode(0o755);
+        fs::set_permissions(&dest, perms)
+            .map_err(|e| format!("Failed to set permissions: {}", e))?;
+    }
+
+    // A previously resolved whisper-cli elsewhere would otherwise keep being used
+    *app.state::<WhisperState>().resolved.lock().unwrap() = None;
+    Ok(dest)
+}
+
+fn is_shared_library(name: &str) -> bool {
+    name.ends_with(".dylib") || name.ends_with(".dll") || name.ends_with(".so") || name.contains(".so.")
+}
+
+/// Install the shared libraries anywhere under `dir` beside whisper-cli in the binaries dir,
+/// keeping version symlinks (libwhisper.so -> libwhisper.so.1) as links. Returns how many.
+fn install_libraries(dir: &Path, binaries_dir: &Path) -> Result<usize, String> {
+    let mut installed = 0;
+    for entry in fs::read_dir(dir).map_err(|e| format!("Failed to read {}: {}", dir.display(), e))?.flatten() {
+        let path = entry.path();
+        let file_type = entry.file_type().map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+        if file_type.is_dir() {
+            installed += install_libraries(&path, binaries_dir)?;
+            continue;
+        }
+        let name = entry.file_name();
+        if !is_shared_library(&name.to_string_lossy()) {
+            continue;
+        }
+        let dest = binaries_dir.join(&name);
+        let _ = fs::remove_file(&dest);
+        #[cfg(unix)]
+        if file_type.is_symlink() {
+            let target = fs::read_link(&path).map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+            std::os::unix::fs::symlink(&target, &dest)
+                .map_err(|e| format!("Failed to install {}: {}", name.to_string_lossy(), e))?;
+            installed += 1;
+            continue;
+        }
+        fs::copy(&path, &dest).map_err(|e| format!("Failed to install {}: {}", name.to_string_lossy(), e))?;
+        installed += 1;
+    }
+    Ok(installed)
+}
+
+/// Download the configured prebuilt Linux whisper-cli archive into the binaries dir
+pub async fn download_prebuilt(window: &tauri::Window) -> Result<String, AppError> {
+    let source = load_source();
+    let Some(url) = source.url.filter(|u| !u.trim().is_empty()) else {
+        return Err(
+            "No prebuilt whisper-cli is configured for Linux. Set one with set_whisper_download_source, \
+             or compile it with build_whisper_from_source."
+                .into(),
+        );
+    };
+
+    let head = downloads::http_client()?
+        .head(&url)
+        .send()
+        .await
+        .map_err(|e| format!("Download request failed: {}", e))?;
+    if head.status() == reqwest::StatusCode::NOT_FOUND {
+        return Err(format!(
+            "No prebuilt whisper-cli at {} (HTTP 404). Compile it instead with build_whisper_from_source.",
+            url
+        )
+        .into());
+    }
+
+    let binaries_dir = get_binaries_dir()?;
+    let archive_name = url.rsplit('/').next().filter(|n| !n.is_empty()).unwrap_or("whisper-cli-linux.tar.gz");
+    let archive_path = binaries_dir.join(archive_name);
+    let download = downloads::ActiveDownload::start(window, "whisper");
+    let (downloaded, total_size) =
+        downloads::fetch_verified(window, &download, &url, &archive_path, source.sha256.as_deref()).await?;
+
+    emit_progress(window, &download.id, downloaded, total_size, "Extracting...");
+    let extract_dir = binaries_dir.join("whisper-prebuilt");
+    let _ = fs::remove_dir_all(&extract_dir);
+    archive::extract_archive(&archive_path, &extract_dir)?;
+    fs::remove_file(&archive_path).ok();
+
+    let binary = archive::find_file(&extract_dir, exe_name(), 3)
+        .ok_or("whisper-cli wasn't found in the downloaded archive")?;
+    // Dynamically linked builds need their libwhisper/libggml beside them
+    let libraries = install_libraries(&extract_dir, &binaries_dir)?;
+    if libraries > 0 {
+        log::info!("Installed {} shared libraries with whisper-cli", libraries);
+    }
+    let installed = install_binary(window.app_handle(), &binary, &binaries_dir)?;
+    let _ = fs::remove_dir_all(&extract_dir);
+
+    emit_progress(window, &download.id, downloaded, total_size, "Complete!");
+    Ok(installed.to_string_lossy().to_string())
+}
+
+/// Set (or clear, with no url) the prebuilt whisper-cli archive download_whisper uses on Linux
+#[tauri::command]
+pub async fn set_whisper_download_source(url: Option<String>, sha256: Option<String>) -> Result<WhisperSource, String> {
+    let source = WhisperSource {
+        url: url.map(|u| u.trim().to_string()).filter(|u| !u.is_empty()),
+        sha256: sha256.map(|s| s.trim().to_lowercase()).filter(|s| !s.is_empty()),
+    };
+    if let Some(sha256) = &source.sha256 {
+        if sha256.len() != 64 || !sha256.chars().all(|c| c.is_ascii_hexdigit()) {
+            return Err(format!("'{}' is not a SHA-256 hex digest", sha256));
+        }
+    }
+    let json = serde_json::to_string_pretty(&source)
+        .map_err(|e| format!("Failed to serialize whisper download source: {}", e))?;
+    fs::write(get_config_dir()?.join(SOURCE_CONFIG_FILE), json)
+        .map_err(|e| format!("Failed to save whisper download source: {}", e))?;
+    Ok(source)
+}
+
+/// Build tools from BUILD_TOOLS that aren't installed
+fn missing_build_tools() -> Vec<&'static str> {
+    BUILD_TOOLS
+        .iter()
+        .filter(|(_, commands)| !commands.iter().any(|c| recorder::has_tool(c)))
+        .map(|(name, _)| *name)
+        .collect()
+}
+
+/// Run one build step, streaming each output line as `whisper-build-progress`
+fn run_step(app: &tauri::AppHandle, stage: &'static str, cmd: &mut Command) -> Result<(), String> {
+    let _ = app.emit("whisper-build-progress", BuildProgress { stage, line: format!("$ {:?}", cmd) });
+    let mut child = processes::spawn(app, cmd.stdout(Stdio::piped()).stderr(Stdio::piped()))
+        .map_err(|e| format!("Failed to start {}: {}", stage, e))?;
+
+    let stderr = child.stderr.take();
+    let stderr_app = app.clone();
+    // Keep the tail of stderr for the error message while it streams
+    let stderr_thread = std::thread::spawn(move || {
+        let mut tail: Vec<String> = Vec::new();
+        for line in stderr.map(BufReader::new).into_iter().flat_map(|r| r.lines().map_while(Result::ok)) {
+            let _ = stderr_app.emit("whisper-build-progress", BuildProgress { stage, line: line.clone() });
+            tail.push(line);
+            if tail.len() > 20 {
+                tail.remove(0);
+            }
+        }
+        tail
+    });
+    if let Some(stdout) = child.stdout.take() {
+        for line in BufReader::new(stdout).lines().map_while(Result::ok) {
+            let _ = app.emit("whisper-build-progress", BuildProgress { stage, line });
+        }
+    }
+
+    let status = processes::wait(app, &mut child).map_err(|e| format!("Failed to wait for {}: {}", stage, e))?;
+    let tail = stderr_thread.join().unwrap_or_default();
+    if !status.success() {
+        return Err(format!("whisper.cpp {} failed: {}", stage, tail.join("\n")));
+    }
+    Ok(())
+}
+
+/// Download the whisper.cpp source, build whisper-cli with cmake, and install it into the binaries dir
+#[tauri::command]
+pub async fn build_whisper_from_source(window: tauri::Window) -> Result<String, String> {
+    let missing = missing_build_tools();
+    if !missing.is_empty() {
+        return Err(format!(
+            "Building whisper.cpp needs {}, which {} not installed (e.g. `sudo apt install build-essential cmake`)",
+            missing.join(", "),
+            if missing.len() == 1 { "is" } else { "are" }
+        ));
+    }
+
+    let build_root = get_cache_dir()?.join("whisper-build");
+    let tarball = build_root.join(format!("whisper.cpp-{}.tar.gz", WHISPER_VERSION));
+    fs::create_dir_all(&build_root).map_err(|e| format!("Failed to create build directory: {}", e))?;
+
+    let download = downloads::ActiveDownload::start(&window, "whisper-source");
+    let url = format!("{}/v{}.tar.gz", SOURCE_URL, WHISPER_VERSION);
+    // GitHub's generated tag tarballs have no published checksum
+    downloads::fetch_verified(&window, &download, &url, &tarball, None).await?;
+    drop(download);
+
+    let app = window.app_handle().clone();
+    let binaries_dir = get_binaries_dir()?;
+    tauri::async_runtime::spawn_blocking(move || {
+        let _ = app.emit("whisper-build-progress", BuildProgress { stage: "extract", line: tarball.display().to_string() });
+        let source_dir = build_root.join(format!("whisper.cpp-{}", WHISPER_VERSION));
+        let _ = fs::remove_dir_all(&source_dir);
+        archive::extract_archive(&tarball, &build_root)?;
+        fs::remove_file(&tarball).ok();
+
+        let jobs = std::thread::available_parallelism().map(|n| n.get()).unwrap_or(4).to_string();
+        run_step(
+            &app,
+            "configure",
+            Command::new("cmake")
+                .current_dir(&source_dir)
+                .arg("-B").arg("build")
+                .arg("-DCMAKE_BUILD_TYPE=Release")
+                // A static binary can be copied out of the build tree on its own
+                .arg("-DBUILD_SHARED_LIBS=OFF")
+                .stdin(Stdio::null()),
+        )?;
+        run_step(
+            &app,
+            "build",
+            Command::new("cmake")
+                .current_dir(&source_dir)
+                .arg("--build").arg("build")
+                .arg("--config").arg("Release")
+                .arg("--target").arg("whisper-cli")
+                .arg("-j").arg(&jobs)
+                .stdin(Stdio::null()),
+        )?;
+
+        let binary = archive::find_file(&source_dir.join("build"), exe_name(), 3)
+            .ok_or("The build finished but whisper-cli wasn't produced")?;
+        let installed = install_binary(&app, &binary, &binaries_dir)?;
+        let _ = fs::remove_dir_all(&source_dir);
+        let _ = app.emit("whisper-build-progress", BuildProgress { stage: "complete", line: installed.display().to_string() });
+        Ok(installed.to_string_lossy().to_string())
+    })
+    .await
+    .map_err(|e| format!("Build task failed: {}", e))?
+}
@@ -3,6 +3,7 @@ import { LiveTranscriberV2 } from "./components/LiveTranscriberV2";
 import { SettingsProvider, useSettings } from "./components/SettingsContext";
 import React from "react";
 import { invoke } from "@tauri-apps/api/core";
+import { errorMessage } from "./errors";
 
 function App() {
   const [showSettings, setShowSettings] = React.useState(false);
@@ -79,7 +80,7 @@ function SettingsModalContent() {
       await invoke('stop_live_recording');
       setTestStatus('Mic test complete.');
     } catch (e) {
-      setTestStatus(`Mic test failed: ${e}`);
+      setTestStatus(`Mic test failed: ${errorMessage(e)}`);
     }
   };
 
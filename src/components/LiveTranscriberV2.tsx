@@ -4,6 +4,7 @@ import { listen } from '@tauri-apps/api/event';
 import { save } from '@tauri-apps/plugin-dialog';
 import { writeTextFile } from '@tauri-apps/plugin-fs';
 import { useSettings } from './SettingsContext';
+import { errorMessage } from '../errors';
 
 interface LiveChunk {
   chunk: number;
@@ -13,6 +14,12 @@ interface LiveChunk {
   size?: number;
 }
 
+interface LiveRecordingResult {
+  transcript: string;
+  note_id: number | null;
+  recording_path: string | null;
+}
+
 interface DetectedTopic {
   keyword: string;
   confidence: number;
@@ -43,7 +50,8 @@ export function LiveTranscriberV2() {
   const [elapsed, setElapsed] = useState<string>('00:00');
   const [summary, setSummary] = useState<string>('');  
   const [isSummarizing, setIsSummarizing] = useState<boolean>(false);
-  const [pendingChunk, setPendingChunk] = useState<number | null>(null);  const extractTopics = useCallback((allChunks: LiveChunk[]) => {
+  const [pendingChunk, setPendingChunk] = useState<number | null>(null);
+  const [saved, setSaved] = useState<LiveRecordingResult | null>(null);  const extractTopics = useCallback((allChunks: LiveChunk[]) => {
     if (allChunks.length < minChunksPerTopic) {
       setTopics([]);
       return;
@@ -196,8 +204,8 @@ export function LiveTranscriberV2() {
       setError(null);
     });
 
-    const unlistenError = listen<string>('live-recording-error', (event) => {
-      setError(event.payload);
+    const unlistenError = listen<{ seq: number; message: string }>('live-recording-error', (event) => {
+      setError(event.payload.message);
       setPendingChunk(null);
     });
 
@@ -215,6 +223,7 @@ export function LiveTranscriberV2() {
       setSummary('');
       setElapsed('00:00');
       setPendingChunk(0);
+      setSaved(null);
       await invoke<string>('start_live_recording', {
         preferred_recorder: recorderPreference,
         segment_seconds: segmentSeconds,
@@ -222,14 +231,15 @@ export function LiveTranscriberV2() {
       setIsRecording(true);
       setStartTime(Date.now());
     } catch (e) {
-      setError('Failed to start: ' + e);
+      setError('Failed to start: ' + errorMessage(e));
       setPendingChunk(null);
     }
   }, [recorderPreference, segmentSeconds]);
 
   const stopRecording = useCallback(async () => {
     try {
-      await invoke<string>('stop_live_recording');
+      const result = await invoke<LiveRecordingResult>('stop_live_recording', { saveNote: true });
+      setSaved(result);
       setIsRecording(false);
       setStartTime(null);
       setPendingChunk(null);
@@ -237,7 +247,7 @@ export function LiveTranscriberV2() {
         await summarizeTranscript(chunks);
       }
     } catch (e) {
-      setError('Failed to stop: ' + e);
+      setError('Failed to stop: ' + errorMessage(e));
       setIsRecording(false);
     }
   }, [chunks, enableLLMSummary, summarizeTranscript]);
@@ -249,6 +259,8 @@ export function LiveTranscriberV2() {
           duration: chunks.length * segmentSeconds,
           chunks_count: chunks.length,
           created: new Date().toISOString(),
+          note_id: saved?.note_id ?? null,
+          recording_path: saved?.recording_path ?? null,
         },
         chunks: chunks.map(c => ({ index: c.chunk, time: c.timestamp, text: c.text })),
         full_text: chunks.map(c => c.text).join(' '),
@@ -264,7 +276,7 @@ export function LiveTranscriberV2() {
     } catch (e) {
       setError('Export failed: ' + e);
     }
-  }, [chunks, segmentSeconds, topics, summary]);
+  }, [chunks, segmentSeconds, topics, summary, saved]);
 
   const copyText = useCallback(() => {
     navigator.clipboard.writeText(chunks.map(c => c.text).join('\n\n'));
@@ -284,6 +296,7 @@ export function LiveTranscriberV2() {
           <div style={styles.statusInfo}>
             <span>Encoder: <strong>{recorderPreference}</strong></span>
             <span>Segment: <strong>{segmentSeconds}s</strong></span>
+            {saved?.note_id != null && <span>Saved as note <strong>#{saved.note_id}</strong></span>}
           </div>
         </div>
         <div style={styles.headerRight}>
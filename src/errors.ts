@@ -0,0 +1,13 @@
+// Commands migrated to AppError reject with { code, message, details }; older ones reject with a string
+export interface AppError {
+  code: string;
+  message: string;
+  details: unknown;
+}
+
+export function errorMessage(e: unknown): string {
+  if (e && typeof e === 'object' && 'message' in e) {
+    return String((e as { message: unknown }).message);
+  }
+  return String(e);
+}